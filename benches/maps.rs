@@ -0,0 +1,151 @@
+//! Peak heap use and time of `maps_has_library` / `parse_status_fields`
+//! reading line by line, against reading the whole file first with
+//! `read_to_end`. The maps file has 10 000 entries, about a JVM's worth.
+//! Run with `cargo bench --bench maps`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use fpid_rs::maps::maps_has_library;
+use fpid_rs::status::parse_status_fields;
+
+const MAPS_ENTRIES: usize = 10_000;
+const ROUNDS: u32 = 50;
+
+/// Tracks live and peak heap bytes so each variant's high-water mark can
+/// be read back.
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: forwarded unchanged to the system allocator.
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: `ptr` came from `alloc` with this layout.
+        unsafe { System.dealloc(ptr, layout) };
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: Counting = Counting;
+
+/// Mean time of `f` and the most heap it had allocated at once, above what
+/// was live before it started.
+fn measure(mut f: impl FnMut() -> bool) -> (Duration, usize) {
+    let base = LIVE.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    black_box(f());
+    let peak = PEAK.load(Ordering::Relaxed) - base;
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(f());
+    }
+    (start.elapsed() / ROUNDS, peak)
+}
+
+fn report(name: &str, streamed: (Duration, usize), whole: (Duration, usize)) {
+    println!(
+        "{name:<24} {:>12?} {:>9} B   {:>12?} {:>9} B",
+        streamed.0, streamed.1, whole.0, whole.1
+    );
+}
+
+/// A maps file where every entry is an anonymous region or a numbered
+/// shared object, so the searched library is never found and every line is
+/// read.
+fn write_maps(path: &Path) {
+    let mut text = String::new();
+    for i in 0..MAPS_ENTRIES {
+        let start = 0x7f00_0000_0000_u64 + i as u64 * 0x2000;
+        if i % 3 == 0 {
+            text.push_str(&format!(
+                "{start:x}-{:x} rw-p 00000000 00:00 0 \n",
+                start + 0x1000
+            ));
+        } else {
+            text.push_str(&format!(
+                "{start:x}-{:x} r-xp 00001000 fd:01 {} /usr/lib/jvm/lib/libjvm-part{i}.so\n",
+                start + 0x1000,
+                100_000 + i
+            ));
+        }
+    }
+    std::fs::write(path, text).unwrap();
+}
+
+fn write_status(path: &Path) {
+    let mut text = String::from("Name:\tjava\nUmask:\t0022\nState:\tS (sleeping)\n");
+    for key in ["Tgid", "Ngid", "Pid", "PPid", "TracerPid"] {
+        text.push_str(&format!("{key}:\t4242\n"));
+    }
+    for i in 0..40 {
+        text.push_str(&format!("Field{i}:\t{i} kB\n"));
+    }
+    text.push_str("VmRSS:\t 1843200 kB\nThreads:\t212\n");
+    std::fs::write(path, text).unwrap();
+}
+
+fn read_whole(path: &Path) -> Vec<u8> {
+    let mut buf = Vec::new();
+    File::open(path).unwrap().read_to_end(&mut buf).unwrap();
+    buf
+}
+
+fn main() {
+    let dir = std::env::temp_dir().join(format!("fpid-bench-maps-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let maps = dir.join("maps");
+    let status = dir.join("status");
+    write_maps(&maps);
+    write_status(&status);
+    let maps_len = std::fs::metadata(&maps).unwrap().len();
+
+    println!("maps: {MAPS_ENTRIES} entries, {maps_len} bytes; mean of {ROUNDS} rounds");
+    println!(
+        "{:<24} {:>12} {:>11}   {:>12} {:>11}",
+        "", "BufRead", "peak heap", "read_to_end", "peak heap"
+    );
+    report(
+        "maps_has_library (miss)",
+        measure(|| maps_has_library(BufReader::new(File::open(&maps).unwrap()), "libssl").unwrap()),
+        measure(|| maps_has_library(&read_whole(&maps)[..], "libssl").unwrap()),
+    );
+    report(
+        "maps_has_library (hit)",
+        measure(|| maps_has_library(BufReader::new(File::open(&maps).unwrap()), "libjvm").unwrap()),
+        measure(|| maps_has_library(&read_whole(&maps)[..], "libjvm").unwrap()),
+    );
+    const FIELDS: [&str; 2] = ["PPid", "TracerPid"];
+    report(
+        "parse_status_fields",
+        measure(|| {
+            parse_status_fields(BufReader::new(File::open(&status).unwrap()), &FIELDS)
+                .unwrap()
+                .iter()
+                .all(Option::is_some)
+        }),
+        measure(|| {
+            parse_status_fields(&read_whole(&status)[..], &FIELDS)
+                .unwrap()
+                .iter()
+                .all(Option::is_some)
+        }),
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
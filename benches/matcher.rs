@@ -0,0 +1,81 @@
+//! `--match-cmdline` cost: one Aho-Corasick pass against searching for each
+//! target in turn. Run with `cargo bench --features aho-corasick`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use fpid_rs::aho_corasick::AhoCorasick;
+
+const CMDLINES: usize = 2_000;
+const ROUNDS: usize = 20;
+
+/// Cmdlines shaped like a busy host: an interpreter, a script path and a
+/// handful of flags, about 120 bytes each.
+fn cmdlines() -> Vec<Vec<u8>> {
+    (0..CMDLINES)
+        .map(|i| {
+            format!(
+                "/usr/bin/python3\0/srv/app{i}/venv/bin/worker-{}\0--queue\0jobs-{}\0--concurrency\0{}\0--log-level\0info\0",
+                i % 97,
+                i % 13,
+                i % 8 + 1
+            )
+            .into_bytes()
+        })
+        .collect()
+}
+
+/// Targets that mostly miss, so both approaches read every byte.
+fn targets(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("svc-{i:03}-daemon")).collect()
+}
+
+/// What `--match-cmdline` would do without the automaton: each target is
+/// searched for separately, and the first one found wins.
+fn linear_first(targets: &[String], haystack: &[u8]) -> Option<usize> {
+    targets.iter().position(|t| {
+        let t = t.as_bytes();
+        haystack.windows(t.len()).any(|w| w == t)
+    })
+}
+
+fn time(mut f: impl FnMut() -> usize) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..ROUNDS {
+        found = black_box(f());
+    }
+    (start.elapsed() / ROUNDS as u32, found)
+}
+
+fn main() {
+    let lines = cmdlines();
+    println!("{CMDLINES} cmdlines, mean of {ROUNDS} rounds");
+    println!(
+        "{:>8} {:>14} {:>14} {:>8}",
+        "targets", "linear", "aho-corasick", "speedup"
+    );
+    for n in [1, 8, 64] {
+        let targets = targets(n);
+        let ac = AhoCorasick::new(&targets);
+        let (linear, linear_found) = time(|| {
+            lines
+                .iter()
+                .filter(|l| linear_first(&targets, black_box(l)).is_some())
+                .count()
+        });
+        let (automaton, ac_found) = time(|| {
+            lines
+                .iter()
+                .filter(|l| ac.first_pattern(black_box(l)).is_some())
+                .count()
+        });
+        assert_eq!(linear_found, ac_found);
+        println!(
+            "{n:>8} {:>14?} {:>14?} {:>7.1}x",
+            linear,
+            automaton,
+            linear.as_secs_f64() / automaton.as_secs_f64()
+        );
+    }
+}
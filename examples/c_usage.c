@@ -0,0 +1,31 @@
+/* Build:
+ *   cargo build --release --features capi
+ *   cc examples/c_usage.c -Iinclude target/release/libfpid_rs.a -o c_usage
+ * Run:
+ *   ./c_usage sshd
+ */
+#include <stdio.h>
+
+#include "fpid.h"
+
+int main(int argc, char **argv)
+{
+    uint32_t pids[64];
+    int n, i;
+
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <name|/path/to/exe>\n", argv[0]);
+        return 2;
+    }
+    if (argv[1][0] == '/')
+        n = fpid_find_by_path(argv[1], pids, 64);
+    else
+        n = fpid_find_by_name(argv[1], pids, 64);
+    if (n < 0) {
+        fprintf(stderr, "fpid lookup failed\n");
+        return 2;
+    }
+    for (i = 0; i < n && i < 64; i++)
+        printf("%u\n", pids[i]);
+    return n > 0 ? 0 : 1;
+}
@@ -0,0 +1,27 @@
+/* C API of fpid-rs, built with `cargo build --release --features capi`.
+ * Link against target/release/libfpid_rs.a. */
+#ifndef FPID_H
+#define FPID_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Finds processes whose argv[0] basename is `name` and writes up to
+ * `max_pids` of their PIDs to `pids_out`. Returns the number found (which
+ * may exceed `max_pids`), or -1 if an argument is NULL/invalid or /proc
+ * cannot be read. `pids_out` may be NULL when `max_pids` is 0. */
+int fpid_find_by_name(const char *name, uint32_t *pids_out, size_t max_pids);
+
+/* Same as fpid_find_by_name, matching the executable path; `path` must
+ * contain a '/'. */
+int fpid_find_by_path(const char *path, uint32_t *pids_out, size_t max_pids);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FPID_H */
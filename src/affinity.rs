@@ -0,0 +1,92 @@
+use crate::error::ParseError;
+use crate::status::StatusInfo;
+
+/// Parses a kernel cpu-list such as `0-3,6,8-10` into sorted, deduplicated
+/// CPU numbers. An empty string is an empty set.
+pub fn parse_cpu_list(s: &str) -> Result<Vec<u32>, ParseError> {
+    let invalid = || ParseError::InvalidValue(s.to_string());
+    let mut cpus = Vec::new();
+    for part in s.trim().split(',').filter(|p| !p.is_empty()) {
+        let (lo, hi) = match part.split_once('-') {
+            Some((lo, hi)) => (lo, hi),
+            None => (part, part),
+        };
+        let lo: u32 = lo.trim().parse().map_err(|_| invalid())?;
+        let hi: u32 = hi.trim().parse().map_err(|_| invalid())?;
+        if lo > hi {
+            return Err(invalid());
+        }
+        cpus.extend(lo..=hi);
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
+
+/// Parses a `Cpus_allowed:` hex mask, written as comma-separated 32-bit
+/// words with the highest CPUs first (`ff,00000001`).
+pub fn parse_cpu_mask(s: &str) -> Result<Vec<u32>, ParseError> {
+    let mut cpus = Vec::new();
+    for (word_idx, word) in s.trim().rsplit(',').enumerate() {
+        let bits =
+            u32::from_str_radix(word, 16).map_err(|_| ParseError::InvalidValue(s.to_string()))?;
+        for bit in 0..32 {
+            if bits & (1 << bit) != 0 {
+                cpus.push(word_idx as u32 * 32 + bit);
+            }
+        }
+    }
+    Ok(cpus)
+}
+
+/// CPUs the process may run on, from `Cpus_allowed_list` or, on kernels
+/// without it, the `Cpus_allowed` mask.
+pub fn allowed_cpus(status: &StatusInfo) -> Option<Vec<u32>> {
+    if !status.cpus_allowed_list.is_empty() {
+        return parse_cpu_list(&status.cpus_allowed_list).ok();
+    }
+    if !status.cpus_allowed.is_empty() {
+        return parse_cpu_mask(&status.cpus_allowed).ok();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("3").unwrap(), vec![3]);
+        assert_eq!(parse_cpu_list("0-3").unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(
+            parse_cpu_list("0-3,6,8-10").unwrap(),
+            vec![0, 1, 2, 3, 6, 8, 9, 10]
+        );
+        assert_eq!(parse_cpu_list("6,1-2,2\n").unwrap(), vec![1, 2, 6]);
+        assert_eq!(parse_cpu_list("").unwrap(), Vec::<u32>::new());
+        for bad in ["3-1", "a", "1-", "-2", "1,,x"] {
+            assert_eq!(
+                parse_cpu_list(bad),
+                Err(ParseError::InvalidValue(bad.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_cpu_mask() {
+        assert_eq!(parse_cpu_mask("f").unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(parse_cpu_mask("1,00000001").unwrap(), vec![0, 32]);
+        assert!(parse_cpu_mask("xyz").is_err());
+    }
+
+    #[test]
+    fn test_allowed_cpus() {
+        let mut st = StatusInfo::default();
+        assert_eq!(allowed_cpus(&st), None);
+        st.cpus_allowed = "5".to_string();
+        assert_eq!(allowed_cpus(&st), Some(vec![0, 2]));
+        st.cpus_allowed_list = "1-2".to_string();
+        assert_eq!(allowed_cpus(&st), Some(vec![1, 2]));
+    }
+}
@@ -0,0 +1,150 @@
+//! Aho-Corasick automaton for `--match-cmdline` (`aho-corasick` feature).
+//!
+//! The patterns are compiled into a dense DFA, one 256-entry row per trie
+//! state, so each haystack byte costs one table lookup however many
+//! patterns there are. Only "which pattern occurs first in pattern order"
+//! is answered, which is all target matching needs.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+// No pattern ends at this state.
+const NONE: usize = usize::MAX;
+
+/// Finds which of several byte patterns occur in a haystack in one pass.
+#[derive(Clone)]
+pub struct AhoCorasick {
+    /// `trans[state * 256 + byte]` is the next state; state 0 is the root.
+    trans: Vec<u32>,
+    /// Lowest pattern index ending at each state, including the patterns
+    /// reached through its failure links.
+    out: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// Compiles `patterns`; a reported index is the position in `patterns`
+    /// (the first of duplicates). An empty pattern occurs everywhere.
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        // The trie first, with 0 for a missing edge (no edge leads back to
+        // the root).
+        let mut trans = vec![0u32; 256];
+        let mut out = vec![NONE];
+        for (idx, pattern) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for &b in pattern.as_ref() {
+                let slot = state * 256 + usize::from(b);
+                if trans[slot] == 0 {
+                    trans[slot] = out.len() as u32;
+                    trans.extend([0; 256]);
+                    out.push(NONE);
+                }
+                state = trans[slot] as usize;
+            }
+            out[state] = out[state].min(idx);
+        }
+
+        // Breadth first, so a state's failure target is complete before the
+        // state itself: missing edges copy the failure target's, turning
+        // the trie into a DFA.
+        let mut fail = vec![0usize; out.len()];
+        let mut queue: VecDeque<usize> = trans[..256]
+            .iter()
+            .filter(|t| **t != 0)
+            .map(|t| *t as usize)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            out[state] = out[state].min(out[fail[state]]);
+            for b in 0..256 {
+                let slot = state * 256 + b;
+                let via_fail = trans[fail[state] * 256 + b];
+                match trans[slot] as usize {
+                    0 => trans[slot] = via_fail,
+                    next => {
+                        fail[next] = via_fail as usize;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        Self { trans, out }
+    }
+
+    /// Lowest index among the patterns that occur anywhere in `haystack`.
+    /// Stops early once pattern 0 is seen.
+    pub fn first_pattern(&self, haystack: &[u8]) -> Option<usize> {
+        let mut state = 0;
+        let mut best = self.out[0];
+        for &b in haystack {
+            if best == 0 {
+                break;
+            }
+            state = self.trans[state * 256 + usize::from(b)] as usize;
+            best = best.min(self.out[state]);
+        }
+        (best != NONE).then_some(best)
+    }
+
+    /// Number of DFA states, one per distinct pattern prefix plus the root.
+    pub fn states(&self) -> usize {
+        self.out.len()
+    }
+}
+
+impl Default for AhoCorasick {
+    fn default() -> Self {
+        Self::new::<&[u8]>(&[])
+    }
+}
+
+// The transition table is 1 KiB per state; print its size instead.
+impl fmt::Debug for AhoCorasick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AhoCorasick")
+            .field("states", &self.states())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_pattern() {
+        let ac = AhoCorasick::new(&["he", "she", "his", "hers"]);
+        assert_eq!(ac.first_pattern(b"ushers"), Some(0));
+        assert_eq!(ac.first_pattern(b"ahishe"), Some(0));
+        assert_eq!(ac.first_pattern(b"this"), Some(2));
+        // "she" is only found through the failure link from "hers".
+        let ac = AhoCorasick::new(&["she", "hers"]);
+        assert_eq!(ac.first_pattern(b"xhershe"), Some(0));
+        assert_eq!(ac.first_pattern(b"xhers"), Some(1));
+        assert_eq!(ac.first_pattern(b"shhe"), None);
+        assert_eq!(ac.states(), 8);
+    }
+
+    #[test]
+    fn test_first_pattern_cmdline() {
+        let ac = AhoCorasick::new(&["gunicorn", "celery", "nginx"]);
+        let cmdline = b"/usr/bin/python3\0/srv/venv/bin/celery\0-A\0app\0worker\0";
+        assert_eq!(ac.first_pattern(cmdline), Some(1));
+        // Patterns may span the NUL between arguments.
+        let ac = AhoCorasick::new(&["-A\0app"]);
+        assert_eq!(ac.first_pattern(cmdline), Some(0));
+        assert_eq!(
+            AhoCorasick::new(&["nginx", "nginx"]).first_pattern(b"nginx"),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_first_pattern_edge_cases() {
+        assert_eq!(AhoCorasick::default().first_pattern(b"anything"), None);
+        assert_eq!(AhoCorasick::new(&["x"]).first_pattern(b""), None);
+        assert_eq!(AhoCorasick::new(&["abc", ""]).first_pattern(b""), Some(1));
+        assert_eq!(
+            AhoCorasick::new(&[b"\xff\x00"]).first_pattern(b"a\xff\x00"),
+            Some(0)
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::output::open_output;
+use crate::scan::ProcessInfo;
+
+/// Appends one line per match to an `--audit-log` file:
+///
+/// ```text
+/// 2026-10-14T08:15:30Z MATCH pid=1234 name=nginx exe=/usr/sbin/nginx query=nginx
+/// ```
+///
+/// Each entry is flushed as it is written, so the log is complete even if
+/// fpid is killed mid-scan.
+#[derive(Debug)]
+pub struct AuditLogger {
+    file: BufWriter<File>,
+}
+
+impl AuditLogger {
+    /// Opens `path` for appending, creating it owner-only (0600).
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(open_output(path, true, 0o600)?),
+        })
+    }
+
+    /// Records `info` as found by the target `query`.
+    pub fn log_match(&mut self, info: &ProcessInfo, query: &str) -> io::Result<()> {
+        let line = audit_line(info, query, SystemTime::now());
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()
+    }
+}
+
+/// One audit entry, newline included. Whitespace, control characters and
+/// `\` inside values are written as `\xNN` so a crafted process name can't
+/// split or forge entries.
+pub fn audit_line(info: &ProcessInfo, query: &str, at: SystemTime) -> String {
+    let exe = info
+        .exe
+        .as_ref()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "-".to_string());
+    format!(
+        "{} MATCH pid={} name={} exe={} query={}\n",
+        iso8601_utc(at),
+        info.pid,
+        escape_value(&info.name),
+        escape_value(&exe),
+        escape_value(query)
+    )
+}
+
+fn escape_value(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_whitespace() || c.is_control() || c == '\\' {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("\\x{:02x}", b));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ` in UTC; times before 1970 clamp to the epoch.
+pub fn iso8601_utc(at: SystemTime) -> String {
+    let secs = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (y, m, d) = civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y,
+        m,
+        d,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's
+// civil_from_days).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn info(pid: u32, name: &str, exe: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            target: 0,
+            name: name.to_string(),
+            exe: exe.map(PathBuf::from),
+            cmdline: vec![name.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_iso8601_utc() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        assert_eq!(iso8601_utc(at(0)), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601_utc(at(951_782_400)), "2000-02-29T00:00:00Z");
+        assert_eq!(iso8601_utc(at(1_791_969_330)), "2026-10-14T09:15:30Z");
+        assert_eq!(
+            iso8601_utc(UNIX_EPOCH - Duration::from_secs(5)),
+            "1970-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_audit_line() {
+        let at = UNIX_EPOCH + Duration::from_secs(1_791_969_330);
+        assert_eq!(
+            audit_line(&info(1234, "nginx", Some("/usr/sbin/nginx")), "nginx", at),
+            "2026-10-14T09:15:30Z MATCH pid=1234 name=nginx exe=/usr/sbin/nginx query=nginx\n"
+        );
+        assert_eq!(
+            audit_line(&info(7, "evil\nx y", None), "evil", at),
+            "2026-10-14T09:15:30Z MATCH pid=7 name=evil\\x0ax\\x20y exe=- query=evil\n"
+        );
+    }
+
+    #[test]
+    fn test_log_match_appends() {
+        let path = std::env::temp_dir().join(format!("fpid-audit-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut log = AuditLogger::open(&path).unwrap();
+        log.log_match(&info(10, "nginx", Some("/usr/sbin/nginx")), "nginx")
+            .unwrap();
+        log.log_match(&info(11, "nginx", None), "/usr/sbin/nginx")
+            .unwrap();
+        // Flushed per entry: readable before the logger is dropped.
+        let text = std::fs::read_to_string(&path).unwrap();
+        drop(log);
+        let mut log = AuditLogger::open(&path).unwrap();
+        log.log_match(&info(12, "bash", None), "bash").unwrap();
+        let all = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" MATCH pid=10 name=nginx exe=/usr/sbin/nginx query=nginx"));
+        assert!(lines[1].ends_with(" MATCH pid=11 name=nginx exe=- query=/usr/sbin/nginx"));
+        let stamp = lines[0].split(' ').next().unwrap();
+        assert_eq!(stamp.len(), "2026-10-14T09:15:30Z".len());
+        assert!(stamp.ends_with('Z') && stamp.as_bytes()[10] == b'T');
+        assert_eq!(all.lines().count(), 3);
+        assert!(all.ends_with("pid=12 name=bash exe=- query=bash\n"));
+    }
+}
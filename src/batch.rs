@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+/// PIDs in batches of `batch_size`, pausing `sleep_us` microseconds between
+/// batches so a scan on a loaded host leaves the CPU to others
+/// (`--proc-batch-size`, `--proc-batch-sleep-us`).
+///
+/// There is no pause before the first batch or after the last one. A
+/// `batch_size` of 0 never pauses. The sleep function is a parameter so
+/// tests can count pauses instead of waiting.
+#[derive(Debug)]
+pub struct BatchedProcIterator<I, S> {
+    inner: I,
+    batch_size: usize,
+    sleep_us: u64,
+    /// PIDs handed out in the current batch.
+    in_batch: usize,
+    /// Pauses taken so far, i.e. completed batches followed by another.
+    pub batch_count: usize,
+    sleep: S,
+}
+
+impl<I: Iterator<Item = u32>, S: FnMut(Duration)> BatchedProcIterator<I, S> {
+    pub fn new(inner: I, batch_size: usize, sleep_us: u64, sleep: S) -> Self {
+        Self {
+            inner,
+            batch_size,
+            sleep_us,
+            in_batch: 0,
+            batch_count: 0,
+            sleep,
+        }
+    }
+}
+
+impl<I: Iterator<Item = u32>, S: FnMut(Duration)> Iterator for BatchedProcIterator<I, S> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let pid = self.inner.next()?;
+        if self.batch_size > 0 && self.in_batch == self.batch_size {
+            (self.sleep)(Duration::from_micros(self.sleep_us));
+            self.batch_count += 1;
+            self.in_batch = 0;
+        }
+        self.in_batch += 1;
+        Some(pid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(pids: u32, batch_size: usize) -> (Vec<u32>, Vec<Duration>) {
+        let mut sleeps = Vec::new();
+        let (seen, count) = {
+            let mut it = BatchedProcIterator::new(1..=pids, batch_size, 250, |d| sleeps.push(d));
+            let seen: Vec<u32> = it.by_ref().collect();
+            (seen, it.batch_count)
+        };
+        assert_eq!(count, sleeps.len());
+        (seen, sleeps)
+    }
+
+    #[test]
+    fn test_sleeps_between_batches() {
+        let (seen, sleeps) = run(10, 3);
+        assert_eq!(seen, (1..=10).collect::<Vec<_>>());
+        assert_eq!(sleeps, vec![Duration::from_micros(250); 3]);
+        // A full last batch is not followed by a pause.
+        assert_eq!(run(9, 3).1.len(), 2);
+        assert_eq!(run(3, 3).1.len(), 0);
+        assert_eq!(run(0, 3).1.len(), 0);
+    }
+
+    #[test]
+    fn test_unbatched() {
+        let (seen, sleeps) = run(100, 0);
+        assert_eq!(seen.len(), 100);
+        assert!(sleeps.is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+/// Linux capability names (without `CAP_`) and bit numbers, sorted by name
+/// for binary search.
+pub const CAPS: [(&str, u32); 41] = [
+    ("AUDIT_CONTROL", 30),
+    ("AUDIT_READ", 37),
+    ("AUDIT_WRITE", 29),
+    ("BLOCK_SUSPEND", 36),
+    ("BPF", 39),
+    ("CHECKPOINT_RESTORE", 40),
+    ("CHOWN", 0),
+    ("DAC_OVERRIDE", 1),
+    ("DAC_READ_SEARCH", 2),
+    ("FOWNER", 3),
+    ("FSETID", 4),
+    ("IPC_LOCK", 14),
+    ("IPC_OWNER", 15),
+    ("KILL", 5),
+    ("LEASE", 28),
+    ("LINUX_IMMUTABLE", 9),
+    ("MAC_ADMIN", 33),
+    ("MAC_OVERRIDE", 32),
+    ("MKNOD", 27),
+    ("NET_ADMIN", 12),
+    ("NET_BIND_SERVICE", 10),
+    ("NET_BROADCAST", 11),
+    ("NET_RAW", 13),
+    ("PERFMON", 38),
+    ("SETFCAP", 31),
+    ("SETGID", 6),
+    ("SETPCAP", 8),
+    ("SETUID", 7),
+    ("SYSLOG", 34),
+    ("SYS_ADMIN", 21),
+    ("SYS_BOOT", 22),
+    ("SYS_CHROOT", 18),
+    ("SYS_MODULE", 16),
+    ("SYS_NICE", 23),
+    ("SYS_PACCT", 20),
+    ("SYS_PTRACE", 19),
+    ("SYS_RAWIO", 17),
+    ("SYS_RESOURCE", 24),
+    ("SYS_TIME", 25),
+    ("SYS_TTY_CONFIG", 26),
+    ("WAKE_ALARM", 35),
+];
+
+/// Every capability by bit number: its short name as `--print-caps` shows
+/// it and what it allows, for `--print-caps-verbose`. Descriptions follow
+/// capabilities(7) and contain no `;`.
+pub const CAPABILITY_DESCS: [(u8, &str, &str); 41] = [
+    (0, "chown", "change file owners and groups"),
+    (
+        1,
+        "dac_override",
+        "bypass file read, write and execute permission checks",
+    ),
+    (
+        2,
+        "dac_read_search",
+        "bypass file read and directory search checks",
+    ),
+    (3, "fowner", "bypass checks that require owning the file"),
+    (
+        4,
+        "fsetid",
+        "keep set-user-ID and set-group-ID bits when modifying files",
+    ),
+    (5, "kill", "send signals to processes of other users"),
+    (6, "setgid", "change group IDs and supplementary groups"),
+    (7, "setuid", "change user IDs"),
+    (
+        8,
+        "setpcap",
+        "change capability bounding sets and other processes' capabilities",
+    ),
+    (
+        9,
+        "linux_immutable",
+        "set the immutable and append-only file attributes",
+    ),
+    (10, "net_bind_service", "bind to ports below 1024"),
+    (
+        11,
+        "net_broadcast",
+        "send broadcasts and listen to multicast",
+    ),
+    (
+        12,
+        "net_admin",
+        "configure interfaces, routing, firewall and other network settings",
+    ),
+    (13, "net_raw", "use RAW and PACKET sockets"),
+    (
+        14,
+        "ipc_lock",
+        "lock memory (mlock, mmap MAP_LOCKED, SHM_LOCK)",
+    ),
+    (
+        15,
+        "ipc_owner",
+        "bypass permission checks on System V IPC objects",
+    ),
+    (16, "sys_module", "load and unload kernel modules"),
+    (
+        17,
+        "sys_rawio",
+        "perform I/O port operations and access /dev/mem",
+    ),
+    (18, "sys_chroot", "use chroot and change mount namespaces"),
+    (19, "sys_ptrace", "trace and inspect any process"),
+    (20, "sys_pacct", "turn process accounting on and off"),
+    (
+        21,
+        "sys_admin",
+        "mount filesystems and many other system administration tasks",
+    ),
+    (22, "sys_boot", "reboot and load a new kernel with kexec"),
+    (
+        23,
+        "sys_nice",
+        "raise priorities and set real-time scheduling and CPU affinity",
+    ),
+    (
+        24,
+        "sys_resource",
+        "override resource limits and disk quotas",
+    ),
+    (25, "sys_time", "set the system clock"),
+    (26, "sys_tty_config", "configure terminals with vhangup"),
+    (27, "mknod", "create device special files"),
+    (28, "lease", "take leases on files the process does not own"),
+    (29, "audit_write", "write records to the kernel audit log"),
+    (
+        30,
+        "audit_control",
+        "configure kernel auditing and its rules",
+    ),
+    (31, "setfcap", "set file capabilities"),
+    (
+        32,
+        "mac_override",
+        "override Mandatory Access Control (Smack)",
+    ),
+    (
+        33,
+        "mac_admin",
+        "configure Mandatory Access Control (Smack)",
+    ),
+    (
+        34,
+        "syslog",
+        "use privileged syslog operations and read kernel addresses",
+    ),
+    (
+        35,
+        "wake_alarm",
+        "set timers that wake the system from suspend",
+    ),
+    (36, "block_suspend", "prevent the system from suspending"),
+    (
+        37,
+        "audit_read",
+        "read the audit log over multicast netlink",
+    ),
+    (
+        38,
+        "perfmon",
+        "use perf_events and other performance monitoring",
+    ),
+    (39, "bpf", "load BPF programs and create BPF maps"),
+    (
+        40,
+        "checkpoint_restore",
+        "checkpoint and restore processes (set PIDs, read map_files)",
+    ),
+];
+
+/// Short names of the capabilities set in `mask`, by bit number.
+pub fn cap_names(mask: u64) -> Vec<&'static str> {
+    CAPABILITY_DESCS
+        .iter()
+        .filter(|(bit, ..)| has_cap(mask, u32::from(*bit)))
+        .map(|(_, name, _)| *name)
+        .collect()
+}
+
+/// What capability `bit` allows, e.g. `bind to ports below 1024`.
+pub fn cap_description(bit: u32) -> Option<&'static str> {
+    CAPABILITY_DESCS
+        .get(usize::try_from(bit).ok()?)
+        .map(|(_, _, desc)| *desc)
+}
+
+/// Bit number of `CAP_NET_ADMIN`, `net_admin` or any other casing.
+pub fn cap_bit(name: &str) -> Option<u32> {
+    let upper = name.to_ascii_uppercase();
+    let key = upper.strip_prefix("CAP_").unwrap_or(&upper);
+    CAPS.binary_search_by(|(n, _)| (*n).cmp(key))
+        .ok()
+        .map(|i| CAPS[i].1)
+}
+
+/// Whether capability `bit` is set in a `CapEff`-style mask.
+pub fn has_cap(mask: u64, bit: u32) -> bool {
+    bit < 64 && mask & (1 << bit) != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_table_sorted() {
+        assert!(CAPS.windows(2).all(|w| w[0].0 < w[1].0));
+        let mut bits: Vec<u32> = CAPS.iter().map(|c| c.1).collect();
+        bits.sort_unstable();
+        assert_eq!(bits, (0..41).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_cap_bit() {
+        assert_eq!(cap_bit("CAP_NET_ADMIN"), Some(12));
+        assert_eq!(cap_bit("sys_admin"), Some(21));
+        assert_eq!(cap_bit("Cap_Dac_Override"), Some(1));
+        assert_eq!(cap_bit("CAP_SETUID"), Some(7));
+        assert_eq!(cap_bit("CAP_CHECKPOINT_RESTORE"), Some(40));
+        assert_eq!(cap_bit("CAP_FLY"), None);
+        assert_eq!(cap_bit(""), None);
+    }
+
+    #[test]
+    fn test_capability_descs() {
+        for (i, (bit, name, desc)) in CAPABILITY_DESCS.iter().enumerate() {
+            assert_eq!(usize::from(*bit), i);
+            assert_eq!(cap_bit(name), Some(u32::from(*bit)), "{name}");
+            assert!(!desc.is_empty() && !desc.contains(';'), "{name}");
+        }
+        assert_eq!(
+            cap_description(21),
+            Some("mount filesystems and many other system administration tasks")
+        );
+        assert_eq!(cap_description(10), Some("bind to ports below 1024"));
+        assert_eq!(cap_description(41), None);
+    }
+
+    #[test]
+    fn test_cap_names() {
+        assert_eq!(
+            cap_names(0x3400),
+            vec!["net_bind_service", "net_admin", "net_raw"]
+        );
+        assert_eq!(cap_names(1 << 21), vec!["sys_admin"]);
+        assert!(cap_names(0).is_empty());
+        assert_eq!(cap_names(u64::MAX).len(), 41);
+    }
+
+    #[test]
+    fn test_has_cap() {
+        // CAP_NET_BIND_SERVICE only.
+        let mask = 0x0000_0000_0000_0400;
+        assert!(has_cap(mask, 10));
+        assert!(!has_cap(mask, 12));
+        assert!(has_cap(u64::MAX, 40));
+        assert!(!has_cap(u64::MAX, 64));
+    }
+}
@@ -0,0 +1,138 @@
+use std::io;
+use std::str::FromStr;
+
+use crate::reader::ProcReader;
+
+/// How `--cgroup-filter` compares its path with each cgroup of a process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CgroupMatchMode {
+    /// The cgroup is exactly the path.
+    Exact,
+    /// The cgroup is the path or lies below it, by whole components.
+    Prefix,
+    /// The cgroup path ends with it.
+    Suffix,
+    /// The path occurs anywhere in the cgroup path.
+    #[default]
+    Contains,
+}
+
+impl FromStr for CgroupMatchMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "exact" => Ok(CgroupMatchMode::Exact),
+            "prefix" => Ok(CgroupMatchMode::Prefix),
+            "suffix" => Ok(CgroupMatchMode::Suffix),
+            "contains" => Ok(CgroupMatchMode::Contains),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Paths of a `/proc/<pid>/cgroup` buffer, one per hierarchy. Lines are
+/// `id:controllers:path`; cgroup v2 has a single `0::path` line.
+pub fn cgroup_paths(cgroup_lines: &[u8]) -> impl Iterator<Item = &[u8]> {
+    cgroup_lines
+        .split(|b| *b == b'\n')
+        .filter_map(|line| line.splitn(3, |b| *b == b':').nth(2))
+}
+
+/// Whether any cgroup in `cgroup_lines` matches `filter` under `mode`. A
+/// trailing `/` on `filter` is ignored.
+pub fn cgroup_matches_path(cgroup_lines: &[u8], filter: &str, mode: CgroupMatchMode) -> bool {
+    let want = match filter.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    }
+    .as_bytes();
+    cgroup_paths(cgroup_lines).any(|path| match mode {
+        CgroupMatchMode::Exact => path == want,
+        CgroupMatchMode::Prefix => {
+            want == b"/"
+                || path
+                    .strip_prefix(want)
+                    .is_some_and(|rest| rest.is_empty() || rest[0] == b'/')
+        }
+        CgroupMatchMode::Suffix => path.ends_with(want),
+        CgroupMatchMode::Contains => path.windows(want.len()).any(|w| w == want),
+    })
+}
+
+/// Raw `/proc/<pid>/cgroup`.
+pub fn read_cgroup(pid: u32, reader: &impl ProcReader) -> Result<Vec<u8>, io::Error> {
+    reader.read(pid, "cgroup")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const V2: &[u8] = b"0::/system.slice/nginx.service\n";
+    const V1: &[u8] = b"12:pids:/user.slice/user-1000.slice\n\
+11:cpu,cpuacct:/docker/4f1d2c\n\
+1:name=systemd:/docker/4f1d2c\n";
+
+    #[test]
+    fn test_cgroup_paths() {
+        assert_eq!(
+            cgroup_paths(V1).collect::<Vec<_>>(),
+            vec![
+                &b"/user.slice/user-1000.slice"[..],
+                b"/docker/4f1d2c",
+                b"/docker/4f1d2c"
+            ]
+        );
+        assert_eq!(cgroup_paths(b"").count(), 0);
+    }
+
+    #[test]
+    fn test_cgroup_exact_and_prefix() {
+        use CgroupMatchMode::*;
+        assert!(cgroup_matches_path(
+            V2,
+            "/system.slice/nginx.service",
+            Exact
+        ));
+        assert!(!cgroup_matches_path(V2, "/system.slice", Exact));
+        assert!(cgroup_matches_path(V2, "/system.slice", Prefix));
+        assert!(cgroup_matches_path(V2, "/system.slice/", Prefix));
+        assert!(cgroup_matches_path(V2, "/", Prefix));
+        assert!(cgroup_matches_path(
+            V2,
+            "/system.slice/nginx.service",
+            Prefix
+        ));
+        assert!(!cgroup_matches_path(V2, "/system.sl", Prefix));
+        assert!(cgroup_matches_path(V1, "/docker", Prefix));
+        assert!(!cgroup_matches_path(V1, "/system.slice", Prefix));
+    }
+
+    #[test]
+    fn test_cgroup_suffix_and_contains() {
+        use CgroupMatchMode::*;
+        assert!(cgroup_matches_path(V2, "nginx.service", Suffix));
+        assert!(!cgroup_matches_path(V2, "nginx", Suffix));
+        assert!(cgroup_matches_path(V2, "nginx", Contains));
+        assert!(cgroup_matches_path(V1, "user-1000", Contains));
+        assert!(!cgroup_matches_path(V1, "user-1001", Contains));
+        // Controller names are not part of the path.
+        assert!(!cgroup_matches_path(V1, "cpuacct", Contains));
+    }
+
+    #[test]
+    fn test_cgroup_mode_from_str() {
+        assert_eq!("prefix".parse(), Ok(CgroupMatchMode::Prefix));
+        assert_eq!("Exact".parse::<CgroupMatchMode>(), Err(()));
+        assert_eq!(CgroupMatchMode::default(), CgroupMatchMode::Contains);
+    }
+
+    #[test]
+    fn test_read_cgroup() {
+        let reader = MockProcReader::new().file(1, "cgroup", V2);
+        assert_eq!(read_cgroup(1, &reader).unwrap(), V2);
+        assert!(read_cgroup(2, &reader).is_err());
+    }
+}
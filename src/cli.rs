@@ -0,0 +1,2699 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use fpid_rs::affinity::parse_cpu_list;
+use fpid_rs::caps::cap_bit;
+use fpid_rs::cgroup::CgroupMatchMode;
+use fpid_rs::column::{COLUMN_NAMES, parse_duration};
+use fpid_rs::coredump::parse_coredump_mask;
+use fpid_rs::fd::DEFAULT_OPEN_FILES;
+use fpid_rs::namespace::ns_type;
+use fpid_rs::nice::NICE_RANGE;
+use fpid_rs::oom::OOM_ADJ_RANGE;
+use fpid_rs::output::{Format, HeaderMode};
+use fpid_rs::pid_range::{pid_range, read_pid_max};
+use fpid_rs::sched_policy::parse_policy_name;
+use fpid_rs::signal::{Signal, parse_signal_name, signal_names};
+use fpid_rs::sort::{SortDirection, SortField, SortSpec};
+use fpid_rs::statm::{StatmField, StatmFilter};
+use fpid_rs::status::{VmField, parse_seccomp_mode};
+use fpid_rs::syscall::SyscallState;
+use fpid_rs::users::{GROUP_PATH, PASSWD_PATH, UserDb};
+use fpid_rs::{Column, Filter, MatchMode};
+use fpid_rs::{log_error, log_warn};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flags {
+    pub quiet: bool,
+    pub single: bool,
+    pub verbose: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub flags: Flags,
+    // Positional target first (if any), then each --name in order.
+    pub targets: Vec<String>,
+    /// Names or paths whose matches are dropped (`--exclude-name`).
+    pub exclude_names: Vec<String>,
+    /// Whether every target must match instead of any (`--match-all`).
+    pub match_mode: MatchMode,
+    /// `--match-cmdline`: targets are substrings of the whole cmdline.
+    pub match_cmdline: bool,
+    /// `--group-by-target`: one `target: pid...` line per target at the end.
+    pub group_by_target: bool,
+    /// Thread comms to look for instead of processes (`--thread-name`).
+    pub thread_names: Vec<String>,
+    // Extra values appended after the PID, in the order given.
+    pub columns: Vec<Column>,
+    /// Set by `--columns`: each line is exactly `columns`, without the
+    /// leading PID.
+    pub columns_only: bool,
+    pub filters: Vec<Filter>,
+    /// `--relative-time`: print `--print-starttime` as an age.
+    pub relative_time: bool,
+    /// `-H` / `--human-readable`: sizes with a unit, times as `3h 5m 12s`.
+    pub human_readable: bool,
+    /// `--no-deleted`: leave deleted files out of `--print-open-files`.
+    pub no_deleted: bool,
+    /// `--cgroup-mode`, applied to every `--cgroup-filter`.
+    pub cgroup_mode: Option<CgroupMatchMode>,
+    /// `--local-numa-node`, applied to `--print-numa-local-pct` and
+    /// `--min-numa-local-pct`; node 0 when unset.
+    pub local_numa_node: Option<u32>,
+    /// `--min-pid` / `--max-pid` as given, before clamping to `pid_max`.
+    pub min_pid: Option<u32>,
+    pub max_pid: Option<u32>,
+    /// `--ppid-chain`: only report this PID and its descendants.
+    pub ppid_chain: Option<u32>,
+    pub format: Format,
+    // Print every process name instead of matching; no target needed.
+    pub list_all: bool,
+    // Dump every /proc/<pid>/stat field of each match.
+    pub stat: bool,
+    /// `--sort-by` keys and the `--sort-*` shorthands, first key first.
+    pub sort: Vec<SortSpec>,
+    pub self_test: bool,
+    /// `--no-header` / `--header-only`.
+    pub header_mode: HeaderMode,
+    pub pipe_null: bool,
+    pub signal: Option<Signal>,
+    pub dry_run: bool,
+    /// `--renice`: nice value set on each match.
+    pub renice: Option<i32>,
+    /// `--set-oom-adj`: written to each match's oom_score_adj.
+    pub set_oom_adj: Option<i32>,
+    pub confirm: bool,
+    pub no_partial: bool,
+    pub stats: bool,
+    pub stats_json: bool,
+    pub debug: bool,
+    /// Rescan until interrupted and report PIDs appearing and disappearing.
+    pub watch: bool,
+    /// `--watch-events`: follow proc connector events instead of rescanning.
+    pub watch_events: bool,
+    /// `--pid-file`: check the PID in this file instead of scanning.
+    pub pid_file: Option<PathBuf>,
+    /// `--write-pid-file`: record the first match's PID here.
+    pub write_pid_file: Option<PathBuf>,
+    /// `--write-pid-file-mode`; 0o644 when unset.
+    pub write_pid_file_mode: Option<u32>,
+    /// `--write-pid-file-dir-create`.
+    pub write_pid_file_dir_create: bool,
+    /// `--compare-snapshot`: diff one scan against this saved NDJSON output.
+    pub compare_snapshot: Option<PathBuf>,
+    /// `--proc-root`; see [`fpid_rs::reader::resolve_proc_root`].
+    pub proc_root: Option<PathBuf>,
+    /// `--max-proc-read-bytes`; `Some(0)` lifts the cap.
+    pub max_proc_read_bytes: Option<usize>,
+    pub proc_batch_size: usize,
+    pub proc_batch_sleep_us: u64,
+    pub output: Option<PathBuf>,
+    /// File that gets a timestamped `MATCH` line per match (`--audit-log`).
+    pub audit_log: Option<PathBuf>,
+    pub append: bool,
+    /// Permissions for a newly created `--output` file; 0o644 when unset.
+    pub output_mode: Option<u32>,
+    pub daemon: bool,
+    pub daemon_stop: bool,
+    pub socket: Option<PathBuf>,
+    pub pidfile: Option<PathBuf>,
+}
+
+const OPTIONS_HELP: &str = r#"Options:
+  -q    Quiet mode: suppress output, exit 0 if found
+  -s    Single shot: exit after first match
+  -v    Verbose: log debug diagnostics to stderr (also RUST_LOG=debug)
+  -h    Show this help
+  -H, --human-readable
+        Print size columns with a unit (rss=4.2 MB, vm_size=1.1 GB) and time
+        columns as age=3h 5m 12s, including the sizes in --print-statm,
+        --print-huge-pages and --print-maps-summary, the byte counts of
+        --print-io-full / --print-io-rchar-wchar and the times of
+        --print-sched; without it sizes stay in kB
+  --list-all
+        List all running process names (sorted, unique); no target needed
+  --daemon [--socket PATH] [--pidfile PATH]
+        Fork into the background and answer "<name>\n" queries on a Unix
+        socket (default /run/fpid.sock) with PIDs followed by ".\n"; the
+        daemon's PID goes to PATH (default /run/fpid.pid)
+  --daemon-stop [--pidfile PATH]
+        Send SIGTERM to the daemon whose PID is in PATH (default /run/fpid.pid)
+  --self-test
+        Check that fpid finds itself by name and exe path; exits 3 on failure
+  --format <auto|text|json|ndjson|procps|tsv|table|msgpack>
+        Output format (auto, the default, is text with the PID highlighted
+        on a terminal unless NO_COLOR is set; json prints one array of
+        name/count objects with --list-all or events with --watch and
+        --compare-snapshot; ndjson streams one JSON object
+        per line instead, a process per match or a name/count with --list-all;
+        procps prints a ps-style PID TTY TIME CMD table, tsv prints pid and
+        the --print-* values tab-separated under a header row, table aligns
+        them in columns cut to the terminal width (COLUMNS, else 80 when not
+        a terminal), msgpack
+        writes a binary array of processes when built with the msgpack feature)
+  -S, --signal SIG
+        Send SIG (name such as TERM/SIGTERM or number) to every match; names:
+        HUP INT QUIT ILL TRAP ABRT BUS FPE KILL USR1 SEGV USR2 PIPE ALRM TERM
+        STKFLT CHLD CONT STOP TSTP TTIN TTOU URG XCPU XFSZ VTALRM PROF WINCH
+        IO PWR SYS
+  --dry-run
+        Only print what --signal, --set-oom-adj, --renice, --daemon or
+        --daemon-stop would do
+  --confirm
+        With --signal, list the matches and ask before sending anything;
+        any answer but y aborts with status 1 (--dry-run answers yes)
+  --stats, --stats-json
+        After the scan, print PIDs scanned, matched and skipped (EPERM or
+        exited) and the duration to stderr, as text or a JSON object
+  --watch
+        Rescan every 500ms until Ctrl-C: print the current matches, then
+        +PID / -PID as processes appear and exit; with --format=json or
+        ndjson, one JSON event object per line instead
+  --watch-events
+        Like --watch, but learn about new and exited processes from the
+        kernel's proc connector (fork, exec and exit events) and check only
+        those PIDs; needs root or CAP_NET_ADMIN and falls back to polling
+        with a warning when the subscription fails
+  --pid-file PATH
+        Check only the PID on the first line of PATH and print it if that
+        process matches the target (and any filters); exits 0 if it does,
+        1 if it is not running or does not match, 2 if PATH is unreadable
+        or holds no PID
+  --write-pid-file PATH [--write-pid-file-mode OCTAL]
+  [--write-pid-file-dir-create]
+        Write the PID of the first match to PATH (created or truncated, mode
+        0644 unless given, missing directories created on request) instead
+        of printing it; exits 1 if nothing matches
+  --compare-snapshot FILE
+        Scan once and print -PID / +PID for matches missing from or new
+        since FILE, the saved --format=ndjson output of an earlier run
+        (JSON events with --format=json or ndjson); writes no files and
+        exits 0 if nothing changed, 1 if something did, 2 on a read error
+  --proc-batch-size N, --proc-batch-sleep-us U
+        Throttle the scan: after every N PIDs sleep U microseconds so a
+        loaded host gets the CPU back (default N=0, no pauses)
+  --proc-root PATH
+        Scan the procfs mounted at PATH instead of /proc (e.g. a container's
+        or a host's /proc bind-mounted elsewhere); without the flag the
+        FPID_PROC_ROOT environment variable is used if set and non-empty
+  --max-proc-read-bytes N
+        Read at most N bytes of each cmdline, environ and status file
+        (default 65536, 0 for no limit)
+  --debug
+        Trace every scanned PID to stderr: raw cmdline (hex-escaped), exe,
+        comm, match result and each filter check
+  --no-partial
+        On Ctrl-C, exit 130 without printing matches collected so far
+  -o, --output PATH
+        Write match output to PATH instead of stdout (truncated; see --append)
+  --append
+        Append to the --output file instead of truncating it
+  --output-mode MODE
+        Octal permissions for a newly created --output file (default 0644)
+  --audit-log PATH
+        Append "<UTC time> MATCH pid=N name=.. exe=.. query=.." to PATH
+        for every match (file created with mode 0600)
+  --pipe-null
+        When stdout is not a terminal, end records with NUL instead of a
+        newline (also FPID_NULL_OUTPUT=1)
+  --no-header
+        Omit the header row of --format=tsv, --format=procps and
+        --format=table
+  --header-only
+        Print only that header row (for table, the header and separator
+        lines), e.g. to see the column names a set of --print-* flags
+        produces; no target needed
+  --stat
+        Print all /proc/<pid>/stat fields of each match as "name: value" lines
+  --name <name or path>
+        Additional target; any target matching counts (repeatable)
+  --match-all
+        Require every target to match instead of any one, e.g. a name and
+        an exe path together; the filters apply on top as always
+  --match-cmdline
+        Match a target anywhere in the whole cmdline (arguments joined by
+        NUL) instead of comparing argv[0] and exe; all targets are found
+        in one Aho-Corasick pass per process. Needs the aho-corasick feature
+  --group-by-target
+        Print one "target: PID PID ..." line per target that matched, in
+        target order, after the scan instead of one PID per line
+  --min-pid N, --max-pid N
+        Only report PIDs from N / up to N (inclusive); --max-pid is
+        clamped to /proc/sys/kernel/pid_max (32768 if unreadable)
+  --ppid-chain PID
+        Only report PID and its descendants, from a process tree built at
+        start; with no target every process in that subtree is reported,
+        otherwise only those matching a target (--ppid-chain 100 worker)
+  --exclude-name <name or path>
+        Drop matches that also match this name or path, compared like a
+        target (repeatable)
+  --thread-name <name>
+        Match thread names (/proc/<pid>/task/<tid>/comm) instead of
+        processes and print pid/tid per thread (repeatable)
+  --columns COL[,COL...]
+        Print exactly these values per match, in order, instead of the PID
+        (e.g. --columns pid,name,rss prints pid=1234 name=nginx rss=5120kB);
+        COL is one of: pid name exe cmdline uid gid ppid state rss
+        starttime age cputime utime stime pss swap oom coredump statm io
+        io_chars schedstat pressure rss_pct cpu_pct ctx_rate pgflt_rate
+        minflt_rate majflt_rate cpus numa numa_local sched maps ns tracer
+        wchan syscall ns_pid cwd maps_count anon_exec vm_size vm_peak vm_data
+        vm_exe vm_lib pin locked huge_pages stack_kb total_stack fd_count
+        fd_types sockets listen mounts seccomp caps caps_verbose tls jvm
+        interp fd_limit.
+  --sort-by FIELD[,asc|desc]
+        Print matches ordered by FIELD, ascending unless desc is given;
+        repeat for tie-breakers (--sort-by name --sort-by rss,desc).
+        FIELD is one of: pid ppid pgid uid name rss vm_size starttime cpu
+        read_bytes write_bytes maps_count. Unreadable values sort last.
+  --print-*
+        Each --print-* flag adds its column after the ones already given,
+        including those from --columns
+  --print-age
+        Append the process age (e.g. age=3d5h12m)
+  --print-environ-var KEY
+        Append KEY=VALUE from the process environment (KEY= if unset;
+        repeatable, one column per KEY)
+  --print-starttime
+        Append the UTC start time (e.g. starttime=2026-10-14T09:15:30Z)
+  --relative-time
+        Print --print-starttime as an age instead (e.g. starttime=2h 14m ago)
+  --started-within <D>, --started-more-than <D>
+        Only report processes started at most / more than D ago (90, 45s,
+        5m, 2h, 3d or combined like 1h30m)
+  --print-cputime
+        Append CPU time used, utime+stime (e.g. cpu=1.25s)
+  --print-user-time, --print-sys-time
+        Append user-mode (utime) or kernel-mode (stime) CPU time separately
+        (e.g. utime=12.34s stime=0.56s); high stime points at syscall- or
+        I/O-heavy work, high utime at computation
+  --min-user-time <N>, --min-sys-time <N>
+        Only report processes with at least N seconds of user / system time
+  --print-pagefault-rate
+        Append minor plus major page faults per second of process lifetime
+        (e.g. pgflt_rate=20.50/s)
+  --print-minflt-rate, --print-majflt-rate
+        Append the minor or major page fault rate alone; a high major rate
+        means pages are being read back from disk under memory pressure
+  --min-majflt-rate <N>
+        Only report processes with at least N major page faults per second
+  --print-ctx-voluntary-pct
+        Append voluntary context switches per CPU second (CPU time below 1s
+        counts as 1s), a rough lock-wait rate: high values suggest lock or
+        I/O contention (e.g. ctx/cpu_s=308.5)
+  --min-ctx-rate <N>
+        Only report processes with at least N voluntary switches per CPU second
+  --print-uptime-pct
+        Append CPU time as a percentage of the process's lifetime, its
+        historical utilization (e.g. cpu_pct=12.50%; above 100% for busy
+        multi-threaded processes)
+  --min-cpu-pct <N>
+        Only report processes whose lifetime CPU usage is at least N%
+  --min-cpu-sec <N>
+        Only report processes that used at least N CPU seconds
+  --print-pss, --print-swap
+        Append proportional set size / swap usage (e.g. pss=2048kB)
+  --min-pss <KB>, --max-swap <KB>
+        Only report processes with PSS >= KB / swap <= KB
+  --print-oom
+        Append OOM score and adjustment (e.g. oom=667,adj=300)
+  --print-oom-adj
+        Append just the OOM adjustment (e.g. oom_adj=-500)
+  --set-oom-adj <N>
+        Write N (-1000..1000) to oom_score_adj of every match; lowering it
+        needs root or CAP_SYS_RESOURCE, failures are reported per process
+  --min-oom <N>
+        Only report processes with an OOM score of at least N (0-1000)
+  --print-coredump-filter
+        Append decoded coredump_filter flags (e.g. coredump=anon_private|elf_headers)
+  --coredump-filter <hex mask>
+        Only report processes whose coredump_filter has all bits of mask set
+  --print-statm
+        Append virtual, resident and shared memory (e.g. size=9984kB,rss=5120kB,shr=4096kB)
+  --min-rss <KB>, --max-rss <KB>
+        Only report processes with resident memory >= KB / <= KB
+  --print-tracer
+        Append the PID of the attached debugger or strace (tracer=0 if none)
+  --is-traced, --not-traced
+        Only report processes that are / are not being ptraced
+  --print-fd-count, --print-fd-limit
+        Append the number of open fds / the soft open-files limit
+        (e.g. fd_count=12, fd_limit=1024); --open-file-count is the same
+        as --print-fd-count
+  --print-fd-types
+        Append open fds per type: reg, sock and pipe always, dir, chr, blk,
+        eventfd, timerfd, signalfd, epoll, inotify and anon when present,
+        then other (e.g. reg=3,sock=2,pipe=1,epoll=1,other=0)
+  --print-socket-count
+        Append TCP, UDP and Unix sockets among the open fds, matched against
+        /proc/net/{tcp,tcp6,udp,udp6,unix} (e.g. tcp=2,udp=0,unix=1;
+        listening sockets count; read once per scan)
+  --print-listen-ports
+        Append the ports listened on: TCP sockets in LISTEN and bound UDP
+        sockets (e.g. listen=tcp:80,tcp:443,udp:53; none if there are none)
+  --listening-on <PORT>
+        Only report processes with a TCP socket listening on PORT
+  --has-tcp-connections
+        Only report processes with at least one TCP socket
+  --print-open-files [N]
+        Append what the first N fds (default 5) point to, ;-separated
+        (e.g. files=/dev/null;socket:[4242]); <EACCES> where unreadable
+  --no-deleted
+        Leave deleted files out of --print-open-files
+  --fd-usage-pct-min N
+        Only report processes using more than N% of their fd limit
+  --cgroup-filter <path>
+        Only report processes with a cgroup (any line of /proc/<pid>/cgroup)
+        matching path, by default as a substring; repeatable
+  --cgroup-mode <exact|prefix|suffix|contains>
+        How --cgroup-filter compares: exact cgroup, the subtree under path,
+        paths ending in it, or paths containing it (default)
+  --jvm-detect
+        Only report JVM processes, appending the main class and heap sizes
+        from the cmdline, e.g. java_class=com.example.App,xmx=512m,xms=256m
+  --print-interpreter
+        Append the script interpreter, its version and the script it runs,
+        e.g. interp=python,version=3.11,script=/opt/app/worker.py
+  --print-tls-info
+        Append the TLS libraries mapped into the process, e.g. tls=openssl
+  --print-seccomp
+        Append the seccomp mode from status: DISABLED, STRICT or FILTER
+  --has-tls
+        Only report processes with a known TLS library mapped
+  --has-seccomp
+        Only report processes running under seccomp (strict or filter)
+  --seccomp-mode <disabled|strict|filter>
+        Only report processes in exactly this seccomp mode
+  --print-mountinfo-count
+        Append the number of mounts the process sees in mountinfo, the same
+        for every process of a mount namespace (e.g. mounts=31)
+  --has-mount <PATH>
+        Only report processes that see a mount at exactly PATH
+  --uid <UID>, --gid <GID>
+        Only report processes whose real UID / GID is this number
+  --username <NAME>, --group <NAME>
+        Like --uid / --gid with NAME looked up in /etc/passwd / /etc/group
+        (local entries only); an unknown name is an error
+  --print-cwd
+        Append the working directory (cwd=- without ptrace access)
+  --cwd <path>
+        Only report processes whose working directory is path or below it
+  --print-wchan
+        Append the kernel function the process is blocked in (e.g.
+        wchan=do_sys_poll; 0 while running)
+  --wchan <text>
+        Only report processes blocked in a kernel function containing text
+  --print-syscall
+        Append the syscall the process is in, from /proc/<pid>/syscall (e.g.
+        syscall=epoll_wait; running in user space, blocked outside a
+        syscall, the number for syscalls without a known name)
+  --in-syscall <name>
+        Only report processes in this syscall (name or number), or running
+        or blocked
+  --print-maps-summary
+        Append mapping counts and the largest region from maps
+        (e.g. anon=12,file=30,special=4,max=65536kB)
+  --print-vm-size, --print-vm-peak, --print-vm-data, --print-vm-exe,
+  --print-vm-lib
+        Append VmSize, VmPeak, VmData, VmExe or VmLib from status in kB
+        (e.g. vm_size=4194304kB); all come from the one status read
+  --min-vm-size <KB>, --max-vm-size <KB>
+        Only report processes whose VmSize is at least / at most KB kB
+  --print-locked-memory
+        Append VmLck from status, memory locked with mlock/mlockall
+        (e.g. locked=128kB)
+  --locked-only
+        Only report processes with locked memory (VmLck above 0)
+  --min-locked-kb <KB>
+        Only report processes with at least KB kB of locked memory
+  --print-pinned-memory
+        Append VmPin from status, memory that cannot be swapped or moved
+        such as driver DMA buffers (e.g. pin=65536kB)
+  --min-pinned <KB>
+        Only report processes with at least KB kB of pinned memory
+  --print-huge-pages
+        Append VmHuge and HugetlbPages from status in kB
+        (e.g. huge=0kB,htlb=2097152kB; - where the kernel lacks the line)
+  --min-huge-pages <KB>
+        Only report processes with at least KB kB in huge pages
+  --print-stack-depth
+        Append the main thread's stack size in kB (VmStk, or the size of
+        the [stack] mapping; e.g. stack_kb=132)
+  --print-total-stack
+        Append the combined size of [stack] and the [stack:TID] thread
+        stacks (e.g. total_stack=8324kB); kernels since 4.5 no longer
+        label thread stacks, so there it equals the main stack
+  --min-stack-kb N
+        Only report processes whose main stack is at least N kB
+  --print-maps-regions
+        Append anonymous executable regions, typically JIT code or injected
+        payloads (e.g. anon_exec=rwx@7f1c30000000; none if there are none)
+  --has-wxmem
+        Only report processes with a mapping both writable and executable
+  --print-maps-count
+        Append the number of mappings (VMAs) in maps, which the kernel caps
+        at vm.max_map_count (e.g. maps_count=118)
+  --min-maps-count N, --max-maps-count N
+        Only report processes with at least / at most N mappings
+  --sort-maps-count, --sort-maps-count-desc
+        Print matches ordered by mapping count, fewest / most first
+  --print-io-full
+        Append all /proc/<pid>/io counters (needs root for other users' processes)
+  --print-io-rchar-wchar
+        Append the bytes read and written through syscalls, page cache hits
+        included, e.g. rchar=8675309,wchar=4096
+  --min-rchar N, --min-wchar N
+        Only report processes that read / wrote at least N bytes that way
+  --sort-read-bytes, --sort-write-bytes
+        Print matches ordered by storage bytes read / written, largest first
+  --print-sched, --print-sched-pressure
+        Append schedstat run/wait time and timeslices / run-queue wait share
+  --min-wait-time-ns N
+        Only report processes that waited at least N ns in the run queue
+  --print-rss-pct
+        Append resident memory as a percentage of MemTotal
+  --print-affinity
+        Append the CPUs the process may run on (Cpus_allowed_list)
+  --cpu-affinity LIST
+        Only report processes allowed on every CPU in LIST (e.g. 0-3,6)
+  --print-numa
+        Append the NUMA memory policy from numa_maps (e.g. default, bind:0)
+  --numa-policy POLICY
+        Only report processes whose NUMA policy is exactly POLICY
+  --print-numa-local-pct
+        Append the share of pages in numa_maps on the local node
+        (e.g. numa_local=93.75%)
+  --local-numa-node <N>
+        Node counted as local by the NUMA percentage options (default 0)
+  --min-numa-local-pct <N>
+        Only report processes with at least N% of their pages on the local node
+  --print-niceness
+        Append the nice value from stat (e.g. nice=5)
+  --renice <N>
+        Set the nice value (-20..19) of every match like renice -n N -p;
+        lowering it needs root or CAP_SYS_NICE
+  --print-scheduler
+        Append scheduling policy and priority (e.g. SCHED_FIFO/prio=80)
+  --sched-policy NAME
+        Only report processes scheduled as NAME (other, fifo, rr, batch, idle, deadline)
+  --print-ns-pid
+        Append the PID inside the innermost PID namespace (e.g. ns_pid=1)
+  --ns-pid-filter N
+        Only report processes whose innermost namespace PID is N, e.g. to
+        find the host PID of a container's PID 1
+  --ns-init-only
+        Only report processes that are PID 1 of their innermost PID
+        namespace (container inits); same as --ns-pid-filter 1
+  --has-cap CAP, --no-cap CAP
+        Only report processes with / without CAP (e.g. CAP_SYS_ADMIN or
+        net_admin) in their effective capability set
+  --any-cap
+        Only report processes with any effective capability
+  --print-caps
+        Append the effective capabilities by name
+        (caps=net_bind_service,net_raw, or caps=none); with -v, as with
+        --print-caps-verbose
+  --print-caps-verbose
+        Append the effective capabilities, each with a one-line
+        description of what it allows (caps_verbose=net_raw (use RAW and
+        PACKET sockets); ...)
+  --print-ns
+        Append namespace inodes (pid_ns=N,mnt_ns=M,net_ns=K,uts_ns=..,
+        ipc_ns=..,user_ns=..); needs ptrace access to the process
+  --ns-type TYPE INODE
+        Only report processes whose TYPE namespace (pid, mnt, net, uts,
+        ipc, user) has inode INODE
+  --same-ns-as PID
+        Only report processes in the same PID namespace as PID
+  --host-ns-only
+        Only report processes in the host PID namespace (that of PID 1),
+        skipping everything running inside containers"#;
+
+fn print_help(program: &str) {
+    println!(
+        "Usage: {program} [-q] [-s] [-v] [-h] [options] <program name or path>\n{OPTIONS_HELP}"
+    );
+}
+
+fn parse_signal(value: &str) -> Result<Signal, i32> {
+    parse_signal_name(value).map_err(|e| {
+        log_error!("Error: {}; valid signals: {}", e, signal_names());
+        1
+    })
+}
+
+fn parse_column(value: &str) -> Result<Column, i32> {
+    value.parse().map_err(|_| {
+        log_error!(
+            "Error: unknown column '{}'; valid columns: {}",
+            value,
+            COLUMN_NAMES
+        );
+        1
+    })
+}
+
+fn usage_error(program: &str) -> i32 {
+    log_error!(
+        "Error: Missing program name or path\nUsage: {} [-qhs] <program name or path>",
+        program
+    );
+    1
+}
+
+// Value of a long option: inline `--key=value`, or the following argument.
+fn take_value(
+    argv: &[String],
+    i: &mut usize,
+    key: &str,
+    inline: Option<&str>,
+    program: &str,
+) -> Result<String, i32> {
+    if let Some(v) = inline {
+        return Ok(v.to_string());
+    }
+    *i += 1;
+    match argv.get(*i) {
+        Some(v) => Ok(v.clone()),
+        None => {
+            log_error!(
+                "Option '--{}' requires a value (see \"{} -h\")",
+                key,
+                program
+            );
+            Err(1)
+        }
+    }
+}
+
+fn invalid_value(key: &str, value: &str, program: &str) -> i32 {
+    log_error!(
+        "Invalid value '{}' for '--{}' (see \"{} -h\")",
+        value,
+        key,
+        program
+    );
+    1
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str, program: &str) -> Result<T, i32> {
+    value
+        .parse()
+        .map_err(|_| invalid_value(key, value, program))
+}
+
+// `--username` / `--group`: `name` in the local `db` file, failing at
+// startup rather than matching nothing.
+fn resolve_id(db: &str, name: &str, what: &str) -> Result<u32, i32> {
+    let users = match UserDb::load(db.as_ref()) {
+        Ok(users) => users,
+        Err(e) => {
+            log_error!("Error: read {} failed: {}", db, e);
+            return Err(1);
+        }
+    };
+    users.lookup(name).ok_or_else(|| {
+        log_error!("Error: unknown {}: {}", what, name);
+        1
+    })
+}
+
+fn push_column(columns: &mut Vec<Column>, col: Column) {
+    if !columns.contains(&col) {
+        columns.push(col);
+    }
+}
+
+pub fn parse_args_from_vec(argv: Vec<String>) -> Result<Cli, i32> {
+    let program = argv.first().cloned().unwrap_or_else(|| "fpid".to_string());
+    let mut cli = Cli::default();
+    let mut target: Option<String> = None;
+
+    let mut i = 1;
+    while i < argv.len() {
+        let arg = &argv[i];
+        if !arg.starts_with('-') || arg == "-" {
+            if target.is_some() {
+                // Extra positional args -> treat as usage error like C code
+                return Err(usage_error(&program));
+            }
+            target = Some(arg.clone());
+            i += 1;
+            continue;
+        }
+
+        if let Some(long) = arg.strip_prefix("--") {
+            let (key, inline) = match long.split_once('=') {
+                Some((k, v)) => (k, Some(v)),
+                None => (long, None),
+            };
+            match key {
+                "help" => {
+                    print_help(&program);
+                    return Err(0);
+                }
+                "name" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.targets.push(v);
+                }
+                "min-pid" | "pid-min" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.min_pid = Some(parse_value(key, &v, &program)?);
+                }
+                "max-pid" | "pid-max" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.max_pid = Some(parse_value(key, &v, &program)?);
+                }
+                "ppid-chain" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.ppid_chain = Some(parse_value(key, &v, &program)?);
+                }
+                "match-all" => cli.match_mode = MatchMode::All,
+                "match-cmdline" => {
+                    if !cfg!(feature = "aho-corasick") {
+                        log_error!(
+                            "Error: --match-cmdline needs fpid built with the aho-corasick feature"
+                        );
+                        return Err(1);
+                    }
+                    cli.match_cmdline = true;
+                }
+                "group-by-target" => cli.group_by_target = true,
+                "exclude-name" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.exclude_names.push(v);
+                }
+                "thread-name" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.thread_names.push(v);
+                }
+                "list-all" => cli.list_all = true,
+                "stat" => cli.stat = true,
+                "format" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.format = parse_value(key, &v, &program)?;
+                }
+                "columns" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    for name in v.split(',') {
+                        push_column(&mut cli.columns, parse_column(name)?);
+                    }
+                    cli.columns_only = true;
+                }
+                "print-age" => push_column(&mut cli.columns, Column::Age),
+                "print-environ-var" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    if v.is_empty() || v.contains(['=', '\0']) {
+                        return Err(invalid_value(key, &v, &program));
+                    }
+                    // Columns are Copy; the key lives for the whole run anyway.
+                    push_column(&mut cli.columns, Column::EnvVar(v.leak()));
+                }
+                "print-starttime" => push_column(&mut cli.columns, Column::StartTime),
+                "relative-time" => cli.relative_time = true,
+                "started-within" | "started-more-than" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let Some(d) = parse_duration(&v) else {
+                        return Err(invalid_value(key, &v, &program));
+                    };
+                    cli.filters.push(if key == "started-within" {
+                        Filter::StartedWithin(d)
+                    } else {
+                        Filter::StartedMoreThan(d)
+                    });
+                }
+                "print-cputime" => push_column(&mut cli.columns, Column::CpuTime),
+                "print-user-time" => push_column(&mut cli.columns, Column::UserTime),
+                "print-sys-time" => push_column(&mut cli.columns, Column::SysTime),
+                "print-pagefault-rate" => push_column(&mut cli.columns, Column::FaultRate),
+                "print-minflt-rate" => push_column(&mut cli.columns, Column::MinFltRate),
+                "print-majflt-rate" => push_column(&mut cli.columns, Column::MajFltRate),
+                "min-majflt-rate" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinMajFltRate(parse_value(key, &v, &program)?));
+                }
+                "print-ctx-voluntary-pct" => push_column(&mut cli.columns, Column::CtxRate),
+                "min-ctx-rate" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinCtxRate(parse_value(key, &v, &program)?));
+                }
+                "print-uptime-pct" => push_column(&mut cli.columns, Column::CpuPct),
+                "print-pss" => push_column(&mut cli.columns, Column::Pss),
+                "print-swap" => push_column(&mut cli.columns, Column::Swap),
+                "print-oom" => push_column(&mut cli.columns, Column::Oom),
+                "print-oom-adj" => push_column(&mut cli.columns, Column::OomAdj),
+                "set-oom-adj" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let adj = parse_value(key, &v, &program)?;
+                    if !OOM_ADJ_RANGE.contains(&adj) {
+                        log_error!(
+                            "Error: --set-oom-adj must be between -1000 and 1000, got {}",
+                            adj
+                        );
+                        return Err(1);
+                    }
+                    cli.set_oom_adj = Some(adj);
+                }
+                "min-oom" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinOom(parse_value(key, &v, &program)?));
+                }
+                "print-coredump-filter" => push_column(&mut cli.columns, Column::CoredumpFilter),
+                "coredump-filter" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    match parse_coredump_mask(&v) {
+                        Some(mask) => cli.filters.push(Filter::CoredumpFilter(mask)),
+                        None => return Err(invalid_value(key, &v, &program)),
+                    }
+                }
+                "print-statm" => push_column(&mut cli.columns, Column::Statm),
+                "print-tracer" => push_column(&mut cli.columns, Column::Tracer),
+                "is-traced" => cli.filters.push(Filter::Traced(true)),
+                "not-traced" => cli.filters.push(Filter::Traced(false)),
+                "print-fd-count" | "open-file-count" => {
+                    push_column(&mut cli.columns, Column::FdCount)
+                }
+                "print-open-files" => {
+                    // The count is optional, so only a numeric next argument is taken.
+                    let max = match inline {
+                        Some(v) => parse_value(key, v, &program)?,
+                        None => match argv.get(i + 1).and_then(|v| v.parse().ok()) {
+                            Some(n) => {
+                                i += 1;
+                                n
+                            }
+                            None => DEFAULT_OPEN_FILES,
+                        },
+                    };
+                    push_column(&mut cli.columns, Column::OpenFiles { max, deleted: true });
+                }
+                "no-deleted" => cli.no_deleted = true,
+                "print-socket-count" => push_column(&mut cli.columns, Column::Sockets),
+                "print-listen-ports" => push_column(&mut cli.columns, Column::ListenPorts),
+                "listening-on" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::ListeningOn(parse_value(key, &v, &program)?));
+                }
+                "has-tcp-connections" => cli.filters.push(Filter::HasTcp),
+                "print-fd-types" => push_column(&mut cli.columns, Column::FdTypes),
+                "print-fd-limit" => push_column(&mut cli.columns, Column::FdLimit),
+                "fd-usage-pct-min" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::FdUsagePctMin(parse_value(key, &v, &program)?));
+                }
+                "cgroup-filter" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters.push(Filter::Cgroup {
+                        path: v,
+                        mode: CgroupMatchMode::default(),
+                    });
+                }
+                "cgroup-mode" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.cgroup_mode = Some(parse_value(key, &v, &program)?);
+                }
+                "jvm-detect" => {
+                    cli.filters.push(Filter::Jvm);
+                    push_column(&mut cli.columns, Column::Jvm);
+                }
+                "print-interpreter" => push_column(&mut cli.columns, Column::Interpreter),
+                "print-tls-info" => push_column(&mut cli.columns, Column::TlsInfo),
+                "has-tls" => cli.filters.push(Filter::HasTls),
+                "print-seccomp" => push_column(&mut cli.columns, Column::Seccomp),
+                "has-seccomp" => cli.filters.push(Filter::HasSeccomp),
+                "seccomp-mode" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let mode =
+                        parse_seccomp_mode(&v).ok_or_else(|| invalid_value(key, &v, &program))?;
+                    cli.filters.push(Filter::SeccompMode(mode));
+                }
+                "print-mountinfo-count" => push_column(&mut cli.columns, Column::MountCount),
+                "has-mount" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters.push(Filter::HasMount(PathBuf::from(v)));
+                }
+                "uid" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::Uid(parse_value(key, &v, &program)?));
+                }
+                "gid" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::Gid(parse_value(key, &v, &program)?));
+                }
+                "username" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::Uid(resolve_id(PASSWD_PATH, &v, "user")?));
+                }
+                "group" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::Gid(resolve_id(GROUP_PATH, &v, "group")?));
+                }
+                "print-cwd" => push_column(&mut cli.columns, Column::Cwd),
+                "cwd" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters.push(Filter::Cwd(PathBuf::from(v)));
+                }
+                "print-wchan" => push_column(&mut cli.columns, Column::Wchan),
+                "print-syscall" => push_column(&mut cli.columns, Column::Syscall),
+                "in-syscall" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let state = SyscallState::parse_arg(&v)
+                        .ok_or_else(|| invalid_value(key, &v, &program))?;
+                    cli.filters.push(Filter::InSyscall(state));
+                }
+                "wchan" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters.push(Filter::Wchan(v));
+                }
+                "print-maps-summary" => push_column(&mut cli.columns, Column::MapsSummary),
+                "min-rss" | "max-rss" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let kb = Some(parse_value(key, &v, &program)?);
+                    let (min_kb, max_kb) = if key == "min-rss" {
+                        (kb, None)
+                    } else {
+                        (None, kb)
+                    };
+                    cli.filters.push(Filter::Statm(StatmFilter {
+                        field: StatmField::Resident,
+                        min_kb,
+                        max_kb,
+                    }));
+                }
+                "self-test" => cli.self_test = true,
+                "no-header" | "header-only" => {
+                    let mode = if key == "no-header" {
+                        HeaderMode::Suppress
+                    } else {
+                        HeaderMode::Only
+                    };
+                    if cli.header_mode != HeaderMode::Emit && cli.header_mode != mode {
+                        log_error!("Error: --no-header and --header-only are mutually exclusive");
+                        return Err(1);
+                    }
+                    cli.header_mode = mode;
+                }
+                "pipe-null" => cli.pipe_null = true,
+                "proc-root" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    if v.is_empty() {
+                        return Err(invalid_value(key, &v, &program));
+                    }
+                    cli.proc_root = Some(v.into());
+                }
+                "signal" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.signal = Some(parse_signal(&v)?);
+                }
+                "dry-run" => cli.dry_run = true,
+                "confirm" => cli.confirm = true,
+                "no-partial" => cli.no_partial = true,
+                "stats" => cli.stats = true,
+                "stats-json" => cli.stats_json = true,
+                "debug" => cli.debug = true,
+                "watch" => cli.watch = true,
+                "watch-events" => {
+                    cli.watch = true;
+                    cli.watch_events = true;
+                }
+                "pid-file" => {
+                    cli.pid_file = Some(take_value(&argv, &mut i, key, inline, &program)?.into());
+                }
+                "write-pid-file" => {
+                    cli.write_pid_file =
+                        Some(take_value(&argv, &mut i, key, inline, &program)?.into());
+                }
+                "write-pid-file-mode" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    match u32::from_str_radix(&v, 8) {
+                        Ok(mode) if mode <= 0o7777 => cli.write_pid_file_mode = Some(mode),
+                        _ => return Err(invalid_value(key, &v, &program)),
+                    }
+                }
+                "write-pid-file-dir-create" => cli.write_pid_file_dir_create = true,
+                "compare-snapshot" => {
+                    cli.compare_snapshot =
+                        Some(take_value(&argv, &mut i, key, inline, &program)?.into());
+                }
+                "proc-batch-size" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.proc_batch_size = parse_value(key, &v, &program)?;
+                }
+                "proc-batch-sleep-us" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.proc_batch_sleep_us = parse_value(key, &v, &program)?;
+                }
+                "max-proc-read-bytes" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.max_proc_read_bytes = Some(parse_value(key, &v, &program)?);
+                }
+                "output" => {
+                    cli.output = Some(take_value(&argv, &mut i, key, inline, &program)?.into());
+                }
+                "append" => cli.append = true,
+                "audit-log" => {
+                    cli.audit_log = Some(take_value(&argv, &mut i, key, inline, &program)?.into());
+                }
+                "output-mode" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    match u32::from_str_radix(&v, 8) {
+                        Ok(mode) if mode <= 0o7777 => cli.output_mode = Some(mode),
+                        _ => return Err(invalid_value(key, &v, &program)),
+                    }
+                }
+                "daemon" => cli.daemon = true,
+                "daemon-stop" => cli.daemon_stop = true,
+                "socket" => {
+                    cli.socket = Some(take_value(&argv, &mut i, key, inline, &program)?.into());
+                }
+                "pidfile" => {
+                    cli.pidfile = Some(take_value(&argv, &mut i, key, inline, &program)?.into());
+                }
+                "print-io-full" => push_column(&mut cli.columns, Column::IoFull),
+                "print-io-rchar-wchar" => push_column(&mut cli.columns, Column::IoChars),
+                "min-rchar" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinRchar(parse_value(key, &v, &program)?));
+                }
+                "min-wchar" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinWchar(parse_value(key, &v, &program)?));
+                }
+                "sort-by" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.sort.push(parse_value(key, &v, &program)?);
+                }
+                "sort-read-bytes" => cli
+                    .sort
+                    .push(SortSpec::new(SortField::ReadBytes, SortDirection::Desc)),
+                "sort-write-bytes" => cli
+                    .sort
+                    .push(SortSpec::new(SortField::WriteBytes, SortDirection::Desc)),
+                "sort-maps-count" | "sort-maps-count-desc" => {
+                    let direction = if key == "sort-maps-count" {
+                        SortDirection::Asc
+                    } else {
+                        SortDirection::Desc
+                    };
+                    cli.sort
+                        .push(SortSpec::new(SortField::MapsCount, direction));
+                }
+                "print-vm-size" => push_column(&mut cli.columns, Column::Vm(VmField::Size)),
+                "print-vm-peak" => push_column(&mut cli.columns, Column::Vm(VmField::Peak)),
+                "print-vm-data" => push_column(&mut cli.columns, Column::Vm(VmField::Data)),
+                "print-vm-exe" => push_column(&mut cli.columns, Column::Vm(VmField::Exe)),
+                "print-vm-lib" => push_column(&mut cli.columns, Column::Vm(VmField::Lib)),
+                "human-readable" => cli.human_readable = true,
+                "min-vm-size" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinVmSizeKb(parse_value(key, &v, &program)?));
+                }
+                "max-vm-size" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MaxVmSizeKb(parse_value(key, &v, &program)?));
+                }
+                "print-locked-memory" => push_column(&mut cli.columns, Column::Vm(VmField::Lck)),
+                "locked-only" => cli.filters.push(Filter::MinLockedKb(1)),
+                "min-locked-kb" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinLockedKb(parse_value(key, &v, &program)?));
+                }
+                "print-pinned-memory" => push_column(&mut cli.columns, Column::Vm(VmField::Pin)),
+                "min-pinned" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinPinnedKb(parse_value(key, &v, &program)?));
+                }
+                "print-huge-pages" => push_column(&mut cli.columns, Column::HugePages),
+                "min-huge-pages" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinHugePagesKb(parse_value(key, &v, &program)?));
+                }
+                "print-stack-depth" => push_column(&mut cli.columns, Column::StackKb),
+                "print-total-stack" => push_column(&mut cli.columns, Column::TotalStack),
+                "min-stack-kb" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinStackKb(parse_value(key, &v, &program)?));
+                }
+                "print-maps-regions" => push_column(&mut cli.columns, Column::AnonExec),
+                "has-wxmem" => cli.filters.push(Filter::HasWxMem),
+                "print-maps-count" => push_column(&mut cli.columns, Column::MapsCount),
+                "min-maps-count" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinMapsCount(parse_value(key, &v, &program)?));
+                }
+                "max-maps-count" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MaxMapsCount(parse_value(key, &v, &program)?));
+                }
+                "print-affinity" => push_column(&mut cli.columns, Column::Affinity),
+                "cpu-affinity" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    match parse_cpu_list(&v) {
+                        Ok(cpus) if !cpus.is_empty() => cli.filters.push(Filter::CpuAffinity(cpus)),
+                        _ => return Err(invalid_value(key, &v, &program)),
+                    }
+                }
+                "print-numa" => push_column(&mut cli.columns, Column::Numa),
+                "print-numa-local-pct" => push_column(&mut cli.columns, Column::NumaLocalPct(0)),
+                "local-numa-node" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.local_numa_node = Some(parse_value(key, &v, &program)?);
+                }
+                "min-numa-local-pct" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let pct: f64 = parse_value(key, &v, &program)?;
+                    if !pct.is_finite() || pct < 0.0 {
+                        return Err(invalid_value(key, &v, &program));
+                    }
+                    cli.filters.push(Filter::MinNumaLocalPct { node: 0, pct });
+                }
+                "numa-policy" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters.push(Filter::NumaPolicy(v));
+                }
+                "print-niceness" => push_column(&mut cli.columns, Column::Nice),
+                "renice" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let nice = parse_value(key, &v, &program)?;
+                    if !NICE_RANGE.contains(&nice) {
+                        log_error!("Error: --renice must be between -20 and 19, got {}", nice);
+                        return Err(1);
+                    }
+                    cli.renice = Some(nice);
+                }
+                "print-scheduler" => push_column(&mut cli.columns, Column::Scheduler),
+                "sched-policy" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    match parse_policy_name(&v) {
+                        Some(code) => cli.filters.push(Filter::SchedPolicy(code)),
+                        None => return Err(invalid_value(key, &v, &program)),
+                    }
+                }
+                "print-ns-pid" => push_column(&mut cli.columns, Column::NsPid),
+                "ns-pid-filter" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::NsPid(parse_value(key, &v, &program)?));
+                }
+                "same-ns-as" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::SamePidNs(parse_value(key, &v, &program)?));
+                }
+                // Same test as is_ns_init, on the status the scan already caches.
+                "ns-init-only" => cli.filters.push(Filter::NsPid(1)),
+                "has-cap" | "no-cap" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let Some(bit) = cap_bit(&v) else {
+                        return Err(invalid_value(key, &v, &program));
+                    };
+                    cli.filters.push(if key == "has-cap" {
+                        Filter::HasCap(bit)
+                    } else {
+                        Filter::NoCap(bit)
+                    });
+                }
+                "any-cap" => cli.filters.push(Filter::AnyCap),
+                "print-caps" => push_column(&mut cli.columns, Column::Caps { verbose: false }),
+                "print-caps-verbose" => {
+                    push_column(&mut cli.columns, Column::Caps { verbose: true });
+                }
+                "print-ns" => push_column(&mut cli.columns, Column::Ns),
+                "ns-type" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let Some(kind) = ns_type(&v) else {
+                        return Err(invalid_value(key, &v, &program));
+                    };
+                    let inode = take_value(&argv, &mut i, key, None, &program)?;
+                    cli.filters.push(Filter::Namespace {
+                        kind,
+                        inode: parse_value(key, &inode, &program)?,
+                    });
+                }
+                "host-ns-only" => cli.filters.push(Filter::SamePidNs(1)),
+                "print-rss-pct" => push_column(&mut cli.columns, Column::RssPct),
+                "print-sched" => push_column(&mut cli.columns, Column::Sched),
+                "print-sched-pressure" => push_column(&mut cli.columns, Column::SchedPressure),
+                "min-wait-time-ns" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinWaitNs(parse_value(key, &v, &program)?));
+                }
+                "min-pss" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinPssKb(parse_value(key, &v, &program)?));
+                }
+                "max-swap" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MaxSwapKb(parse_value(key, &v, &program)?));
+                }
+                "min-cpu-pct" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    let pct: f64 = parse_value(key, &v, &program)?;
+                    if !pct.is_finite() || pct < 0.0 {
+                        return Err(invalid_value(key, &v, &program));
+                    }
+                    cli.filters.push(Filter::MinCpuPct(pct));
+                }
+                "min-user-time" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinUserSec(parse_value(key, &v, &program)?));
+                }
+                "min-sys-time" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinSysSec(parse_value(key, &v, &program)?));
+                }
+                "min-cpu-sec" => {
+                    let v = take_value(&argv, &mut i, key, inline, &program)?;
+                    cli.filters
+                        .push(Filter::MinCpuSec(parse_value(key, &v, &program)?));
+                }
+                _ => {
+                    log_error!("Unknown option '--{}' (see \"{} -h\")", key, program);
+                    return Err(1);
+                }
+            }
+            i += 1;
+            continue;
+        }
+
+        let short = &arg[1..];
+        for (pos, ch) in short.char_indices() {
+            match ch {
+                // -o takes the rest of the cluster (`-ofile`) or the next argument.
+                'o' => {
+                    let rest = &short[pos + 1..];
+                    let inline = (!rest.is_empty()).then_some(rest);
+                    cli.output =
+                        Some(take_value(&argv, &mut i, "output", inline, &program)?.into());
+                    break;
+                }
+                'S' => {
+                    let rest = &short[pos + 1..];
+                    let inline = (!rest.is_empty()).then_some(rest);
+                    let v = take_value(&argv, &mut i, "signal", inline, &program)?;
+                    cli.signal = Some(parse_signal(&v)?);
+                    break;
+                }
+                'q' => cli.flags.quiet = true,
+                'H' => cli.human_readable = true,
+                's' => cli.flags.single = true,
+                'v' => cli.flags.verbose = true,
+                'h' => {
+                    print_help(&program);
+                    return Err(0);
+                }
+                _ => {
+                    log_error!("Unknown option '{}' (see \"{} -h\")", ch, program);
+                    return Err(1);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    if let Some(t) = target {
+        cli.targets.insert(0, t);
+    }
+    let threads = !cli.thread_names.is_empty();
+    let header_only = cli.header_mode == HeaderMode::Only;
+    if header_only && !matches!(cli.format, Format::Tsv | Format::Procps | Format::Table) {
+        log_error!("Error: --header-only requires --format=tsv, --format=procps or --format=table");
+        return Err(1);
+    }
+    let no_target_needed = cli.list_all
+        || cli.self_test
+        || cli.daemon
+        || cli.daemon_stop
+        || threads
+        || header_only
+        || cli.ppid_chain.is_some();
+    if cli.targets.is_empty() && !no_target_needed {
+        return Err(usage_error(&program));
+    }
+    if threads && !cli.targets.is_empty() {
+        log_error!("Error: --thread-name cannot be combined with process targets");
+        return Err(1);
+    }
+    if cli.match_cmdline && cli.match_mode == MatchMode::All {
+        log_error!("Error: --match-cmdline cannot be combined with --match-all");
+        return Err(1);
+    }
+    if cli.group_by_target
+        && (!matches!(cli.format, Format::Auto | Format::Text)
+            || !cli.columns.is_empty()
+            || !cli.sort.is_empty())
+    {
+        log_error!(
+            "Error: --group-by-target only prints plain PIDs (no --format, columns or --sort-by)"
+        );
+        return Err(1);
+    }
+    if let Some(want) = cli.cgroup_mode {
+        for filter in &mut cli.filters {
+            if let Filter::Cgroup { mode, .. } = filter {
+                *mode = want;
+            }
+        }
+    }
+    if let Some(local) = cli.local_numa_node {
+        for col in &mut cli.columns {
+            if let Column::NumaLocalPct(node) = col {
+                *node = local;
+            }
+        }
+        for filter in &mut cli.filters {
+            if let Filter::MinNumaLocalPct { node, .. } = filter {
+                *node = local;
+            }
+        }
+    }
+    if cli.no_deleted {
+        for col in &mut cli.columns {
+            if let Column::OpenFiles { deleted, .. } = col {
+                *deleted = false;
+            }
+        }
+    }
+    if cli.relative_time {
+        for col in &mut cli.columns {
+            if *col == Column::StartTime {
+                *col = Column::StartTimeRelative;
+            }
+        }
+    }
+    if cli.min_pid.is_some() || cli.max_pid.is_some() {
+        let pid_max = read_pid_max();
+        if let Some(max) = cli.max_pid
+            && max > pid_max
+        {
+            log_warn!(
+                "--max-pid {} exceeds pid_max {}; using {}",
+                max,
+                pid_max,
+                pid_max
+            );
+        }
+        match pid_range(cli.min_pid, cli.max_pid, pid_max) {
+            Ok((min, max)) => cli.filters.push(Filter::PidRange { min, max }),
+            Err(e) => {
+                log_error!("Error: {}", e);
+                return Err(1);
+            }
+        }
+    }
+    // -v --print-caps is --print-caps-verbose.
+    if cli.flags.verbose {
+        let columns = std::mem::take(&mut cli.columns);
+        for col in columns {
+            let col = match col {
+                Column::Caps { .. } => Column::Caps { verbose: true },
+                col => col,
+            };
+            push_column(&mut cli.columns, col);
+        }
+    }
+    if cli.format == Format::Msgpack && cli.list_all {
+        log_error!("Error: --format=msgpack is not supported with --list-all");
+        return Err(1);
+    }
+    if cli.compare_snapshot.is_some() && cli.watch {
+        log_error!("Error: --compare-snapshot cannot be combined with --watch");
+        return Err(1);
+    }
+    if cli.write_pid_file.is_some() && (cli.watch || cli.compare_snapshot.is_some()) {
+        log_error!("Error: --write-pid-file cannot be combined with --watch or --compare-snapshot");
+        return Err(1);
+    }
+    if cli.pid_file.is_some() && (cli.watch || cli.compare_snapshot.is_some()) {
+        log_error!("Error: --pid-file cannot be combined with --watch or --compare-snapshot");
+        return Err(1);
+    }
+    if cli.format == Format::Json && !cli.list_all && !cli.watch && cli.compare_snapshot.is_none() {
+        log_error!(
+            "Error: --format=json is only supported with --list-all, --watch or --compare-snapshot"
+        );
+        return Err(1);
+    }
+    Ok(cli)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_help_returns_code0() {
+        let argv = vec!["fpid".to_string(), "-h".to_string()];
+        let res = parse_args_from_vec(argv);
+        assert!(matches!(res, Err(0)));
+    }
+
+    #[test]
+    fn test_missing_target() {
+        let argv = vec!["fpid".to_string()];
+        let res = parse_args_from_vec(argv);
+        assert!(matches!(res, Err(1)));
+    }
+
+    #[test]
+    fn test_parse_flags_and_target() {
+        let argv = vec!["fpid".to_string(), "-qs".to_string(), "sshd".to_string()];
+        let cli = parse_args_from_vec(argv).unwrap();
+        assert_eq!(
+            cli.flags,
+            Flags {
+                quiet: true,
+                single: true,
+                verbose: false,
+            }
+        );
+        assert_eq!(cli.targets, vec!["sshd"]);
+    }
+
+    #[test]
+    fn test_unknown_option() {
+        let argv = vec!["fpid".to_string(), "-x".to_string()];
+        let res = parse_args_from_vec(argv);
+        assert!(matches!(res, Err(1)));
+    }
+
+    #[test]
+    fn test_extra_positional_error() {
+        let argv = vec![
+            "fpid".to_string(),
+            "-q".to_string(),
+            "sshd".to_string(),
+            "extra".to_string(),
+        ];
+        let res = parse_args_from_vec(argv);
+        assert!(matches!(res, Err(1)));
+    }
+
+    #[test]
+    fn test_name_targets() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--name",
+            "nginx",
+            "sshd",
+            "--name=/usr/bin/bash",
+        ]))
+        .unwrap();
+        assert_eq!(cli.targets, vec!["sshd", "nginx", "/usr/bin/bash"]);
+        assert_eq!(cli.match_mode, MatchMode::Any);
+        let cli = parse_args_from_vec(args(&["fpid", "--name", "nginx"])).unwrap();
+        assert_eq!(cli.targets, vec!["nginx"]);
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--match-all",
+            "python3",
+            "--name",
+            "/usr/bin/python3.11",
+        ]))
+        .unwrap();
+        assert_eq!(cli.match_mode, MatchMode::All);
+        assert_eq!(cli.targets, vec!["python3", "/usr/bin/python3.11"]);
+        assert!(matches!(
+            parse_args_from_vec(args(&["fpid", "--name"])),
+            Err(1)
+        ));
+        assert!(matches!(
+            parse_args_from_vec(args(&["fpid", "--bogus", "x"])),
+            Err(1)
+        ));
+    }
+
+    #[test]
+    fn test_print_columns() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--print-age", "sshd", "--print-age"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Age]);
+    }
+
+    #[test]
+    fn test_cpu_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-cputime",
+            "--min-cpu-sec=2.5",
+            "java",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::CpuTime]);
+        assert_eq!(cli.filters, vec![Filter::MinCpuSec(2.5)]);
+        assert!(matches!(
+            parse_args_from_vec(args(&["fpid", "--min-cpu-sec", "lots", "java"])),
+            Err(1)
+        ));
+    }
+
+    #[test]
+    fn test_smaps_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-swap",
+            "--print-pss",
+            "--min-pss",
+            "100",
+            "--max-swap=0",
+            "java",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Swap, Column::Pss]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinPssKb(100), Filter::MaxSwapKb(0)]
+        );
+    }
+
+    #[test]
+    fn test_oom_options() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-oom", "--min-oom", "800", "chrome"]))
+            .unwrap();
+        assert_eq!(cli.columns, vec![Column::Oom]);
+        assert_eq!(cli.filters, vec![Filter::MinOom(800)]);
+    }
+
+    #[test]
+    fn test_coredump_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-coredump-filter",
+            "--coredump-filter=0x10",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::CoredumpFilter]);
+        assert_eq!(cli.filters, vec![Filter::CoredumpFilter(0x10)]);
+        assert!(matches!(
+            parse_args_from_vec(args(&["fpid", "--coredump-filter", "xyz", "x"])),
+            Err(1)
+        ));
+    }
+
+    #[test]
+    fn test_list_all_without_target() {
+        let cli = parse_args_from_vec(args(&["fpid", "--list-all", "--format=json"])).unwrap();
+        assert!(cli.list_all);
+        assert!(cli.targets.is_empty());
+        assert_eq!(cli.format, Format::Json);
+        assert!(matches!(
+            parse_args_from_vec(args(&["fpid", "--format", "json", "sshd"])),
+            Err(1)
+        ));
+        assert!(matches!(
+            parse_args_from_vec(args(&["fpid", "--format", "yaml", "--list-all"])),
+            Err(1)
+        ));
+    }
+
+    #[test]
+    fn test_stat_mode() {
+        let cli = parse_args_from_vec(args(&["fpid", "--stat", "sshd"])).unwrap();
+        assert!(cli.stat);
+    }
+
+    #[test]
+    fn test_statm_options() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-statm", "--min-rss", "1024", "x"]))
+            .unwrap();
+        assert_eq!(cli.columns, vec![Column::Statm]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::Statm(StatmFilter {
+                field: StatmField::Resident,
+                min_kb: Some(1024),
+                max_kb: None
+            })]
+        );
+    }
+
+    #[test]
+    fn test_io_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-io-full",
+            "--sort-write-bytes",
+            "--print-io-rchar-wchar",
+            "--min-rchar",
+            "1048576",
+            "--min-wchar=0",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::IoFull, Column::IoChars]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinRchar(1048576), Filter::MinWchar(0)]
+        );
+        assert_eq!(
+            cli.sort,
+            vec![SortSpec::new(SortField::WriteBytes, SortDirection::Desc)]
+        );
+    }
+
+    #[test]
+    fn test_sched_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-sched-pressure",
+            "--min-wait-time-ns=100",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::SchedPressure]);
+        assert_eq!(cli.filters, vec![Filter::MinWaitNs(100)]);
+    }
+
+    #[test]
+    fn test_format_procps() {
+        let cli = parse_args_from_vec(args(&["fpid", "--format=procps", "sshd"])).unwrap();
+        assert_eq!(cli.format, Format::Procps);
+    }
+
+    #[test]
+    fn test_self_test_needs_no_target() {
+        let cli = parse_args_from_vec(args(&["fpid", "--self-test"])).unwrap();
+        assert!(cli.self_test);
+    }
+
+    #[test]
+    fn test_cpu_affinity_option() {
+        let cli = parse_args_from_vec(args(&["fpid", "--cpu-affinity", "0-1,4", "x"])).unwrap();
+        assert_eq!(cli.filters, vec![Filter::CpuAffinity(vec![0, 1, 4])]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--cpu-affinity=3-1", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_sched_policy_option() {
+        let cli = parse_args_from_vec(args(&["fpid", "--sched-policy=SCHED_RR", "x"])).unwrap();
+        assert_eq!(cli.filters, vec![Filter::SchedPolicy(2)]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--sched-policy", "turbo", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_daemon_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--daemon",
+            "--socket",
+            "/tmp/f.sock",
+            "--pidfile=/tmp/f.pid",
+        ]))
+        .unwrap();
+        assert!(cli.daemon);
+        assert_eq!(cli.socket, Some(PathBuf::from("/tmp/f.sock")));
+        assert_eq!(cli.pidfile, Some(PathBuf::from("/tmp/f.pid")));
+        assert!(
+            parse_args_from_vec(args(&["fpid", "--daemon-stop"]))
+                .unwrap()
+                .daemon_stop
+        );
+    }
+
+    #[test]
+    fn test_verbose_flag() {
+        let cli = parse_args_from_vec(args(&["fpid", "-v", "sshd"])).unwrap();
+        assert!(cli.flags.verbose);
+    }
+
+    #[test]
+    fn test_format_tsv() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--format=tsv", "--no-header", "sshd"])).unwrap();
+        assert_eq!(cli.format, Format::Tsv);
+        assert_eq!(cli.header_mode, HeaderMode::Suppress);
+    }
+
+    #[test]
+    fn test_header_mode() {
+        let cli = parse_args_from_vec(args(&["fpid", "--format=tsv", "sshd"])).unwrap();
+        assert_eq!(cli.header_mode, HeaderMode::Emit);
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--format=tsv",
+            "--print-pss",
+            "--header-only",
+        ]))
+        .unwrap();
+        assert_eq!(cli.header_mode, HeaderMode::Only);
+        assert!(cli.targets.is_empty());
+        assert!(parse_args_from_vec(args(&["fpid", "--header-only"])).is_err());
+        let cli = parse_args_from_vec(args(&["fpid", "--format=table", "--header-only"])).unwrap();
+        assert_eq!(cli.header_mode, HeaderMode::Only);
+        assert!(
+            parse_args_from_vec(args(&[
+                "fpid",
+                "--format=tsv",
+                "--no-header",
+                "--header-only",
+                "x"
+            ]))
+            .is_err()
+        );
+        // Repeating the same one is fine.
+        let cli = parse_args_from_vec(args(&["fpid", "--no-header", "--no-header", "x"])).unwrap();
+        assert_eq!(cli.header_mode, HeaderMode::Suppress);
+    }
+
+    #[test]
+    fn test_output_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "-qo",
+            "/tmp/out",
+            "--append",
+            "--output-mode=600",
+            "x",
+        ]))
+        .unwrap();
+        assert!(cli.flags.quiet);
+        assert_eq!(cli.output, Some(PathBuf::from("/tmp/out")));
+        assert!(cli.append);
+        assert_eq!(cli.output_mode, Some(0o600));
+        let cli = parse_args_from_vec(args(&["fpid", "-o/tmp/o2", "x"])).unwrap();
+        assert_eq!(cli.output, Some(PathBuf::from("/tmp/o2")));
+        assert_eq!(cli.targets, vec!["x"]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--output-mode", "999", "x"])),
+            Err(1)
+        );
+        assert_eq!(parse_args_from_vec(args(&["fpid", "x", "-o"])), Err(1));
+    }
+
+    #[test]
+    fn test_signal_options() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--dry-run", "-S", "SIGKILL", "bash"])).unwrap();
+        assert!(cli.dry_run);
+        assert_eq!(cli.signal.map(|s| s.number), Some(9));
+        assert_eq!(cli.targets, vec!["bash"]);
+        let cli = parse_args_from_vec(args(&["fpid", "-STERM", "bash"])).unwrap();
+        assert_eq!(cli.signal.map(|s| s.name), Some("SIGTERM"));
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--signal", "SIGTERMM", "bash"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_no_partial_flag() {
+        assert!(
+            parse_args_from_vec(args(&["fpid", "--no-partial", "x"]))
+                .unwrap()
+                .no_partial
+        );
+    }
+
+    #[test]
+    fn test_stats_flags() {
+        let cli = parse_args_from_vec(args(&["fpid", "--stats", "--stats-json", "x"])).unwrap();
+        assert!(cli.stats && cli.stats_json);
+    }
+
+    #[test]
+    fn test_debug_flag() {
+        assert!(
+            parse_args_from_vec(args(&["fpid", "--debug", "x"]))
+                .unwrap()
+                .debug
+        );
+    }
+
+    #[test]
+    fn test_confirm_flag() {
+        let cli = parse_args_from_vec(args(&["fpid", "-S", "TERM", "--confirm", "x"])).unwrap();
+        assert!(cli.confirm);
+    }
+
+    #[test]
+    fn test_thread_name() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--thread-name",
+            "GC Thread#0",
+            "--thread-name=worker",
+        ]))
+        .unwrap();
+        assert_eq!(cli.thread_names, vec!["GC Thread#0", "worker"]);
+        assert!(cli.targets.is_empty());
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--thread-name", "worker", "java"])).err(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_ns_pid_options() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-ns-pid", "--ns-pid-filter=1", "x"]))
+            .unwrap();
+        assert_eq!(cli.columns, vec![Column::NsPid]);
+        assert_eq!(cli.filters, vec![Filter::NsPid(1)]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--ns-pid-filter", "-3", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_namespace_filters() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--same-ns-as",
+            "4242",
+            "--host-ns-only",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.filters,
+            vec![Filter::SamePidNs(4242), Filter::SamePidNs(1)]
+        );
+        let cli = parse_args_from_vec(args(&["fpid", "--ns-init-only", "x"])).unwrap();
+        assert_eq!(cli.filters, vec![Filter::NsPid(1)]);
+    }
+
+    #[test]
+    fn test_audit_log() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--audit-log", "/var/log/fpid.log", "x"])).unwrap();
+        assert_eq!(cli.audit_log, Some(PathBuf::from("/var/log/fpid.log")));
+    }
+
+    #[test]
+    fn test_watch_flag() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--watch", "--format=json", "nginx"])).unwrap();
+        assert!(cli.watch);
+        assert!(!cli.watch_events);
+        assert_eq!(cli.format, Format::Json);
+
+        let cli = parse_args_from_vec(args(&["fpid", "--watch-events", "nginx"])).unwrap();
+        assert!(cli.watch && cli.watch_events);
+        assert!(
+            parse_args_from_vec(args(&[
+                "fpid",
+                "--watch-events",
+                "--pid-file=/run/x.pid",
+                "x"
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_max_proc_read_bytes() {
+        let cli = parse_args_from_vec(args(&["fpid", "--max-proc-read-bytes=4096", "x"])).unwrap();
+        assert_eq!(cli.max_proc_read_bytes, Some(4096));
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--max-proc-read-bytes", "lots", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_proc_batch_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--proc-batch-size",
+            "64",
+            "--proc-batch-sleep-us=2000",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!((cli.proc_batch_size, cli.proc_batch_sleep_us), (64, 2000));
+    }
+
+    #[test]
+    fn test_cap_filters() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--has-cap",
+            "CAP_NET_ADMIN",
+            "--no-cap=sys_admin",
+            "--any-cap",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.filters,
+            vec![Filter::HasCap(12), Filter::NoCap(21), Filter::AnyCap]
+        );
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--has-cap", "CAP_FLY", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_wchan_options() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--print-wchan", "--wchan", "poll", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Wchan]);
+        assert_eq!(cli.filters, vec![Filter::Wchan("poll".to_string())]);
+    }
+
+    #[test]
+    fn test_syscall_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-syscall",
+            "--in-syscall",
+            "running",
+            "--in-syscall=202",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Syscall]);
+        assert_eq!(
+            cli.filters,
+            vec![
+                Filter::InSyscall(SyscallState::Running),
+                Filter::InSyscall(SyscallState::Syscall(202))
+            ]
+        );
+        assert!(parse_args_from_vec(args(&["fpid", "--in-syscall", "bogus", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_ns_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-ns",
+            "--ns-type",
+            "net",
+            "4026532300",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Ns]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::Namespace {
+                kind: "net",
+                inode: 4026532300
+            }]
+        );
+        assert_eq!(cli.targets, vec!["x"]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--ns-type", "time", "1", "x"])),
+            Err(1)
+        );
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--ns-type", "net"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_tracer_options() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--print-tracer", "--is-traced", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Tracer]);
+        assert_eq!(cli.filters, vec![Filter::Traced(true)]);
+        let cli = parse_args_from_vec(args(&["fpid", "--not-traced", "x"])).unwrap();
+        assert_eq!(cli.filters, vec![Filter::Traced(false)]);
+    }
+
+    #[test]
+    fn test_columns_option() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--columns",
+            "name,pid,rss",
+            "--print-age",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.columns,
+            vec![Column::Name, Column::Pid, Column::Rss, Column::Age]
+        );
+        assert!(cli.columns_only);
+        let cli = parse_args_from_vec(args(&["fpid", "--columns=cputime,age,cpu", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::CpuTime, Column::Age]);
+        assert!(
+            !parse_args_from_vec(args(&["fpid", "--print-age", "x"]))
+                .unwrap()
+                .columns_only
+        );
+        for bad in ["pid,bogus", "", "pid,"] {
+            assert_eq!(
+                parse_args_from_vec(args(&["fpid", "--columns", bad, "x"])),
+                Err(1),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_exclude_name() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--exclude-name",
+            "python2",
+            "--exclude-name=/usr/bin/python3",
+            "python",
+        ]))
+        .unwrap();
+        assert_eq!(cli.targets, vec!["python"]);
+        assert_eq!(cli.exclude_names, vec!["python2", "/usr/bin/python3"]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "x", "--exclude-name"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_ppid_chain() {
+        let cli = parse_args_from_vec(args(&["fpid", "--ppid-chain", "100", "worker"])).unwrap();
+        assert_eq!(cli.ppid_chain, Some(100));
+        assert_eq!(cli.targets, vec!["worker"]);
+        let cli = parse_args_from_vec(args(&["fpid", "--ppid-chain=1"])).unwrap();
+        assert_eq!(cli.ppid_chain, Some(1));
+        assert!(cli.targets.is_empty());
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--ppid-chain", "init"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_pid_range_options() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--min-pid", "100", "--max-pid=200", "x"])).unwrap();
+        assert_eq!(cli.filters, vec![Filter::PidRange { min: 100, max: 200 }]);
+        let cli = parse_args_from_vec(args(&["fpid", "--pid-min", "5", "x"])).unwrap();
+        assert_eq!(cli.min_pid, Some(5));
+        assert!(matches!(
+            cli.filters[..],
+            [Filter::PidRange { min: 5, max }] if max == read_pid_max()
+        ));
+        // pid_max is at most 2^22, so the first is always out of range.
+        for bad in [
+            &["--min-pid", "4294967295"][..],
+            &["--min-pid", "200", "--max-pid", "100"],
+            &["--max-pid", "-1"],
+        ] {
+            let mut argv = vec!["fpid"];
+            argv.extend(bad);
+            argv.push("x");
+            assert_eq!(parse_args_from_vec(args(&argv)), Err(1), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_starttime_options() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-starttime", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::StartTime]);
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--relative-time",
+            "--columns",
+            "pid,starttime",
+            "--started-within",
+            "1h30m",
+            "--started-more-than=45s",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Pid, Column::StartTimeRelative]);
+        assert_eq!(
+            cli.filters,
+            vec![
+                Filter::StartedWithin(Duration::from_secs(5400)),
+                Filter::StartedMoreThan(Duration::from_secs(45)),
+            ]
+        );
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--started-within", "soon", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_print_environ_var() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-environ-var",
+            "VIRTUAL_ENV",
+            "--print-environ-var=APP_ENV",
+            "python3",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.columns,
+            vec![Column::EnvVar("VIRTUAL_ENV"), Column::EnvVar("APP_ENV")]
+        );
+        for bad in ["", "A=B"] {
+            assert_eq!(
+                parse_args_from_vec(args(&["fpid", "--print-environ-var", bad, "x"])),
+                Err(1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_cwd_options() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--print-cwd", "--cwd", "/srv/app", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Cwd]);
+        assert_eq!(cli.filters, vec![Filter::Cwd(PathBuf::from("/srv/app"))]);
+    }
+
+    #[test]
+    fn test_fd_count_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-fd-count",
+            "--print-fd-limit",
+            "--fd-usage-pct-min",
+            "80",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::FdCount, Column::FdLimit]);
+        assert_eq!(cli.filters, vec![Filter::FdUsagePctMin(80.0)]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--fd-usage-pct-min", "lots", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_maps_count_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-maps-count",
+            "--min-maps-count",
+            "100",
+            "--max-maps-count=65530",
+            "--sort-maps-count-desc",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::MapsCount]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinMapsCount(100), Filter::MaxMapsCount(65530)]
+        );
+        assert_eq!(
+            cli.sort,
+            vec![SortSpec::new(SortField::MapsCount, SortDirection::Desc)]
+        );
+        let cli = parse_args_from_vec(args(&["fpid", "--sort-maps-count", "x"])).unwrap();
+        assert_eq!(
+            cli.sort,
+            vec![SortSpec::new(SortField::MapsCount, SortDirection::Asc)]
+        );
+    }
+
+    #[test]
+    fn test_cgroup_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--cgroup-filter",
+            "/system.slice",
+            "--cgroup-mode=prefix",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.filters,
+            vec![Filter::Cgroup {
+                path: "/system.slice".to_string(),
+                mode: CgroupMatchMode::Prefix
+            }]
+        );
+        let cli = parse_args_from_vec(args(&["fpid", "--cgroup-filter", "docker", "x"])).unwrap();
+        assert!(matches!(
+            cli.filters[..],
+            [Filter::Cgroup {
+                mode: CgroupMatchMode::Contains,
+                ..
+            }]
+        ));
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--cgroup-mode", "regex", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_oom_adj_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-oom-adj",
+            "--set-oom-adj",
+            "-1000",
+            "--dry-run",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::OomAdj]);
+        assert_eq!(cli.set_oom_adj, Some(-1000));
+        assert!(cli.dry_run);
+        let cli = parse_args_from_vec(args(&["fpid", "--set-oom-adj=1000", "x"])).unwrap();
+        assert_eq!(cli.set_oom_adj, Some(1000));
+        for bad in ["1001", "-1001", "high"] {
+            assert_eq!(
+                parse_args_from_vec(args(&["fpid", "--set-oom-adj", bad, "x"])),
+                Err(1),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_renice_options() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--print-niceness", "--renice", "5", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Nice]);
+        assert_eq!(cli.renice, Some(5));
+        for ok in ["-20", "19"] {
+            assert!(parse_args_from_vec(args(&["fpid", "--renice", ok, "x"])).is_ok());
+        }
+        for bad in ["20", "-21", "+"] {
+            assert_eq!(
+                parse_args_from_vec(args(&["fpid", "--renice", bad, "x"])),
+                Err(1),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_open_files_options() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-open-files", "3", "x"])).unwrap();
+        assert_eq!(
+            cli.columns,
+            vec![Column::OpenFiles {
+                max: 3,
+                deleted: true
+            }]
+        );
+        assert_eq!(cli.targets, vec!["x"]);
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-open-files",
+            "x",
+            "--no-deleted",
+            "--open-file-count",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.columns,
+            vec![
+                Column::OpenFiles {
+                    max: DEFAULT_OPEN_FILES,
+                    deleted: false
+                },
+                Column::FdCount
+            ]
+        );
+        assert_eq!(cli.targets, vec!["x"]);
+        assert_eq!(
+            parse_args_from_vec(args(&["fpid", "--print-open-files=many", "x"])),
+            Err(1)
+        );
+    }
+
+    #[test]
+    fn test_maps_regions_options() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-maps-regions", "--has-wxmem", "x"]))
+            .unwrap();
+        assert_eq!(cli.columns, vec![Column::AnonExec]);
+        assert_eq!(cli.filters, vec![Filter::HasWxMem]);
+    }
+
+    #[test]
+    fn test_stack_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-stack-depth",
+            "--print-total-stack",
+            "--min-stack-kb=512",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::StackKb, Column::TotalStack]);
+        assert_eq!(cli.filters, vec![Filter::MinStackKb(512)]);
+        assert!(parse_args_from_vec(args(&["fpid", "--min-stack-kb", "lots", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_cpu_pct_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-uptime-pct",
+            "--min-cpu-pct=12.5",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::CpuPct]);
+        assert_eq!(cli.filters, vec![Filter::MinCpuPct(12.5)]);
+        for bad in ["-1", "NaN", "inf", "lots"] {
+            assert!(
+                parse_args_from_vec(args(&["fpid", "--min-cpu-pct", bad, "x"])).is_err(),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_sys_time_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-user-time",
+            "--print-sys-time",
+            "--min-user-time=1.5",
+            "--min-sys-time",
+            "0.25",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::UserTime, Column::SysTime]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinUserSec(1.5), Filter::MinSysSec(0.25)]
+        );
+        assert!(parse_args_from_vec(args(&["fpid", "--min-sys-time", "lots", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_ctx_rate_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-ctx-voluntary-pct",
+            "--min-ctx-rate=100",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::CtxRate]);
+        assert_eq!(cli.filters, vec![Filter::MinCtxRate(100.0)]);
+    }
+
+    #[test]
+    fn test_fault_rate_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-pagefault-rate",
+            "--print-minflt-rate",
+            "--print-majflt-rate",
+            "--min-majflt-rate=0.5",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.columns,
+            vec![Column::FaultRate, Column::MinFltRate, Column::MajFltRate]
+        );
+        assert_eq!(cli.filters, vec![Filter::MinMajFltRate(0.5)]);
+    }
+
+    #[test]
+    fn test_vm_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-vm-size",
+            "--print-vm-peak",
+            "--print-vm-data",
+            "--print-vm-exe",
+            "--print-vm-lib",
+            "--min-vm-size=1024",
+            "--max-vm-size",
+            "8388608",
+            "x",
+        ]))
+        .unwrap();
+        let fields = [
+            VmField::Size,
+            VmField::Peak,
+            VmField::Data,
+            VmField::Exe,
+            VmField::Lib,
+        ];
+        assert_eq!(cli.columns, fields.map(Column::Vm).to_vec());
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinVmSizeKb(1024), Filter::MaxVmSizeKb(8388608)]
+        );
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--columns=vm_size,pid",
+            "--human-readable",
+            "--print-vm-lib",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.columns,
+            vec![
+                Column::Vm(VmField::Size),
+                Column::Pid,
+                Column::Vm(VmField::Lib),
+            ]
+        );
+        assert!(cli.human_readable);
+        let cli = parse_args_from_vec(args(&["fpid", "-Hq", "x"])).unwrap();
+        assert!(cli.human_readable && cli.flags.quiet);
+    }
+
+    #[test]
+    fn test_proc_root() {
+        let cli = parse_args_from_vec(args(&["fpid", "--proc-root", "/host/proc", "x"])).unwrap();
+        assert_eq!(cli.proc_root, Some(PathBuf::from("/host/proc")));
+        let cli = parse_args_from_vec(args(&["fpid", "x"])).unwrap();
+        assert_eq!(cli.proc_root, None);
+        assert!(parse_args_from_vec(args(&["fpid", "--proc-root=", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_print_fd_types() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-fd-types", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::FdTypes]);
+        let cli = parse_args_from_vec(args(&["fpid", "--columns=pid,fd_types", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Pid, Column::FdTypes]);
+    }
+
+    #[test]
+    fn test_socket_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-socket-count",
+            "--has-tcp-connections",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Sockets]);
+        assert_eq!(cli.filters, vec![Filter::HasTcp]);
+    }
+
+    #[test]
+    fn test_listen_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-listen-ports",
+            "--listening-on=443",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::ListenPorts]);
+        assert_eq!(cli.filters, vec![Filter::ListeningOn(443)]);
+        assert!(parse_args_from_vec(args(&["fpid", "--listening-on", "70000", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_numa_local_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-numa-local-pct",
+            "--min-numa-local-pct=90",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::NumaLocalPct(0)]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinNumaLocalPct { node: 0, pct: 90.0 }]
+        );
+        // The node applies wherever it appears on the command line.
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-numa-local-pct",
+            "--columns=numa_local",
+            "--min-numa-local-pct=50",
+            "--local-numa-node",
+            "1",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::NumaLocalPct(1)]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinNumaLocalPct { node: 1, pct: 50.0 }]
+        );
+        for bad in [
+            &["--min-numa-local-pct", "-5"][..],
+            &["--min-numa-local-pct", "NaN"],
+            &["--local-numa-node", "-1"],
+        ] {
+            let mut argv = vec!["fpid"];
+            argv.extend_from_slice(bad);
+            argv.push("x");
+            assert!(parse_args_from_vec(args(&argv)).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_compare_snapshot_flag() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--compare-snapshot",
+            "/var/run/fpid-baseline.json",
+            "--format=json",
+            "sshd",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.compare_snapshot,
+            Some(PathBuf::from("/var/run/fpid-baseline.json"))
+        );
+        assert_eq!(cli.targets, vec!["sshd"]);
+        assert!(
+            parse_args_from_vec(args(&["fpid", "--compare-snapshot=b.json", "--watch", "x"]))
+                .is_err()
+        );
+        assert!(parse_args_from_vec(args(&["fpid", "--compare-snapshot=b.json"])).is_err());
+    }
+
+    #[test]
+    fn test_huge_pages_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-huge-pages",
+            "--min-huge-pages=2048",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::HugePages]);
+        assert_eq!(cli.filters, vec![Filter::MinHugePagesKb(2048)]);
+        assert!(parse_args_from_vec(args(&["fpid", "--min-huge-pages", "2M", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_pinned_memory_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-pinned-memory",
+            "--min-pinned",
+            "1024",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Vm(VmField::Pin)]);
+        assert_eq!(cli.filters, vec![Filter::MinPinnedKb(1024)]);
+        assert!(parse_args_from_vec(args(&["fpid", "--min-pinned=-1", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_locked_memory_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-locked-memory",
+            "--locked-only",
+            "--min-locked-kb=64",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Vm(VmField::Lck)]);
+        assert_eq!(
+            cli.filters,
+            vec![Filter::MinLockedKb(1), Filter::MinLockedKb(64)]
+        );
+        assert!(parse_args_from_vec(args(&["fpid", "--min-locked-kb", "lots", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--sort-by",
+            "name,asc",
+            "--sort-by=rss,desc",
+            "--sort-by",
+            "pid",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.sort,
+            vec![
+                SortSpec::new(SortField::Name, SortDirection::Asc),
+                SortSpec::new(SortField::Rss, SortDirection::Desc),
+                SortSpec::new(SortField::Pid, SortDirection::Asc),
+            ]
+        );
+        for bad in ["bogus", "rss,up", ""] {
+            assert!(
+                parse_args_from_vec(args(&["fpid", "--sort-by", bad, "x"])).is_err(),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pid_file_flag() {
+        let cli = parse_args_from_vec(args(&["fpid", "--pid-file", "/var/run/nginx.pid", "nginx"]))
+            .unwrap();
+        assert_eq!(cli.pid_file, Some(PathBuf::from("/var/run/nginx.pid")));
+        // Not the daemon's --pidfile.
+        assert_eq!(cli.pidfile, None);
+        assert!(parse_args_from_vec(args(&["fpid", "--pid-file=/run/x.pid"])).is_err());
+        assert!(
+            parse_args_from_vec(args(&["fpid", "--pid-file=/run/x.pid", "--watch", "x"])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_write_pid_file_flags() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--write-pid-file",
+            "/run/app/app.pid",
+            "--write-pid-file-mode=600",
+            "--write-pid-file-dir-create",
+            "app",
+        ]))
+        .unwrap();
+        assert_eq!(cli.write_pid_file, Some(PathBuf::from("/run/app/app.pid")));
+        assert_eq!(cli.write_pid_file_mode, Some(0o600));
+        assert!(cli.write_pid_file_dir_create);
+        for bad in ["8", "rw", "17777"] {
+            assert!(
+                parse_args_from_vec(args(&["fpid", "--write-pid-file-mode", bad, "x"])).is_err(),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_uid_gid_options() {
+        let cli = parse_args_from_vec(args(&["fpid", "--uid", "33", "--gid=33", "x"])).unwrap();
+        assert_eq!(cli.filters, vec![Filter::Uid(33), Filter::Gid(33)]);
+        assert!(parse_args_from_vec(args(&["fpid", "--uid", "www-data", "x"])).is_err());
+        // root is in every /etc/passwd and /etc/group.
+        let cli = parse_args_from_vec(args(&["fpid", "--username", "root", "--group=root", "x"]))
+            .unwrap();
+        assert_eq!(cli.filters, vec![Filter::Uid(0), Filter::Gid(0)]);
+        let cli = parse_args_from_vec(args(&["fpid", "--username", "4242", "x"])).unwrap();
+        assert_eq!(cli.filters, vec![Filter::Uid(4242)]);
+        for flag in ["--username", "--group"] {
+            assert_eq!(
+                parse_args_from_vec(args(&["fpid", flag, "no-such-name-for-fpid", "x"])),
+                Err(1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mountinfo_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-mountinfo-count",
+            "--has-mount",
+            "/data",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::MountCount]);
+        assert_eq!(cli.filters, vec![Filter::HasMount(PathBuf::from("/data"))]);
+    }
+
+    #[test]
+    fn test_seccomp_options() {
+        let cli = parse_args_from_vec(args(&[
+            "fpid",
+            "--print-seccomp",
+            "--has-seccomp",
+            "--seccomp-mode=filter",
+            "--seccomp-mode",
+            "1",
+            "x",
+        ]))
+        .unwrap();
+        assert_eq!(cli.columns, vec![Column::Seccomp]);
+        assert_eq!(
+            cli.filters,
+            vec![
+                Filter::HasSeccomp,
+                Filter::SeccompMode(2),
+                Filter::SeccompMode(1)
+            ]
+        );
+        assert!(parse_args_from_vec(args(&["fpid", "--seccomp-mode", "bpf", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_print_caps() {
+        let cli = parse_args_from_vec(args(&["fpid", "--print-caps", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Caps { verbose: false }]);
+        let cli = parse_args_from_vec(args(&["fpid", "--print-caps-verbose", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Caps { verbose: true }]);
+        let cli = parse_args_from_vec(args(&["fpid", "-v", "--print-caps", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Caps { verbose: true }]);
+    }
+
+    #[test]
+    fn test_tls_options() {
+        let cli =
+            parse_args_from_vec(args(&["fpid", "--print-tls-info", "--has-tls", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::TlsInfo]);
+        assert_eq!(cli.filters, vec![Filter::HasTls]);
+    }
+
+    #[test]
+    fn test_jvm_detect() {
+        let cli = parse_args_from_vec(args(&["fpid", "--jvm-detect", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Jvm]);
+        assert_eq!(cli.filters, vec![Filter::Jvm]);
+        let cli = parse_args_from_vec(args(&["fpid", "--print-interpreter", "x"])).unwrap();
+        assert_eq!(cli.columns, vec![Column::Interpreter]);
+    }
+}
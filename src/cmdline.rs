@@ -0,0 +1,113 @@
+use std::io;
+
+use crate::reader::ProcReader;
+
+/// Lazily splits a NUL-separated cmdline buffer into its arguments.
+///
+/// A trailing NUL terminates the last argument rather than starting an empty
+/// one, so `b"ls\0-l\0"` yields `ls`, `-l`. Empty arguments in the middle are
+/// kept. A buffer without a trailing NUL (truncated read, or a process that
+/// rewrote its argv) still yields its last argument.
+#[derive(Debug, Clone)]
+pub struct CmdlineReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CmdlineReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for CmdlineReader<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.pos >= self.buf.len() {
+            return None;
+        }
+        let rest = &self.buf[self.pos..];
+        match rest.iter().position(|b| *b == 0) {
+            Some(end) => {
+                self.pos += end + 1;
+                Some(&rest[..end])
+            }
+            None => {
+                self.pos = self.buf.len();
+                Some(rest)
+            }
+        }
+    }
+}
+
+/// Raw contents of `/proc/<pid>/cmdline`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cmdline {
+    buf: Vec<u8>,
+}
+
+impl Cmdline {
+    pub fn from_bytes(buf: Vec<u8>) -> Self {
+        Self { buf }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn args(&self) -> CmdlineReader<'_> {
+        CmdlineReader::new(&self.buf)
+    }
+
+    /// argv[0], or `None` for kernel threads and zombies (empty cmdline).
+    pub fn argv0(&self) -> Option<&[u8]> {
+        self.args().next()
+    }
+}
+
+/// Reads `/proc/<pid>/cmdline` once; arguments are then borrowed from it via
+/// [`Cmdline::args`] without allocating per argument.
+pub fn open_cmdline_reader(pid: u32, reader: &impl ProcReader) -> Result<Cmdline, io::Error> {
+    reader.read(pid, "cmdline").map(Cmdline::from_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    fn args(buf: &[u8]) -> Vec<&[u8]> {
+        CmdlineReader::new(buf).collect()
+    }
+
+    #[test]
+    fn test_cmdline_trailing_nul() {
+        assert_eq!(args(b"/usr/bin/ls\0-l\0"), vec![&b"/usr/bin/ls"[..], b"-l"]);
+    }
+
+    #[test]
+    fn test_cmdline_no_trailing_nul() {
+        assert_eq!(
+            args(b"nginx: worker process"),
+            vec![&b"nginx: worker process"[..]]
+        );
+        assert_eq!(args(b"a\0b"), vec![&b"a"[..], b"b"]);
+    }
+
+    #[test]
+    fn test_cmdline_empty_args() {
+        assert!(args(b"").is_empty());
+        assert_eq!(args(b"\0"), vec![&b""[..]]);
+        assert_eq!(args(b"a\0\0b\0"), vec![&b"a"[..], b"", b"b"]);
+    }
+
+    #[test]
+    fn test_open_cmdline_reader() {
+        let reader = MockProcReader::new().file(7, "cmdline", b"python3\0app.py\0--port\08080\0");
+        let cmdline = open_cmdline_reader(7, &reader).unwrap();
+        assert_eq!(cmdline.argv0(), Some(&b"python3"[..]));
+        assert_eq!(cmdline.args().count(), 4);
+        assert!(open_cmdline_reader(8, &reader).is_err());
+    }
+}
@@ -0,0 +1,1954 @@
+use std::str::FromStr;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::audit::iso8601_utc;
+
+use crate::caps::{CAPABILITY_DESCS, cap_names, has_cap};
+use crate::coredump::{decode_coredump_filter, read_coredump_filter};
+use crate::cwd::read_cwd;
+use crate::environ::environ_get;
+use crate::fd::{count_fds, fd_type_counts, open_files, read_fd_soft_limit};
+use crate::interpreter::detect_interpreter;
+use crate::jvm::detect_jvm;
+use crate::maps::{
+    read_anon_exec_regions, read_maps_count, read_maps_summary, read_stack_usage,
+    read_tls_libraries,
+};
+use crate::meminfo::rss_percent;
+use crate::mountinfo::count_mountinfo;
+use crate::namespace::{NS_TYPES, read_ns_inodes};
+use crate::net::{find_listen_ports, socket_counts};
+use crate::numa::{read_numa_local_pct, read_numa_policy};
+use crate::process::Process;
+use crate::reader::ProcReader;
+use crate::sched_policy::read_sched_policy;
+use crate::schedstat::sched_cpu_pressure;
+use crate::statm::statm_to_kb;
+use crate::status::{VmField, decode_seccomp_mode, read_tracer_pid};
+use crate::syscall::read_syscall;
+use crate::wchan::read_wchan;
+
+/// Per-process values printed for each match: appended after the PID by
+/// the `--print-*` flags, or the whole line with `--columns`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    /// The PID itself, for `--columns` lines that don't start with it.
+    Pid,
+    /// Basename of argv[0], or the stat comm for kernel threads.
+    Name,
+    /// Target of the exe link.
+    Exe,
+    /// Arguments joined with spaces.
+    Cmdline,
+    /// Real UID from status.
+    Uid,
+    /// Real GID from status.
+    Gid,
+    /// Parent PID from stat.
+    Ppid,
+    /// State letter from stat (R, S, D, Z, ...).
+    State,
+    /// Resident memory in kB from statm.
+    Rss,
+    /// Wall-clock start time in UTC (`--print-starttime`).
+    StartTime,
+    /// How long ago the process started, e.g. `2h 14m ago`
+    /// (`--print-starttime --relative-time`).
+    StartTimeRelative,
+    /// Time since the process started (`--print-age`).
+    Age,
+    /// utime + stime in seconds (`--print-cputime`).
+    CpuTime,
+    /// Proportional set size in kB (`--print-pss`).
+    Pss,
+    /// Swapped-out memory in kB (`--print-swap`).
+    Swap,
+    /// OOM badness score and adjustment (`--print-oom`).
+    Oom,
+    /// Just the OOM adjustment, oom_score_adj (`--print-oom-adj`).
+    OomAdj,
+    /// Decoded coredump_filter flags (`--print-coredump-filter`).
+    CoredumpFilter,
+    /// Virtual size, resident and shared memory from statm (`--print-statm`).
+    Statm,
+    /// All seven `/proc/<pid>/io` counters (`--print-io-full`).
+    IoFull,
+    /// Bytes passed to read and write syscalls, page cache hits included
+    /// (`--print-io-rchar-wchar`).
+    IoChars,
+    /// Run time, run-queue wait and timeslices from schedstat (`--print-sched`).
+    Sched,
+    /// Share of runnable time spent waiting (`--print-sched-pressure`).
+    SchedPressure,
+    /// Resident memory as a share of MemTotal (`--print-rss-pct`).
+    RssPct,
+    /// User-mode CPU time (`--print-user-time`).
+    UserTime,
+    /// Kernel-mode CPU time (`--print-sys-time`).
+    SysTime,
+    /// Minor plus major page faults per second of lifetime
+    /// (`--print-pagefault-rate`).
+    FaultRate,
+    /// Minor page faults per second of lifetime (`--print-minflt-rate`).
+    MinFltRate,
+    /// Major page faults per second of lifetime (`--print-majflt-rate`).
+    MajFltRate,
+    /// Voluntary context switches per CPU second (`--print-ctx-voluntary-pct`).
+    CtxRate,
+    /// CPU time as a share of the process's lifetime (`--print-uptime-pct`).
+    CpuPct,
+    /// `Cpus_allowed_list` from status (`--print-affinity`).
+    Affinity,
+    /// Memory policy of the first numa_maps entry (`--print-numa`).
+    Numa,
+    /// Share of numa_maps pages on the given node (`--print-numa-local-pct`).
+    NumaLocalPct(u32),
+    /// Nice value from stat (`--print-niceness`).
+    Nice,
+    /// Scheduling policy and priority (`--print-scheduler`).
+    Scheduler,
+    /// Anonymous, file-backed and special region counts plus the largest
+    /// region, from maps (`--print-maps-summary`).
+    MapsSummary,
+    /// Inodes of the pid, mnt, net, uts, ipc and user namespaces (`--print-ns`).
+    Ns,
+    /// PID of the attached ptrace tracer, 0 if none (`--print-tracer`).
+    Tracer,
+    /// Kernel function the process is blocked in (`--print-wchan`).
+    Wchan,
+    /// Syscall the process is in, from `/proc/<pid>/syscall` (`--print-syscall`).
+    Syscall,
+    /// PID in the innermost PID namespace, from status `NSpid` (`--print-ns-pid`).
+    NsPid,
+    /// Value of this environment variable, empty if unset
+    /// (`--print-environ-var`).
+    EnvVar(&'static str),
+    /// Working directory (`--print-cwd`).
+    Cwd,
+    /// A `Vm*` line of status in kB (`--print-vm-size` and friends).
+    Vm(VmField),
+    /// `VmHuge` and `HugetlbPages` from status, `huge=NkB,htlb=MkB`
+    /// (`--print-huge-pages`).
+    HugePages,
+    /// Main-thread stack size in kB (`--print-stack-depth`).
+    StackKb,
+    /// `[stack]` plus every `[stack:TID]` mapping in kB (`--print-total-stack`).
+    TotalStack,
+    /// Anonymous executable regions as `<perms>@<start>` (`--print-maps-regions`).
+    AnonExec,
+    /// Number of mappings (VMAs) in maps (`--print-maps-count`).
+    MapsCount,
+    /// Number of open file descriptors (`--print-fd-count`).
+    FdCount,
+    /// Listening ports, `tcp:80,udp:53` (`--print-listen-ports`).
+    ListenPorts,
+    /// TLS libraries mapped, `openssl,gnutls` (`--print-tls-info`).
+    TlsInfo,
+    /// Main class and heap sizes of a JVM (`--jvm-detect`).
+    Jvm,
+    /// Script interpreter, its version and the script (`--print-interpreter`).
+    Interpreter,
+    /// Seccomp mode from status, e.g. `FILTER` (`--print-seccomp`).
+    Seccomp,
+    /// Effective capabilities, `net_bind_service,net_raw` (`--print-caps`);
+    /// with `verbose`, each followed by what it allows
+    /// (`--print-caps-verbose`).
+    Caps { verbose: bool },
+    /// Mounts in mountinfo (`--print-mountinfo-count`).
+    MountCount,
+    /// Sockets by protocol, `tcp=N,udp=M,unix=K` (`--print-socket-count`).
+    Sockets,
+    /// fds per type, `reg=N,sock=M,pipe=K,...,other=L` (`--print-fd-types`).
+    FdTypes,
+    /// Soft `RLIMIT_NOFILE` from limits (`--print-fd-limit`).
+    FdLimit,
+    /// The first `max` fd targets, `;`-separated; `deleted: false` skips
+    /// deleted files (`--print-open-files`, `--no-deleted`).
+    OpenFiles { max: usize, deleted: bool },
+}
+
+// Placeholder for values that could not be read (process gone, EACCES).
+pub const MISSING: &str = "-";
+
+impl Column {
+    /// Header name for table formats; single-valued columns print as
+    /// `<name>=<value>` in text mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Pid => "pid",
+            Column::Name => "name",
+            Column::Exe => "exe",
+            Column::Cmdline => "cmdline",
+            Column::Uid => "uid",
+            Column::Gid => "gid",
+            Column::Ppid => "ppid",
+            Column::State => "state",
+            Column::Rss => "rss",
+            Column::StartTime | Column::StartTimeRelative => "starttime",
+            Column::Age => "age",
+            Column::CpuTime => "cpu",
+            Column::UserTime => "utime",
+            Column::SysTime => "stime",
+            Column::Pss => "pss",
+            Column::Swap => "swap",
+            Column::Oom => "oom",
+            Column::OomAdj => "oom_adj",
+            Column::CoredumpFilter => "coredump",
+            Column::Statm => "statm",
+            Column::IoFull => "io",
+            Column::IoChars => "io_chars",
+            Column::Sched => "schedstat",
+            Column::SchedPressure => "pressure",
+            Column::RssPct => "rss_pct",
+            Column::CpuPct => "cpu_pct",
+            Column::CtxRate => "ctx/cpu_s",
+            Column::FaultRate => "pgflt_rate",
+            Column::MinFltRate => "minflt_rate",
+            Column::MajFltRate => "majflt_rate",
+            Column::Affinity => "cpus",
+            Column::Numa => "numa",
+            Column::NumaLocalPct(_) => "numa_local",
+            Column::Scheduler => "sched",
+            Column::Nice => "nice",
+            Column::NsPid => "ns_pid",
+            Column::MapsSummary => "maps",
+            Column::Wchan => "wchan",
+            Column::Syscall => "syscall",
+            Column::Tracer => "tracer",
+            Column::Ns => "ns",
+            Column::EnvVar(key) => key,
+            Column::Cwd => "cwd",
+            Column::MapsCount => "maps_count",
+            Column::AnonExec => "anon_exec",
+            Column::Vm(field) => match field {
+                VmField::Size => "vm_size",
+                VmField::Peak => "vm_peak",
+                VmField::Data => "vm_data",
+                VmField::Exe => "vm_exe",
+                VmField::Lib => "vm_lib",
+                VmField::Pin => "pin",
+                VmField::Lck => "locked",
+            },
+            Column::StackKb => "stack_kb",
+            Column::TotalStack => "total_stack",
+            Column::FdCount => "fd_count",
+            Column::FdLimit => "fd_limit",
+            Column::OpenFiles { .. } => "files",
+            Column::FdTypes => "fd_types",
+            Column::HugePages => "huge_pages",
+            Column::Sockets => "sockets",
+            Column::ListenPorts => "listen",
+            Column::MountCount => "mounts",
+            Column::Seccomp => "seccomp",
+            Column::Caps { verbose: false } => "caps",
+            Column::Caps { verbose: true } => "caps_verbose",
+            Column::TlsInfo => "tls",
+            Column::Jvm => "jvm",
+            Column::Interpreter => "interp",
+        }
+    }
+}
+
+/// Names accepted by `--columns`, in help order.
+pub const COLUMN_NAMES: &str = "pid name exe cmdline uid gid ppid state rss starttime age \
+cputime utime stime pss swap oom oom_adj coredump statm io io_chars schedstat pressure rss_pct cpu_pct \
+ctx_rate pgflt_rate minflt_rate majflt_rate cpus numa numa_local sched nice maps ns tracer wchan syscall ns_pid cwd maps_count anon_exec vm_size \
+vm_peak vm_data vm_exe vm_lib pin locked huge_pages stack_kb total_stack fd_count fd_types sockets listen mounts seccomp caps caps_verbose tls jvm interp fd_limit";
+
+impl FromStr for Column {
+    type Err = ();
+
+    /// A [`Column::name`], or `cputime` for [`Column::CpuTime`].
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let col = match s {
+            "pid" => Column::Pid,
+            "name" => Column::Name,
+            "exe" => Column::Exe,
+            "cmdline" => Column::Cmdline,
+            "uid" => Column::Uid,
+            "gid" => Column::Gid,
+            "ppid" => Column::Ppid,
+            "state" => Column::State,
+            "rss" => Column::Rss,
+            "starttime" => Column::StartTime,
+            "age" => Column::Age,
+            "cpu" | "cputime" => Column::CpuTime,
+            "pss" => Column::Pss,
+            "swap" => Column::Swap,
+            "oom" => Column::Oom,
+            "oom_adj" => Column::OomAdj,
+            "coredump" => Column::CoredumpFilter,
+            "statm" => Column::Statm,
+            "io" => Column::IoFull,
+            "io_chars" => Column::IoChars,
+            "schedstat" => Column::Sched,
+            "pressure" => Column::SchedPressure,
+            "rss_pct" => Column::RssPct,
+            "cpu_pct" => Column::CpuPct,
+            "ctx_rate" | "ctx/cpu_s" => Column::CtxRate,
+            "pgflt_rate" => Column::FaultRate,
+            "minflt_rate" => Column::MinFltRate,
+            "majflt_rate" => Column::MajFltRate,
+            "utime" => Column::UserTime,
+            "stime" => Column::SysTime,
+            "cpus" => Column::Affinity,
+            "numa" => Column::Numa,
+            "numa_local" => Column::NumaLocalPct(0),
+            "sched" => Column::Scheduler,
+            "nice" => Column::Nice,
+            "maps" => Column::MapsSummary,
+            "ns" => Column::Ns,
+            "tracer" => Column::Tracer,
+            "wchan" => Column::Wchan,
+            "syscall" => Column::Syscall,
+            "ns_pid" => Column::NsPid,
+            "cwd" => Column::Cwd,
+            "maps_count" => Column::MapsCount,
+            "anon_exec" => Column::AnonExec,
+            "vm_size" => Column::Vm(VmField::Size),
+            "vm_peak" => Column::Vm(VmField::Peak),
+            "vm_data" => Column::Vm(VmField::Data),
+            "vm_exe" => Column::Vm(VmField::Exe),
+            "vm_lib" => Column::Vm(VmField::Lib),
+            "pin" => Column::Vm(VmField::Pin),
+            "locked" => Column::Vm(VmField::Lck),
+            "stack_kb" => Column::StackKb,
+            "total_stack" => Column::TotalStack,
+            "fd_count" => Column::FdCount,
+            "fd_types" => Column::FdTypes,
+            "huge_pages" => Column::HugePages,
+            "sockets" => Column::Sockets,
+            "listen" => Column::ListenPorts,
+            "mounts" => Column::MountCount,
+            "seccomp" => Column::Seccomp,
+            "caps" => Column::Caps { verbose: false },
+            "caps_verbose" => Column::Caps { verbose: true },
+            "tls" => Column::TlsInfo,
+            "jvm" => Column::Jvm,
+            "interp" => Column::Interpreter,
+            "fd_limit" => Column::FdLimit,
+            _ => return Err(()),
+        };
+        Ok(col)
+    }
+}
+
+/// [`format_column`] without the leading `<name>=`, for table formats.
+/// Multi-valued columns keep their `key=value,...` form.
+/// With `human`, values are rendered as by [`format_column_human`].
+pub fn column_value<R: ProcReader>(col: Column, proc: &Process<'_, R>, human: bool) -> String {
+    let text = if human {
+        format_column_human(col, proc)
+    } else {
+        format_column(col, proc)
+    };
+    match text
+        .strip_prefix(col.name())
+        .and_then(|r| r.strip_prefix('='))
+    {
+        Some(value) => value.to_string(),
+        None => text,
+    }
+}
+
+/// Renders `col` for `proc` as it appears in text output.
+pub fn format_column<R: ProcReader>(col: Column, proc: &Process<'_, R>) -> String {
+    match col {
+        Column::Pid => format!("pid={}", proc.pid()),
+        Column::Name => match proc.info().name.as_str() {
+            "" => match proc.stat() {
+                Some(stat) => format!("name={}", stat.comm),
+                None => format!("name={MISSING}"),
+            },
+            name => format!("name={name}"),
+        },
+        Column::Exe => match &proc.info().exe {
+            Some(exe) => format!("exe={}", exe.display()),
+            None => format!("exe={MISSING}"),
+        },
+        Column::Cmdline => match proc.info().cmdline.join(" ") {
+            args if args.is_empty() => format!("cmdline={MISSING}"),
+            args => format!("cmdline={args}"),
+        },
+        Column::Uid => match proc.status() {
+            Some(st) => format!("uid={}", st.uid[0]),
+            None => format!("uid={MISSING}"),
+        },
+        Column::Gid => match proc.status() {
+            Some(st) => format!("gid={}", st.gid[0]),
+            None => format!("gid={MISSING}"),
+        },
+        Column::Ppid => match proc.stat() {
+            Some(stat) => format!("ppid={}", stat.ppid),
+            None => format!("ppid={MISSING}"),
+        },
+        Column::State => match proc.stat() {
+            Some(stat) => format!("state={}", stat.state),
+            None => format!("state={MISSING}"),
+        },
+        Column::Rss => kb_column("rss", proc.statm().map(|m| statm_to_kb(m.resident))),
+        Column::StartTime => match (proc.stat(), proc.sys().boot_time(proc.reader())) {
+            (Some(stat), Some(btime)) => {
+                let ticks = proc.sys().clk_tck().max(1);
+                let start = UNIX_EPOCH
+                    + Duration::from_secs(btime)
+                    + Duration::from_millis(stat.starttime * 1000 / ticks);
+                format!("starttime={}", iso8601_utc(start))
+            }
+            _ => format!("starttime={MISSING}"),
+        },
+        Column::StartTimeRelative => match proc.age() {
+            Some(age) => format!("starttime={}", to_relative_time(age)),
+            None => format!("starttime={MISSING}"),
+        },
+        Column::Age => proc
+            .age()
+            .map(|d| format!("age={}", format_duration(d)))
+            .unwrap_or_else(|| format!("age={MISSING}")),
+        Column::CpuTime => proc
+            .cpu_seconds()
+            .map(|s| format!("cpu={s:.2}s"))
+            .unwrap_or_else(|| format!("cpu={MISSING}")),
+        Column::UserTime => proc
+            .user_seconds()
+            .map(|s| format!("utime={s:.2}s"))
+            .unwrap_or_else(|| format!("utime={MISSING}")),
+        Column::SysTime => proc
+            .sys_seconds()
+            .map(|s| format!("stime={s:.2}s"))
+            .unwrap_or_else(|| format!("stime={MISSING}")),
+        Column::Pss => kb_column("pss", proc.smaps().map(|s| s.pss)),
+        Column::Swap => kb_column("swap", proc.smaps().map(|s| s.swap)),
+        Column::Oom => match proc.oom() {
+            Some((score, adj)) => format!("oom={score},adj={adj}"),
+            None => format!("oom={MISSING},adj={MISSING}"),
+        },
+        Column::OomAdj => match proc.oom() {
+            Some((_, adj)) => format!("oom_adj={adj}"),
+            None => format!("oom_adj={MISSING}"),
+        },
+        Column::CoredumpFilter => match read_coredump_filter(proc.pid(), proc.reader()) {
+            Ok(0) => "coredump=none".to_string(),
+            Ok(mask) => format!("coredump={}", decode_coredump_filter(mask).join("|")),
+            Err(_) => format!("coredump={MISSING}"),
+        },
+        Column::Statm => match proc.statm() {
+            Some(m) => format!(
+                "size={}kB,rss={}kB,shr={}kB",
+                statm_to_kb(m.size),
+                statm_to_kb(m.resident),
+                statm_to_kb(m.shared)
+            ),
+            None => format!("size={MISSING},rss={MISSING},shr={MISSING}"),
+        },
+        Column::IoFull => match proc.io() {
+            Some(io) => format!(
+                "rchar={},wchar={},syscr={},syscw={},read_bytes={},write_bytes={},cancelled_write_bytes={}",
+                io.rchar,
+                io.wchar,
+                io.syscr,
+                io.syscw,
+                io.read_bytes,
+                io.write_bytes,
+                io.cancelled_write_bytes
+            ),
+            None => format!("io={MISSING}"),
+        },
+        Column::IoChars => match proc.io() {
+            Some(io) => format!("rchar={},wchar={}", io.rchar, io.wchar),
+            None => format!("rchar={MISSING},wchar={MISSING}"),
+        },
+        Column::Sched => match proc.sched() {
+            Some(s) => format!(
+                "run={}ns,wait={}ns,slices={}",
+                s.run_time_ns, s.wait_time_ns, s.time_slices
+            ),
+            None => format!("run={MISSING},wait={MISSING},slices={MISSING}"),
+        },
+        Column::SchedPressure => match proc.sched() {
+            Some(s) => format!("pressure={:.3}", sched_cpu_pressure(s)),
+            None => format!("pressure={MISSING}"),
+        },
+        Column::Affinity => match proc.status() {
+            Some(st) if !st.cpus_allowed_list.is_empty() => {
+                format!("cpus={}", st.cpus_allowed_list)
+            }
+            _ => format!("cpus={MISSING}"),
+        },
+        Column::Numa => match read_numa_policy(proc.pid(), proc.reader()) {
+            Ok(Some(policy)) => format!("numa={policy}"),
+            _ => format!("numa={MISSING}"),
+        },
+        Column::Nice => match proc.stat() {
+            Some(stat) => format!("nice={}", stat.nice),
+            None => format!("nice={MISSING}"),
+        },
+        Column::Scheduler => match read_sched_policy(proc.pid(), proc.reader()) {
+            Ok(policy) => format!("sched={policy}"),
+            Err(_) => format!("sched={MISSING}"),
+        },
+        Column::MapsSummary => match read_maps_summary(proc.pid(), proc.reader()) {
+            Ok(m) => format!(
+                "anon={},file={},special={},max={}kB",
+                m.anon, m.file, m.special, m.max_kb
+            ),
+            Err(_) => format!("anon={MISSING},file={MISSING},special={MISSING},max={MISSING}"),
+        },
+        Column::Ns => {
+            let inodes = read_ns_inodes(proc.pid(), proc.reader()).ok();
+            NS_TYPES
+                .iter()
+                .map(|kind| match inodes.as_ref().map(|m| m[kind]) {
+                    Some(inode) => format!("{kind}_ns={inode}"),
+                    None => format!("{kind}_ns={MISSING}"),
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+        Column::Tracer => match read_tracer_pid(proc.pid(), proc.reader()) {
+            Ok(tracer) => format!("tracer={tracer}"),
+            Err(_) => format!("tracer={MISSING}"),
+        },
+        Column::Wchan => match read_wchan(proc.pid(), proc.reader()) {
+            Ok(wchan) if !wchan.is_empty() => format!("wchan={wchan}"),
+            _ => format!("wchan={MISSING}"),
+        },
+        Column::Syscall => match read_syscall(proc.pid(), proc.reader()) {
+            Ok(state) => format!("syscall={}", state.label()),
+            Err(_) => format!("syscall={MISSING}"),
+        },
+        Column::NsPid => match proc.status().and_then(|st| st.ns_pid.last()) {
+            Some(ns_pid) => format!("ns_pid={ns_pid}"),
+            None => format!("ns_pid={MISSING}"),
+        },
+        Column::Vm(field) => kb_column(col.name(), proc.status().and_then(|st| field.get(st))),
+        Column::HugePages => {
+            let st = proc.status();
+            format!(
+                "{},{}",
+                kb_column("huge", st.and_then(|st| st.vm_huge)),
+                kb_column("htlb", st.and_then(|st| st.hugetlb_pages))
+            )
+        }
+        Column::StackKb => match proc.stack_kb() {
+            Some(kb) => format!("stack_kb={kb}"),
+            None => format!("stack_kb={MISSING}"),
+        },
+        Column::TotalStack => match read_stack_usage(proc.pid(), proc.reader()) {
+            Ok(usage) => format!("total_stack={}kB", usage.total_kb()),
+            Err(_) => format!("total_stack={MISSING}"),
+        },
+        Column::AnonExec => match read_anon_exec_regions(proc.pid(), proc.reader()) {
+            Ok(regions) if regions.is_empty() => "anon_exec=none".to_string(),
+            Ok(regions) => {
+                let list: Vec<String> = regions
+                    .iter()
+                    .map(|e| format!("{}@{:x}", e.perms_str(), e.start))
+                    .collect();
+                format!("anon_exec={}", list.join(","))
+            }
+            Err(_) => format!("anon_exec={MISSING}"),
+        },
+        Column::MapsCount => match read_maps_count(proc.pid(), proc.reader()) {
+            Ok(n) => format!("maps_count={n}"),
+            Err(_) => format!("maps_count={MISSING}"),
+        },
+        Column::TlsInfo => match read_tls_libraries(proc.pid(), proc.reader()) {
+            Ok(libs) if libs.is_empty() => "tls=none".to_string(),
+            Ok(libs) => format!("tls={}", libs.join(",")),
+            Err(_) => format!("tls={MISSING}"),
+        },
+        Column::Jvm => {
+            let args: Vec<&[u8]> = proc.info().cmdline.iter().map(|a| a.as_bytes()).collect();
+            let jvm = detect_jvm(&args).unwrap_or_default();
+            let field = |v: Option<String>| v.unwrap_or_else(|| MISSING.to_string());
+            format!(
+                "java_class={},xmx={},xms={}",
+                field(jvm.main_class),
+                field(jvm.xmx),
+                field(jvm.xms)
+            )
+        }
+        Column::Interpreter => {
+            let args: Vec<&[u8]> = proc.info().cmdline.iter().map(|a| a.as_bytes()).collect();
+            match detect_interpreter(&args) {
+                Some(interp) => format!(
+                    "interp={},version={},script={}",
+                    interp.name,
+                    interp.version.as_deref().unwrap_or(MISSING),
+                    interp.script.as_deref().unwrap_or(MISSING)
+                ),
+                None => format!("interp={MISSING},version={MISSING},script={MISSING}"),
+            }
+        }
+        Column::Seccomp => match proc.status().and_then(|st| st.seccomp) {
+            Some(mode) => format!("seccomp={}", decode_seccomp_mode(mode)),
+            None => format!("seccomp={MISSING}"),
+        },
+        Column::Caps { verbose } => {
+            let name = col.name();
+            match proc.status().map(|st| st.cap_eff) {
+                None => format!("{name}={MISSING}"),
+                Some(0) => format!("{name}=none"),
+                Some(mask) if verbose => {
+                    let list: Vec<String> = CAPABILITY_DESCS
+                        .iter()
+                        .filter(|(bit, ..)| has_cap(mask, u32::from(*bit)))
+                        .map(|(_, cap, desc)| format!("{cap} ({desc})"))
+                        .collect();
+                    format!("{name}={}", list.join("; "))
+                }
+                Some(mask) => format!("{name}={}", cap_names(mask).join(",")),
+            }
+        }
+        Column::MountCount => match count_mountinfo(proc.pid(), proc.reader()) {
+            Ok(n) => format!("mounts={n}"),
+            Err(_) => format!("mounts={MISSING}"),
+        },
+        Column::FdCount => match count_fds(proc.pid(), proc.reader()) {
+            Ok(n) => format!("fd_count={n}"),
+            Err(_) => format!("fd_count={MISSING}"),
+        },
+        Column::FdLimit => match read_fd_soft_limit(proc.pid(), proc.reader()) {
+            Ok(u64::MAX) => "fd_limit=unlimited".to_string(),
+            Ok(limit) => format!("fd_limit={limit}"),
+            Err(_) => format!("fd_limit={MISSING}"),
+        },
+        Column::ListenPorts => {
+            match find_listen_ports(proc.pid(), proc.reader(), proc.sys().sockets(proc.reader())) {
+                Ok(ports) if ports.is_empty() => "listen=none".to_string(),
+                Ok(ports) => {
+                    let list: Vec<String> = ports
+                        .iter()
+                        .map(|(kind, port)| format!("{}:{port}", kind.name()))
+                        .collect();
+                    format!("listen={}", list.join(","))
+                }
+                Err(_) => format!("listen={MISSING}"),
+            }
+        }
+        Column::Sockets => {
+            match socket_counts(proc.pid(), proc.reader(), proc.sys().sockets(proc.reader())) {
+                Ok(c) => format!("tcp={},udp={},unix={}", c.tcp, c.udp, c.unix),
+                Err(_) => format!("tcp={MISSING},udp={MISSING},unix={MISSING}"),
+            }
+        }
+        Column::FdTypes => match fd_type_counts(proc.pid(), proc.reader()) {
+            Ok(counts) => counts.summary(),
+            Err(_) => format!("reg={MISSING},sock={MISSING},pipe={MISSING},other={MISSING}"),
+        },
+        Column::OpenFiles { max, deleted } => {
+            match open_files(proc.pid(), proc.reader(), max, deleted) {
+                Ok(files) => format!("files={}", files.join(";")),
+                Err(_) => format!("files={MISSING}"),
+            }
+        }
+        Column::Cwd => match read_cwd(proc.pid(), proc.reader()) {
+            Ok(cwd) => format!("cwd={}", cwd.display()),
+            Err(_) => format!("cwd={MISSING}"),
+        },
+        Column::EnvVar(key) => match proc.environ() {
+            Some(buf) => {
+                let value = environ_get(buf, key.as_bytes()).unwrap_or_default();
+                format!("{}={}", key, String::from_utf8_lossy(value))
+            }
+            None => format!("{key}={MISSING}"),
+        },
+        Column::FaultRate | Column::MinFltRate | Column::MajFltRate => {
+            let rate = proc.fault_rates().map(|(min, maj)| match col {
+                Column::MinFltRate => min,
+                Column::MajFltRate => maj,
+                _ => min + maj,
+            });
+            match rate {
+                Some(rate) => format!("{}={rate:.2}/s", col.name()),
+                None => format!("{}={MISSING}", col.name()),
+            }
+        }
+        Column::CtxRate => match proc.ctx_rate() {
+            Some(rate) => format!("ctx/cpu_s={rate:.1}"),
+            None => format!("ctx/cpu_s={MISSING}"),
+        },
+        Column::NumaLocalPct(node) => match read_numa_local_pct(proc.pid(), proc.reader(), node) {
+            Ok(Some(pct)) => format!("numa_local={pct:.2}%"),
+            _ => format!("numa_local={MISSING}"),
+        },
+        Column::CpuPct => match proc.cpu_lifetime_pct() {
+            Some(pct) => format!("cpu_pct={pct:.2}%"),
+            None => format!("cpu_pct={MISSING}"),
+        },
+        Column::RssPct => {
+            let mem = proc.sys().meminfo(proc.reader());
+            match (proc.statm(), mem) {
+                (Some(m), Some(mem)) => match rss_percent(statm_to_kb(m.resident), mem) {
+                    Some(pct) => format!("rss_pct={:.2}%", pct),
+                    None => format!("rss_pct={MISSING}"),
+                },
+                _ => format!("rss_pct={MISSING}"),
+            }
+        }
+    }
+}
+
+fn kb_column(label: &str, kb: Option<u64>) -> String {
+    match kb {
+        Some(kb) => format!("{label}={kb}kB"),
+        None => format!("{label}={MISSING}"),
+    }
+}
+
+/// kB in the largest 1024-based unit that keeps the value at least 1:
+/// `512 kB`, `4.2 MB`, `1.1 GB`.
+pub fn human_size(kb: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    let mut value = kb as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{kb} kB")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Seconds as `3h 5m 12s`, leaving out zero units; below one second the
+/// fraction is kept (`0.47s`).
+pub fn human_duration(secs: f64) -> String {
+    if secs.is_nan() || secs < 1.0 {
+        return format!("{:.2}s", secs.max(0.0));
+    }
+    let secs = secs as u64;
+    let parts = [
+        (secs / 86400, "d"),
+        (secs / 3600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let parts: Vec<String> = parts
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect();
+    parts.join(" ")
+}
+
+// The raw value of a size or time column, for `-H`.
+enum Measure {
+    Kb(u64),
+    Secs(f64),
+}
+
+// `None` for columns that are neither sizes nor times; `Some(None)` when
+// the value cannot be read.
+fn measure<R: ProcReader>(col: Column, proc: &Process<'_, R>) -> Option<Option<Measure>> {
+    let value = match col {
+        Column::Rss => proc.statm().map(|m| Measure::Kb(statm_to_kb(m.resident))),
+        Column::Pss => proc.smaps().map(|s| Measure::Kb(s.pss)),
+        Column::Swap => proc.smaps().map(|s| Measure::Kb(s.swap)),
+        Column::Vm(field) => proc.status().and_then(|st| field.get(st)).map(Measure::Kb),
+        Column::StackKb => proc.stack_kb().map(Measure::Kb),
+        Column::TotalStack => read_stack_usage(proc.pid(), proc.reader())
+            .ok()
+            .map(|usage| Measure::Kb(usage.total_kb())),
+        Column::Age => proc.age().map(|d| Measure::Secs(d.as_secs_f64())),
+        Column::CpuTime => proc.cpu_seconds().map(Measure::Secs),
+        Column::UserTime => proc.user_seconds().map(Measure::Secs),
+        Column::SysTime => proc.sys_seconds().map(Measure::Secs),
+        _ => return None,
+    };
+    Some(value)
+}
+
+// Byte counts below 1 kB stay in bytes, the rest go through `human_size`.
+fn human_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else {
+        human_size(bytes / 1024)
+    }
+}
+
+fn human_ns(ns: u64) -> String {
+    human_duration(ns as f64 / 1e9)
+}
+
+// `-H` for the columns holding several values; `None` for other columns
+// and when the values cannot be read, which `format_column` reports.
+fn format_fields_human<R: ProcReader>(col: Column, proc: &Process<'_, R>) -> Option<String> {
+    let text = match col {
+        Column::Statm => {
+            let m = proc.statm()?;
+            format!(
+                "size={},rss={},shr={}",
+                human_size(statm_to_kb(m.size)),
+                human_size(statm_to_kb(m.resident)),
+                human_size(statm_to_kb(m.shared))
+            )
+        }
+        Column::HugePages => {
+            let st = proc.status()?;
+            let kb = |v: Option<u64>| v.map_or_else(|| MISSING.to_string(), human_size);
+            format!("huge={},htlb={}", kb(st.vm_huge), kb(st.hugetlb_pages))
+        }
+        Column::MapsSummary => {
+            let m = read_maps_summary(proc.pid(), proc.reader()).ok()?;
+            format!(
+                "anon={},file={},special={},max={}",
+                m.anon,
+                m.file,
+                m.special,
+                human_size(m.max_kb)
+            )
+        }
+        Column::IoFull => {
+            let io = proc.io()?;
+            format!(
+                "rchar={},wchar={},syscr={},syscw={},read_bytes={},write_bytes={},cancelled_write_bytes={}",
+                human_bytes(io.rchar),
+                human_bytes(io.wchar),
+                io.syscr,
+                io.syscw,
+                human_bytes(io.read_bytes),
+                human_bytes(io.write_bytes),
+                human_bytes(io.cancelled_write_bytes)
+            )
+        }
+        Column::IoChars => {
+            let io = proc.io()?;
+            format!(
+                "rchar={},wchar={}",
+                human_bytes(io.rchar),
+                human_bytes(io.wchar)
+            )
+        }
+        Column::Sched => {
+            let s = proc.sched()?;
+            format!(
+                "run={},wait={},slices={}",
+                human_ns(s.run_time_ns),
+                human_ns(s.wait_time_ns),
+                s.time_slices
+            )
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// [`format_column`] with sizes through [`human_size`] and times through
+/// [`human_duration`] (`-H`), including the ones inside multi-valued
+/// columns such as statm, io and schedstat; other columns are unchanged.
+pub fn format_column_human<R: ProcReader>(col: Column, proc: &Process<'_, R>) -> String {
+    if let Some(text) = format_fields_human(col, proc) {
+        return text;
+    }
+    match measure(col, proc) {
+        Some(Some(Measure::Kb(kb))) => format!("{}={}", col.name(), human_size(kb)),
+        Some(Some(Measure::Secs(secs))) => format!("{}={}", col.name(), human_duration(secs)),
+        Some(None) => format!("{}={MISSING}", col.name()),
+        None => format_column(col, proc),
+    }
+}
+
+/// Compact duration: `3d5h12m`, `2h0m`, `4m9s`, `42s`.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (days, hours, mins, s) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{days}d{hours}h{mins}m")
+    } else if hours > 0 {
+        format!("{hours}h{mins}m")
+    } else if mins > 0 {
+        format!("{mins}m{s}s")
+    } else {
+        format!("{s}s")
+    }
+}
+
+/// Age as a phrase: `42s ago`, `4m 9s ago`, `2h 14m ago`, `3d 5h ago`; a
+/// zero second unit is left out (`5m ago`, `3d ago`).
+pub fn to_relative_time(age: Duration) -> String {
+    let secs = age.as_secs();
+    let (big, small) = if secs < 60 {
+        ((secs, 's'), None)
+    } else if secs < 3600 {
+        ((secs / 60, 'm'), Some((secs % 60, 's')))
+    } else if secs < 86400 {
+        ((secs / 3600, 'h'), Some((secs / 60 % 60, 'm')))
+    } else {
+        ((secs / 86400, 'd'), Some((secs / 3600 % 24, 'h')))
+    };
+    match small {
+        Some((n, unit)) if n > 0 => format!("{}{} {}{} ago", big.0, big.1, n, unit),
+        _ => format!("{}{} ago", big.0, big.1),
+    }
+}
+
+/// Parses `90`, `45s`, `5m`, `2h`, `3d` or combinations such as `1h30m`;
+/// a bare number is seconds.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    if let Ok(secs) = s.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let mut total = 0u64;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        if digits == 0 {
+            return None;
+        }
+        let n: u64 = rest[..digits].parse().ok()?;
+        let unit = match rest.as_bytes()[digits] {
+            b's' => 1,
+            b'm' => 60,
+            b'h' => 3600,
+            b'd' => 86400,
+            _ => return None,
+        };
+        total = total.checked_add(n.checked_mul(unit)?)?;
+        rest = &rest[digits + 1..];
+    }
+    (!s.is_empty()).then(|| Duration::from_secs(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::SystemCache;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+        assert_eq!(format_duration(Duration::from_secs(59)), "59s");
+        assert_eq!(format_duration(Duration::from_secs(249)), "4m9s");
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2h0m");
+        assert_eq!(
+            format_duration(Duration::from_secs(3 * 86400 + 5 * 3600 + 12 * 60 + 7)),
+            "3d5h12m"
+        );
+    }
+
+    #[test]
+    fn test_format_age_column() {
+        let reader = MockProcReader::new()
+            .file(
+                9,
+                "stat",
+                "9 (sleep) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 10000 0 0\n",
+            )
+            .root_file("uptime", "3760.00 0.00\n");
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Age, &Process::new(9, &reader, &sys)),
+            "age=1h1m"
+        );
+        assert_eq!(
+            format_column(Column::Age, &Process::new(10, &reader, &sys)),
+            "age=-"
+        );
+    }
+
+    #[test]
+    fn test_format_smaps_columns() {
+        let reader = MockProcReader::new().file(3, "smaps_rollup", "Pss:  2048 kB\nSwap:  16 kB\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert_eq!(format_column(Column::Pss, &proc), "pss=2048kB");
+        assert_eq!(format_column(Column::Swap, &proc), "swap=16kB");
+        assert_eq!(
+            format_column(Column::Pss, &Process::new(4, &reader, &sys)),
+            "pss=-"
+        );
+    }
+
+    #[test]
+    fn test_format_oom_column() {
+        let reader =
+            MockProcReader::new()
+                .file(3, "oom_score", "987\n")
+                .file(3, "oom_score_adj", "-17\n");
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Oom, &Process::new(3, &reader, &sys)),
+            "oom=987,adj=-17"
+        );
+        assert_eq!(
+            format_column(Column::Oom, &Process::new(4, &reader, &sys)),
+            "oom=-,adj=-"
+        );
+        assert_eq!(
+            format_column(Column::OomAdj, &Process::new(3, &reader, &sys)),
+            "oom_adj=-17"
+        );
+        assert_eq!(
+            format_column(Column::OomAdj, &Process::new(4, &reader, &sys)),
+            "oom_adj=-"
+        );
+    }
+
+    #[test]
+    fn test_format_coredump_column() {
+        let reader = MockProcReader::new()
+            .file(3, "coredump_filter", "00000013\n")
+            .file(4, "coredump_filter", "00000000\n");
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::CoredumpFilter, &Process::new(3, &reader, &sys)),
+            "coredump=anon_private|anon_shared|elf_headers"
+        );
+        assert_eq!(
+            format_column(Column::CoredumpFilter, &Process::new(4, &reader, &sys)),
+            "coredump=none"
+        );
+        assert_eq!(
+            format_column(Column::CoredumpFilter, &Process::new(5, &reader, &sys)),
+            "coredump=-"
+        );
+    }
+
+    #[test]
+    fn test_format_statm_column() {
+        let reader = MockProcReader::new().file(3, "statm", "300 200 100 1 0 1 0\n");
+        let sys = SystemCache::new();
+        let kb = |pages| statm_to_kb(pages);
+        assert_eq!(
+            format_column(Column::Statm, &Process::new(3, &reader, &sys)),
+            format!("size={}kB,rss={}kB,shr={}kB", kb(300), kb(200), kb(100))
+        );
+        assert_eq!(
+            format_column(Column::Statm, &Process::new(4, &reader, &sys)),
+            "size=-,rss=-,shr=-"
+        );
+    }
+
+    #[test]
+    fn test_format_io_full_column() {
+        let reader = MockProcReader::new()
+            .file(3, "io", "rchar: 1\nwchar: 2\nsyscr: 3\nsyscw: 4\nread_bytes: 5\nwrite_bytes: 6\ncancelled_write_bytes: 7\n")
+            .error(4, "io", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::IoFull, &Process::new(3, &reader, &sys)),
+            "rchar=1,wchar=2,syscr=3,syscw=4,read_bytes=5,write_bytes=6,cancelled_write_bytes=7"
+        );
+        assert_eq!(
+            format_column(Column::IoFull, &Process::new(4, &reader, &sys)),
+            "io=-"
+        );
+    }
+
+    #[test]
+    fn test_format_io_chars_column() {
+        let reader = MockProcReader::new()
+            .file(3, "io", "rchar: 8675309\nwchar: 4096\nsyscr: 120\nsyscw: 3\nread_bytes: 0\nwrite_bytes: 0\n")
+            .error(4, "io", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::IoChars, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "rchar=8675309,wchar=4096");
+        assert_eq!(col(4), "rchar=-,wchar=-");
+    }
+
+    #[test]
+    fn test_format_sched_columns() {
+        let reader = MockProcReader::new().file(3, "schedstat", "300 100 7\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert_eq!(
+            format_column(Column::Sched, &proc),
+            "run=300ns,wait=100ns,slices=7"
+        );
+        assert_eq!(
+            format_column(Column::SchedPressure, &proc),
+            "pressure=0.250"
+        );
+        assert_eq!(
+            format_column(Column::SchedPressure, &Process::new(4, &reader, &sys)),
+            "pressure=-"
+        );
+    }
+
+    #[test]
+    fn test_format_rss_pct_column() {
+        let meminfo = format!("MemTotal: {} kB\n", statm_to_kb(1000));
+        let reader = MockProcReader::new()
+            .file(3, "statm", "300 100 50 1 0 1 0\n")
+            .root_file("meminfo", &meminfo);
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::RssPct, &Process::new(3, &reader, &sys)),
+            "rss_pct=10.00%"
+        );
+        assert_eq!(
+            format_column(Column::RssPct, &Process::new(4, &reader, &sys)),
+            "rss_pct=-"
+        );
+    }
+
+    #[test]
+    fn test_format_affinity_column() {
+        let reader =
+            MockProcReader::new().file(3, "status", "Name:\tx\nCpus_allowed_list:\t0-3,7\n");
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Affinity, &Process::new(3, &reader, &sys)),
+            "cpus=0-3,7"
+        );
+        assert_eq!(
+            format_column(Column::Affinity, &Process::new(4, &reader, &sys)),
+            "cpus=-"
+        );
+    }
+
+    #[test]
+    fn test_format_numa_column() {
+        let reader = MockProcReader::new()
+            .file(3, "numa_maps", "1000 bind:0 anon=1\n")
+            .error(4, "numa_maps", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Numa, &Process::new(3, &reader, &sys)),
+            "numa=bind:0"
+        );
+        assert_eq!(
+            format_column(Column::Numa, &Process::new(4, &reader, &sys)),
+            "numa=-"
+        );
+    }
+
+    #[test]
+    fn test_format_scheduler_column() {
+        let reader = MockProcReader::new().file(3, "sched", "policy : 0\nprio : 120\n");
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Scheduler, &Process::new(3, &reader, &sys)),
+            "sched=SCHED_OTHER/nice=0"
+        );
+        assert_eq!(
+            format_column(Column::Scheduler, &Process::new(4, &reader, &sys)),
+            "sched=-"
+        );
+    }
+
+    #[test]
+    fn test_format_maps_summary_column() {
+        let reader = MockProcReader::new().file(
+            3,
+            "maps",
+            "00400000-00500000 r-xp 00000000 08:01 12 /usr/bin/x\n\
+             7f00000000-7f00001000 rw-p 00000000 00:00 0 \n\
+             7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0 [stack]\n",
+        );
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::MapsSummary, &Process::new(3, &reader, &sys)),
+            "anon=1,file=1,special=1,max=1024kB"
+        );
+        assert_eq!(
+            format_column(Column::MapsSummary, &Process::new(4, &reader, &sys)),
+            "anon=-,file=-,special=-,max=-"
+        );
+    }
+
+    #[test]
+    fn test_format_ns_column() {
+        let reader = ["pid", "mnt", "net", "uts", "ipc", "user"]
+            .iter()
+            .zip(1..)
+            .fold(MockProcReader::new(), |r, (kind, n)| {
+                r.link(3, &format!("ns/{kind}"), format!("{kind}:[{n}]"))
+            });
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Ns, &Process::new(3, &reader, &sys)),
+            "pid_ns=1,mnt_ns=2,net_ns=3,uts_ns=4,ipc_ns=5,user_ns=6"
+        );
+        assert_eq!(
+            format_column(Column::Ns, &Process::new(4, &reader, &sys)),
+            "pid_ns=-,mnt_ns=-,net_ns=-,uts_ns=-,ipc_ns=-,user_ns=-"
+        );
+    }
+
+    #[test]
+    fn test_format_tracer_column() {
+        let reader = MockProcReader::new()
+            .file(3, "status", "Name:\tx\nTracerPid:\t4100\n")
+            .file(4, "status", "Name:\tx\nTracerPid:\t0\n");
+        let sys = SystemCache::new();
+        let tracer = |pid| format_column(Column::Tracer, &Process::new(pid, &reader, &sys));
+        assert_eq!(tracer(3), "tracer=4100");
+        assert_eq!(tracer(4), "tracer=0");
+        assert_eq!(tracer(5), "tracer=-");
+    }
+
+    #[test]
+    fn test_format_wchan_column() {
+        let reader = MockProcReader::new()
+            .file(3, "wchan", "do_sys_poll")
+            .file(4, "wchan", "0");
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Wchan, &Process::new(3, &reader, &sys)),
+            "wchan=do_sys_poll"
+        );
+        assert_eq!(
+            format_column(Column::Wchan, &Process::new(4, &reader, &sys)),
+            "wchan=0"
+        );
+        assert_eq!(
+            format_column(Column::Wchan, &Process::new(5, &reader, &sys)),
+            "wchan=-"
+        );
+    }
+
+    #[test]
+    fn test_format_ns_pid_column() {
+        let reader = MockProcReader::new()
+            .file(3, "status", "Name:\tx\nNSpid:\t9001\t311\t8\n")
+            .file(4, "status", "Name:\tx\n");
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::NsPid, &Process::new(3, &reader, &sys)),
+            "ns_pid=8"
+        );
+        assert_eq!(
+            format_column(Column::NsPid, &Process::new(4, &reader, &sys)),
+            "ns_pid=-"
+        );
+    }
+
+    #[test]
+    fn test_column_value() {
+        let reader = MockProcReader::new()
+            .file(3, "smaps_rollup", "Pss: 2048 kB\nSwap: 0 kB\n")
+            .file(3, "statm", "1 1 1 1 0 1 0\n")
+            .file(3, "oom_score", "5\n")
+            .file(3, "oom_score_adj", "0\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert_eq!(column_value(Column::Pss, &proc, false), "2048kB");
+        assert_eq!(column_value(Column::Oom, &proc, false), "5,adj=0");
+        assert!(column_value(Column::Statm, &proc, false).starts_with("size="));
+        assert_eq!(column_value(Column::Age, &proc, false), "-");
+    }
+
+    #[test]
+    fn test_format_identity_columns() {
+        let reader = MockProcReader::new()
+            .file(3, "cmdline", "/usr/sbin/nginx\0-g\0daemon off;\0")
+            .link(3, "exe", "/usr/sbin/nginx")
+            .file(
+                3,
+                "stat",
+                "3 (nginx) S 1 3 3 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 500 0 0\n",
+            )
+            .file(
+                3,
+                "status",
+                "Name:\tnginx\nUid:\t33\t0\t0\t0\nGid:\t44\t0\t0\t0\n",
+            )
+            .file(3, "statm", "300 200 100 1 0 1 0\n")
+            .root_file("stat", "cpu  1 2 3 4\nbtime 1791969330\n")
+            .file(
+                4,
+                "stat",
+                "4 (kworker/0:1) I 2 0 0 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 5 0 0\n",
+            );
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        let col = |c| format_column(c, &proc);
+        assert_eq!(col(Column::Pid), "pid=3");
+        assert_eq!(col(Column::Name), "name=nginx");
+        assert_eq!(col(Column::Exe), "exe=/usr/sbin/nginx");
+        assert_eq!(
+            col(Column::Cmdline),
+            "cmdline=/usr/sbin/nginx -g daemon off;"
+        );
+        assert_eq!(col(Column::Uid), "uid=33");
+        assert_eq!(col(Column::Gid), "gid=44");
+        assert_eq!(col(Column::Ppid), "ppid=1");
+        assert_eq!(col(Column::State), "state=S");
+        assert_eq!(col(Column::Nice), "nice=0");
+        assert_eq!(col(Column::Rss), format!("rss={}kB", statm_to_kb(200)));
+        assert_eq!(
+            col(Column::StartTime),
+            format!(
+                "starttime={}",
+                iso8601_utc(UNIX_EPOCH + Duration::from_secs(1_791_969_330 + 500 / sys.clk_tck()))
+            )
+        );
+
+        let kthread = Process::new(4, &reader, &sys);
+        assert_eq!(format_column(Column::Name, &kthread), "name=kworker/0:1");
+        assert_eq!(format_column(Column::Exe, &kthread), "exe=-");
+        assert_eq!(format_column(Column::Cmdline, &kthread), "cmdline=-");
+        assert_eq!(format_column(Column::Uid, &kthread), "uid=-");
+        let gone = Process::new(5, &reader, &sys);
+        assert_eq!(format_column(Column::Name, &gone), "name=-");
+        assert_eq!(format_column(Column::StartTime, &gone), "starttime=-");
+        assert_eq!(format_column(Column::Nice, &gone), "nice=-");
+    }
+
+    #[test]
+    fn test_column_from_str() {
+        for name in COLUMN_NAMES.split(' ') {
+            let col: Column = name.parse().unwrap();
+            assert!(
+                col.name() == name || ["cputime", "ctx_rate"].contains(&name),
+                "{name}"
+            );
+        }
+        assert_eq!("cpu".parse(), Ok(Column::CpuTime));
+        assert_eq!("Pid".parse::<Column>(), Err(()));
+        assert_eq!("".parse::<Column>(), Err(()));
+    }
+
+    #[test]
+    fn test_to_relative_time() {
+        let rel = |secs| to_relative_time(Duration::from_secs(secs));
+        assert_eq!(rel(0), "0s ago");
+        assert_eq!(rel(59), "59s ago");
+        assert_eq!(rel(300), "5m ago");
+        assert_eq!(rel(249), "4m 9s ago");
+        assert_eq!(rel(2 * 3600 + 14 * 60 + 5), "2h 14m ago");
+        assert_eq!(rel(3 * 86400 + 59), "3d ago");
+        assert_eq!(rel(3 * 86400 + 5 * 3600), "3d 5h ago");
+    }
+
+    #[test]
+    fn test_start_time_relative_column() {
+        let reader = MockProcReader::new()
+            .file(
+                9,
+                "stat",
+                "9 (sleep) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 10000 0 0\n",
+            )
+            .root_file("uptime", "3760.00 0.00\n");
+        let sys = SystemCache::new();
+        let ticks = sys.clk_tck() as f64;
+        let want = to_relative_time(Duration::from_secs_f64(3760.0 - 10000.0 / ticks));
+        assert_eq!(
+            format_column(Column::StartTimeRelative, &Process::new(9, &reader, &sys)),
+            format!("starttime={want}")
+        );
+        assert_eq!(
+            format_column(Column::StartTime, &Process::new(9, &reader, &sys)),
+            "starttime=-"
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        let secs = |s| parse_duration(s).map(|d| d.as_secs());
+        assert_eq!(secs("90"), Some(90));
+        assert_eq!(secs("45s"), Some(45));
+        assert_eq!(secs("5m"), Some(300));
+        assert_eq!(secs("2h"), Some(7200));
+        assert_eq!(secs("3d"), Some(259200));
+        assert_eq!(secs("1h30m"), Some(5400));
+        for bad in [
+            "",
+            "m",
+            "5x",
+            "1h30",
+            "-5m",
+            "1.5h",
+            "99999999999999999999d",
+        ] {
+            assert_eq!(secs(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_format_env_var_column() {
+        let reader = MockProcReader::new()
+            .file(3, "environ", b"LANG=C\0VIRTUAL_ENV=/srv/venv\0APP_ENV=\0")
+            .error(4, "environ", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        let env = |key| format_column(Column::EnvVar(key), &proc);
+        assert_eq!(env("VIRTUAL_ENV"), "VIRTUAL_ENV=/srv/venv");
+        assert_eq!(env("APP_ENV"), "APP_ENV=");
+        assert_eq!(env("HOME"), "HOME=");
+        assert_eq!(column_value(Column::EnvVar("LANG"), &proc, false), "C");
+        assert_eq!(
+            format_column(Column::EnvVar("LANG"), &Process::new(4, &reader, &sys)),
+            "LANG=-"
+        );
+    }
+
+    #[test]
+    fn test_format_cwd_column() {
+        let reader = MockProcReader::new()
+            .link(3, "cwd", "/home/dev/project")
+            .error(4, "cwd", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Cwd, &Process::new(3, &reader, &sys)),
+            "cwd=/home/dev/project"
+        );
+        assert_eq!(
+            format_column(Column::Cwd, &Process::new(4, &reader, &sys)),
+            "cwd=-"
+        );
+    }
+
+    #[test]
+    fn test_format_fd_columns() {
+        let reader = MockProcReader::new()
+            .link(3, "fd/0", "/dev/null")
+            .link(3, "fd/1", "pipe:[1]")
+            .file(
+                3,
+                "limits",
+                "Max open files            1024                 4096                 files\n",
+            )
+            .file(
+                4,
+                "limits",
+                "Max open files            unlimited            unlimited            files\n",
+            )
+            .error(4, "fd", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let (p3, p4) = (
+            Process::new(3, &reader, &sys),
+            Process::new(4, &reader, &sys),
+        );
+        assert_eq!(format_column(Column::FdCount, &p3), "fd_count=2");
+        assert_eq!(format_column(Column::FdLimit, &p3), "fd_limit=1024");
+        assert_eq!(format_column(Column::FdCount, &p4), "fd_count=-");
+        assert_eq!(format_column(Column::FdLimit, &p4), "fd_limit=unlimited");
+        assert_eq!(
+            format_column(Column::FdLimit, &Process::new(5, &reader, &sys)),
+            "fd_limit=-"
+        );
+    }
+
+    #[test]
+    fn test_format_maps_count_column() {
+        let reader = MockProcReader::new().file(
+            3,
+            "maps",
+            "00400000-00500000 r-xp 00000000 08:01 12 /usr/bin/x\n\
+             7f00000000-7f00001000 rw-p 00000000 00:00 0 \n",
+        );
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::MapsCount, &Process::new(3, &reader, &sys)),
+            "maps_count=2"
+        );
+        assert_eq!(
+            format_column(Column::MapsCount, &Process::new(4, &reader, &sys)),
+            "maps_count=-"
+        );
+    }
+
+    #[test]
+    fn test_format_open_files_column() {
+        let reader = MockProcReader::new()
+            .link(3, "fd/0", "/dev/pts/0")
+            .link(3, "fd/1", "/tmp/out.log (deleted)")
+            .link(3, "fd/2", "socket:[9]")
+            .error(4, "fd", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let files = |max, deleted, pid| {
+            format_column(
+                Column::OpenFiles { max, deleted },
+                &Process::new(pid, &reader, &sys),
+            )
+        };
+        assert_eq!(
+            files(5, true, 3),
+            "files=/dev/pts/0;/tmp/out.log (deleted);socket:[9]"
+        );
+        assert_eq!(files(5, false, 3), "files=/dev/pts/0;socket:[9]");
+        assert_eq!(files(1, true, 3), "files=/dev/pts/0");
+        assert_eq!(files(5, true, 4), "files=-");
+    }
+
+    #[test]
+    fn test_format_anon_exec_column() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "maps",
+                "00400000-00500000 r-xp 00000000 08:01 12 /usr/bin/node\n\
+                 3a1c00000000-3a1c00040000 rwxp 00000000 00:00 0 \n",
+            )
+            .file(
+                4,
+                "maps",
+                "00400000-00500000 r-xp 00000000 08:01 12 /usr/bin/x\n",
+            );
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::AnonExec, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "anon_exec=rwx@3a1c00000000");
+        assert_eq!(col(4), "anon_exec=none");
+        assert_eq!(col(5), "anon_exec=-");
+    }
+
+    #[test]
+    fn test_format_stack_columns() {
+        let maps = "7ffd6e9e5000-7ffd6ea06000 rw-p 00000000 00:00 0   [stack]\n\
+                    7f1c2a000000-7f1c2a800000 rw-p 00000000 00:00 0   [stack:41]\n";
+        let reader = MockProcReader::new()
+            .file(3, "status", "Name:\tjava\nVmStk:\t     136 kB\n")
+            .file(3, "maps", maps)
+            .file(4, "status", "Name:\tjava\n")
+            .file(4, "maps", maps);
+        let sys = SystemCache::new();
+        let col = |c, pid| format_column(c, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(Column::StackKb, 3), "stack_kb=136");
+        // Without VmStk the [stack] mapping is measured instead.
+        assert_eq!(col(Column::StackKb, 4), "stack_kb=132");
+        assert_eq!(col(Column::StackKb, 5), "stack_kb=-");
+        assert_eq!(col(Column::TotalStack, 3), "total_stack=8324kB");
+        assert_eq!(col(Column::TotalStack, 5), "total_stack=-");
+    }
+
+    #[test]
+    fn test_format_cpu_pct_column() {
+        // Started 100s after boot, 3660s ago; 300 + 66 CPU seconds at USER_HZ=100.
+        let reader = MockProcReader::new()
+            .file(
+                9,
+                "stat",
+                "9 (java) S 1 9 9 0 -1 0 0 0 0 0 30000 6600 0 0 20 0 1 0 10000 0 0\n",
+            )
+            .file(
+                10,
+                "stat",
+                "10 (true) R 1 10 10 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 376000 0 0\n",
+            )
+            .root_file("uptime", "3760.00 0.00\n");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::CpuPct, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(9), "cpu_pct=10.00%");
+        // Younger than one tick.
+        assert_eq!(col(10), "cpu_pct=0.00%");
+        assert_eq!(col(11), "cpu_pct=-");
+    }
+
+    #[test]
+    fn test_format_user_sys_time_columns() {
+        // utime 1234 ticks, stime 56 ticks at USER_HZ=100.
+        let reader = MockProcReader::new().file(
+            9,
+            "stat",
+            "9 (rsync) D 1 9 9 0 -1 0 0 0 0 0 1234 56 0 0 20 0 1 0 10000 0 0\n",
+        );
+        let sys = SystemCache::new();
+        let col = |c, pid| format_column(c, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(Column::UserTime, 9), "utime=12.34s");
+        assert_eq!(col(Column::SysTime, 9), "stime=0.56s");
+        assert_eq!(col(Column::CpuTime, 9), "cpu=12.90s");
+        assert_eq!(col(Column::UserTime, 10), "utime=-");
+        assert_eq!(col(Column::SysTime, 10), "stime=-");
+    }
+
+    #[test]
+    fn test_format_ctx_rate_column() {
+        // 4 CPU seconds at USER_HZ=100.
+        let reader = MockProcReader::new()
+            .file(
+                9,
+                "stat",
+                "9 (pg) S 1 9 9 0 -1 0 0 0 0 0 300 100 0 0 20 0 1 0 1 0 0\n",
+            )
+            .file(9, "status", "Name:\tpg\nvoluntary_ctxt_switches:\t1234\n")
+            .file(
+                10,
+                "stat",
+                "10 (pg) S 1 10 10 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 1 0 0\n",
+            )
+            .file(10, "status", "Name:\tpg\nvoluntary_ctxt_switches:\t7\n")
+            .file(11, "status", "Name:\tpg\nvoluntary_ctxt_switches:\t7\n");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::CtxRate, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(9), "ctx/cpu_s=308.5");
+        assert_eq!(col(10), "ctx/cpu_s=7.0");
+        assert_eq!(col(11), "ctx/cpu_s=-");
+        assert_eq!("ctx/cpu_s".parse(), Ok(Column::CtxRate));
+    }
+
+    #[test]
+    fn test_format_fault_rate_columns() {
+        // minflt 12000, majflt 300; started 100s after boot, 600s ago.
+        let reader = MockProcReader::new()
+            .file(
+                9,
+                "stat",
+                "9 (pg) S 1 9 9 0 -1 0 12000 0 300 0 0 0 0 0 20 0 1 0 10000 0 0\n",
+            )
+            .root_file("uptime", "700.00 0.00\n");
+        let sys = SystemCache::new();
+        let col = |c, pid| format_column(c, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(Column::FaultRate, 9), "pgflt_rate=20.50/s");
+        assert_eq!(col(Column::MinFltRate, 9), "minflt_rate=20.00/s");
+        assert_eq!(col(Column::MajFltRate, 9), "majflt_rate=0.50/s");
+        assert_eq!(col(Column::MajFltRate, 10), "majflt_rate=-");
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0 kB");
+        assert_eq!(human_size(1023), "1023 kB");
+        assert_eq!(human_size(1024), "1.0 MB");
+        assert_eq!(human_size(4300), "4.2 MB");
+        assert_eq!(human_size(1024 * 1024 - 1), "1024.0 MB");
+        assert_eq!(human_size(1024 * 1024), "1.0 GB");
+        assert_eq!(human_size(12_897_485), "12.3 GB");
+        assert_eq!(human_size(5 << 40), "5120.0 TB");
+    }
+
+    #[test]
+    fn test_human_duration() {
+        assert_eq!(human_duration(0.0), "0.00s");
+        assert_eq!(human_duration(0.47), "0.47s");
+        assert_eq!(human_duration(1.0), "1s");
+        assert_eq!(human_duration(59.9), "59s");
+        assert_eq!(human_duration(60.0), "1m");
+        assert_eq!(human_duration(11112.0), "3h 5m 12s");
+        assert_eq!(human_duration(86400.0 + 7.0), "1d 7s");
+        assert_eq!(human_duration(-3.0), "0.00s");
+        assert_eq!(human_duration(f64::NAN), "0.00s");
+    }
+
+    #[test]
+    fn test_format_column_human() {
+        // 100s after boot, 11112s ago; 1234 + 56 CPU ticks at USER_HZ=100.
+        let reader = MockProcReader::new()
+            .file(
+                9,
+                "stat",
+                "9 (java) S 1 9 9 0 -1 0 0 0 0 0 1234 56 0 0 20 0 1 0 10000 0 0\n",
+            )
+            .file(9, "status", "Name:\tjava\nVmSize:\t 4404019 kB\n")
+            .file(9, "smaps_rollup", "Pss:  1023 kB\nSwap:  0 kB\n")
+            .root_file("uptime", "11212.00 0.00\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(9, &reader, &sys);
+        let human = |c| format_column_human(c, &proc);
+        assert_eq!(human(Column::Vm(VmField::Size)), "vm_size=4.2 GB");
+        assert_eq!(human(Column::Pss), "pss=1023 kB");
+        assert_eq!(human(Column::Swap), "swap=0 kB");
+        assert_eq!(human(Column::Age), "age=3h 5m 12s");
+        assert_eq!(human(Column::CpuTime), "cpu=12s");
+        assert_eq!(human(Column::SysTime), "stime=0.56s");
+        // Not a size or time.
+        assert_eq!(human(Column::Ppid), format_column(Column::Ppid, &proc));
+        assert_eq!(human(Column::Rss), "rss=-");
+        assert_eq!(column_value(Column::Age, &proc, true), "3h 5m 12s");
+        assert_eq!(column_value(Column::Age, &proc, false), "3h5m");
+    }
+
+    #[test]
+    fn test_format_fields_human() {
+        let reader = MockProcReader::new()
+            .file(3, "statm", "1048576 512 1 1 0 1 0\n")
+            .file(3, "status", "Name:\tdb\nHugetlbPages:\t 2097152 kB\n")
+            .file(
+                3,
+                "io",
+                "rchar: 8675309\nwchar: 0\nsyscr: 120\nsyscw: 3\nread_bytes: 4096\n\
+                 write_bytes: 1023\ncancelled_write_bytes: 0\n",
+            )
+            .file(3, "schedstat", "11112000000000 470000000 7\n")
+            .file(
+                3,
+                "maps",
+                "00400000-80400000 r-xp 00000000 08:01 12 /usr/bin/db\n",
+            );
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        let human = |c| format_column_human(c, &proc);
+        let kb = |pages| human_size(statm_to_kb(pages));
+        assert_eq!(
+            human(Column::Statm),
+            format!("size={},rss={},shr={}", kb(1048576), kb(512), kb(1))
+        );
+        assert_eq!(human(Column::HugePages), "huge=-,htlb=2.0 GB");
+        assert_eq!(
+            human(Column::MapsSummary),
+            "anon=0,file=1,special=0,max=2.0 GB"
+        );
+        assert_eq!(
+            human(Column::IoFull),
+            "rchar=8.3 MB,wchar=0 B,syscr=120,syscw=3,read_bytes=4 kB,\
+             write_bytes=1023 B,cancelled_write_bytes=0 B"
+        );
+        assert_eq!(human(Column::IoChars), "rchar=8.3 MB,wchar=0 B");
+        assert_eq!(human(Column::Sched), "run=3h 5m 12s,wait=0.47s,slices=7");
+        // Unreadable values keep the plain format.
+        let missing = Process::new(4, &reader, &sys);
+        assert_eq!(
+            format_column_human(Column::Statm, &missing),
+            "size=-,rss=-,shr=-"
+        );
+        assert_eq!(format_column_human(Column::IoFull, &missing), "io=-");
+    }
+
+    #[test]
+    fn test_format_vm_columns() {
+        let reader = MockProcReader::new().file(
+            9,
+            "status",
+            "Name:\tjava\nVmPeak:\t 4310016 kB\nVmSize:\t 4194304 kB\n\
+             VmData:\t  912384 kB\nVmExe:\t       4 kB\nVmLib:\t   23552 kB\nVmLck:\t      64 kB\nVmPin:\t       0 kB\n",
+        );
+        let sys = SystemCache::new();
+        let col = |c, pid| format_column(c, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(Column::Vm(VmField::Size), 9), "vm_size=4194304kB");
+        assert_eq!(col(Column::Vm(VmField::Peak), 9), "vm_peak=4310016kB");
+        assert_eq!(col(Column::Vm(VmField::Data), 9), "vm_data=912384kB");
+        assert_eq!(col(Column::Vm(VmField::Exe), 9), "vm_exe=4kB");
+        assert_eq!(col(Column::Vm(VmField::Lib), 9), "vm_lib=23552kB");
+        assert_eq!(col(Column::Vm(VmField::Pin), 9), "pin=0kB");
+        assert_eq!(col(Column::Vm(VmField::Lck), 9), "locked=64kB");
+        assert_eq!(col(Column::Vm(VmField::Size), 10), "vm_size=-");
+        assert_eq!(col(Column::Vm(VmField::Pin), 10), "pin=-");
+    }
+
+    #[test]
+    fn test_format_fd_types_column() {
+        let reader = MockProcReader::new()
+            .link(3, "fd/0", "/dev/null")
+            .link(3, "fd/1", "socket:[99]")
+            .link(3, "fd/2", "pipe:[98]")
+            .link(3, "fd/3", "anon_inode:[eventpoll]")
+            .error(4, "fd", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::FdTypes, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "reg=0,sock=1,pipe=1,chr=1,epoll=1,other=0");
+        assert_eq!(col(4), "reg=-,sock=-,pipe=-,other=-");
+        assert_eq!(
+            column_value(Column::FdTypes, &Process::new(3, &reader, &sys), false),
+            "reg=0,sock=1,pipe=1,chr=1,epoll=1,other=0"
+        );
+    }
+
+    #[test]
+    fn test_format_sockets_column() {
+        let reader = MockProcReader::new()
+            .root_file(
+                "net/tcp",
+                "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+                 0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 501 1\n",
+            )
+            .root_file(
+                "net/unix",
+                "Num       RefCount Protocol Flags    Type St Inode Path\n\
+                 0000000000000000: 00000002 00000000 00010000 0001 01 502 /run/x.sock\n",
+            )
+            .link(3, "fd/3", "socket:[501]")
+            .link(3, "fd/4", "socket:[502]")
+            .link(3, "fd/5", "socket:[503]")
+            .link(4, "fd/0", "/dev/null");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::Sockets, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "tcp=1,udp=0,unix=1");
+        assert_eq!(col(4), "tcp=0,udp=0,unix=0");
+        assert_eq!(col(5), "tcp=-,udp=-,unix=-");
+    }
+
+    #[test]
+    fn test_format_listen_ports_column() {
+        let reader = MockProcReader::new()
+            .root_file(
+                "net/tcp",
+                "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+                 0: 00000000:0050 00000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 601 1\n\
+                 1: 0100007F:0050 0100007F:D431 01 00000000:00000000 00:00000000 00000000 0 0 602 1\n",
+            )
+            .root_file(
+                "net/udp",
+                "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+                 0: 00000000:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000 0 0 603 2\n",
+            )
+            .link(3, "fd/3", "socket:[601]")
+            .link(3, "fd/4", "socket:[603]")
+            .link(4, "fd/3", "socket:[602]");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::ListenPorts, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "listen=tcp:80,udp:53");
+        assert_eq!(col(4), "listen=none");
+        assert_eq!(col(5), "listen=-");
+    }
+
+    #[test]
+    fn test_format_numa_local_pct_column() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "numa_maps",
+                "1000 default anon=8 N0=6 N1=2\n2000 default\n",
+            )
+            .file(4, "numa_maps", "");
+        let sys = SystemCache::new();
+        let col = |c, pid| format_column(c, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(Column::NumaLocalPct(0), 3), "numa_local=75.00%");
+        assert_eq!(col(Column::NumaLocalPct(1), 3), "numa_local=25.00%");
+        assert_eq!(col(Column::NumaLocalPct(0), 4), "numa_local=-");
+        assert_eq!(col(Column::NumaLocalPct(0), 5), "numa_local=-");
+    }
+
+    #[test]
+    fn test_format_huge_pages_column() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "status",
+                "Name:\tdb\nVmHuge:\t4096 kB\nHugetlbPages:\t2048 kB\n",
+            )
+            .file(4, "status", "Name:\tdb\nHugetlbPages:\t0 kB\n")
+            .file(5, "status", "Name:\told\nVmRSS:\t512 kB\n");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::HugePages, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "huge=4096kB,htlb=2048kB");
+        assert_eq!(col(4), "huge=-,htlb=0kB");
+        assert_eq!(col(5), "huge=-,htlb=-");
+        assert_eq!(col(6), "huge=-,htlb=-");
+    }
+
+    #[test]
+    fn test_format_mount_count_column() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "mountinfo",
+                "22 1 8:2 / / rw - ext4 /dev/sda2 rw\n23 22 0:21 / /proc rw - proc proc rw\n",
+            )
+            .error(4, "mountinfo", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::MountCount, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "mounts=2");
+        assert_eq!(col(4), "mounts=-");
+    }
+
+    #[test]
+    fn test_format_seccomp_column() {
+        let reader = MockProcReader::new()
+            .file(3, "status", "Name:\tchrome\nSeccomp:\t2\n")
+            .file(4, "status", "Name:\tbash\nSeccomp:\t0\n")
+            .file(5, "status", "Name:\told\n");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::Seccomp, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "seccomp=FILTER");
+        assert_eq!(col(4), "seccomp=DISABLED");
+        // No Seccomp line (CONFIG_SECCOMP off) or no status.
+        assert_eq!(col(5), "seccomp=-");
+        assert_eq!(col(6), "seccomp=-");
+    }
+
+    #[test]
+    fn test_format_caps_column() {
+        let reader = MockProcReader::new()
+            .file(3, "status", "Name:\tnginx\nCapEff:\t0000000000002400\n")
+            .file(4, "status", "Name:\tbash\nCapEff:\t0000000000000000\n");
+        let sys = SystemCache::new();
+        let col = |pid, verbose| {
+            format_column(Column::Caps { verbose }, &Process::new(pid, &reader, &sys))
+        };
+        assert_eq!(col(3, false), "caps=net_bind_service,net_raw");
+        assert_eq!(
+            col(3, true),
+            "caps_verbose=net_bind_service (bind to ports below 1024); \
+             net_raw (use RAW and PACKET sockets)"
+        );
+        assert_eq!(col(4, false), "caps=none");
+        assert_eq!(col(4, true), "caps_verbose=none");
+        assert_eq!(col(5, false), "caps=-");
+    }
+
+    #[test]
+    fn test_format_tls_column() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "maps",
+                "7f00-7f01 r-xp 00000000 08:01 7 /usr/lib/libgnutls.so.30\n\
+                 7f01-7f02 r-xp 00000000 08:01 8 /usr/lib/libssl.so.3\n",
+            )
+            .file(
+                4,
+                "maps",
+                "7f00-7f01 r-xp 00000000 08:01 9 /usr/lib/libc.so.6\n",
+            )
+            .error(5, "maps", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::TlsInfo, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "tls=openssl,gnutls");
+        assert_eq!(col(4), "tls=none");
+        assert_eq!(col(5), "tls=-");
+    }
+
+    #[test]
+    fn test_format_jvm_column() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "cmdline",
+                "/usr/bin/java\0-Xmx512m\0-Xms256m\0-cp\0app.jar\0com.example.App\0",
+            )
+            .file(4, "cmdline", "java\0-jar\0app.jar\0")
+            .file(5, "cmdline", "/usr/sbin/nginx\0");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::Jvm, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "java_class=com.example.App,xmx=512m,xms=256m");
+        assert_eq!(col(4), "java_class=app.jar,xmx=-,xms=-");
+        assert_eq!(col(5), "java_class=-,xmx=-,xms=-");
+    }
+
+    #[test]
+    fn test_format_interpreter_column() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "cmdline",
+                "/usr/bin/python3.11\0/opt/app/worker.py\0--queue\0",
+            )
+            .file(4, "cmdline", "/bin/sh\0-c\0sleep 60\0")
+            .file(5, "cmdline", "/usr/sbin/nginx\0");
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::Interpreter, &Process::new(pid, &reader, &sys));
+        assert_eq!(
+            col(3),
+            "interp=python,version=3.11,script=/opt/app/worker.py"
+        );
+        assert_eq!(col(4), "interp=sh,version=-,script=-");
+        assert_eq!(col(5), "interp=-,version=-,script=-");
+    }
+
+    #[test]
+    fn test_format_syscall_column() {
+        let reader = MockProcReader::new()
+            .file(3, "syscall", "running\n")
+            .file(4, "syscall", "-1 0x7ffd3a1c8e48 0x7f2b9c4e1a2d\n")
+            .file(5, "syscall", "999 0x0 0x0 0x0 0x0 0x0 0x0 0x7ffd 0x7f2b\n")
+            .error(6, "syscall", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let col = |pid| format_column(Column::Syscall, &Process::new(pid, &reader, &sys));
+        assert_eq!(col(3), "syscall=running");
+        assert_eq!(col(4), "syscall=blocked");
+        assert_eq!(col(5), "syscall=999");
+        assert_eq!(col(6), "syscall=-");
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_format_syscall_column_names() {
+        let reader = MockProcReader::new().file(
+            3,
+            "syscall",
+            "232 0x4 0x7ffd3a1c8f10 0x400 0xffffffff 0x0 0x0 0x7ffd3a1c8ee8 0x7f2b9c51e0da\n",
+        );
+        let sys = SystemCache::new();
+        assert_eq!(
+            format_column(Column::Syscall, &Process::new(3, &reader, &sys)),
+            "syscall=epoll_wait"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::io;
+
+use crate::reader::ProcReader;
+
+/// Bits of `/proc/<pid>/coredump_filter`, see core(5).
+pub const COREDUMP_FLAGS: [(u32, &str); 9] = [
+    (1 << 0, "anon_private"),
+    (1 << 1, "anon_shared"),
+    (1 << 2, "file_private"),
+    (1 << 3, "file_shared"),
+    (1 << 4, "elf_headers"),
+    (1 << 5, "hugetlb_private"),
+    (1 << 6, "hugetlb_shared"),
+    (1 << 7, "dax_private"),
+    (1 << 8, "dax_shared"),
+];
+
+/// Names of the flags set in `mask`, in bit order. Undefined bits are ignored.
+pub fn decode_coredump_filter(mask: u32) -> Vec<&'static str> {
+    COREDUMP_FLAGS
+        .iter()
+        .filter(|(bit, _)| mask & bit != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Parses a hex mask as written in the file (`00000033`) or on the command
+/// line (`0x33`).
+pub fn parse_coredump_mask(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let hex = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u32::from_str_radix(hex, 16).ok()
+}
+
+pub fn read_coredump_filter(pid: u32, reader: &impl ProcReader) -> Result<u32, io::Error> {
+    let buf = reader.read(pid, "coredump_filter")?;
+    std::str::from_utf8(&buf)
+        .ok()
+        .and_then(parse_coredump_mask)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed coredump_filter"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_decode_coredump_filter() {
+        // Kernel default: anon private/shared, elf headers, hugetlb private.
+        assert_eq!(
+            decode_coredump_filter(0x33),
+            vec![
+                "anon_private",
+                "anon_shared",
+                "elf_headers",
+                "hugetlb_private"
+            ]
+        );
+        assert!(decode_coredump_filter(0).is_empty());
+        assert_eq!(decode_coredump_filter(0x1ff).len(), 9);
+        assert_eq!(decode_coredump_filter(0x200 | 0x4), vec!["file_private"]);
+    }
+
+    #[test]
+    fn test_parse_coredump_mask() {
+        assert_eq!(parse_coredump_mask("00000033\n"), Some(0x33));
+        assert_eq!(parse_coredump_mask("0x10"), Some(0x10));
+        assert_eq!(parse_coredump_mask("zz"), None);
+    }
+
+    #[test]
+    fn test_read_coredump_filter() {
+        let reader = MockProcReader::new().file(1, "coredump_filter", "00000023\n");
+        assert_eq!(read_coredump_filter(1, &reader).unwrap(), 0x23);
+        assert!(read_coredump_filter(2, &reader).is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::reader::ProcReader;
+
+/// Working directory of `pid`, from the `/proc/<pid>/cwd` link. Reading
+/// another user's link needs ptrace access and fails with EACCES.
+pub fn read_cwd(pid: u32, reader: &impl ProcReader) -> Result<PathBuf, io::Error> {
+    reader.read_link(pid, "cwd")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_read_cwd() {
+        let reader = MockProcReader::new().link(1, "cwd", "/srv/app").error(
+            2,
+            "cwd",
+            io::ErrorKind::PermissionDenied,
+        );
+        assert_eq!(read_cwd(1, &reader).unwrap(), PathBuf::from("/srv/app"));
+        assert_eq!(
+            read_cwd(2, &reader).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        assert!(read_cwd(3, &reader).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::reader::FsProcReader;
+use crate::scan::{ScanConfig, scan_pids};
+
+pub const DEFAULT_SOCKET: &str = "/run/fpid.sock";
+pub const DEFAULT_PIDFILE: &str = "/run/fpid.pid";
+
+/// Pause after a failed accept before trying again.
+const ACCEPT_RETRY: Duration = Duration::from_millis(100);
+
+// EBADF and EINVAL (not listening): the listener itself is broken. Both
+// have the same number on every Linux architecture.
+const LISTENER_ERRNOS: [i32; 2] = [9, 22];
+
+fn is_listener_error(e: &io::Error) -> bool {
+    e.raw_os_error()
+        .is_some_and(|n| LISTENER_ERRNOS.contains(&n))
+}
+
+/// Query server for `--daemon`: each request line is a name or path, each
+/// reply the matching PIDs one per line followed by a `.` line.
+pub struct DaemonServer {
+    listener: UnixListener,
+}
+
+impl DaemonServer {
+    /// Binds `path`, replacing a socket file left behind by a previous run.
+    /// Anything else at `path` is left alone and fails with `AlreadyExists`.
+    pub fn bind(path: &Path) -> io::Result<DaemonServer> {
+        match std::fs::symlink_metadata(path) {
+            Ok(meta) if !meta.file_type().is_socket() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ));
+            }
+            Ok(_) if UnixStream::connect(path).is_err() => std::fs::remove_file(path)?,
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        Ok(DaemonServer {
+            listener: UnixListener::bind(path)?,
+        })
+    }
+
+    /// Serves clients forever, one thread each. A failed accept (out of
+    /// descriptors, a client that hung up) is logged and skipped; only an
+    /// unusable listener ends the loop. Every client scans with a clone of
+    /// `reader`, so its proc root and read limit apply to all queries.
+    pub fn serve(&self, reader: &FsProcReader) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) if is_listener_error(&e) => return Err(e),
+                Err(e) => {
+                    crate::log_warn!("accept on daemon socket failed: {}", e);
+                    // EMFILE stays until a client thread closes its socket.
+                    thread::sleep(ACCEPT_RETRY);
+                    continue;
+                }
+            };
+            let reader = reader.clone();
+            thread::spawn(move || handle_client(stream, &reader));
+        }
+        Ok(())
+    }
+}
+
+/// Answers queries on one connection until the client closes it. An empty
+/// line or unreadable proc root gets an empty reply.
+pub fn handle_client(stream: UnixStream, reader: &FsProcReader) {
+    let Ok(mut out) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let target = line.trim();
+        let mut reply = String::new();
+        if !target.is_empty()
+            && let Ok(pids) = scan_pids(&ScanConfig::new(&[target]), reader)
+        {
+            for pid in pids {
+                reply.push_str(&pid.to_string());
+                reply.push('\n');
+            }
+        }
+        reply.push_str(".\n");
+        if out.write_all(reply.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn fixture(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fpid-daemon-{}-{}", tag, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (pid, cmdline) in [
+            (10, "nginx\0-g\0"),
+            (11, "/usr/sbin/nginx\0"),
+            (12, "bash\0"),
+        ] {
+            fs::create_dir_all(dir.join(pid.to_string())).unwrap();
+            fs::write(dir.join(pid.to_string()).join("cmdline"), cmdline).unwrap();
+        }
+        dir
+    }
+
+    fn query(stream: &mut BufReader<UnixStream>, name: &str) -> Vec<String> {
+        stream
+            .get_mut()
+            .write_all(format!("{name}\n").as_bytes())
+            .unwrap();
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            stream.read_line(&mut line).unwrap();
+            if line == ".\n" {
+                return lines;
+            }
+            lines.push(line.trim_end().to_string());
+        }
+    }
+
+    #[test]
+    fn test_daemon_queries() {
+        let root = fixture("q");
+        let sock = root.join("fpid.sock");
+        let server = DaemonServer::bind(&sock).unwrap();
+        let reader = FsProcReader::new(&root);
+        thread::spawn(move || {
+            let (stream, _) = server.listener.accept().unwrap();
+            handle_client(stream, &reader);
+        });
+
+        let mut client = BufReader::new(UnixStream::connect(&sock).unwrap());
+        let mut nginx = query(&mut client, "nginx");
+        nginx.sort();
+        assert_eq!(nginx, vec!["10", "11"]);
+        assert_eq!(query(&mut client, "bash"), vec!["12"]);
+        assert!(query(&mut client, "sshd").is_empty());
+        assert!(query(&mut client, "").is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_daemon_read_limit() {
+        let root = fixture("limit");
+        let sock = root.join("fpid.sock");
+        let server = DaemonServer::bind(&sock).unwrap();
+        // "/usr/sbin/nginx" is cut to "/usr/", which no longer matches.
+        let reader = FsProcReader::new(&root).with_read_limit(Some(5));
+        thread::spawn(move || server.serve(&reader));
+
+        let mut client = BufReader::new(UnixStream::connect(&sock).unwrap());
+        assert_eq!(query(&mut client, "nginx"), vec!["10"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_bind_replaces_stale_socket() {
+        let root = fixture("stale");
+        let sock = root.join("fpid.sock");
+        drop(DaemonServer::bind(&sock).unwrap());
+        assert!(sock.exists());
+        DaemonServer::bind(&sock).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_listener_errors() {
+        assert!(is_listener_error(&io::Error::from_raw_os_error(9)));
+        // EMFILE, ECONNABORTED and EINTR are worth retrying.
+        for errno in [24, 103, 4] {
+            assert!(!is_listener_error(&io::Error::from_raw_os_error(errno)));
+        }
+        assert!(!is_listener_error(&io::Error::from(io::ErrorKind::Other)));
+    }
+
+    #[test]
+    fn test_bind_keeps_other_files() {
+        let root = fixture("file");
+        let path = root.join("config");
+        fs::write(&path, "keep me\n").unwrap();
+        let err = DaemonServer::bind(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me\n");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+use std::io;
+
+use crate::cmdline::CmdlineReader;
+use crate::reader::ProcReader;
+
+/// Iterates `(key, value)` pairs of a NUL-separated environ buffer.
+///
+/// Entries are split on the first `=`, so values may contain `=` themselves.
+/// An entry without any `=` yields an empty value; empty entries are skipped.
+#[derive(Debug, Clone)]
+pub struct EnvironReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> EnvironReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for EnvironReader<'a> {
+    type Item = (&'a [u8], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Same NUL splitting rules as cmdline, resumed from our offset.
+            let rest = &self.buf[self.pos..];
+            let entry = CmdlineReader::new(rest).next()?;
+            self.pos += (entry.len() + 1).min(rest.len());
+            if entry.is_empty() {
+                continue;
+            }
+            return Some(match entry.iter().position(|b| *b == b'=') {
+                Some(eq) => (&entry[..eq], &entry[eq + 1..]),
+                None => (entry, &entry[entry.len()..]),
+            });
+        }
+    }
+}
+
+/// Value of the first `key` entry in an environ buffer.
+pub fn environ_get<'a>(buf: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    EnvironReader::new(buf)
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+/// Looks up `key` in `/proc/<pid>/environ`, stopping at the first match.
+pub fn environ_find(
+    pid: u32,
+    key: &[u8],
+    reader: &impl ProcReader,
+) -> Result<Option<Vec<u8>>, io::Error> {
+    let buf = reader.read(pid, "environ")?;
+    Ok(environ_get(&buf, key).map(<[u8]>::to_vec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    fn pairs(buf: &[u8]) -> Vec<(&[u8], &[u8])> {
+        EnvironReader::new(buf).collect()
+    }
+
+    #[test]
+    fn test_environ_pairs() {
+        assert_eq!(
+            pairs(b"HOME=/root\0PATH=/bin:/usr/bin\0"),
+            vec![(&b"HOME"[..], &b"/root"[..]), (b"PATH", b"/bin:/usr/bin")]
+        );
+    }
+
+    #[test]
+    fn test_environ_odd_entries() {
+        assert_eq!(
+            pairs(b"NOVALUE\0EMPTY=\0OPTS=a=b=c\0\0LAST=1"),
+            vec![
+                (&b"NOVALUE"[..], &b""[..]),
+                (b"EMPTY", b""),
+                (b"OPTS", b"a=b=c"),
+                (b"LAST", b"1")
+            ]
+        );
+        assert!(pairs(b"").is_empty());
+        assert!(pairs(b"\0\0").is_empty());
+    }
+
+    #[test]
+    fn test_environ_get() {
+        let buf = b"VIRTUAL_ENV=/srv/app/venv\0APP_ENV=\0PATH=/bin\0APP_ENV=later\0";
+        assert_eq!(
+            environ_get(buf, b"VIRTUAL_ENV"),
+            Some(&b"/srv/app/venv"[..])
+        );
+        assert_eq!(environ_get(buf, b"APP_ENV"), Some(&b""[..]));
+        assert_eq!(environ_get(buf, b"PATH=/bin"), None);
+        assert_eq!(environ_get(buf, b"VIRTUAL"), None);
+        assert_eq!(environ_get(b"", b"PATH"), None);
+    }
+
+    #[test]
+    fn test_environ_find() {
+        let reader = MockProcReader::new().file(3, "environ", b"LANG=C\0APP_ENV=prod\0X=1=2\0");
+        assert_eq!(
+            environ_find(3, b"APP_ENV", &reader).unwrap(),
+            Some(b"prod".to_vec())
+        );
+        assert_eq!(
+            environ_find(3, b"X", &reader).unwrap(),
+            Some(b"1=2".to_vec())
+        );
+        assert_eq!(environ_find(3, b"APP", &reader).unwrap(), None);
+        assert!(environ_find(4, b"LANG", &reader).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::io;
+
+/// Errors surfaced by the library's top-level scan functions.
+#[derive(Debug)]
+pub enum FpidError {
+    /// The proc root itself could not be read.
+    Io(io::Error),
+    /// A `--self-test` check did not hold.
+    SelfTest(String),
+    /// A `--signal` value that names no known signal.
+    InvalidSignal(String),
+    /// A PID file whose first line is not a PID, with that line.
+    InvalidPidFile(String),
+}
+
+impl fmt::Display for FpidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FpidError::Io(e) => write!(f, "{}", e),
+            FpidError::SelfTest(msg) => write!(f, "self-test failed: {}", msg),
+            FpidError::InvalidSignal(name) => write!(f, "invalid signal '{}'", name),
+            FpidError::InvalidPidFile(line) => write!(f, "no PID in PID file ('{}')", line),
+        }
+    }
+}
+
+impl std::error::Error for FpidError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FpidError::Io(e) => Some(e),
+            FpidError::SelfTest(_) | FpidError::InvalidSignal(_) | FpidError::InvalidPidFile(_) => {
+                None
+            }
+        }
+    }
+}
+
+/// A `/proc` file or argument that does not have the expected shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A required `key: value` line is absent.
+    MissingField(&'static str),
+    /// A value that could not be parsed, with what was found.
+    InvalidValue(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingField(name) => write!(f, "missing field '{}'", name),
+            ParseError::InvalidValue(v) => write!(f, "invalid value '{}'", v),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+impl From<io::Error> for FpidError {
+    fn from(e: io::Error) -> Self {
+        FpidError::Io(e)
+    }
+}
@@ -0,0 +1,402 @@
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+
+use crate::reader::ProcReader;
+
+// fd entries in numeric order; anything non-numeric is ignored.
+fn fd_numbers(pid: u32, reader: &impl ProcReader) -> io::Result<Vec<u32>> {
+    let mut fds: Vec<u32> = reader
+        .list_dir(pid, "fd")?
+        .iter()
+        .filter_map(|name| name.to_str()?.parse().ok())
+        .collect();
+    fds.sort_unstable();
+    Ok(fds)
+}
+
+/// Yields the symlink targets of `/proc/<pid>/fd/*` in fd order.
+///
+/// Failing to list the directory (process gone, no permission) is returned up
+/// front. Per entry, fds closed between listing and resolving (`ENOENT`) are
+/// skipped; other errors such as `EACCES` are yielded so callers can report
+/// them without aborting the walk.
+pub fn iter_fds(
+    pid: u32,
+    reader: &impl ProcReader,
+) -> Result<impl Iterator<Item = Result<PathBuf, io::Error>> + '_, io::Error> {
+    let fds = fd_numbers(pid, reader)?;
+    Ok(fds
+        .into_iter()
+        .map(move |fd| reader.read_link(pid, &format!("fd/{fd}")))
+        .filter(|res| !matches!(res, Err(e) if e.kind() == io::ErrorKind::NotFound)))
+}
+
+/// Number of open fds, without resolving any of them.
+pub fn count_fds(pid: u32, reader: &impl ProcReader) -> Result<usize, io::Error> {
+    fd_numbers(pid, reader).map(|fds| fds.len())
+}
+
+/// Default `--print-open-files` count.
+pub const DEFAULT_OPEN_FILES: usize = 5;
+
+/// Up to `max` fd targets of `pid` in fd order, for `--print-open-files`.
+/// Unreadable entries come out as `<EACCES>` (or the error kind); with
+/// `deleted` false, files shown as `... (deleted)` are left out.
+pub fn open_files(
+    pid: u32,
+    reader: &impl ProcReader,
+    max: usize,
+    deleted: bool,
+) -> Result<Vec<String>, io::Error> {
+    Ok(iter_fds(pid, reader)?
+        .filter_map(|res| match res {
+            Ok(path)
+                if !deleted && path.as_os_str().as_encoded_bytes().ends_with(b" (deleted)") =>
+            {
+                None
+            }
+            Ok(path) => Some(path.to_string_lossy().into_owned()),
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => Some("<EACCES>".to_string()),
+            Err(e) => Some(format!("<{:?}>", e.kind())),
+        })
+        .take(max)
+        .collect())
+}
+
+/// Soft `Max open files` limit from a `/proc/<pid>/limits` buffer;
+/// `unlimited` reads as `u64::MAX`.
+pub fn parse_fd_soft_limit(buf: &[u8]) -> Option<u64> {
+    let s = std::str::from_utf8(buf).ok()?;
+    let soft = s
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))?
+        .split_whitespace()
+        .next()?;
+    match soft {
+        "unlimited" => Some(u64::MAX),
+        n => n.parse().ok(),
+    }
+}
+
+/// Soft fd limit of `pid` (`RLIMIT_NOFILE`).
+pub fn read_fd_soft_limit(pid: u32, reader: &impl ProcReader) -> Result<u64, io::Error> {
+    parse_fd_soft_limit(&reader.read(pid, "limits")?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no Max open files in limits"))
+}
+
+/// `count` as a percentage of `soft_limit`; 0 for a limit of 0.
+pub fn fd_usage_pct(count: usize, soft_limit: u64) -> f64 {
+    if soft_limit == 0 {
+        return 0.0;
+    }
+    count as f64 * 100.0 / soft_limit as f64
+}
+
+/// What an fd refers to, for `--print-fd-types`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FdType {
+    Reg,
+    Dir,
+    Chr,
+    Blk,
+    Sock,
+    /// Anonymous pipes and FIFOs.
+    Pipe,
+    EventFd,
+    TimerFd,
+    SignalFd,
+    Epoll,
+    Inotify,
+    /// Any other `anon_inode:` (io_uring, perf_event, bpf-map, ...).
+    AnonInode,
+    /// Unresolvable targets and unreadable fds.
+    Other,
+}
+
+impl FdType {
+    /// Every type, in output order.
+    pub const ALL: [FdType; 13] = [
+        FdType::Reg,
+        FdType::Dir,
+        FdType::Chr,
+        FdType::Blk,
+        FdType::Sock,
+        FdType::Pipe,
+        FdType::EventFd,
+        FdType::TimerFd,
+        FdType::SignalFd,
+        FdType::Epoll,
+        FdType::Inotify,
+        FdType::AnonInode,
+        FdType::Other,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FdType::Reg => "reg",
+            FdType::Dir => "dir",
+            FdType::Chr => "chr",
+            FdType::Blk => "blk",
+            FdType::Sock => "sock",
+            FdType::Pipe => "pipe",
+            FdType::EventFd => "eventfd",
+            FdType::TimerFd => "timerfd",
+            FdType::SignalFd => "signalfd",
+            FdType::Epoll => "epoll",
+            FdType::Inotify => "inotify",
+            FdType::AnonInode => "anon",
+            FdType::Other => "other",
+        }
+    }
+}
+
+/// Classifies an fd symlink target. Kernel pseudo-files (`socket:[N]`,
+/// `pipe:[N]`, `anon_inode:[eventfd]`, ...) are recognized by name; paths
+/// are `stat()`ed. A path that no longer exists, typically `... (deleted)`,
+/// counts as a regular file.
+///
+/// The path is resolved in fpid's mount namespace, so fds of processes in
+/// another one may come out as `reg` or the type of an unrelated file.
+pub fn classify_fd_target(target: &Path) -> FdType {
+    let bytes = target.as_os_str().as_encoded_bytes();
+    if bytes.starts_with(b"socket:[") {
+        return FdType::Sock;
+    }
+    if bytes.starts_with(b"pipe:[") {
+        return FdType::Pipe;
+    }
+    if let Some(kind) = bytes.strip_prefix(b"anon_inode:") {
+        return match kind {
+            b"[eventfd]" => FdType::EventFd,
+            b"[timerfd]" => FdType::TimerFd,
+            b"[signalfd]" => FdType::SignalFd,
+            b"[eventpoll]" => FdType::Epoll,
+            b"inotify" | b"[inotify]" => FdType::Inotify,
+            _ => FdType::AnonInode,
+        };
+    }
+    if !target.is_absolute() {
+        return FdType::Other;
+    }
+    let file_type = match std::fs::metadata(target) {
+        Ok(meta) => meta.file_type(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return FdType::Reg,
+        Err(_) => return FdType::Other,
+    };
+    if file_type.is_file() {
+        FdType::Reg
+    } else if file_type.is_dir() {
+        FdType::Dir
+    } else if file_type.is_char_device() {
+        FdType::Chr
+    } else if file_type.is_block_device() {
+        FdType::Blk
+    } else if file_type.is_socket() {
+        FdType::Sock
+    } else if file_type.is_fifo() {
+        FdType::Pipe
+    } else {
+        FdType::Other
+    }
+}
+
+/// Per-[`FdType`] fd counts of one process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FdTypeCounts([usize; FdType::ALL.len()]);
+
+impl FdTypeCounts {
+    pub fn get(&self, ty: FdType) -> usize {
+        self.0[ty as usize]
+    }
+
+    fn add(&mut self, ty: FdType) {
+        self.0[ty as usize] += 1;
+    }
+
+    /// `reg=N,sock=M,pipe=K`, then any other non-zero types, then
+    /// `other=L`.
+    pub fn summary(&self) -> String {
+        const ALWAYS: [FdType; 3] = [FdType::Reg, FdType::Sock, FdType::Pipe];
+        let mut parts: Vec<String> = ALWAYS
+            .iter()
+            .map(|ty| format!("{}={}", ty.name(), self.get(*ty)))
+            .collect();
+        parts.extend(
+            FdType::ALL
+                .iter()
+                .filter(|ty| !ALWAYS.contains(ty) && **ty != FdType::Other && self.get(**ty) > 0)
+                .map(|ty| format!("{}={}", ty.name(), self.get(*ty))),
+        );
+        parts.push(format!("other={}", self.get(FdType::Other)));
+        parts.join(",")
+    }
+}
+
+/// Counts the fds of `pid` by [`classify_fd_target`]; unreadable entries
+/// count as [`FdType::Other`].
+pub fn fd_type_counts(pid: u32, reader: &impl ProcReader) -> Result<FdTypeCounts, io::Error> {
+    let mut counts = FdTypeCounts::default();
+    for res in iter_fds(pid, reader)? {
+        counts.add(match res {
+            Ok(target) => classify_fd_target(&target),
+            Err(_) => FdType::Other,
+        });
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    fn mock() -> MockProcReader {
+        MockProcReader::new()
+            .link(10, "fd/0", "/dev/null")
+            .link(10, "fd/1", "pipe:[31337]")
+            .link(10, "fd/10", "socket:[4242]")
+            .link(10, "fd/2", "/var/log/app.log (deleted)")
+            .error(10, "fd/3", io::ErrorKind::PermissionDenied)
+            .error(10, "fd/4", io::ErrorKind::NotFound)
+    }
+
+    #[test]
+    fn test_iter_fds() {
+        let reader = mock();
+        let fds: Vec<_> = iter_fds(10, &reader).unwrap().collect();
+        assert_eq!(fds.len(), 5);
+        assert_eq!(fds[0].as_ref().unwrap(), &PathBuf::from("/dev/null"));
+        assert_eq!(fds[1].as_ref().unwrap(), &PathBuf::from("pipe:[31337]"));
+        assert_eq!(
+            fds[2].as_ref().unwrap(),
+            &PathBuf::from("/var/log/app.log (deleted)")
+        );
+        assert_eq!(
+            fds[3].as_ref().unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        assert_eq!(fds[4].as_ref().unwrap(), &PathBuf::from("socket:[4242]"));
+    }
+
+    #[test]
+    fn test_count_fds() {
+        assert_eq!(count_fds(10, &mock()).unwrap(), 6);
+        assert!(count_fds(11, &mock()).is_err());
+    }
+
+    #[test]
+    fn test_fd_dir_denied() {
+        let reader = MockProcReader::new().error(1, "fd", io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            iter_fds(1, &reader).err().unwrap().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+    }
+
+    const LIMITS: &str = "Limit                     Soft Limit           Hard Limit           Units     \n\
+Max cpu time              unlimited            unlimited            seconds   \n\
+Max open files            1024                 524288               files     \n\
+Max locked memory         8388608              8388608              bytes     \n";
+
+    #[test]
+    fn test_fd_soft_limit() {
+        assert_eq!(parse_fd_soft_limit(LIMITS.as_bytes()), Some(1024));
+        assert_eq!(
+            parse_fd_soft_limit(
+                b"Max open files            unlimited            unlimited            files\n"
+            ),
+            Some(u64::MAX)
+        );
+        assert_eq!(
+            parse_fd_soft_limit(b"Max cpu time  unlimited  unlimited  seconds\n"),
+            None
+        );
+        let reader = MockProcReader::new().file(1, "limits", LIMITS);
+        assert_eq!(read_fd_soft_limit(1, &reader).unwrap(), 1024);
+        assert!(read_fd_soft_limit(2, &reader).is_err());
+    }
+
+    #[test]
+    fn test_fd_usage_pct() {
+        assert_eq!(fd_usage_pct(256, 1024), 25.0);
+        assert_eq!(fd_usage_pct(1024, 1024), 100.0);
+        assert_eq!(fd_usage_pct(0, 1024), 0.0);
+        assert_eq!(fd_usage_pct(5, 0), 0.0);
+        assert!(fd_usage_pct(1_000_000, u64::MAX) < 1e-6);
+    }
+
+    #[test]
+    fn test_open_files() {
+        let reader = mock().link(10, "fd/11", "/srv/data.db");
+        assert_eq!(
+            open_files(10, &reader, 5, true).unwrap(),
+            vec![
+                "/dev/null",
+                "pipe:[31337]",
+                "/var/log/app.log (deleted)",
+                "<EACCES>",
+                "socket:[4242]"
+            ]
+        );
+        assert_eq!(
+            open_files(10, &reader, 5, false).unwrap(),
+            vec![
+                "/dev/null",
+                "pipe:[31337]",
+                "<EACCES>",
+                "socket:[4242]",
+                "/srv/data.db"
+            ]
+        );
+        assert_eq!(open_files(10, &reader, 1, true).unwrap(), vec!["/dev/null"]);
+        assert!(open_files(10, &reader, 0, true).unwrap().is_empty());
+        assert!(open_files(11, &reader, 5, true).is_err());
+    }
+
+    #[test]
+    fn test_classify_fd_target() {
+        let cases = [
+            ("socket:[4242]", FdType::Sock),
+            ("pipe:[31337]", FdType::Pipe),
+            ("anon_inode:[eventfd]", FdType::EventFd),
+            ("anon_inode:[timerfd]", FdType::TimerFd),
+            ("anon_inode:[signalfd]", FdType::SignalFd),
+            ("anon_inode:[eventpoll]", FdType::Epoll),
+            ("anon_inode:inotify", FdType::Inotify),
+            ("anon_inode:[io_uring]", FdType::AnonInode),
+            ("/dev/null", FdType::Chr),
+            ("/", FdType::Dir),
+            ("/nonexistent/app.log (deleted)", FdType::Reg),
+            ("net:[4026531840]", FdType::Other),
+        ];
+        for (target, want) in cases {
+            assert_eq!(classify_fd_target(Path::new(target)), want, "{target}");
+        }
+        let file = std::env::temp_dir().join(format!("fpid-fdtype-{}", std::process::id()));
+        std::fs::write(&file, b"x").unwrap();
+        assert_eq!(classify_fd_target(&file), FdType::Reg);
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_fd_type_counts() {
+        let reader = mock()
+            .link(10, "fd/5", "socket:[4243]")
+            .link(10, "fd/6", "anon_inode:[eventfd]")
+            .link(10, "fd/7", "/");
+        let counts = fd_type_counts(10, &reader).unwrap();
+        assert_eq!(counts.get(FdType::Reg), 1);
+        assert_eq!(counts.get(FdType::Sock), 2);
+        assert_eq!(counts.get(FdType::Other), 1);
+        assert_eq!(
+            counts.summary(),
+            "reg=1,sock=2,pipe=1,dir=1,chr=1,eventfd=1,other=1"
+        );
+        assert_eq!(
+            FdTypeCounts::default().summary(),
+            "reg=0,sock=0,pipe=0,other=0"
+        );
+        assert!(fd_type_counts(11, &reader).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! C API (`capi` feature), declared in `include/fpid.h`.
+
+use std::ffi::{CStr, c_char, c_int};
+
+use crate::reader::FsProcReader;
+use crate::scan::{ScanConfig, scan_pids};
+
+// Shared body of the two entry points; `want_path` selects which kind of
+// target the string must be, so a name can't silently match as a path.
+unsafe fn find(
+    target: *const c_char,
+    want_path: bool,
+    pids_out: *mut u32,
+    max_pids: usize,
+) -> c_int {
+    if target.is_null() || (pids_out.is_null() && max_pids > 0) {
+        return -1;
+    }
+    // SAFETY: non-null and NUL-terminated per the caller contract.
+    let Ok(target) = unsafe { CStr::from_ptr(target) }.to_str() else {
+        return -1;
+    };
+    if target.is_empty() || target.contains('/') != want_path {
+        return -1;
+    }
+    let Ok(pids) = scan_pids(&ScanConfig::new(&[target]), &FsProcReader::default()) else {
+        return -1;
+    };
+    let n = pids.len().min(max_pids);
+    if n > 0 {
+        // SAFETY: the caller provides room for `max_pids` values.
+        unsafe { std::ptr::copy_nonoverlapping(pids.as_ptr(), pids_out, n) };
+    }
+    c_int::try_from(pids.len()).unwrap_or(c_int::MAX)
+}
+
+/// Finds processes whose argv\[0\] basename is `name`, writing up to
+/// `max_pids` PIDs to `pids_out`. Returns the number found, which may exceed
+/// `max_pids`, or -1 for a null/invalid argument or unreadable `/proc`.
+///
+/// # Safety
+///
+/// `name` must be a NUL-terminated string and `pids_out` must be valid for
+/// `max_pids` writes (it may be null when `max_pids` is 0).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fpid_find_by_name(
+    name: *const c_char,
+    pids_out: *mut u32,
+    max_pids: usize,
+) -> c_int {
+    unsafe { find(name, false, pids_out, max_pids) }
+}
+
+/// Like [`fpid_find_by_name`], matching the executable path (must contain `/`).
+///
+/// # Safety
+///
+/// Same contract as [`fpid_find_by_name`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fpid_find_by_path(
+    path: *const c_char,
+    pids_out: *mut u32,
+    max_pids: usize,
+) -> c_int {
+    unsafe { find(path, true, pids_out, max_pids) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::ptr;
+
+    fn own_argv0() -> Vec<u8> {
+        std::env::args_os().next().unwrap().as_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let argv0 = own_argv0();
+        let name = CString::new(crate::matcher::basename(&argv0)).unwrap();
+        let mut pids = [0u32; 64];
+        let n = unsafe { fpid_find_by_name(name.as_ptr(), pids.as_mut_ptr(), pids.len()) };
+        assert!(n >= 1);
+        assert!(pids[..n as usize].contains(&std::process::id()));
+        // Counting only.
+        assert_eq!(
+            unsafe { fpid_find_by_name(name.as_ptr(), ptr::null_mut(), 0) },
+            n
+        );
+    }
+
+    #[test]
+    fn test_find_by_path() {
+        let exe = std::fs::read_link("/proc/self/exe").unwrap();
+        let path = CString::new(exe.as_os_str().as_bytes()).unwrap();
+        let mut pid = 0u32;
+        let n = unsafe { fpid_find_by_path(path.as_ptr(), &mut pid, 1) };
+        assert!(n >= 1);
+        assert_ne!(pid, 0);
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let name = CString::new("sshd").unwrap();
+        let path = CString::new("/usr/sbin/sshd").unwrap();
+        let mut pids = [0u32; 4];
+        unsafe {
+            assert_eq!(fpid_find_by_name(ptr::null(), pids.as_mut_ptr(), 4), -1);
+            assert_eq!(fpid_find_by_name(name.as_ptr(), ptr::null_mut(), 4), -1);
+            assert_eq!(fpid_find_by_name(path.as_ptr(), pids.as_mut_ptr(), 4), -1);
+            assert_eq!(fpid_find_by_path(name.as_ptr(), pids.as_mut_ptr(), 4), -1);
+        }
+    }
+}
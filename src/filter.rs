@@ -0,0 +1,903 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::affinity::allowed_cpus;
+use crate::caps::has_cap;
+use crate::cgroup::{CgroupMatchMode, cgroup_matches_path, read_cgroup};
+use crate::coredump::read_coredump_filter;
+use crate::cwd::read_cwd;
+use crate::fd::{count_fds, fd_usage_pct, read_fd_soft_limit};
+use crate::jvm::detect_jvm;
+use crate::maps::{read_has_wx_mapping, read_maps_count, read_tls_libraries};
+use crate::mountinfo::has_mount_point;
+use crate::namespace::{ns_inode, same_pid_namespace};
+use crate::net::{SocketKind, find_listen_ports, socket_counts};
+use crate::numa::{read_numa_local_pct, read_numa_policy};
+use crate::process::Process;
+use crate::reader::ProcReader;
+use crate::sched_policy::read_sched_policy;
+use crate::statm::StatmFilter;
+use crate::status::read_tracer_pid;
+use crate::syscall::{SyscallState, read_syscall};
+use crate::wchan::{is_waiting, read_wchan};
+
+/// Post-match conditions; a process must pass all of them to be reported.
+///
+/// A process whose data cannot be read (gone, EACCES) fails the filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// At least this many CPU seconds consumed (`--min-cpu-sec`).
+    MinCpuSec(f64),
+    /// At least this many seconds of user-mode CPU time (`--min-user-time`).
+    MinUserSec(f64),
+    /// At least this many seconds of kernel-mode CPU time (`--min-sys-time`).
+    MinSysSec(f64),
+    /// At least this many major page faults per second of lifetime
+    /// (`--min-majflt-rate`).
+    MinMajFltRate(f64),
+    /// At least this many voluntary context switches per CPU second
+    /// (`--min-ctx-rate`).
+    MinCtxRate(f64),
+    /// CPU time of at least this percentage of the process's lifetime
+    /// (`--min-cpu-pct`).
+    MinCpuPct(f64),
+    /// PSS of at least this many kB (`--min-pss`).
+    MinPssKb(u64),
+    /// `rchar` of io at least this many bytes (`--min-rchar`).
+    MinRchar(u64),
+    /// `wchar` of io at least this many bytes (`--min-wchar`).
+    MinWchar(u64),
+    /// Swap usage of at most this many kB (`--max-swap`).
+    MaxSwapKb(u64),
+    /// OOM score of at least this value (`--min-oom`).
+    MinOom(i32),
+    /// coredump_filter has every bit of this mask set (`--coredump-filter`).
+    CoredumpFilter(u32),
+    /// statm-based memory range (`--min-rss`, `--max-rss`).
+    Statm(StatmFilter),
+    /// At least this many nanoseconds waiting in the run queue (`--min-wait-time-ns`).
+    MinWaitNs(u64),
+    /// Allowed to run on every one of these CPUs (`--cpu-affinity`).
+    CpuAffinity(Vec<u32>),
+    /// First numa_maps entry has exactly this policy (`--numa-policy`).
+    NumaPolicy(String),
+    /// At least `pct`% of the numa_maps pages on `node`
+    /// (`--min-numa-local-pct`).
+    MinNumaLocalPct { node: u32, pct: f64 },
+    /// Scheduling policy code, one of the `SCHED_*` values (`--sched-policy`).
+    SchedPolicy(u32),
+    /// PID in the innermost PID namespace equals this (`--ns-pid-filter`).
+    NsPid(u32),
+    /// Same PID namespace as this PID (`--same-ns-as`, `--host-ns-only`
+    /// with PID 1).
+    SamePidNs(u32),
+    /// Namespace of this type (see [`crate::namespace::NS_TYPES`]) has this
+    /// inode (`--ns-type`).
+    Namespace { kind: &'static str, inode: u64 },
+    /// Capability bit set in `CapEff` (`--has-cap`).
+    HasCap(u32),
+    /// Capability bit clear in `CapEff` (`--no-cap`).
+    NoCap(u32),
+    /// Any effective capability at all (`--any-cap`).
+    AnyCap,
+    /// Being ptraced (`--is-traced`), or with `false` not (`--not-traced`).
+    Traced(bool),
+    /// Blocked in a kernel function whose name contains this (`--wchan`).
+    Wchan(String),
+    /// In this syscall, or running or blocked (`--in-syscall`).
+    InSyscall(SyscallState),
+    /// Started at most this long ago (`--started-within`).
+    StartedWithin(Duration),
+    /// Started more than this long ago (`--started-more-than`).
+    StartedMoreThan(Duration),
+    /// Working directory is this path or below it, compared by whole
+    /// components (`--cwd`).
+    Cwd(PathBuf),
+    /// Open fds above this percentage of the soft fd limit
+    /// (`--fd-usage-pct-min`).
+    FdUsagePctMin(f64),
+    /// VmSize of at least this many kB (`--min-vm-size`).
+    MinVmSizeKb(u64),
+    /// VmSize of at most this many kB (`--max-vm-size`).
+    MaxVmSizeKb(u64),
+    /// VmLck of at least this many kB (`--min-locked-kb`; `--locked-only`
+    /// is 1).
+    MinLockedKb(u64),
+    /// VmPin of at least this many kB (`--min-pinned`).
+    MinPinnedKb(u64),
+    /// `VmHuge` plus `HugetlbPages` of at least this many kB
+    /// (`--min-huge-pages`); never matches when status has neither.
+    MinHugePagesKb(u64),
+    /// Seccomp enabled, strict or filter (`--has-seccomp`).
+    HasSeccomp,
+    /// Exactly this `Seccomp:` mode (`--seccomp-mode`).
+    SeccompMode(u8),
+    /// This path is a mount point in mountinfo (`--has-mount`).
+    HasMount(PathBuf),
+    /// Real UID from status (`--uid`, `--username`).
+    Uid(u32),
+    /// Real GID from status (`--gid`, `--group`).
+    Gid(u32),
+    /// Main-thread stack of at least this many kB (`--min-stack-kb`).
+    MinStackKb(u64),
+    /// A TCP socket in LISTEN on this local port (`--listening-on`).
+    ListeningOn(u16),
+    /// At least one TCP socket listed in `/proc/net/tcp{,6}`
+    /// (`--has-tcp-connections`).
+    HasTcp,
+    /// Some mapping is both writable and executable (`--has-wxmem`).
+    HasWxMem,
+    /// A known TLS library is mapped (`--has-tls`).
+    HasTls,
+    /// Launched as a JVM (`--jvm-detect`).
+    Jvm,
+    /// At least this many mappings in maps (`--min-maps-count`).
+    MinMapsCount(usize),
+    /// At most this many mappings in maps (`--max-maps-count`).
+    MaxMapsCount(usize),
+    /// Some cgroup path matches this one (`--cgroup-filter`, `--cgroup-mode`).
+    Cgroup { path: String, mode: CgroupMatchMode },
+    /// PID within these inclusive bounds (`--min-pid`, `--max-pid`).
+    PidRange { min: u32, max: u32 },
+    /// PID in this set, e.g. the subtree `--ppid-chain` resolved to.
+    Pids(HashSet<u32>),
+}
+
+impl Filter {
+    pub fn matches<R: ProcReader>(&self, proc: &Process<'_, R>) -> bool {
+        match self {
+            Filter::MinCpuSec(min) => proc.cpu_seconds().is_some_and(|s| s >= *min),
+            Filter::MinUserSec(min) => proc.user_seconds().is_some_and(|s| s >= *min),
+            Filter::MinSysSec(min) => proc.sys_seconds().is_some_and(|s| s >= *min),
+            Filter::MinMajFltRate(min) => proc.fault_rates().is_some_and(|(_, maj)| maj >= *min),
+            Filter::MinCtxRate(min) => proc.ctx_rate().is_some_and(|rate| rate >= *min),
+            Filter::MinCpuPct(min) => proc.cpu_lifetime_pct().is_some_and(|pct| pct >= *min),
+            Filter::MinPssKb(min) => proc.smaps().is_some_and(|s| s.pss >= *min),
+            Filter::MinRchar(min) => proc.io().is_some_and(|io| io.rchar >= *min),
+            Filter::MinWchar(min) => proc.io().is_some_and(|io| io.wchar >= *min),
+            Filter::MaxSwapKb(max) => proc.smaps().is_some_and(|s| s.swap <= *max),
+            Filter::MinOom(min) => proc.oom().is_some_and(|(score, _)| score >= *min),
+            Filter::CoredumpFilter(mask) => {
+                read_coredump_filter(proc.pid(), proc.reader()).is_ok_and(|m| m & mask == *mask)
+            }
+            Filter::Statm(f) => proc.statm().is_some_and(|m| f.matches(m)),
+            Filter::CpuAffinity(want) => proc
+                .status()
+                .and_then(allowed_cpus)
+                .is_some_and(|cpus| want.iter().all(|c| cpus.binary_search(c).is_ok())),
+            Filter::NumaPolicy(want) => read_numa_policy(proc.pid(), proc.reader())
+                .is_ok_and(|p| p.as_deref() == Some(want.as_str())),
+            Filter::MinNumaLocalPct { node, pct } => {
+                read_numa_local_pct(proc.pid(), proc.reader(), *node)
+                    .is_ok_and(|local| local.is_some_and(|local| local >= *pct))
+            }
+            Filter::SchedPolicy(want) => {
+                read_sched_policy(proc.pid(), proc.reader()).is_ok_and(|p| p.policy == *want)
+            }
+            Filter::MinWaitNs(min) => proc.sched().is_some_and(|s| s.wait_time_ns >= *min),
+            Filter::NsPid(want) => proc
+                .status()
+                .is_some_and(|st| st.ns_pid.last() == Some(want)),
+            Filter::HasCap(bit) => proc.status().is_some_and(|st| has_cap(st.cap_eff, *bit)),
+            Filter::NoCap(bit) => proc.status().is_some_and(|st| !has_cap(st.cap_eff, *bit)),
+            Filter::Traced(want) => {
+                read_tracer_pid(proc.pid(), proc.reader()).is_ok_and(|t| (t != 0) == *want)
+            }
+            Filter::InSyscall(want) => {
+                read_syscall(proc.pid(), proc.reader()).is_ok_and(|s| s == *want)
+            }
+            Filter::Wchan(part) => read_wchan(proc.pid(), proc.reader())
+                .is_ok_and(|w| is_waiting(&w) && w.contains(part.as_str())),
+            Filter::StartedWithin(max) => proc.age().is_some_and(|age| age <= *max),
+            Filter::StartedMoreThan(min) => proc.age().is_some_and(|age| age > *min),
+            Filter::Cwd(dir) => {
+                read_cwd(proc.pid(), proc.reader()).is_ok_and(|c| c.starts_with(dir))
+            }
+            Filter::FdUsagePctMin(min) => {
+                match (
+                    count_fds(proc.pid(), proc.reader()),
+                    read_fd_soft_limit(proc.pid(), proc.reader()),
+                ) {
+                    (Ok(count), Ok(limit)) => fd_usage_pct(count, limit) > *min,
+                    _ => false,
+                }
+            }
+            Filter::MinVmSizeKb(min) => proc
+                .status()
+                .and_then(|st| st.vm_size)
+                .is_some_and(|kb| kb >= *min),
+            Filter::MaxVmSizeKb(max) => proc
+                .status()
+                .and_then(|st| st.vm_size)
+                .is_some_and(|kb| kb <= *max),
+            Filter::MinLockedKb(min) => proc
+                .status()
+                .and_then(|st| st.vm_lck)
+                .is_some_and(|kb| kb >= *min),
+            Filter::MinPinnedKb(min) => proc
+                .status()
+                .and_then(|st| st.vm_pin)
+                .is_some_and(|kb| kb >= *min),
+            Filter::MinHugePagesKb(min) => proc.status().is_some_and(|st| {
+                (st.vm_huge.is_some() || st.hugetlb_pages.is_some())
+                    && st.vm_huge.unwrap_or(0) + st.hugetlb_pages.unwrap_or(0) >= *min
+            }),
+            Filter::HasSeccomp => proc
+                .status()
+                .and_then(|st| st.seccomp)
+                .is_some_and(|m| m != 0),
+            Filter::SeccompMode(mode) => proc.status().and_then(|st| st.seccomp) == Some(*mode),
+            Filter::HasMount(path) => {
+                has_mount_point(proc.pid(), proc.reader(), path).unwrap_or(false)
+            }
+            Filter::Uid(uid) => proc.status().is_some_and(|st| st.uid[0] == *uid),
+            Filter::Gid(gid) => proc.status().is_some_and(|st| st.gid[0] == *gid),
+            Filter::MinStackKb(min) => proc.stack_kb().is_some_and(|kb| kb >= *min),
+            Filter::ListeningOn(port) => {
+                find_listen_ports(proc.pid(), proc.reader(), proc.sys().sockets(proc.reader()))
+                    .is_ok_and(|ports| ports.contains(&(SocketKind::Tcp, *port)))
+            }
+            Filter::HasTcp => {
+                socket_counts(proc.pid(), proc.reader(), proc.sys().sockets(proc.reader()))
+                    .is_ok_and(|c| c.tcp > 0)
+            }
+            Filter::HasWxMem => read_has_wx_mapping(proc.pid(), proc.reader()).unwrap_or(false),
+            Filter::Jvm => {
+                let args: Vec<&[u8]> = proc.info().cmdline.iter().map(|a| a.as_bytes()).collect();
+                detect_jvm(&args).is_some()
+            }
+            Filter::HasTls => {
+                read_tls_libraries(proc.pid(), proc.reader()).is_ok_and(|libs| !libs.is_empty())
+            }
+            Filter::MinMapsCount(min) => {
+                read_maps_count(proc.pid(), proc.reader()).is_ok_and(|n| n >= *min)
+            }
+            Filter::MaxMapsCount(max) => {
+                read_maps_count(proc.pid(), proc.reader()).is_ok_and(|n| n <= *max)
+            }
+            Filter::Cgroup { path, mode } => read_cgroup(proc.pid(), proc.reader())
+                .is_ok_and(|buf| cgroup_matches_path(&buf, path, *mode)),
+            Filter::PidRange { min, max } => (*min..=*max).contains(&proc.pid()),
+            Filter::Pids(pids) => pids.contains(&proc.pid()),
+            Filter::AnyCap => proc.status().is_some_and(|st| st.cap_eff != 0),
+            Filter::Namespace { kind, inode } => {
+                ns_inode(proc.pid(), kind, proc.reader()).is_ok_and(|i| i == *inode)
+            }
+            Filter::SamePidNs(other) => {
+                same_pid_namespace(proc.pid(), *other, proc.reader()).unwrap_or(false)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::SystemCache;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_min_cpu_sec() {
+        // 250 + 50 ticks = 3s at USER_HZ=100.
+        let reader = MockProcReader::new().file(
+            5,
+            "stat",
+            "5 (cc1) R 1 5 5 0 -1 0 0 0 0 0 250 50 0 0 20 0 1 0 1 0 0\n",
+        );
+        let sys = SystemCache::new();
+        let proc = Process::new(5, &reader, &sys);
+        assert!(Filter::MinCpuSec(3.0).matches(&proc));
+        assert!(!Filter::MinCpuSec(3.5).matches(&proc));
+        assert!(!Filter::MinCpuSec(0.0).matches(&Process::new(6, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_min_user_sys_time() {
+        // 250 ticks user, 50 ticks system at USER_HZ=100.
+        let reader = MockProcReader::new().file(
+            5,
+            "stat",
+            "5 (cc1) R 1 5 5 0 -1 0 0 0 0 0 250 50 0 0 20 0 1 0 1 0 0\n",
+        );
+        let sys = SystemCache::new();
+        let proc = Process::new(5, &reader, &sys);
+        assert!(Filter::MinUserSec(2.5).matches(&proc));
+        assert!(!Filter::MinUserSec(2.6).matches(&proc));
+        assert!(Filter::MinSysSec(0.5).matches(&proc));
+        assert!(!Filter::MinSysSec(0.6).matches(&proc));
+        assert!(!Filter::MinUserSec(0.0).matches(&Process::new(6, &reader, &sys)));
+        assert!(!Filter::MinSysSec(0.0).matches(&Process::new(6, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_min_majflt_rate() {
+        // 90 major faults over a 30s lifetime.
+        let reader = MockProcReader::new()
+            .file(
+                5,
+                "stat",
+                "5 (db) S 1 5 5 0 -1 0 0 0 90 0 0 0 0 0 20 0 1 0 1000 0 0\n",
+            )
+            .root_file("uptime", "40.00 0.00\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(5, &reader, &sys);
+        assert!(Filter::MinMajFltRate(3.0).matches(&proc));
+        assert!(!Filter::MinMajFltRate(3.1).matches(&proc));
+        assert!(!Filter::MinMajFltRate(0.0).matches(&Process::new(6, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_min_ctx_rate() {
+        // 2 CPU seconds at USER_HZ=100.
+        let reader = MockProcReader::new()
+            .file(
+                5,
+                "stat",
+                "5 (pg) S 1 5 5 0 -1 0 0 0 0 0 150 50 0 0 20 0 1 0 1 0 0\n",
+            )
+            .file(5, "status", "Name:\tpg\nvoluntary_ctxt_switches:\t900\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(5, &reader, &sys);
+        assert!(Filter::MinCtxRate(450.0).matches(&proc));
+        assert!(!Filter::MinCtxRate(451.0).matches(&proc));
+        assert!(!Filter::MinCtxRate(0.0).matches(&Process::new(6, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_min_cpu_pct() {
+        // 60s of CPU over a 240s lifetime at USER_HZ=100.
+        let reader = MockProcReader::new()
+            .file(
+                5,
+                "stat",
+                "5 (cc1) R 1 5 5 0 -1 0 0 0 0 0 5000 1000 0 0 20 0 1 0 6000 0 0\n",
+            )
+            .root_file("uptime", "300.00 0.00\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(5, &reader, &sys);
+        assert!(Filter::MinCpuPct(25.0).matches(&proc));
+        assert!(!Filter::MinCpuPct(25.5).matches(&proc));
+        assert!(!Filter::MinCpuPct(0.0).matches(&Process::new(6, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_io_char_filters() {
+        // Cache-served reads: large rchar, no read_bytes.
+        let reader = MockProcReader::new().file(
+            7,
+            "io",
+            "rchar: 1048576\nwchar: 512\nsyscr: 256\nsyscw: 1\nread_bytes: 0\nwrite_bytes: 0\n",
+        );
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::MinRchar(1048576).matches(&proc));
+        assert!(!Filter::MinRchar(1048577).matches(&proc));
+        assert!(Filter::MinWchar(512).matches(&proc));
+        assert!(!Filter::MinWchar(513).matches(&proc));
+        assert!(!Filter::MinRchar(0).matches(&Process::new(8, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_smaps_filters() {
+        let reader = MockProcReader::new().file(7, "smaps_rollup", "Pss:  900 kB\nSwap:  40 kB\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::MinPssKb(900).matches(&proc));
+        assert!(!Filter::MinPssKb(901).matches(&proc));
+        assert!(Filter::MaxSwapKb(40).matches(&proc));
+        assert!(!Filter::MaxSwapKb(39).matches(&proc));
+        assert!(!Filter::MaxSwapKb(u64::MAX).matches(&Process::new(8, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_min_oom() {
+        let reader =
+            MockProcReader::new()
+                .file(7, "oom_score", "500\n")
+                .file(7, "oom_score_adj", "0\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::MinOom(500).matches(&proc));
+        assert!(!Filter::MinOom(501).matches(&proc));
+    }
+
+    #[test]
+    fn test_coredump_filter() {
+        let reader = MockProcReader::new().file(7, "coredump_filter", "00000033\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::CoredumpFilter(0x10).matches(&proc));
+        assert!(Filter::CoredumpFilter(0x03).matches(&proc));
+        assert!(!Filter::CoredumpFilter(0x14).matches(&proc));
+    }
+
+    #[test]
+    fn test_min_wait_ns() {
+        let reader = MockProcReader::new().file(7, "schedstat", "10 5000 2\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::MinWaitNs(5000).matches(&proc));
+        assert!(!Filter::MinWaitNs(5001).matches(&proc));
+        assert!(!Filter::MinWaitNs(0).matches(&Process::new(8, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_cpu_affinity() {
+        let reader =
+            MockProcReader::new().file(7, "status", "Name:\tx\nCpus_allowed_list:\t0-3,6\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::CpuAffinity(vec![2, 6]).matches(&proc));
+        assert!(!Filter::CpuAffinity(vec![2, 5]).matches(&proc));
+        assert!(!Filter::CpuAffinity(vec![0]).matches(&Process::new(8, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_numa_policy() {
+        let reader = MockProcReader::new().file(7, "numa_maps", "1000 interleave:0-1 anon=1\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::NumaPolicy("interleave:0-1".to_string()).matches(&proc));
+        assert!(!Filter::NumaPolicy("default".to_string()).matches(&proc));
+        assert!(
+            !Filter::NumaPolicy("default".to_string()).matches(&Process::new(8, &reader, &sys))
+        );
+    }
+
+    #[test]
+    fn test_sched_policy() {
+        let reader = MockProcReader::new().file(7, "sched", "policy : 2\nprio : 49\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::SchedPolicy(2).matches(&proc));
+        assert!(!Filter::SchedPolicy(0).matches(&proc));
+        assert!(!Filter::SchedPolicy(0).matches(&Process::new(8, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_ns_pid() {
+        let reader = MockProcReader::new()
+            .file(7, "status", "Name:\tx\nNSpid:\t4242\t1\n")
+            .file(8, "status", "Name:\tx\nNSpid:\t8\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::NsPid(1).matches(&proc));
+        assert!(!Filter::NsPid(4242).matches(&proc));
+        assert!(Filter::NsPid(8).matches(&Process::new(8, &reader, &sys)));
+        assert!(!Filter::NsPid(1).matches(&Process::new(9, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_same_pid_ns() {
+        let reader = MockProcReader::new()
+            .link(1, "ns/pid", "pid:[4026531836]")
+            .link(7, "ns/pid", "pid:[4026531836]")
+            .link(8, "ns/pid", "pid:[4026532201]");
+        let sys = SystemCache::new();
+        assert!(Filter::SamePidNs(1).matches(&Process::new(7, &reader, &sys)));
+        assert!(!Filter::SamePidNs(1).matches(&Process::new(8, &reader, &sys)));
+        assert!(!Filter::SamePidNs(1).matches(&Process::new(9, &reader, &sys)));
+        assert!(!Filter::SamePidNs(2).matches(&Process::new(7, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_caps() {
+        let reader = MockProcReader::new()
+            // CAP_NET_ADMIN | CAP_NET_RAW
+            .file(7, "status", "Name:\tx\nCapEff:\t0000000000003000\n")
+            .file(8, "status", "Name:\tx\nCapEff:\t0000000000000000\n");
+        let sys = SystemCache::new();
+        let net = Process::new(7, &reader, &sys);
+        let none = Process::new(8, &reader, &sys);
+        let gone = Process::new(9, &reader, &sys);
+        assert!(Filter::HasCap(12).matches(&net));
+        assert!(!Filter::HasCap(21).matches(&net));
+        assert!(Filter::NoCap(21).matches(&net));
+        assert!(!Filter::NoCap(13).matches(&net));
+        assert!(Filter::AnyCap.matches(&net));
+        assert!(!Filter::AnyCap.matches(&none));
+        assert!(Filter::NoCap(12).matches(&none));
+        assert!(!Filter::NoCap(12).matches(&gone));
+        assert!(!Filter::AnyCap.matches(&gone));
+    }
+
+    #[test]
+    fn test_in_syscall() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "syscall",
+                "202 0x7f00 0x80 0x0 0x0 0x0 0x0 0x7ffd 0x7f2b\n",
+            )
+            .file(4, "syscall", "running\n")
+            .error(5, "syscall", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let proc = |pid| Process::new(pid, &reader, &sys);
+        assert!(Filter::InSyscall(SyscallState::Syscall(202)).matches(&proc(3)));
+        assert!(!Filter::InSyscall(SyscallState::Syscall(7)).matches(&proc(3)));
+        assert!(Filter::InSyscall(SyscallState::Running).matches(&proc(4)));
+        assert!(!Filter::InSyscall(SyscallState::Running).matches(&proc(5)));
+    }
+
+    #[test]
+    fn test_wchan() {
+        let reader = MockProcReader::new()
+            .file(7, "wchan", "do_sys_poll")
+            .file(8, "wchan", "0");
+        let sys = SystemCache::new();
+        let poll = Process::new(7, &reader, &sys);
+        assert!(Filter::Wchan("do_sys_poll".to_string()).matches(&poll));
+        assert!(Filter::Wchan("poll".to_string()).matches(&poll));
+        assert!(!Filter::Wchan("futex".to_string()).matches(&poll));
+        // A running process isn't blocked anywhere, not even in "0".
+        assert!(!Filter::Wchan("0".to_string()).matches(&Process::new(8, &reader, &sys)));
+        assert!(!Filter::Wchan("poll".to_string()).matches(&Process::new(9, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_namespace() {
+        let reader = MockProcReader::new()
+            .link(7, "ns/net", "net:[4026532300]")
+            .link(8, "ns/net", "net:[4026531840]");
+        let sys = SystemCache::new();
+        let filter = Filter::Namespace {
+            kind: "net",
+            inode: 4026532300,
+        };
+        assert!(filter.matches(&Process::new(7, &reader, &sys)));
+        assert!(!filter.matches(&Process::new(8, &reader, &sys)));
+        assert!(!filter.matches(&Process::new(9, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_traced() {
+        let reader = MockProcReader::new()
+            .file(7, "status", "Name:\tx\nTracerPid:\t4100\n")
+            .file(8, "status", "Name:\tx\nTracerPid:\t0\n");
+        let sys = SystemCache::new();
+        let traced = Process::new(7, &reader, &sys);
+        let free = Process::new(8, &reader, &sys);
+        assert!(Filter::Traced(true).matches(&traced));
+        assert!(!Filter::Traced(false).matches(&traced));
+        assert!(Filter::Traced(false).matches(&free));
+        assert!(!Filter::Traced(true).matches(&free));
+        assert!(!Filter::Traced(false).matches(&Process::new(9, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_pid_range() {
+        let reader = MockProcReader::new();
+        let sys = SystemCache::new();
+        let range = Filter::PidRange { min: 100, max: 200 };
+        for (pid, want) in [(99, false), (100, true), (200, true), (201, false)] {
+            assert_eq!(
+                range.matches(&Process::new(pid, &reader, &sys)),
+                want,
+                "{pid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_started_filters() {
+        // Started 10000 ticks after boot, uptime 3760s: 3660s old at USER_HZ=100.
+        let reader = MockProcReader::new()
+            .file(
+                9,
+                "stat",
+                "9 (sleep) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 10000 0 0\n",
+            )
+            .root_file("uptime", "3760.00 0.00\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(9, &reader, &sys);
+        let age = proc.age().unwrap();
+        assert!(Filter::StartedWithin(age).matches(&proc));
+        assert!(!Filter::StartedWithin(age - Duration::from_secs(1)).matches(&proc));
+        assert!(Filter::StartedMoreThan(age - Duration::from_secs(1)).matches(&proc));
+        assert!(!Filter::StartedMoreThan(age).matches(&proc));
+        let gone = Process::new(10, &reader, &sys);
+        assert!(!Filter::StartedWithin(Duration::MAX).matches(&gone));
+        assert!(!Filter::StartedMoreThan(Duration::ZERO).matches(&gone));
+    }
+
+    #[test]
+    fn test_cwd_filter() {
+        let reader = MockProcReader::new()
+            .link(3, "cwd", "/home/dev/project/src")
+            .link(4, "cwd", "/home/dev/project-old")
+            .error(5, "cwd", std::io::ErrorKind::PermissionDenied);
+        let sys = SystemCache::new();
+        let cwd = |dir: &str, pid| {
+            Filter::Cwd(PathBuf::from(dir)).matches(&Process::new(pid, &reader, &sys))
+        };
+        assert!(cwd("/home/dev/project", 3));
+        assert!(cwd("/home/dev/project/src", 3));
+        assert!(cwd("/", 3));
+        assert!(!cwd("/home/dev/project", 4));
+        assert!(!cwd("/home/dev/project/src/lib", 3));
+        assert!(!cwd("/", 5));
+    }
+
+    #[test]
+    fn test_fd_usage_filter() {
+        let reader = (0..3)
+            .fold(MockProcReader::new(), |r, fd| {
+                r.link(3, &format!("fd/{fd}"), "/dev/null")
+            })
+            .file(
+                3,
+                "limits",
+                "Max open files            4                    4                    files\n",
+            )
+            .link(4, "fd/0", "/dev/null");
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert!(Filter::FdUsagePctMin(50.0).matches(&proc));
+        assert!(!Filter::FdUsagePctMin(75.0).matches(&proc));
+        // No limits file: unknown usage never passes.
+        assert!(!Filter::FdUsagePctMin(0.0).matches(&Process::new(4, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_maps_count_filters() {
+        let maps = "7f00-7f01 rw-p 00000000 00:00 0\n".repeat(3);
+        let reader = MockProcReader::new().file(3, "maps", maps.as_str());
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert!(Filter::MinMapsCount(3).matches(&proc));
+        assert!(!Filter::MinMapsCount(4).matches(&proc));
+        assert!(Filter::MaxMapsCount(3).matches(&proc));
+        assert!(!Filter::MaxMapsCount(2).matches(&proc));
+        assert!(!Filter::MaxMapsCount(usize::MAX).matches(&Process::new(4, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_cgroup_filter() {
+        let reader = MockProcReader::new().file(3, "cgroup", "0::/system.slice/nginx.service\n");
+        let sys = SystemCache::new();
+        let cgroup = |path: &str, mode, pid| {
+            Filter::Cgroup {
+                path: path.to_string(),
+                mode,
+            }
+            .matches(&Process::new(pid, &reader, &sys))
+        };
+        assert!(cgroup("nginx", CgroupMatchMode::Contains, 3));
+        assert!(cgroup("/system.slice", CgroupMatchMode::Prefix, 3));
+        assert!(!cgroup("/system.slice", CgroupMatchMode::Exact, 3));
+        assert!(!cgroup("/", CgroupMatchMode::Prefix, 4));
+    }
+
+    #[test]
+    fn test_jvm() {
+        let reader = MockProcReader::new()
+            .file(3, "cmdline", "/usr/bin/java\0-jar\0app.jar\0")
+            .file(4, "cmdline", "/usr/sbin/nginx\0");
+        let sys = SystemCache::new();
+        assert!(Filter::Jvm.matches(&Process::new(3, &reader, &sys)));
+        assert!(!Filter::Jvm.matches(&Process::new(4, &reader, &sys)));
+        assert!(!Filter::Jvm.matches(&Process::new(5, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_has_tls() {
+        let reader = MockProcReader::new()
+            .file(
+                3,
+                "maps",
+                "7f00-7f01 r-xp 00000000 08:01 7 /usr/lib/x86_64-linux-gnu/libssl.so.3\n",
+            )
+            .file(
+                4,
+                "maps",
+                "7f00-7f01 r-xp 00000000 08:01 8 /usr/lib/x86_64-linux-gnu/libc.so.6\n",
+            );
+        let sys = SystemCache::new();
+        assert!(Filter::HasTls.matches(&Process::new(3, &reader, &sys)));
+        assert!(!Filter::HasTls.matches(&Process::new(4, &reader, &sys)));
+        assert!(!Filter::HasTls.matches(&Process::new(5, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_has_wxmem() {
+        let reader = MockProcReader::new()
+            .file(3, "maps", "7f00-7f01 rwxp 00000000 00:00 0 \n")
+            .file(
+                4,
+                "maps",
+                "7f00-7f01 r-xp 00000000 00:00 0 \n7f01-7f02 rw-p 00000000 00:00 0 \n",
+            );
+        let sys = SystemCache::new();
+        assert!(Filter::HasWxMem.matches(&Process::new(3, &reader, &sys)));
+        assert!(!Filter::HasWxMem.matches(&Process::new(4, &reader, &sys)));
+        assert!(!Filter::HasWxMem.matches(&Process::new(5, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_vm_size_filters() {
+        let reader = MockProcReader::new()
+            .file(3, "status", "Name:\tx\nVmSize:\t  204800 kB\n")
+            .file(4, "status", "Name:\tkthreadd\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert!(Filter::MinVmSizeKb(204800).matches(&proc));
+        assert!(!Filter::MinVmSizeKb(204801).matches(&proc));
+        assert!(Filter::MaxVmSizeKb(204800).matches(&proc));
+        assert!(!Filter::MaxVmSizeKb(204799).matches(&proc));
+        // Kernel threads have no VmSize.
+        let kthread = Process::new(4, &reader, &sys);
+        assert!(!Filter::MinVmSizeKb(0).matches(&kthread));
+        assert!(!Filter::MaxVmSizeKb(u64::MAX).matches(&kthread));
+    }
+
+    #[test]
+    fn test_min_stack_kb() {
+        let reader = MockProcReader::new().file(3, "status", "Name:\tx\nVmStk:\t     136 kB\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert!(Filter::MinStackKb(136).matches(&proc));
+        assert!(!Filter::MinStackKb(137).matches(&proc));
+        assert!(!Filter::MinStackKb(0).matches(&Process::new(4, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_has_tcp() {
+        let reader = MockProcReader::new()
+            .root_file(
+                "net/tcp6",
+                "  sl  local_address remote_address st tx_queue rx_queue tr tm->when retrnsmt uid timeout inode\n\
+                 0: 00000000000000000000000000000000:0050 00000000000000000000000000000000:0000 0A 0:0 0:0 0 0 0 777 1\n",
+            )
+            .link(3, "fd/3", "socket:[777]")
+            .link(4, "fd/3", "socket:[778]");
+        let sys = SystemCache::new();
+        assert!(Filter::HasTcp.matches(&Process::new(3, &reader, &sys)));
+        assert!(!Filter::HasTcp.matches(&Process::new(4, &reader, &sys)));
+        assert!(!Filter::HasTcp.matches(&Process::new(5, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_listening_on() {
+        let reader = MockProcReader::new()
+            .root_file(
+                "net/tcp",
+                "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+                 0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 701 1\n\
+                 1: 0100007F:1F91 0100007F:D431 01 00000000:00000000 00:00000000 00000000 0 0 702 1\n",
+            )
+            .link(3, "fd/3", "socket:[701]")
+            .link(4, "fd/3", "socket:[702]");
+        let sys = SystemCache::new();
+        let proc = Process::new(3, &reader, &sys);
+        assert!(Filter::ListeningOn(8080).matches(&proc));
+        assert!(!Filter::ListeningOn(80).matches(&proc));
+        // Connected, not listening.
+        assert!(!Filter::ListeningOn(8081).matches(&Process::new(4, &reader, &sys)));
+        assert!(!Filter::ListeningOn(8080).matches(&Process::new(5, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_min_numa_local_pct() {
+        let reader = MockProcReader::new()
+            .file(7, "numa_maps", "1000 default anon=4 N0=3 N1=1\n")
+            .file(8, "numa_maps", "");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        let f = |node, pct| Filter::MinNumaLocalPct { node, pct };
+        assert!(f(0, 75.0).matches(&proc));
+        assert!(!f(0, 75.1).matches(&proc));
+        assert!(f(1, 25.0).matches(&proc));
+        // No pages, or numa_maps unreadable.
+        assert!(!f(0, 0.0).matches(&Process::new(8, &reader, &sys)));
+        assert!(!f(0, 0.0).matches(&Process::new(9, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_min_huge_pages() {
+        let reader = MockProcReader::new()
+            .file(
+                7,
+                "status",
+                "Name:\tdb\nVmHuge:\t1024 kB\nHugetlbPages:\t2048 kB\n",
+            )
+            .file(8, "status", "Name:\tdb\nHugetlbPages:\t0 kB\n")
+            .file(9, "status", "Name:\told\nVmRSS:\t512 kB\n");
+        let sys = SystemCache::new();
+        let proc = |pid| Process::new(pid, &reader, &sys);
+        assert!(Filter::MinHugePagesKb(3072).matches(&proc(7)));
+        assert!(!Filter::MinHugePagesKb(3073).matches(&proc(7)));
+        assert!(Filter::MinHugePagesKb(0).matches(&proc(8)));
+        assert!(!Filter::MinHugePagesKb(1).matches(&proc(8)));
+        // Older kernels without either line.
+        assert!(!Filter::MinHugePagesKb(0).matches(&proc(9)));
+    }
+
+    #[test]
+    fn test_min_pinned() {
+        let reader = MockProcReader::new()
+            .file(
+                7,
+                "status",
+                "Name:\tib_app\nVmLck:\t0 kB\nVmPin:\t65536 kB\n",
+            )
+            .file(8, "status", "Name:\tkworker/0:1\n");
+        let sys = SystemCache::new();
+        let proc = |pid| Process::new(pid, &reader, &sys);
+        assert!(Filter::MinPinnedKb(65536).matches(&proc(7)));
+        assert!(!Filter::MinPinnedKb(65537).matches(&proc(7)));
+        assert!(!Filter::MinPinnedKb(0).matches(&proc(8)));
+    }
+
+    #[test]
+    fn test_min_locked() {
+        let reader = MockProcReader::new()
+            .file(
+                7,
+                "status",
+                "Name:\tkeepassxc\nVmLck:\t  128 kB\nVmPin:\t0 kB\n",
+            )
+            .file(8, "status", "Name:\tbash\nVmLck:\t    0 kB\n")
+            .file(9, "status", "Name:\tkthreadd\n");
+        let sys = SystemCache::new();
+        let proc = |pid| Process::new(pid, &reader, &sys);
+        assert!(Filter::MinLockedKb(1).matches(&proc(7)));
+        assert!(Filter::MinLockedKb(128).matches(&proc(7)));
+        assert!(!Filter::MinLockedKb(129).matches(&proc(7)));
+        assert!(!Filter::MinLockedKb(1).matches(&proc(8)));
+        assert!(!Filter::MinLockedKb(0).matches(&proc(9)));
+    }
+
+    #[test]
+    fn test_uid_gid() {
+        let reader = MockProcReader::new().file(
+            7,
+            "status",
+            "Name:\tnginx\nUid:\t33\t0\t0\t0\nGid:\t33\t33\t33\t33\n",
+        );
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert!(Filter::Uid(33).matches(&proc));
+        // The effective UID does not count.
+        assert!(!Filter::Uid(0).matches(&proc));
+        assert!(Filter::Gid(33).matches(&proc));
+        assert!(!Filter::Gid(0).matches(&proc));
+        assert!(!Filter::Uid(33).matches(&Process::new(8, &reader, &sys)));
+    }
+
+    #[test]
+    fn test_has_mount() {
+        let reader = MockProcReader::new()
+            .file(
+                7,
+                "mountinfo",
+                "22 1 8:2 / / rw - ext4 /dev/sda2 rw\n40 22 0:50 / /data rw - nfs srv:/x rw\n",
+            )
+            .file(8, "mountinfo", "22 1 8:2 / / rw - ext4 /dev/sda2 rw\n");
+        let sys = SystemCache::new();
+        let has = |path: &str, pid| {
+            Filter::HasMount(PathBuf::from(path)).matches(&Process::new(pid, &reader, &sys))
+        };
+        assert!(has("/data", 7));
+        assert!(!has("/data", 8));
+        assert!(has("/", 8));
+        assert!(!has("/", 9));
+    }
+
+    #[test]
+    fn test_seccomp() {
+        let reader = MockProcReader::new()
+            .file(7, "status", "Name:\tsshd\nSeccomp:\t2\n")
+            .file(8, "status", "Name:\tbash\nSeccomp:\t0\n")
+            .file(9, "status", "Name:\told\n");
+        let sys = SystemCache::new();
+        let proc = |pid| Process::new(pid, &reader, &sys);
+        assert!(Filter::HasSeccomp.matches(&proc(7)));
+        assert!(!Filter::HasSeccomp.matches(&proc(8)));
+        assert!(!Filter::HasSeccomp.matches(&proc(9)));
+        assert!(Filter::SeccompMode(2).matches(&proc(7)));
+        assert!(!Filter::SeccompMode(1).matches(&proc(7)));
+        assert!(Filter::SeccompMode(0).matches(&proc(8)));
+        assert!(!Filter::SeccompMode(0).matches(&proc(9)));
+    }
+}
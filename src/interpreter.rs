@@ -0,0 +1,158 @@
+use crate::matcher::basename;
+
+/// An interpreter name and its options that take a separate value.
+type Interpreter = (&'static str, &'static [&'static [u8]]);
+
+/// Known interpreters with the options of each that take the next argument
+/// as their value. `python3.11`, `ruby3.2` and the like match by name plus a
+/// version suffix.
+const INTERPRETERS: [Interpreter; 15] = [
+    ("python", &[b"-W", b"-X", b"--check-hash-based-pycs"]),
+    ("pypy", &[b"-W", b"-X"]),
+    ("ruby", &[b"-I", b"-r", b"-C", b"-E", b"--encoding"]),
+    ("perl", &[]),
+    ("node", &[b"-r", b"--require", b"--import", b"--loader"]),
+    ("nodejs", &[b"-r", b"--require", b"--import", b"--loader"]),
+    ("php", &[b"-d", b"-c", b"-z"]),
+    ("lua", &[b"-l"]),
+    ("tclsh", &[]),
+    ("bash", &[b"-O", b"+O", b"--rcfile", b"--init-file"]),
+    ("sh", &[]),
+    ("dash", &[]),
+    ("zsh", &[]),
+    ("ksh", &[]),
+    ("fish", &[b"-C", b"--init-command"]),
+];
+
+/// Options that run code given on the command line instead of a script.
+/// Checked after the value options, so `-r` only means code for php.
+const INLINE_CODE: [&[u8]; 4] = [b"-c", b"-e", b"--eval", b"-r"];
+
+/// An interpreter process and what it runs (`--print-interpreter`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterpreterInfo {
+    /// Interpreter name without version, e.g. `python`.
+    pub name: &'static str,
+    /// Version suffix of argv[0], e.g. `3.11` for `python3.11`.
+    pub version: Option<String>,
+    /// The script path, or the module after `python -m`; `None` for inline
+    /// code (`-c`, `-e`) and interactive sessions.
+    pub script: Option<String>,
+}
+
+/// Splits an argv[0] basename into a known interpreter and its version
+/// suffix, which must be digits and dots (`python3.11`, `perl5.36`).
+fn split_interpreter(base: &[u8]) -> Option<(Interpreter, &[u8])> {
+    INTERPRETERS.iter().find_map(|entry| {
+        let version = base.strip_prefix(entry.0.as_bytes())?;
+        version
+            .iter()
+            .all(|b| b.is_ascii_digit() || *b == b'.')
+            .then_some((*entry, version))
+    })
+}
+
+/// Recognises a script interpreter by the basename of argv[0] (a login
+/// shell's leading `-` is ignored) and finds the script it runs: the first
+/// argument that is not an option or an option's value.
+pub fn detect_interpreter(argv: &[&[u8]]) -> Option<InterpreterInfo> {
+    let (argv0, args) = argv.split_first()?;
+    let base = basename(argv0);
+    let base = base.strip_prefix(b"-").unwrap_or(base);
+    let ((name, value_opts), version) = split_interpreter(base)?;
+    let text = |arg: &[u8]| String::from_utf8_lossy(arg).into_owned();
+    let mut script = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if *arg == b"-m" && name.starts_with("py") {
+            script = args.next().map(|a| text(a));
+            break;
+        } else if value_opts.contains(arg) {
+            args.next();
+        } else if INLINE_CODE.contains(arg) {
+            break;
+        } else if !arg.starts_with(b"-") && !arg.starts_with(b"+") {
+            script = Some(text(arg));
+            break;
+        }
+    }
+    Some(InterpreterInfo {
+        name,
+        version: (!version.is_empty()).then(|| text(version)),
+        script,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(cmdline: &[u8]) -> Option<InterpreterInfo> {
+        let args: Vec<&[u8]> = cmdline
+            .split(|b| *b == 0)
+            .filter(|a| !a.is_empty())
+            .collect();
+        detect_interpreter(&args)
+    }
+
+    fn info(
+        name: &'static str,
+        version: Option<&str>,
+        script: Option<&str>,
+    ) -> Option<InterpreterInfo> {
+        Some(InterpreterInfo {
+            name,
+            version: version.map(str::to_string),
+            script: script.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn test_detect_interpreter_script() {
+        assert_eq!(
+            detect(
+                b"/usr/bin/python3.11\0-u\0-W\0ignore\0/usr/bin/unattended-upgrade\0--dry-run\0"
+            ),
+            info("python", Some("3.11"), Some("/usr/bin/unattended-upgrade"))
+        );
+        assert_eq!(
+            detect(b"python3\0-m\0http.server\08000\0"),
+            info("python", Some("3"), Some("http.server"))
+        );
+        assert_eq!(
+            detect(b"/usr/bin/ruby3.2\0-I\0lib\0bin/rails\0server\0"),
+            info("ruby", Some("3.2"), Some("bin/rails"))
+        );
+        assert_eq!(
+            detect(b"/usr/bin/perl\0-w\0/usr/sbin/munin-node\0"),
+            info("perl", None, Some("/usr/sbin/munin-node"))
+        );
+        assert_eq!(
+            detect(b"node\0--require\0dotenv/config\0/srv/app/server.js\0"),
+            info("node", None, Some("/srv/app/server.js"))
+        );
+        assert_eq!(
+            detect(b"/bin/bash\0/etc/cron.daily/logrotate\0"),
+            info("bash", None, Some("/etc/cron.daily/logrotate"))
+        );
+        assert_eq!(
+            detect(b"php8.2\0-d\0memory_limit=-1\0artisan\0queue:work\0"),
+            info("php", Some("8.2"), Some("artisan"))
+        );
+    }
+
+    #[test]
+    fn test_detect_interpreter_no_script() {
+        assert_eq!(detect(b"/bin/sh\0-c\0sleep 60\0"), info("sh", None, None));
+        assert_eq!(
+            detect(b"python3\0-c\0import time; time.sleep(9)\0"),
+            info("python", Some("3"), None)
+        );
+        // Login shell.
+        assert_eq!(detect(b"-bash\0"), info("bash", None, None));
+        assert_eq!(detect(b"/usr/sbin/nginx\0-g\0daemon off;\0"), None);
+        assert_eq!(detect(b"/usr/bin/shred\0file\0"), None);
+        assert_eq!(detect(b"python-config\0"), None);
+        assert_eq!(detect(b""), None);
+    }
+}
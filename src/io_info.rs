@@ -0,0 +1,132 @@
+use std::io;
+
+use crate::error::ParseError;
+use crate::reader::{FsProcReader, ProcReader};
+
+/// I/O counters from `/proc/<pid>/io`, in bytes or syscalls.
+///
+/// The file is only readable by the process owner (ptrace access) or root,
+/// so `EACCES` is the normal outcome for other users' processes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoInfo {
+    pub rchar: u64,
+    pub wchar: u64,
+    pub syscr: u64,
+    pub syscw: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub cancelled_write_bytes: u64,
+}
+
+/// Parses the `key: value` lines of `/proc/<pid>/io`.
+///
+/// `rchar` and `wchar` are always present; the storage counters depend on
+/// task I/O accounting and older kernels omit `cancelled_write_bytes`, so
+/// those default to 0.
+pub fn parse_io_file(buf: &[u8]) -> Result<IoInfo, ParseError> {
+    let mut info = IoInfo::default();
+    let (mut have_rchar, mut have_wchar) = (false, false);
+    for line in String::from_utf8_lossy(buf).lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let slot = match key {
+            "rchar" => {
+                have_rchar = true;
+                &mut info.rchar
+            }
+            "wchar" => {
+                have_wchar = true;
+                &mut info.wchar
+            }
+            "syscr" => &mut info.syscr,
+            "syscw" => &mut info.syscw,
+            "read_bytes" => &mut info.read_bytes,
+            "write_bytes" => &mut info.write_bytes,
+            "cancelled_write_bytes" => &mut info.cancelled_write_bytes,
+            _ => continue,
+        };
+        let value = value.trim();
+        *slot = value
+            .parse()
+            .map_err(|_| ParseError::InvalidValue(value.to_string()))?;
+    }
+    if !have_rchar {
+        return Err(ParseError::MissingField("rchar"));
+    }
+    if !have_wchar {
+        return Err(ParseError::MissingField("wchar"));
+    }
+    Ok(info)
+}
+
+impl IoInfo {
+    /// Reads `/proc/<pid>/io`; requires root for other users' processes.
+    pub fn read(pid: u32) -> Result<IoInfo, io::Error> {
+        Self::read_with(pid, &FsProcReader::default())
+    }
+
+    pub fn read_with(pid: u32, reader: &impl ProcReader) -> Result<IoInfo, io::Error> {
+        Ok(parse_io_file(&reader.read(pid, "io")?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const IO: &str = "\
+rchar: 323934931
+wchar: 323929600
+syscr: 632687
+syscw: 632675
+read_bytes: 4096
+write_bytes: 323932160
+cancelled_write_bytes: 1024
+";
+
+    #[test]
+    fn test_parse_io_file() {
+        assert_eq!(
+            parse_io_file(IO.as_bytes()).unwrap(),
+            IoInfo {
+                rchar: 323934931,
+                wchar: 323929600,
+                syscr: 632687,
+                syscw: 632675,
+                read_bytes: 4096,
+                write_bytes: 323932160,
+                cancelled_write_bytes: 1024,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_io_missing_fields() {
+        let old = "rchar: 10\nwchar: 20\nsyscr: 1\nsyscw: 2\nread_bytes: 0\nwrite_bytes: 8\n";
+        let info = parse_io_file(old.as_bytes()).unwrap();
+        assert_eq!((info.write_bytes, info.cancelled_write_bytes), (8, 0));
+        assert_eq!(
+            parse_io_file(b"wchar: 1\n"),
+            Err(ParseError::MissingField("rchar"))
+        );
+        assert_eq!(
+            parse_io_file(b"rchar: 1\nwchar: lots\n"),
+            Err(ParseError::InvalidValue("lots".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_io_info_read_with() {
+        let reader =
+            MockProcReader::new()
+                .file(1, "io", IO)
+                .error(2, "io", io::ErrorKind::PermissionDenied);
+        assert_eq!(IoInfo::read_with(1, &reader).unwrap().syscw, 632675);
+        assert_eq!(
+            IoInfo::read_with(2, &reader).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+    }
+}
@@ -0,0 +1,321 @@
+// Minimal JSON emitters for fpid's flat output records; a serializer
+// dependency would be overkill for strings, numbers and arrays of those.
+// `parse` reads such records back (`--compare-snapshot`).
+
+use std::fmt::Write;
+
+use crate::error::ParseError;
+
+/// Appends `s` as a quoted JSON string.
+pub fn push_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Appends `"key":` (with a leading comma unless `first`).
+pub fn push_key(out: &mut String, key: &str, first: bool) {
+    if !first {
+        out.push(',');
+    }
+    push_str(out, key);
+    out.push(':');
+}
+
+/// A parsed JSON value. Object members keep their order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Member `key` of an object (the first one, if repeated).
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// A number that is a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 && *n <= u64::MAX as f64 => {
+                Some(*n as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses one JSON document; anything but whitespace after it is an error.
+pub fn parse(s: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser {
+        buf: s.as_bytes(),
+        pos: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_ws();
+    if parser.pos != parser.buf.len() {
+        return Err(parser.error());
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> ParseError {
+        ParseError::InvalidValue(format!("JSON at byte {}", self.pos))
+    }
+
+    fn skip_ws(&mut self) {
+        while self.buf.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        self.skip_ws();
+        let found = self.buf.get(self.pos) == Some(&b);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, ParseError> {
+        if !self.buf[self.pos..].starts_with(word.as_bytes()) {
+            return Err(self.error());
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_ws();
+        match self.buf.get(self.pos) {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, ParseError> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        if self.eat(b'}') {
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_ws();
+            if self.buf.get(self.pos) != Some(&b'"') {
+                return Err(self.error());
+            }
+            let key = self.string()?;
+            if !self.eat(b':') {
+                return Err(self.error());
+            }
+            members.push((key, self.value()?));
+            if self.eat(b'}') {
+                return Ok(Value::Object(members));
+            }
+            if !self.eat(b',') {
+                return Err(self.error());
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.eat(b']') {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.eat(b']') {
+                return Ok(Value::Array(items));
+            }
+            if !self.eat(b',') {
+                return Err(self.error());
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        while self
+            .buf
+            .get(self.pos)
+            .is_some_and(|b| matches!(b, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.buf[start..self.pos])
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .map(Value::Number)
+            .ok_or_else(|| ParseError::InvalidValue(format!("JSON at byte {}", start)))
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let digits = self
+            .buf
+            .get(self.pos..self.pos + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error())?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    // At the opening quote.
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            let b = *self.buf.get(self.pos).ok_or_else(|| self.error())?;
+            self.pos += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let esc = *self.buf.get(self.pos).ok_or_else(|| self.error())?;
+                    self.pos += 1;
+                    let c = match esc {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // A surrogate pair spells one character.
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.buf[self.pos..].starts_with(b"\\u")
+                            {
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                code = 0x10000
+                                    + ((code - 0xD800) << 10)
+                                    + (low.wrapping_sub(0xDC00) & 0x3FF);
+                            }
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(self.error()),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                b => out.push(b),
+            }
+        }
+        String::from_utf8(out).map_err(|_| self.error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_str_escapes() {
+        let mut out = String::new();
+        push_str(&mut out, "a\"b\\c\nd\u{1}é");
+        assert_eq!(out, r#""a\"b\\c\nd\u0001é""#);
+    }
+
+    #[test]
+    fn test_push_key() {
+        let mut out = String::from("{");
+        push_key(&mut out, "name", true);
+        push_str(&mut out, "bash");
+        push_key(&mut out, "count", false);
+        out.push_str("3}");
+        assert_eq!(out, r#"{"name":"bash","count":3}"#);
+    }
+
+    #[test]
+    fn test_parse() {
+        let v = parse(r#" {"pid":42,"name":"a\"b\u00e9\ud83d\ude00","exe":null,"cmdline":["x", "-y"],"ok":true,"load":-1.5e2} "#)
+            .unwrap();
+        assert_eq!(v.get("pid").and_then(Value::as_u64), Some(42));
+        assert_eq!(v.get("name").and_then(Value::as_str), Some("a\"bé😀"));
+        assert_eq!(v.get("exe"), Some(&Value::Null));
+        assert_eq!(
+            v.get("cmdline"),
+            Some(&Value::Array(vec![
+                Value::String("x".to_string()),
+                Value::String("-y".to_string())
+            ]))
+        );
+        assert_eq!(v.get("ok"), Some(&Value::Bool(true)));
+        assert_eq!(v.get("load"), Some(&Value::Number(-150.0)));
+        assert_eq!(v.get("load").and_then(Value::as_u64), None);
+        assert_eq!(v.get("missing"), None);
+        assert_eq!(parse("[]").unwrap(), Value::Array(vec![]));
+        assert_eq!(parse("{}").unwrap(), Value::Object(vec![]));
+    }
+
+    #[test]
+    fn test_parse_round_trips_push_str() {
+        let s = "a\"b\\c\nd\u{1}é\t";
+        let mut out = String::new();
+        push_str(&mut out, s);
+        assert_eq!(parse(&out).unwrap(), Value::String(s.to_string()));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        for bad in [
+            "",
+            "{",
+            "[1,",
+            "[1 2]",
+            r#"{"a" 1}"#,
+            r#"{a:1}"#,
+            r#""open"#,
+            "tru",
+            "01x",
+            "1 2",
+            r#""\q""#,
+            "-",
+        ] {
+            assert!(parse(bad).is_err(), "{bad:?}");
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::matcher::basename;
+
+/// Launcher names treated as a JVM regardless of arguments.
+const JVM_LAUNCHERS: [&[u8]; 3] = [b"java", b"javaw", b"jvm"];
+
+/// Launcher options whose value is the next argument, so it is not taken
+/// for the main class.
+const OPTIONS_WITH_VALUE: [&[u8]; 11] = [
+    b"-cp",
+    b"-classpath",
+    b"--class-path",
+    b"-p",
+    b"--module-path",
+    b"--upgrade-module-path",
+    b"--add-modules",
+    b"--add-opens",
+    b"--add-exports",
+    b"--add-reads",
+    b"--patch-module",
+];
+
+/// What the launcher arguments of a Java process say (`--jvm-detect`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JvmInfo {
+    /// The main class, the jar after `-jar` or the `module/class` after
+    /// `-m`; `None` when only options were given.
+    pub main_class: Option<String>,
+    /// Value of the last `-Xmx` option, e.g. `512m`.
+    pub xmx: Option<String>,
+    /// Value of the last `-Xms` option.
+    pub xms: Option<String>,
+}
+
+/// Recognises a JVM by its argv[0] basename (`java`, `javaw`, `jvm`) or a
+/// `-cp`/`-classpath` argument, and reads the main class and heap sizes
+/// from the arguments before it. Arguments after the main class belong to
+/// the application and are not looked at.
+pub fn detect_jvm(cmdline: &[&[u8]]) -> Option<JvmInfo> {
+    let (argv0, args) = cmdline.split_first()?;
+    let is_jvm = JVM_LAUNCHERS.contains(&basename(argv0))
+        || args.iter().any(|a| *a == b"-cp" || *a == b"-classpath");
+    if !is_jvm {
+        return None;
+    }
+    let text = |arg: &[u8]| String::from_utf8_lossy(arg).into_owned();
+    let mut info = JvmInfo::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if *arg == b"-jar" || *arg == b"-m" || *arg == b"--module" {
+            info.main_class = args.next().map(|a| text(a));
+            break;
+        } else if OPTIONS_WITH_VALUE.contains(arg) {
+            args.next();
+        } else if let Some(size) = arg.strip_prefix(b"-Xmx") {
+            info.xmx = Some(text(size));
+        } else if let Some(size) = arg.strip_prefix(b"-Xms") {
+            info.xms = Some(text(size));
+        } else if !arg.starts_with(b"-") {
+            info.main_class = Some(text(arg));
+            break;
+        }
+    }
+    Some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(cmdline: &[u8]) -> Option<JvmInfo> {
+        let args: Vec<&[u8]> = cmdline
+            .split(|b| *b == 0)
+            .filter(|a| !a.is_empty())
+            .collect();
+        detect_jvm(&args)
+    }
+
+    fn info(class: Option<&str>, xmx: Option<&str>, xms: Option<&str>) -> Option<JvmInfo> {
+        Some(JvmInfo {
+            main_class: class.map(str::to_string),
+            xmx: xmx.map(str::to_string),
+            xms: xms.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn test_detect_jvm_main_class() {
+        assert_eq!(
+            detect(
+                b"/usr/lib/jvm/java-17-openjdk-amd64/bin/java\0-Xms256m\0-Xmx512m\0\
+                  -cp\0/opt/app/lib/*:/opt/app/conf\0-Dlog.dir=/var/log/app\0\
+                  com.example.App\0--port\0-Xmx9g\0"
+            ),
+            info(Some("com.example.App"), Some("512m"), Some("256m"))
+        );
+        // Repeated options: the JVM uses the last one.
+        assert_eq!(
+            detect(b"java\0-Xmx1g\0-XX:+UseG1GC\0-Xmx2g\0-jar\0/srv/kafka.jar\0"),
+            info(Some("/srv/kafka.jar"), Some("2g"), None)
+        );
+        assert_eq!(
+            detect(b"javaw\0--module-path\0mods\0-m\0app/com.example.Main\0"),
+            info(Some("app/com.example.Main"), None, None)
+        );
+        assert_eq!(detect(b"java\0-version\0"), info(None, None, None));
+    }
+
+    #[test]
+    fn test_detect_jvm_launcher() {
+        // Renamed launcher, recognised by its classpath.
+        assert_eq!(
+            detect(b"/opt/es/jdk/bin/elasticsearch-jvm\0-Xmx4g\0-classpath\0lib/*\0org.elasticsearch.Boot\0"),
+            info(Some("org.elasticsearch.Boot"), Some("4g"), None)
+        );
+        assert_eq!(detect(b"/usr/sbin/nginx\0-g\0daemon off;\0"), None);
+        assert_eq!(detect(b"/usr/bin/javac\0Main.java\0"), None);
+        assert_eq!(detect(b""), None);
+    }
+}
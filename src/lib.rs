@@ -0,0 +1,71 @@
+//! Library side of fpid: `/proc` parsing helpers used by the `fpid-rs` binary.
+
+pub mod affinity;
+#[cfg(feature = "aho-corasick")]
+pub mod aho_corasick;
+pub mod audit;
+pub mod batch;
+pub mod caps;
+pub mod cgroup;
+pub mod cmdline;
+pub mod column;
+pub mod coredump;
+pub mod cwd;
+pub mod daemon;
+pub mod environ;
+pub mod error;
+pub mod fd;
+#[cfg(feature = "capi")]
+pub mod ffi;
+pub mod filter;
+pub mod interpreter;
+pub mod io_info;
+pub mod json;
+pub mod jvm;
+pub mod log;
+pub mod maps;
+pub mod matcher;
+pub mod meminfo;
+pub mod mountinfo;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod namespace;
+pub mod net;
+pub mod nice;
+pub mod numa;
+pub mod oom;
+pub mod output;
+pub mod pid_file;
+pub mod pid_range;
+pub mod proc_events;
+pub mod process;
+pub mod reader;
+pub mod scan;
+pub mod sched_policy;
+pub mod schedstat;
+pub mod self_test;
+pub mod signal;
+pub mod smaps;
+pub mod snapshot;
+pub mod sort;
+pub mod stat;
+pub mod statm;
+pub mod status;
+pub mod sys;
+pub mod syscall;
+pub mod task;
+pub mod tree;
+pub mod users;
+pub mod watch;
+pub mod wchan;
+
+pub use column::Column;
+pub use error::{FpidError, ParseError};
+pub use filter::Filter;
+pub use matcher::MultiMatcher;
+pub use process::{Process, SystemCache};
+pub use reader::{FsProcReader, ProcReader};
+pub use scan::{
+    MatchMode, ProcessInfo, ScanConfig, Scanner, ScannerBuilder, list_all_names, scan_pids,
+    scan_processes,
+};
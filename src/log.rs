@@ -0,0 +1,141 @@
+//! Minimal logging facade for diagnostics.
+//!
+//! Messages go through one process-wide sink: stderr by default, or a
+//! function installed with [`set_logger`] by library users. `-q` only
+//! silences stdout, so it does not affect this.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    /// Level named by a `RUST_LOG`-style value (`error`..`trace`); `off` and
+    /// unknown values give `None`.
+    pub fn from_name(s: &str) -> Option<Level> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" | "trace" => Some(Level::Debug),
+            _ => None,
+        }
+    }
+}
+
+pub type Logger = fn(Level, &fmt::Arguments<'_>);
+
+// 0 means logging is off.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Sets the most verbose level that is emitted; `None` turns logging off.
+pub fn set_max_level(level: Option<Level>) {
+    MAX_LEVEL.store(level.map_or(0, |l| l as u8), Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Routes records to `logger` instead of stderr. Only the first call takes
+/// effect; later ones return `false`.
+pub fn set_logger(logger: Logger) -> bool {
+    LOGGER.set(logger).is_ok()
+}
+
+/// Applies `RUST_LOG` when set; `verbose` (`-v`) raises the level to debug.
+pub fn init_from_env(verbose: bool) {
+    if let Ok(v) = std::env::var("RUST_LOG") {
+        set_max_level(Level::from_name(&v));
+    }
+    if verbose {
+        set_max_level(Some(Level::Debug));
+    }
+}
+
+#[doc(hidden)]
+pub fn log(level: Level, args: fmt::Arguments<'_>) {
+    if !enabled(level) {
+        return;
+    }
+    match LOGGER.get() {
+        Some(logger) => logger(level, &args),
+        None => {
+            let prefix = match level {
+                Level::Debug => "debug: ",
+                Level::Info => "info: ",
+                _ => "",
+            };
+            let _ = writeln!(io::stderr(), "{}{}", prefix, args);
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Error, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Warn, format_args!($($arg)*)) };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::log::log($crate::log::Level::Debug, format_args!($($arg)*)) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static RECORDS: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+    fn capture(level: Level, args: &fmt::Arguments<'_>) {
+        RECORDS.lock().unwrap().push((level, args.to_string()));
+    }
+
+    #[test]
+    fn test_level_from_name() {
+        assert_eq!(Level::from_name("DEBUG"), Some(Level::Debug));
+        assert_eq!(Level::from_name("trace"), Some(Level::Debug));
+        assert_eq!(Level::from_name("warn"), Some(Level::Warn));
+        assert_eq!(Level::from_name("off"), None);
+    }
+
+    // One test owns the global state so parallel tests can't interleave.
+    #[test]
+    fn test_custom_logger_and_levels() {
+        assert!(set_logger(capture));
+        assert!(!set_logger(capture));
+
+        set_max_level(Some(Level::Warn));
+        log_error!("open dir {} failed", "/proc");
+        log_debug!("hidden");
+        set_max_level(Some(Level::Debug));
+        log_debug!("pid {} matched", 7);
+        set_max_level(None);
+        log_error!("dropped");
+        set_max_level(Some(Level::Warn));
+
+        let got = RECORDS.lock().unwrap().clone();
+        assert_eq!(
+            got,
+            vec![
+                (Level::Error, "open dir /proc failed".to_string()),
+                (Level::Debug, "pid 7 matched".to_string()),
+            ]
+        );
+    }
+}
@@ -1,247 +1,752 @@
+mod cli;
+
 use std::env;
-use std::fs::{File, read_dir, read_link};
-use std::io::{self, Read, Write};
-use std::path::PathBuf;
-
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
-struct Flags {
-    quiet: bool,
-    single: bool,
-}
+use std::io::{self, BufWriter, Write};
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
 
-fn print_help(program: &str) {
-    println!(
-        "Usage: {program} [-q] [-s] [-h] <program name or path>\nOptions:\n  -q    Quiet mode: suppress output, exit 0 if found\n  -s    Single shot: exit after first match\n  -h    Show this help"
-    );
-}
+use fpid_rs::audit::{AuditLogger, iso8601_utc};
+use fpid_rs::column::{column_value, format_column, format_column_human};
+use fpid_rs::daemon::{DEFAULT_PIDFILE, DEFAULT_SOCKET, DaemonServer};
+use fpid_rs::log;
+use fpid_rs::namespace::pid_namespace;
+use fpid_rs::nice::ReniceAction;
+use fpid_rs::oom::OomAdjAction;
+use fpid_rs::output::{
+    Format, HeaderMode, TableFormatter, TsvWriter, format_procps_line, open_output, paint_pid,
+    process_json, record_end, render_name_counts, table_header, table_width, use_color,
+};
+use fpid_rs::pid_file::{read_pid_file, write_pid_file};
+use fpid_rs::proc_events::{ProcEventListener, is_overrun};
+use fpid_rs::reader::{DEFAULT_MAX_READ_BYTES, resolve_proc_root};
+use fpid_rs::scan::{pid_matches, scan_each, scan_pids};
+use fpid_rs::self_test::run_self_test;
+use fpid_rs::signal::{SignalAction, confirm_message, prompt_confirm};
+use fpid_rs::snapshot::{compare_snapshot, load_snapshot};
+use fpid_rs::sort::{apply_sort, sort_values};
+use fpid_rs::stat::ProcStat;
+use fpid_rs::sys::{
+    INTERRUPTED, SIGINT, SIGTERM, fork_detached, install_sigint_flag, is_stdout_tty,
+    restore_default_sigint, send_signal,
+};
+use fpid_rs::task::scan_threads;
+use fpid_rs::tree::{collect_descendants, parent_map};
+use fpid_rs::watch::{WATCH_INTERVAL, Watcher};
+use fpid_rs::{Filter, FsProcReader, ProcReader, Process, ProcessInfo, ScanConfig, list_all_names};
+use fpid_rs::{log_error, log_warn};
 
-fn parse_args_from_vec(argv: Vec<String>) -> Result<(Flags, String), i32> {
-    let program = argv.first().cloned().unwrap_or_else(|| "fpid".to_string());
-    let mut flags = Flags::default();
-    let mut target: Option<String> = None;
-
-    let mut i = 1;
-    while i < argv.len() {
-        let arg = &argv[i];
-        if !arg.starts_with('-') || arg == "-" {
-            if target.is_some() {
-                // Extra positional args -> treat as usage error like C code
-                let _ = writeln!(
-                    io::stderr(),
-                    "Error: Missing program name or path\nUsage: {} [-qhs] <program name or path>",
-                    program
-                );
-                return Err(1);
-            }
-            target = Some(arg.clone());
-            i += 1;
-            continue;
-        }
-
-        for ch in arg.chars().skip(1) {
-            match ch {
-                'q' => flags.quiet = true,
-                's' => flags.single = true,
-                'h' => {
-                    print_help(&program);
-                    return Err(0);
-                }
-                _ => {
-                    let _ = writeln!(
-                        io::stderr(),
-                        "Unknown option '{}' (see \"{} -h\")",
-                        ch,
-                        program
-                    );
-                    return Err(1);
+use cli::{Cli, parse_args_from_vec};
+
+fn main() {
+    log::init_from_env(false);
+    let argv: Vec<String> = env::args().collect();
+    let cli = match parse_args_from_vec(argv) {
+        Ok(v) => v,
+        Err(code) => std::process::exit(code),
+    };
+    let flags = cli.flags;
+    if flags.verbose {
+        log::set_max_level(Some(log::Level::Debug));
+    }
+
+    if cli.daemon_stop {
+        let pidfile = cli
+            .pidfile
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PIDFILE.into());
+        let Ok(pid) = read_pid_file(&pidfile) else {
+            log_error!("no daemon PID in {}", pidfile.display());
+            std::process::exit(1);
+        };
+        if cli.dry_run {
+            println!("[DRY-RUN] would send SIGTERM to daemon pid {}", pid);
+            std::process::exit(0);
+        }
+        if let Err(e) = send_signal(pid, SIGTERM) {
+            log_error!("stop daemon {} failed: {}", pid, e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    if cli.daemon {
+        run_daemon(&cli);
+    }
+
+    if cli.self_test {
+        match run_self_test() {
+            Ok(()) => {
+                if !flags.quiet {
+                    println!("self-test ok");
                 }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                log_error!("{}", e);
+                std::process::exit(3);
             }
         }
-        i += 1;
     }
 
-    match target {
-        Some(t) => Ok((flags, t)),
-        None => {
-            let _ = writeln!(
-                io::stderr(),
-                "Error: Missing program name or path\nUsage: {} [-qhs] <program name or path>",
-                program
-            );
-            Err(1)
+    let mut out: Box<dyn Write> = match &cli.output {
+        Some(path) => match open_output(path, cli.append, cli.output_mode.unwrap_or(0o644)) {
+            Ok(f) => Box::new(BufWriter::new(f)),
+            Err(e) => {
+                log_error!("open {} failed: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout().lock()),
+    };
+    let reader = proc_reader(&cli);
+    let to_tty = cli.output.is_none() && is_stdout_tty();
+    let color = use_color(cli.format, to_tty);
+    // NDJSON records are newline-delimited by definition.
+    let end = if cli.format == Format::Ndjson {
+        "\n"
+    } else {
+        record_end(to_tty, cli.pipe_null)
+    };
+
+    if cli.header_mode == HeaderMode::Only {
+        if !flags.quiet {
+            // Without rows no column is wider than its header, so the
+            // width never matters.
+            if cli.format == Format::Table {
+                let _ = write!(out, "{}", table_formatter(&cli).render(usize::MAX));
+            } else {
+                write_header_row(&mut out, &cli);
+            }
         }
+        finish(out, 0);
     }
-}
 
-fn is_all_digits(s: &str) -> bool {
-    !s.is_empty() && s.bytes().all(|b: u8| b.is_ascii_digit())
-}
+    if cli.list_all {
+        let counts = list_all_names(&reader);
+        if !flags.quiet {
+            let _ = write!(out, "{}", render_name_counts(&counts, cli.format));
+        }
+        finish(out, if counts.is_empty() { 1 } else { 0 });
+    }
 
-fn main() {
-    let argv: Vec<String> = env::args().collect();
-    let (flags, target) = match parse_args_from_vec(argv) {
-        Ok(v) => v,
-        Err(code) => std::process::exit(code),
-    };
+    if !cli.thread_names.is_empty() {
+        let threads = match scan_threads(&cli.thread_names, &reader) {
+            Ok(v) => v,
+            Err(e) => {
+                log_error!("open dir /proc failed: {}", e);
+                finish(out, 1);
+            }
+        };
+        let keep = if flags.single { 1 } else { threads.len() };
+        if !flags.quiet {
+            for (pid, tid) in threads.iter().take(keep) {
+                let _ = write!(out, "{}/{}{}", pid, tid, end);
+            }
+        }
+        finish(out, if threads.is_empty() { 1 } else { 0 });
+    }
 
-    let is_path = target.contains('/');
+    let action = cli.signal.map(|signal| SignalAction {
+        signal,
+        dry_run: cli.dry_run,
+    });
 
-    // Scan /proc
-    let proc_iter = match read_dir("/proc") {
-        Ok(it) => it,
-        Err(e) => {
-            let _ = writeln!(io::stderr(), "open dir /proc failed: {}", e);
-            std::process::exit(1);
+    let oom_action = cli.set_oom_adj.map(|adj| OomAdjAction {
+        adj,
+        dry_run: cli.dry_run,
+    });
+    let renice_action = cli.renice.map(|nice| ReniceAction {
+        nice,
+        dry_run: cli.dry_run,
+    });
+
+    let mut config = ScanConfig::new(&cli.targets).with_exclude(&cli.exclude_names);
+    config.match_mode = cli.match_mode;
+    config.match_every = cli.targets.is_empty() && cli.ppid_chain.is_some();
+    #[cfg(feature = "aho-corasick")]
+    {
+        config.match_cmdline = cli.match_cmdline;
+    }
+    config.single = flags.single;
+    config.filters = cli.filters.clone();
+    config.batch_size = cli.proc_batch_size;
+    config.batch_sleep_us = cli.proc_batch_sleep_us;
+    // Ctrl-C stops the scan instead of killing fpid, so collected matches
+    // still get printed; exit status is then 130 (128 + SIGINT).
+    if install_sigint_flag().is_ok() {
+        config.interrupt = Some(&INTERRUPTED);
+    }
+    if cli.debug {
+        config.debug_logger = Some(Box::new(|line: &str| eprintln!("{}", line)));
+    }
+    if let Some(root) = cli.ppid_chain {
+        match parent_map(&reader) {
+            Ok(parents) => config
+                .filters
+                .push(Filter::Pids(collect_descendants(root, &parents))),
+            Err(e) => {
+                log_error!("open dir /proc failed: {}", e);
+                finish(out, 1);
+            }
         }
-    };
+    }
+    // An unreadable reference namespace would silently filter out every
+    // process, so report it instead.
+    for filter in &config.filters {
+        if let Filter::SamePidNs(pid) = filter
+            && let Err(e) = pid_namespace(*pid, &reader)
+        {
+            log_error!("read PID namespace of {} failed: {}", pid, e);
+            finish(out, 1);
+        }
+    }
 
-    let mut found = false;
+    if cli.watch {
+        run_watch(&cli, &config, &reader, out);
+    }
+    if let Some(path) = &cli.pid_file {
+        // Exit 2 tells an unusable PID file apart from a stale one.
+        let pid = match read_pid_file(path) {
+            Ok(pid) => pid,
+            Err(e) => {
+                log_error!("read PID file {} failed: {}", path.display(), e);
+                finish(out, 2);
+            }
+        };
+        if !pid_matches(&config, &reader, pid) {
+            finish(out, 1);
+        }
+        if !flags.quiet {
+            let _ = write!(out, "{}{}", pid, end);
+        }
+        finish(out, 0);
+    }
+    if let Some(path) = &cli.write_pid_file {
+        config.single = true;
+        let pid = match scan_pids(&config, &reader) {
+            Ok(pids) => pids.first().copied(),
+            Err(e) => {
+                log_error!("open dir /proc failed: {}", e);
+                finish(out, 1);
+            }
+        };
+        let Some(pid) = pid else {
+            finish(out, 1);
+        };
+        let mode = cli.write_pid_file_mode.unwrap_or(0o644);
+        if let Err(e) = write_pid_file(path, pid, mode, cli.write_pid_file_dir_create) {
+            log_error!("write PID file {} failed: {}", path.display(), e);
+            finish(out, 1);
+        }
+        finish(out, 0);
+    }
+    if let Some(path) = &cli.compare_snapshot {
+        run_compare(&cli, path, &config, &reader, out);
+    }
 
-    if is_path {
-        for entry in proc_iter.flatten() {
-            let name = entry.file_name();
-            let name_s = match name.to_str() {
-                Some(s) if is_all_digits(s) => s,
-                _ => continue,
-            };
+    // A sort needs every match first; -s then keeps the top entry.
+    if !cli.sort.is_empty() {
+        config.single = false;
+    }
 
-            // Build /proc/<pid>/exe
-            let mut exe_path = PathBuf::from("/proc");
-            exe_path.push(name_s);
-            exe_path.push("exe");
-
-            if let Ok(link_target) = read_link(&exe_path) {
-                // Compare exact path string (like C: len equal and memcmp)
-                if osstr_eq_str(&link_target, &target) {
-                    found = true;
-                    if !flags.quiet {
-                        println!("{}", name_s);
-                    }
-                    if flags.single {
-                        std::process::exit(0);
-                    }
-                }
+    // Print as we go so -s exits on the first hit without finishing the scan.
+    let mut found = false;
+    let mut sorted = Vec::new();
+    if !flags.quiet {
+        write_header_row(&mut out, &cli);
+    }
+    let mut audit = match &cli.audit_log {
+        Some(path) => match AuditLogger::open(path) {
+            Ok(log) => Some(log),
+            Err(e) => {
+                log_error!("open {} failed: {}", path.display(), e);
+                finish(out, 1);
+            }
+        },
+        None => None,
+    };
+    let mut records = Vec::new();
+    let mut table_rows = Vec::new();
+    let mut tsv_rows = Vec::new();
+    let mut groups = Vec::new();
+    let mut action_failed = false;
+    // --confirm holds the signals back until the whole match list is known.
+    let mut pending = Vec::new();
+    let res = scan_each(&config, &reader, |proc, idx| {
+        found = true;
+        if let Some(log) = &mut audit {
+            let info = ProcessInfo::read(proc.pid(), idx, proc.reader());
+            // `--ppid-chain` alone matches without any target.
+            let query = cli.targets.get(idx).map_or("-", String::as_str);
+            if let Err(e) = log.log_match(&info, query) {
+                log_error!("write audit log failed: {}", e);
             }
         }
-    } else {
-        for entry in proc_iter.flatten() {
-            let name = entry.file_name();
-            let name_s = match name.to_str() {
-                Some(s) if is_all_digits(s) => s,
-                _ => continue,
+        if let Some(action) = &action {
+            let info = ProcessInfo::read(proc.pid(), idx, proc.reader());
+            if cli.confirm {
+                pending.push((proc.pid(), info.name));
+            } else if !send_one(action, proc.pid(), &info.name, &mut out) {
+                action_failed = true;
+            }
+        }
+        if oom_action.is_some() || renice_action.is_some() {
+            let info = ProcessInfo::read(proc.pid(), idx, proc.reader());
+            if let Some(oom) = &oom_action
+                && let Err(e) = oom.apply_in(reader.root(), proc.pid(), &info.name, &mut out)
+            {
+                log_error!("set oom_score_adj of pid {} failed: {}", proc.pid(), e);
+                action_failed = true;
+            }
+            if let Some(renice) = &renice_action
+                && let Err(e) = renice.apply(proc.pid(), &info.name, &mut out)
+            {
+                log_error!("renice pid {} failed: {}", proc.pid(), e);
+                action_failed = true;
+            }
+        }
+        // A dry run prints only what it would do.
+        let acting = action.is_some() || oom_action.is_some() || renice_action.is_some();
+        if acting && cli.dry_run {
+            return if flags.single {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
             };
-
-            // Build /proc/<pid>/cmdline
-            let mut cmd_path = PathBuf::from("/proc");
-            cmd_path.push(name_s);
-            cmd_path.push("cmdline");
-
-            // Read cmdline as bytes, since it is NUL-separated
-            let mut f = match File::open(&cmd_path) {
-                Ok(f) => f,
-                Err(_) => continue,
+        }
+        if cli.format == Format::Msgpack {
+            let key = sort_values(proc, &cli.sort);
+            records.push((key, ProcessInfo::read(proc.pid(), idx, proc.reader())));
+            return if flags.single && cli.sort.is_empty() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            };
+        }
+        if cli.group_by_target {
+            groups.push((idx, proc.pid()));
+            return if flags.single {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            };
+        }
+        // Column widths depend on every row, so the table is laid out last.
+        if cli.format == Format::Table {
+            table_rows.push((sort_values(proc, &cli.sort), row_values(proc, &cli)));
+            return if flags.single && cli.sort.is_empty() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
             };
-            let mut buf = Vec::with_capacity(4096);
-            if f.read_to_end(&mut buf).is_err() || buf.is_empty() {
-                continue;
+        }
+        if cli.format == Format::Tsv {
+            let values = row_values(proc, &cli);
+            if !cli.sort.is_empty() {
+                tsv_rows.push((sort_values(proc, &cli.sort), values));
+                return ControlFlow::Continue(());
             }
-
-            // First arg up to first NUL is argv[0]
-            let first = match buf.split(|b| *b == 0).next() {
-                Some(v) => v,
-                None => continue,
+            if !flags.quiet {
+                let refs: Vec<&str> = values.iter().map(String::as_str).collect();
+                let _ = TsvWriter::new(&mut out).with_end(end).write_row(&refs);
+            }
+            return if flags.single {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
             };
+        }
+        if !cli.sort.is_empty() {
+            sorted.push((
+                sort_values(proc, &cli.sort),
+                format_line(proc, idx, &cli, color),
+            ));
+            return ControlFlow::Continue(());
+        }
+        if !flags.quiet && cli.stat {
+            print_stat_block(&mut out, proc.pid(), proc.stat());
+        } else if !flags.quiet {
+            let _ = write!(out, "{}{}", format_line(proc, idx, &cli, color), end);
+        }
+        if flags.single {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    let stats = match res {
+        Ok(stats) => stats,
+        Err(e) => {
+            log_error!("open dir /proc failed: {}", e);
+            finish(out, 1);
+        }
+    };
+    // Stats go to stderr regardless of -q so they never mix with results.
+    if cli.stats {
+        eprintln!("{}", stats.summary());
+    }
+    if cli.stats_json {
+        eprintln!("{}", stats.to_json());
+    }
 
-            // Get basename of argv[0]
-            let base = match first.rsplit(|b| *b == b'/').next() {
-                Some(v) => v,
-                None => first,
-            };
+    let interrupted = INTERRUPTED.load(Ordering::Relaxed);
+    if let Some(action) = &action
+        && !pending.is_empty()
+        && !interrupted
+    {
+        // Nothing is sent in a dry run, so there is nothing to confirm.
+        let _ = out.flush();
+        if !action.dry_run {
+            // The read would be restarted after our handler ran, so Ctrl-C
+            // at the prompt must kill fpid outright.
+            let _ = restore_default_sigint();
+            let yes = prompt_confirm(&confirm_message(action.signal, &pending));
+            // A Ctrl-C that landed before the default action came back.
+            if INTERRUPTED.load(Ordering::Relaxed) {
+                log_error!("aborted");
+                finish(out, 128 + SIGINT);
+            }
+            if !yes {
+                log_error!("aborted");
+                finish(out, 1);
+            }
+        }
+        for (pid, name) in &pending {
+            if !send_one(action, *pid, name, &mut out) {
+                action_failed = true;
+            }
+        }
+    }
+    let discard_partial = interrupted && cli.no_partial;
+    let show_collected = !flags.quiet && !discard_partial;
+    if show_collected {
+        apply_sort(&mut sorted, &cli.sort);
+        let keep = if flags.single { 1 } else { sorted.len() };
+        for (_, line) in sorted.iter().take(keep) {
+            let _ = write!(out, "{}{}", line, end);
+        }
+    }
+    if cli.format == Format::Tsv && show_collected {
+        apply_sort(&mut tsv_rows, &cli.sort);
+        let keep = if flags.single { 1 } else { tsv_rows.len() };
+        let mut tsv = TsvWriter::new(&mut out).with_end(end);
+        for (_, values) in tsv_rows.iter().take(keep) {
+            let refs: Vec<&str> = values.iter().map(String::as_str).collect();
+            let _ = tsv.write_row(&refs);
+        }
+    }
+    if cli.group_by_target && show_collected {
+        let _ = write!(out, "{}", group_lines(&cli.targets, &mut groups));
+    }
+    if cli.format == Format::Table && show_collected {
+        apply_sort(&mut table_rows, &cli.sort);
+        table_rows.truncate(if flags.single { 1 } else { table_rows.len() });
+        let mut table = table_formatter(&cli);
+        for (_, row) in table_rows {
+            table.push_row(row);
+        }
+        table.align_numeric();
+        let _ = write!(out, "{}", table.render(table_width(to_tty)));
+    }
+    #[cfg(feature = "msgpack")]
+    if cli.format == Format::Msgpack && show_collected {
+        apply_sort(&mut records, &cli.sort);
+        records.truncate(if flags.single { 1 } else { records.len() });
+        let processes: Vec<ProcessInfo> = records.into_iter().map(|(_, p)| p).collect();
+        if let Err(e) = fpid_rs::msgpack::write_msgpack(&processes, &mut out) {
+            log_error!("write msgpack failed: {}", e);
+            finish(out, 1);
+        }
+    }
 
-            if base.len() == target.len() && bytes_eq_ascii(base, target.as_bytes()) {
-                found = true;
-                if !flags.quiet {
-                    println!("{}", name_s);
+    if interrupted {
+        finish(out, 128 + SIGINT);
+    }
+    finish(out, if found && !action_failed { 0 } else { 1 });
+}
+
+// Applies --signal to one match; false (after logging) when it failed.
+fn send_one(action: &SignalAction, pid: u32, name: &str, out: &mut dyn Write) -> bool {
+    match action.apply(pid, name, out) {
+        Ok(()) => true,
+        Err(e) => {
+            log_error!("send {} to pid {} failed: {}", action.signal.name, pid, e);
+            false
+        }
+    }
+}
+
+// The reader for --proc-root / FPID_PROC_ROOT, capped at
+// --max-proc-read-bytes (0 lifts the cap).
+fn proc_reader(cli: &Cli) -> FsProcReader {
+    let read_limit = match cli.max_proc_read_bytes {
+        None => Some(DEFAULT_MAX_READ_BYTES),
+        Some(0) => None,
+        Some(n) => Some(n),
+    };
+    FsProcReader::new(resolve_proc_root(cli.proc_root.as_deref())).with_read_limit(read_limit)
+}
+
+// Binds in the foreground so a bad socket path is reported, then forks; the
+// parent exits and the child serves until it is killed.
+fn run_daemon(cli: &Cli) -> ! {
+    let socket = cli.socket.clone().unwrap_or_else(|| DEFAULT_SOCKET.into());
+    if cli.dry_run {
+        println!("[DRY-RUN] would start daemon on {}", socket.display());
+        std::process::exit(0);
+    }
+    let server = match DaemonServer::bind(&socket) {
+        Ok(s) => s,
+        Err(e) => {
+            log_error!("bind {} failed: {}", socket.display(), e);
+            std::process::exit(1);
+        }
+    };
+    let pidfile = cli
+        .pidfile
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PIDFILE.into());
+    match fork_detached() {
+        // The parent records the child's PID, so a failure still reaches
+        // the caller's terminal and exit status.
+        Ok(Some(child)) => {
+            if let Err(e) = std::fs::write(&pidfile, format!("{}\n", child)) {
+                log_error!("write PID file {} failed: {}", pidfile.display(), e);
+                let _ = send_signal(child, SIGTERM);
+                std::process::exit(1);
+            }
+            std::process::exit(0);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            log_error!("fork failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let res = server.serve(&proc_reader(cli));
+    std::process::exit(if res.is_ok() { 0 } else { 1 });
+}
+
+// Polls until Ctrl-C; a scan cut short by the interrupt is not reported,
+// since its missing PIDs would look like exits.
+fn run_watch(cli: &Cli, config: &ScanConfig, reader: &FsProcReader, mut out: Box<dyn Write>) -> ! {
+    let json = matches!(cli.format, Format::Json | Format::Ndjson);
+    let mut watcher = Watcher::new();
+    let mut listener = None;
+    if cli.watch_events {
+        match ProcEventListener::subscribe() {
+            Ok(l) => listener = Some(l),
+            Err(e) => log_warn!(
+                "proc connector subscription failed: {}; polling every {}ms instead",
+                e,
+                WATCH_INTERVAL.as_millis()
+            ),
+        }
+    }
+    // Subscribe before the initial scan so nothing starts unseen in between.
+    let mut rescan = true;
+    loop {
+        let events = match listener.as_mut() {
+            Some(l) if !rescan => match l.next_events(WATCH_INTERVAL) {
+                Ok(procs) => {
+                    let now = iso8601_utc(SystemTime::now());
+                    watcher.apply_events(&procs, config, reader, &now)
+                }
+                Err(e) if is_overrun(&e) => {
+                    log_warn!("proc connector dropped events; rescanning");
+                    rescan = true;
+                    continue;
                 }
-                if flags.single {
-                    std::process::exit(0);
+                Err(e) => {
+                    log_error!("read proc events failed: {}", e);
+                    finish(out, 1);
                 }
+            },
+            _ => {
+                let now = iso8601_utc(SystemTime::now());
+                match watcher.poll(config, reader, &now) {
+                    Ok(events) => events,
+                    Err(e) => {
+                        log_error!("open dir /proc failed: {}", e);
+                        finish(out, 1);
+                    }
+                }
+            }
+        };
+        rescan = false;
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            finish(out, 128 + SIGINT);
+        }
+        for event in events.iter().filter(|_| !cli.flags.quiet) {
+            let line = if json {
+                event.to_json()
+            } else {
+                event.to_text()
+            };
+            if !line.is_empty() {
+                let _ = writeln!(out, "{}", line);
             }
         }
+        let _ = out.flush();
+        if listener.is_none() {
+            std::thread::sleep(WATCH_INTERVAL);
+        }
     }
+}
 
-    std::process::exit(if found { 0 } else { 1 });
+// Exit status 0 when the matches equal the snapshot, 1 when they differ and
+// 2 when the snapshot or /proc could not be read, so cron jobs can tell a
+// change from a failure.
+fn run_compare(
+    cli: &Cli,
+    path: &Path,
+    config: &ScanConfig,
+    reader: &FsProcReader,
+    mut out: Box<dyn Write>,
+) -> ! {
+    let snapshot = match load_snapshot(path) {
+        Ok(s) => s,
+        Err(e) => {
+            log_error!("read snapshot {} failed: {}", path.display(), e);
+            finish(out, 2);
+        }
+    };
+    let now = iso8601_utc(SystemTime::now());
+    let events = match compare_snapshot(&snapshot, config, reader, &now) {
+        Ok(events) => events,
+        Err(e) => {
+            log_error!("open dir /proc failed: {}", e);
+            finish(out, 2);
+        }
+    };
+    if INTERRUPTED.load(Ordering::Relaxed) {
+        finish(out, 128 + SIGINT);
+    }
+    let json = matches!(cli.format, Format::Json | Format::Ndjson);
+    for event in events.iter().filter(|_| !cli.flags.quiet) {
+        let line = if json {
+            event.to_json()
+        } else {
+            event.to_text()
+        };
+        let _ = writeln!(out, "{}", line);
+    }
+    finish(out, if events.is_empty() { 0 } else { 1 });
 }
 
-fn osstr_eq_str(path: &std::path::Path, s: &str) -> bool {
-    // Compare raw bytes of OsStr to the target str bytes exactly
-    // This mirrors the C code's exact length + memcmp behavior.
-    #[cfg(unix)]
-    {
-        use std::os::unix::ffi::OsStrExt;
-        let os_bytes = path.as_os_str().as_bytes();
-        os_bytes == s.as_bytes()
+fn format_line<R: ProcReader>(proc: &Process<'_, R>, idx: usize, cli: &Cli, color: bool) -> String {
+    if cli.format == Format::Procps {
+        let info = ProcessInfo::read(proc.pid(), idx, proc.reader());
+        let stat = proc.stat().cloned().unwrap_or_else(|| ProcStat {
+            pid: proc.pid(),
+            ..ProcStat::default()
+        });
+        return format_procps_line(&info, &stat);
     }
-    #[cfg(not(unix))]
-    {
-        // On non-unix, fallback to string compare which may not be exact on Windows.
-        // But this tool targets Linux /proc.
-        path.to_string_lossy() == s
+    if cli.format == Format::Ndjson {
+        return process_json(&ProcessInfo::read(proc.pid(), idx, proc.reader()));
     }
+    let render = if cli.human_readable {
+        format_column_human
+    } else {
+        format_column
+    };
+    if cli.columns_only {
+        let values: Vec<String> = cli.columns.iter().map(|c| render(*c, proc)).collect();
+        return values.join(" ");
+    }
+    let mut line = if color {
+        paint_pid(&proc.pid().to_string())
+    } else {
+        proc.pid().to_string()
+    };
+    for col in &cli.columns {
+        line.push(' ');
+        line.push_str(&render(*col, proc));
+    }
+    line
 }
 
-fn bytes_eq_ascii(a: &[u8], b: &[u8]) -> bool {
-    a == b
+// The bare values of one `--format=tsv` / `table` row.
+fn row_values<R: ProcReader>(proc: &Process<'_, R>, cli: &Cli) -> Vec<String> {
+    let mut values = if cli.columns_only {
+        vec![]
+    } else {
+        vec![proc.pid().to_string()]
+    };
+    values.extend(
+        cli.columns
+            .iter()
+            .map(|c| column_value(*c, proc, cli.human_readable)),
+    );
+    values
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// Names of the values in `row_values`.
+fn column_names(cli: &Cli) -> Vec<&'static str> {
+    let mut names = if cli.columns_only {
+        vec![]
+    } else {
+        vec!["pid"]
+    };
+    names.extend(cli.columns.iter().map(|c| c.name()));
+    names
+}
 
-    #[test]
-    fn test_help_returns_code0() {
-        let argv = vec!["fpid".to_string(), "-h".to_string()];
-        let res = parse_args_from_vec(argv);
-        assert!(matches!(res, Err(0)));
-    }
+// An empty `--format=table` for this invocation's columns.
+fn table_formatter(cli: &Cli) -> TableFormatter {
+    let headers: Vec<String> = column_names(cli).iter().map(|n| n.to_uppercase()).collect();
+    let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+    let mut table = TableFormatter::new(&headers);
+    table.header = cli.header_mode != HeaderMode::Suppress;
+    table
+}
 
-    #[test]
-    fn test_missing_target() {
-        let argv = vec!["fpid".to_string()];
-        let res = parse_args_from_vec(argv);
-        assert!(matches!(res, Err(1)));
+// The `--format=tsv` / `procps` header for this invocation's columns, if
+// the format has one.
+fn write_header_row(out: &mut dyn Write, cli: &Cli) {
+    if cli.format == Format::Tsv && cli.header_mode != HeaderMode::Suppress {
+        let _ = TsvWriter::new(out).write_header(&column_names(cli));
+    } else if let Some(header) = table_header(cli.format, cli.header_mode, &column_names(cli)) {
+        let _ = write!(out, "{}", header);
     }
+}
 
-    #[test]
-    fn test_parse_flags_and_target() {
-        let argv = vec!["fpid".to_string(), "-qs".to_string(), "sshd".to_string()];
-        let (flags, target) = parse_args_from_vec(argv).unwrap();
-        assert_eq!(
-            flags,
-            Flags {
-                quiet: true,
-                single: true
-            }
-        );
-        assert_eq!(target, "sshd");
+// `--group-by-target`: "target: pid pid ..." per target that matched, in
+// target order with PIDs ascending. A target-less --ppid-chain prints "-".
+fn group_lines(targets: &[String], groups: &mut [(usize, u32)]) -> String {
+    groups.sort_unstable();
+    let mut lines = String::new();
+    for chunk in groups.chunk_by(|a, b| a.0 == b.0) {
+        let label = targets.get(chunk[0].0).map_or("-", String::as_str);
+        let pids: Vec<String> = chunk.iter().map(|(_, pid)| pid.to_string()).collect();
+        lines.push_str(&format!("{}: {}\n", label, pids.join(" ")));
     }
+    lines
+}
 
-    #[test]
-    fn test_unknown_option() {
-        let argv = vec!["fpid".to_string(), "-x".to_string()];
-        let res = parse_args_from_vec(argv);
-        assert!(matches!(res, Err(1)));
+// process::exit skips destructors, so a buffered --output file is flushed here.
+fn finish(mut out: Box<dyn Write>, code: i32) -> ! {
+    if let Err(e) = out.flush() {
+        log_error!("write output failed: {}", e);
+        std::process::exit(1);
     }
+    std::process::exit(code);
+}
 
-    #[test]
-    fn test_extra_positional_error() {
-        let argv = vec![
-            "fpid".to_string(),
-            "-q".to_string(),
-            "sshd".to_string(),
-            "extra".to_string(),
-        ];
-        let res = parse_args_from_vec(argv);
-        assert!(matches!(res, Err(1)));
+// One "name: value" line per stat field, blank line between processes.
+fn print_stat_block(out: &mut dyn Write, pid: u32, stat: Option<&ProcStat>) {
+    match stat {
+        Some(stat) => {
+            for (name, value) in stat.fields() {
+                let _ = writeln!(out, "{}: {}", name, value);
+            }
+        }
+        None => {
+            let _ = writeln!(out, "pid: {}", pid);
+        }
     }
+    let _ = writeln!(out);
 }
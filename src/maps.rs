@@ -0,0 +1,491 @@
+use std::io::{self, BufRead, BufReader};
+
+use crate::reader::ProcReader;
+
+/// Returns true if any mapping in a maps-formatted stream has a pathname whose
+/// basename starts with `lib` (e.g. `libssl` matches `libssl.so.3`).
+///
+/// Lines are read one at a time so large maps files (JVMs, browsers) never sit
+/// in memory as a whole, and reading stops at the first hit.
+pub fn maps_has_library<R: BufRead>(mut reader: R, lib: &str) -> io::Result<bool> {
+    let mut line = Vec::with_capacity(256);
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(false);
+        }
+        if let Some(path) = maps_pathname(&line) {
+            let base = path.rsplit(|b| *b == b'/').next().unwrap_or(path);
+            if base.starts_with(lib.as_bytes()) {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+/// Checks `/proc/<pid>/maps` for a mapped library, see [`maps_has_library`].
+pub fn read_maps_has_library(pid: u32, lib: &str, reader: &impl ProcReader) -> io::Result<bool> {
+    maps_has_library(BufReader::new(reader.open(pid, "maps")?), lib)
+}
+
+/// Library basename prefixes and the TLS implementation they belong to, in
+/// output order. NSS's `libssl3.so` comes before OpenSSL's `libssl`, and
+/// `libnss3` is spelled out so glibc's `libnss_files` modules don't count.
+const TLS_LIBRARIES: [(&str, &str); 7] = [
+    ("libssl3.so", "nss"),
+    ("libssl", "openssl"),
+    ("libopenssl", "openssl"),
+    ("libboringssl", "boringssl"),
+    ("libgnutls", "gnutls"),
+    ("libmbedtls", "mbedtls"),
+    ("libnss3", "nss"),
+];
+
+/// TLS libraries mapped in a maps file (`--print-tls-info`), each named
+/// once, in [`TLS_LIBRARIES`] order. Statically linked TLS code is not seen.
+pub fn detect_tls_library(maps_buf: &[u8]) -> Vec<&'static str> {
+    let mut found = [false; TLS_LIBRARIES.len()];
+    for line in maps_buf.split(|b| *b == b'\n') {
+        let Some(path) = maps_pathname(line) else {
+            continue;
+        };
+        let base = path.rsplit(|b| *b == b'/').next().unwrap_or(path);
+        if let Some(i) = TLS_LIBRARIES
+            .iter()
+            .position(|(prefix, _)| base.starts_with(prefix.as_bytes()))
+        {
+            found[i] = true;
+        }
+    }
+    let mut names = Vec::new();
+    for ((_, name), _) in TLS_LIBRARIES.iter().zip(found).filter(|(_, hit)| *hit) {
+        if !names.contains(name) {
+            names.push(*name);
+        }
+    }
+    names
+}
+
+/// [`detect_tls_library`] of `/proc/<pid>/maps`.
+pub fn read_tls_libraries(pid: u32, reader: &impl ProcReader) -> io::Result<Vec<&'static str>> {
+    Ok(detect_tls_library(&reader.read(pid, "maps")?))
+}
+
+/// Region counts of a maps file (`--print-maps-summary`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MapsSummary {
+    /// No pathname, or an Android-style `[anon:name]` label.
+    pub anon: usize,
+    pub file: usize,
+    /// Kernel-named regions: `[stack]`, `[heap]`, `[vdso]`, `[vvar]`, ...
+    pub special: usize,
+    /// Size of the largest single region in kB.
+    pub max_kb: u64,
+}
+
+/// Classifies every mapping of a maps-formatted buffer; lines without a
+/// valid `start-end` address range are skipped.
+pub fn summarize_maps(maps_buf: &[u8]) -> MapsSummary {
+    let mut sum = MapsSummary::default();
+    for line in maps_buf.split(|b| *b == b'\n') {
+        let Some(size) = region_size(line) else {
+            continue;
+        };
+        sum.max_kb = sum.max_kb.max(size / 1024);
+        match maps_pathname(line) {
+            None => sum.anon += 1,
+            Some(path) if path.starts_with(b"[anon:") => sum.anon += 1,
+            Some(path) if path.starts_with(b"[") => sum.special += 1,
+            Some(_) => sum.file += 1,
+        }
+    }
+    sum
+}
+
+/// Summarizes `/proc/<pid>/maps`, see [`summarize_maps`].
+pub fn read_maps_summary(pid: u32, reader: &impl ProcReader) -> io::Result<MapsSummary> {
+    Ok(summarize_maps(&reader.read(pid, "maps")?))
+}
+
+/// Number of mappings (VMAs) in a maps-formatted stream: its non-empty
+/// lines, read one at a time like [`maps_has_library`].
+pub fn count_maps<R: BufRead>(mut reader: R) -> io::Result<usize> {
+    let mut line = Vec::with_capacity(256);
+    let mut count = 0;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(count);
+        }
+        if !line.trim_ascii().is_empty() {
+            count += 1;
+        }
+    }
+}
+
+/// Counts the mappings of `/proc/<pid>/maps`, see [`count_maps`].
+pub fn read_maps_count(pid: u32, reader: &impl ProcReader) -> io::Result<usize> {
+    count_maps(BufReader::new(reader.open(pid, "maps")?))
+}
+
+/// `MapsEntry::perms` bits.
+pub const PERM_READ: u8 = 1;
+pub const PERM_WRITE: u8 = 2;
+pub const PERM_EXEC: u8 = 4;
+/// `s` (shared) instead of `p` (private).
+pub const PERM_SHARED: u8 = 8;
+
+/// One line of a maps file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapsEntry {
+    pub start: u64,
+    pub end: u64,
+    /// `PERM_*` bits.
+    pub perms: u8,
+    /// Inode 0, excluding the kernel-provided `[vdso]`, `[vvar]` and
+    /// `[vsyscall]` pages.
+    pub is_anonymous: bool,
+    pub pathname: Option<String>,
+}
+
+impl MapsEntry {
+    /// `rwx`-style permissions without the private/shared flag.
+    pub fn perms_str(&self) -> String {
+        [(PERM_READ, 'r'), (PERM_WRITE, 'w'), (PERM_EXEC, 'x')]
+            .iter()
+            .map(|(bit, c)| if self.perms & bit != 0 { *c } else { '-' })
+            .collect()
+    }
+}
+
+/// Parses `start-end perms offset dev inode [pathname]`.
+pub fn parse_maps_line(line: &[u8]) -> Option<MapsEntry> {
+    let text = std::str::from_utf8(line.split(|b| *b == b'\n').next()?).ok()?;
+    let mut fields = text.split_ascii_whitespace();
+    let (start, end) = fields.next()?.split_once('-')?;
+    let perm_field = fields.next()?.as_bytes();
+    let inode: u64 = fields.nth(2)?.parse().ok()?;
+    if perm_field.len() != 4 {
+        return None;
+    }
+    let perms = [
+        (b'r', PERM_READ),
+        (b'w', PERM_WRITE),
+        (b'x', PERM_EXEC),
+        (b's', PERM_SHARED),
+    ]
+    .iter()
+    .zip(perm_field)
+    .fold(
+        0,
+        |acc, ((c, bit), got)| if got == c { acc | bit } else { acc },
+    );
+    let pathname = maps_pathname(line).map(|p| String::from_utf8_lossy(p).into_owned());
+    let kernel_page = matches!(
+        pathname.as_deref(),
+        Some("[vdso]" | "[vvar]" | "[vsyscall]")
+    );
+    Some(MapsEntry {
+        start: u64::from_str_radix(start, 16).ok()?,
+        end: u64::from_str_radix(end, 16).ok()?,
+        perms,
+        is_anonymous: inode == 0 && !kernel_page,
+        pathname,
+    })
+}
+
+/// Anonymous executable mappings (JIT code, injected shellcode), read one
+/// line at a time.
+pub fn anon_exec_regions<R: BufRead>(mut reader: R) -> io::Result<Vec<MapsEntry>> {
+    let mut line = Vec::with_capacity(256);
+    let mut regions = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(regions);
+        }
+        if let Some(entry) = parse_maps_line(&line)
+            && entry.is_anonymous
+            && entry.perms & PERM_EXEC != 0
+        {
+            regions.push(entry);
+        }
+    }
+}
+
+/// [`anon_exec_regions`] of `/proc/<pid>/maps`.
+pub fn read_anon_exec_regions(pid: u32, reader: &impl ProcReader) -> io::Result<Vec<MapsEntry>> {
+    anon_exec_regions(BufReader::new(reader.open(pid, "maps")?))
+}
+
+/// Whether `pid` has any mapping both writable and executable.
+pub fn read_has_wx_mapping(pid: u32, reader: &impl ProcReader) -> io::Result<bool> {
+    let mut maps = BufReader::new(reader.open(pid, "maps")?);
+    let mut line = Vec::with_capacity(256);
+    loop {
+        line.clear();
+        if maps.read_until(b'\n', &mut line)? == 0 {
+            return Ok(false);
+        }
+        if parse_maps_line(&line)
+            .is_some_and(|e| e.perms & (PERM_WRITE | PERM_EXEC) == PERM_WRITE | PERM_EXEC)
+        {
+            return Ok(true);
+        }
+    }
+}
+
+/// Stack mappings of a maps file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StackUsage {
+    /// Size of `[stack]` in kB, the main thread's stack.
+    pub main_kb: Option<u64>,
+    /// Number of `[stack:TID]` thread stacks.
+    pub threads: usize,
+    /// Combined size of the `[stack:TID]` mappings in kB.
+    pub thread_kb: u64,
+}
+
+impl StackUsage {
+    pub fn total_kb(&self) -> u64 {
+        self.main_kb.unwrap_or(0) + self.thread_kb
+    }
+}
+
+/// Collects `[stack]` and `[stack:TID]` mappings, read one line at a time.
+/// Kernels since 4.5 no longer label thread stacks, so on those only the
+/// main stack is found.
+pub fn stack_usage<R: BufRead>(mut reader: R) -> io::Result<StackUsage> {
+    let mut line = Vec::with_capacity(256);
+    let mut usage = StackUsage::default();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(usage);
+        }
+        let Some(kb) = region_size(&line).map(|size| size / 1024) else {
+            continue;
+        };
+        match maps_pathname(&line) {
+            Some(b"[stack]") => usage.main_kb = Some(usage.main_kb.unwrap_or(0) + kb),
+            Some(name) if name.starts_with(b"[stack:") && name.ends_with(b"]") => {
+                usage.threads += 1;
+                usage.thread_kb += kb;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// [`stack_usage`] of `/proc/<pid>/maps`.
+pub fn read_stack_usage(pid: u32, reader: &impl ProcReader) -> io::Result<StackUsage> {
+    stack_usage(BufReader::new(reader.open(pid, "maps")?))
+}
+
+// Bytes covered by the leading "start-end" hex address range.
+fn region_size(line: &[u8]) -> Option<u64> {
+    let range = line.split(|b| *b == b' ').next()?;
+    let range = std::str::from_utf8(range).ok()?;
+    let (start, end) = range.split_once('-')?;
+    let start = u64::from_str_radix(start, 16).ok()?;
+    let end = u64::from_str_radix(end, 16).ok()?;
+    end.checked_sub(start)
+}
+
+// The pathname is everything after the 5th whitespace-separated column
+// (address, perms, offset, dev, inode). Anonymous mappings have none.
+fn maps_pathname(line: &[u8]) -> Option<&[u8]> {
+    let mut rest = line.strip_suffix(b"\n").unwrap_or(line);
+    for _ in 0..5 {
+        let start = rest.iter().position(|b| *b != b' ')?;
+        rest = &rest[start..];
+        let end = rest.iter().position(|b| *b == b' ').unwrap_or(rest.len());
+        rest = &rest[end..];
+    }
+    let start = rest.iter().position(|b| *b != b' ')?;
+    Some(&rest[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+55d0c0a00000-55d0c0a2c000 r--p 00000000 08:01 1835036                    /usr/bin/bash
+7f1c2a000000-7f1c2a021000 rw-p 00000000 00:00 0 
+7f1c2b200000-7f1c2b228000 r--p 00000000 08:01 1838490                    /usr/lib/x86_64-linux-gnu/libc.so.6
+7f1c2b500000-7f1c2b5a0000 r-xp 00000000 08:01 1840021                    /usr/lib/x86_64-linux-gnu/libssl.so.3
+7ffd6e9e5000-7ffd6ea06000 rw-p 00000000 00:00 0                          [stack]
+";
+
+    #[test]
+    fn test_detect_tls_library() {
+        assert_eq!(detect_tls_library(SAMPLE.as_bytes()), vec!["openssl"]);
+        let maps = "\
+7f00-7f01 r-xp 00000000 08:01 11 /usr/lib/libgnutls.so.30.34.3
+7f01-7f02 r-xp 00000000 08:01 12 /usr/lib/libnss3.so
+7f02-7f03 r-xp 00000000 08:01 13 /usr/lib/libssl3.so
+7f03-7f04 r-xp 00000000 08:01 14 /usr/lib/libnss_files.so.2
+7f04-7f05 r-xp 00000000 08:01 15 /opt/app/libmbedtls.so.14
+7f05-7f06 r--p 00000000 08:01 15 /opt/app/libmbedtls.so.14
+7f06-7f07 r-xp 00000000 08:01 16 /opt/libssl/lib/libcrypto.so.3
+";
+        assert_eq!(
+            detect_tls_library(maps.as_bytes()),
+            vec!["nss", "gnutls", "mbedtls"]
+        );
+        assert!(detect_tls_library(b"").is_empty());
+    }
+
+    #[test]
+    fn test_maps_has_library() {
+        assert!(maps_has_library(SAMPLE.as_bytes(), "libssl").unwrap());
+        assert!(maps_has_library(SAMPLE.as_bytes(), "libc.so").unwrap());
+        assert!(!maps_has_library(SAMPLE.as_bytes(), "libgnutls").unwrap());
+        // Directory components must not match.
+        assert!(!maps_has_library(SAMPLE.as_bytes(), "x86_64").unwrap());
+    }
+
+    #[test]
+    fn test_count_maps() {
+        assert_eq!(count_maps(SAMPLE.as_bytes()).unwrap(), 5);
+        assert_eq!(count_maps(&b""[..]).unwrap(), 0);
+        // Blank lines don't count; a missing final newline still does.
+        let odd = "7f00-7f01 rw-p 00000000 00:00 0\n\n7f01-7f02 r--p 00000000 00:00 0";
+        assert_eq!(count_maps(odd.as_bytes()).unwrap(), 2);
+        let many = SAMPLE.repeat(1000);
+        assert_eq!(count_maps(many.as_bytes()).unwrap(), 5000);
+    }
+
+    #[test]
+    fn test_parse_maps_line() {
+        let entry = parse_maps_line(
+            b"7f1c2b500000-7f1c2b5a0000 r-xp 00000000 08:01 1840021    /usr/lib/libssl.so.3\n",
+        )
+        .unwrap();
+        assert_eq!(
+            entry,
+            MapsEntry {
+                start: 0x7f1c2b500000,
+                end: 0x7f1c2b5a0000,
+                perms: PERM_READ | PERM_EXEC,
+                is_anonymous: false,
+                pathname: Some("/usr/lib/libssl.so.3".to_string()),
+            }
+        );
+        assert_eq!(entry.perms_str(), "r-x");
+        let anon = parse_maps_line(b"7f00-7f01 rwxs 00000000 00:00 0 ").unwrap();
+        assert_eq!(anon.perms, PERM_READ | PERM_WRITE | PERM_EXEC | PERM_SHARED);
+        assert!(anon.is_anonymous);
+        assert_eq!(anon.pathname, None);
+        let vdso = parse_maps_line(b"7ffd-7ffe r-xp 00000000 00:00 0   [vdso]").unwrap();
+        assert!(!vdso.is_anonymous);
+        for bad in [
+            &b""[..],
+            b"garbage line",
+            b"7f00-7f01 rw 0 00:00 0",
+            b"zz-7f01 rw-p 0 00:00 0",
+        ] {
+            assert_eq!(
+                parse_maps_line(bad),
+                None,
+                "{:?}",
+                String::from_utf8_lossy(bad)
+            );
+        }
+    }
+
+    #[test]
+    fn test_anon_exec_regions() {
+        let maps = format!(
+            "{SAMPLE}\
+7f1c30000000-7f1c30100000 rwxp 00000000 00:00 0 \n\
+7f1c31000000-7f1c31100000 r-xp 00000000 00:00 0                          [anon:v8 code]\n\
+7f1c32000000-7f1c32100000 rw-p 00000000 00:00 0 \n\
+7ffd6ebe5000-7ffd6ebe7000 r-xp 00000000 00:00 0                          [vdso]\n"
+        );
+        let regions = anon_exec_regions(maps.as_bytes()).unwrap();
+        let found: Vec<(String, u64)> = regions.iter().map(|e| (e.perms_str(), e.start)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("rwx".to_string(), 0x7f1c30000000),
+                ("r-x".to_string(), 0x7f1c31000000)
+            ]
+        );
+        assert!(anon_exec_regions(SAMPLE.as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_has_wx_mapping() {
+        use crate::reader::mock::MockProcReader;
+        let reader = MockProcReader::new().file(1, "maps", SAMPLE).file(
+            2,
+            "maps",
+            format!("{SAMPLE}7f00-7f01 rwxp 00000000 08:01 7 /tmp/jit\n"),
+        );
+        assert!(!read_has_wx_mapping(1, &reader).unwrap());
+        assert!(read_has_wx_mapping(2, &reader).unwrap());
+        assert!(read_has_wx_mapping(3, &reader).is_err());
+    }
+
+    #[test]
+    fn test_stack_usage() {
+        let maps = format!(
+            "{SAMPLE}\
+7f1c2a000000-7f1c2a800000 rw-p 00000000 00:00 0                          [stack:4121]\n\
+7f1c2b000000-7f1c2b200000 rw-p 00000000 00:00 0                          [stack:4122]\n"
+        );
+        let usage = stack_usage(maps.as_bytes()).unwrap();
+        assert_eq!(
+            usage,
+            StackUsage {
+                main_kb: Some(132),
+                threads: 2,
+                thread_kb: 8192 + 2048,
+            }
+        );
+        assert_eq!(usage.total_kb(), 132 + 8192 + 2048);
+        let none =
+            stack_usage(&b"00400000-00452000 r-xp 00000000 08:02 173521 /usr/bin/x\n"[..]).unwrap();
+        assert_eq!(none, StackUsage::default());
+        assert_eq!(none.total_kb(), 0);
+    }
+
+    #[test]
+    fn test_maps_pathname() {
+        assert_eq!(maps_pathname(b"7f00-7f01 rw-p 00000000 00:00 0 \n"), None);
+        assert_eq!(
+            maps_pathname(b"7f00-7f01 rw-p 00000000 00:00 0      [heap]\n"),
+            Some(&b"[heap]"[..])
+        );
+        assert_eq!(
+            maps_pathname(b"7f00-7f01 r-xp 00000000 08:01 12 /opt/my app/lib.so"),
+            Some(&b"/opt/my app/lib.so"[..])
+        );
+    }
+
+    #[test]
+    fn test_summarize_maps() {
+        let maps = format!(
+            "{SAMPLE}\
+55d0c1e5f000-55d0c1f80000 rw-p 00000000 00:00 0                          [heap]
+7f1c2c000000-7f1c30000000 rw-p 00000000 00:00 0                          [anon:dalvik-main space]
+7ffd6ebe1000-7ffd6ebe5000 r--p 00000000 00:00 0                          [vvar]
+7ffd6ebe5000-7ffd6ebe7000 r-xp 00000000 00:00 0                          [vdso]
+garbage line
+"
+        );
+        assert_eq!(
+            summarize_maps(maps.as_bytes()),
+            MapsSummary {
+                anon: 2,
+                file: 3,
+                special: 4,
+                // The 64 MiB dalvik region.
+                max_kb: 65536,
+            }
+        );
+        assert_eq!(summarize_maps(b""), MapsSummary::default());
+    }
+}
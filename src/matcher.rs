@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+#[cfg(feature = "aho-corasick")]
+use crate::aho_corasick::AhoCorasick;
+
+// Up to this many targets a linear scan beats hashing every candidate.
+const LINEAR_MAX: usize = 8;
+
+/// Matches processes against several targets in a single `/proc` pass.
+///
+/// Targets containing `/` are compared against the `exe` link, all others
+/// against the basename of argv[0], both exactly (like the C version). Since
+/// matches are whole-string, a hash lookup per process gives the same one-pass
+/// cost a substring automaton would, independent of the number of targets.
+/// With the `aho-corasick` feature every target is also compiled into one
+/// automaton for substring matches anywhere in the cmdline
+/// ([`MultiMatcher::match_cmdline`]).
+#[derive(Debug, Clone, Default)]
+pub struct MultiMatcher {
+    names: Lookup,
+    paths: Lookup,
+    len: usize,
+    #[cfg(feature = "aho-corasick")]
+    cmdline: AhoCorasick,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Lookup {
+    list: Vec<(Vec<u8>, usize)>,
+    map: HashMap<Vec<u8>, usize>,
+}
+
+impl Lookup {
+    fn insert(&mut self, key: &[u8], idx: usize) {
+        if self.map.contains_key(key) || self.list.iter().any(|(k, _)| k == key) {
+            return;
+        }
+        if self.map.is_empty() && self.list.len() < LINEAR_MAX {
+            self.list.push((key.to_vec(), idx));
+        } else {
+            self.map.extend(self.list.drain(..));
+            self.map.insert(key.to_vec(), idx);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.list.is_empty() && self.map.is_empty()
+    }
+
+    // Every (distinct) key equals `key`; true when there are none.
+    fn all_eq(&self, key: Option<&[u8]>) -> bool {
+        self.is_empty()
+            || key.is_some_and(|k| self.list.len() + self.map.len() == 1 && self.get(k).is_some())
+    }
+
+    fn get(&self, key: &[u8]) -> Option<usize> {
+        if !self.map.is_empty() {
+            return self.map.get(key).copied();
+        }
+        self.list
+            .iter()
+            .find(|(k, _)| k.len() == key.len() && bytes_eq_ascii(k, key))
+            .map(|(_, idx)| *idx)
+    }
+}
+
+impl MultiMatcher {
+    /// Builds a matcher; the index reported for a match is the target's
+    /// position in `targets` (first occurrence wins for duplicates).
+    pub fn new<S: AsRef<str>>(targets: &[S]) -> Self {
+        let mut m = Self {
+            len: targets.len(),
+            ..Self::default()
+        };
+        for (idx, t) in targets.iter().enumerate() {
+            let t = t.as_ref();
+            if t.contains('/') {
+                m.paths.insert(t.as_bytes(), idx);
+            } else {
+                m.names.insert(t.as_bytes(), idx);
+            }
+        }
+        #[cfg(feature = "aho-corasick")]
+        {
+            let patterns: Vec<&[u8]> = targets.iter().map(|t| t.as_ref().as_bytes()).collect();
+            m.cmdline = AhoCorasick::new(&patterns);
+        }
+        m
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn has_names(&self) -> bool {
+        !self.names.is_empty()
+    }
+
+    pub fn has_paths(&self) -> bool {
+        !self.paths.is_empty()
+    }
+
+    /// Matches the basename of argv[0].
+    pub fn match_argv0(&self, argv0: &[u8]) -> Option<usize> {
+        self.names.get(basename(argv0))
+    }
+
+    /// Matches a resolved `exe` link exactly.
+    pub fn match_exe(&self, exe: &Path) -> Option<usize> {
+        self.paths.get(path_bytes(exe))
+    }
+
+    /// First target (in target order) occurring anywhere in the raw,
+    /// NUL-separated `cmdline` (`--match-cmdline`), in one pass over it.
+    #[cfg(feature = "aho-corasick")]
+    pub fn match_cmdline(&self, cmdline: &[u8]) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        self.cmdline.first_pattern(cmdline)
+    }
+
+    /// Whether argv[0] and `exe` match every target at once (`--match-all`):
+    /// the basename equals each name and the link each path, so two
+    /// different names can never both match. `None` fails any target of
+    /// that kind.
+    pub fn match_all(&self, argv0: Option<&[u8]>, exe: Option<&Path>) -> bool {
+        self.names.all_eq(argv0.map(basename)) && self.paths.all_eq(exe.map(path_bytes))
+    }
+}
+
+/// Everything after the last `/`.
+pub fn basename(path: &[u8]) -> &[u8] {
+    match path.rsplit(|b| *b == b'/').next() {
+        Some(v) => v,
+        None => path,
+    }
+}
+
+pub fn bytes_eq_ascii(a: &[u8], b: &[u8]) -> bool {
+    a == b
+}
+
+fn path_bytes(path: &Path) -> &[u8] {
+    // Compare raw bytes, mirroring the C code's exact length + memcmp.
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_names_and_paths() {
+        let m = MultiMatcher::new(&["sshd", "/usr/sbin/nginx", "bash"]);
+        assert_eq!(m.len(), 3);
+        assert!(m.has_names() && m.has_paths());
+        assert_eq!(m.match_argv0(b"/usr/sbin/sshd"), Some(0));
+        assert_eq!(m.match_argv0(b"bash"), Some(2));
+        assert_eq!(m.match_argv0(b"nginx"), None);
+        assert_eq!(m.match_argv0(b"sshd: root@pts/0"), None);
+        assert_eq!(m.match_exe(Path::new("/usr/sbin/nginx")), Some(1));
+        assert_eq!(m.match_exe(Path::new("/usr/sbin/nginx2")), None);
+    }
+
+    #[test]
+    fn test_match_all() {
+        let m = MultiMatcher::new(&["python3", "/usr/bin/python3.11"]);
+        let exe = Path::new("/usr/bin/python3.11");
+        assert!(m.match_all(Some(b"/usr/bin/python3"), Some(exe)));
+        assert!(!m.match_all(Some(b"python3"), Some(Path::new("/opt/py/bin/python3"))));
+        assert!(!m.match_all(Some(b"python3.11"), Some(exe)));
+        assert!(!m.match_all(Some(b"python3"), None));
+        // Duplicates count once; different names never match together.
+        assert!(MultiMatcher::new(&["bash", "bash"]).match_all(Some(b"bash"), None));
+        assert!(!MultiMatcher::new(&["bash", "sh"]).match_all(Some(b"bash"), None));
+        assert!(
+            MultiMatcher::new(&["/usr/bin/bash"]).match_all(None, Some(Path::new("/usr/bin/bash")))
+        );
+    }
+
+    #[test]
+    fn test_match_many_targets() {
+        let names: Vec<String> = (0..100).map(|i| format!("worker{i}")).collect();
+        let m = MultiMatcher::new(&names);
+        assert!(!m.has_paths());
+        for (i, n) in names.iter().enumerate() {
+            assert_eq!(m.match_argv0(format!("/opt/bin/{n}").as_bytes()), Some(i));
+        }
+        assert_eq!(m.match_argv0(b"worker100"), None);
+    }
+
+    #[test]
+    fn test_duplicate_target_keeps_first() {
+        let m = MultiMatcher::new(&["a", "b", "a"]);
+        assert_eq!(m.match_argv0(b"a"), Some(0));
+        let names: Vec<&str> = ["x"; 20].to_vec();
+        assert_eq!(MultiMatcher::new(&names).match_argv0(b"x"), Some(0));
+    }
+
+    #[cfg(feature = "aho-corasick")]
+    #[test]
+    fn test_match_cmdline() {
+        let m = MultiMatcher::new(&["celery", "gunicorn", "/srv/app"]);
+        let worker = b"/usr/bin/python3\0/srv/app/venv/bin/celery\0worker\0";
+        assert_eq!(m.match_cmdline(worker), Some(0));
+        assert_eq!(m.match_cmdline(b"gunicorn: master [app]\0"), Some(1));
+        assert_eq!(m.match_cmdline(b"/usr/sbin/nginx\0"), None);
+        assert_eq!(MultiMatcher::new::<&str>(&[]).match_cmdline(worker), None);
+    }
+
+    #[test]
+    fn test_basename() {
+        assert_eq!(basename(b"/usr/bin/env"), b"env");
+        assert_eq!(basename(b"env"), b"env");
+        assert_eq!(basename(b"/usr/bin/"), b"");
+    }
+}
@@ -0,0 +1,119 @@
+use std::io;
+
+use crate::reader::{FsProcReader, ProcReader};
+
+/// System-wide memory totals from `/proc/meminfo`, in kB.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemMemInfo {
+    pub mem_total_kb: u64,
+    pub mem_available_kb: u64,
+    pub swap_total_kb: u64,
+    pub swap_free_kb: u64,
+}
+
+impl SystemMemInfo {
+    /// Parses the `Key:   N kB` lines; `MemTotal` is required, the rest
+    /// default to 0 (`MemAvailable` only exists since Linux 3.14).
+    pub fn parse(buf: &[u8]) -> Result<SystemMemInfo, io::Error> {
+        let mut info = SystemMemInfo::default();
+        let mut have_total = false;
+        for line in String::from_utf8_lossy(buf).lines() {
+            let Some((key, rest)) = line.split_once(':') else {
+                continue;
+            };
+            let slot = match key {
+                "MemTotal" => {
+                    have_total = true;
+                    &mut info.mem_total_kb
+                }
+                "MemAvailable" => &mut info.mem_available_kb,
+                "SwapTotal" => &mut info.swap_total_kb,
+                "SwapFree" => &mut info.swap_free_kb,
+                _ => continue,
+            };
+            let value = rest.split_ascii_whitespace().next().unwrap_or("");
+            *slot = value.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("bad meminfo value '{value}'"),
+                )
+            })?;
+        }
+        if !have_total {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "meminfo without MemTotal",
+            ));
+        }
+        Ok(info)
+    }
+
+    pub fn read() -> Result<SystemMemInfo, io::Error> {
+        Self::read_with(&FsProcReader::default())
+    }
+
+    pub fn read_with(reader: &impl ProcReader) -> Result<SystemMemInfo, io::Error> {
+        Self::parse(&reader.read_root("meminfo")?)
+    }
+}
+
+/// `rss_kb` as a percentage of `mem_total_kb`; `None` if the total is 0.
+pub fn rss_percent(rss_kb: u64, mem: &SystemMemInfo) -> Option<f64> {
+    if mem.mem_total_kb == 0 {
+        return None;
+    }
+    Some(rss_kb as f64 / mem.mem_total_kb as f64 * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const MEMINFO: &str = "\
+MemTotal:        8000000 kB
+MemFree:          123456 kB
+MemAvailable:    4000000 kB
+Buffers:           10000 kB
+SwapTotal:       2097148 kB
+SwapFree:        2000000 kB
+";
+
+    #[test]
+    fn test_parse_meminfo() {
+        assert_eq!(
+            SystemMemInfo::parse(MEMINFO.as_bytes()).unwrap(),
+            SystemMemInfo {
+                mem_total_kb: 8000000,
+                mem_available_kb: 4000000,
+                swap_total_kb: 2097148,
+                swap_free_kb: 2000000,
+            }
+        );
+        assert_eq!(
+            SystemMemInfo::parse(b"MemTotal: 100 kB\n")
+                .unwrap()
+                .mem_available_kb,
+            0
+        );
+        assert!(SystemMemInfo::parse(b"MemFree: 100 kB\n").is_err());
+        assert!(SystemMemInfo::parse(b"MemTotal: lots kB\n").is_err());
+    }
+
+    #[test]
+    fn test_rss_percent() {
+        let mem = SystemMemInfo::parse(MEMINFO.as_bytes()).unwrap();
+        assert_eq!(rss_percent(80000, &mem), Some(1.0));
+        assert_eq!(rss_percent(1, &SystemMemInfo::default()), None);
+    }
+
+    #[test]
+    fn test_read_with() {
+        let reader = MockProcReader::new().root_file("meminfo", MEMINFO);
+        assert_eq!(
+            SystemMemInfo::read_with(&reader).unwrap().mem_total_kb,
+            8000000
+        );
+        assert!(SystemMemInfo::read_with(&MockProcReader::new()).is_err());
+    }
+}
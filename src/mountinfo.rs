@@ -0,0 +1,107 @@
+use std::ffi::OsString;
+use std::io::{self, BufRead, BufReader};
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+
+use crate::maps::count_maps;
+use crate::reader::ProcReader;
+
+/// Mounts visible to `pid`: the lines of `/proc/<pid>/mountinfo`. Processes
+/// in one mount namespace see the same count.
+pub fn count_mountinfo(pid: u32, reader: &impl ProcReader) -> Result<usize, io::Error> {
+    // Like maps, one non-empty line per entry.
+    count_maps(BufReader::new(reader.open(pid, "mountinfo")?))
+}
+
+/// The mount point (fifth field) of a mountinfo line, with the kernel's
+/// octal escapes (`\040` for a space) decoded.
+pub fn mount_point(line: &[u8]) -> Option<PathBuf> {
+    let field = line
+        .split(|b| *b == b' ')
+        .filter(|f| !f.is_empty())
+        .nth(4)?;
+    let mut out = Vec::with_capacity(field.len());
+    let mut i = 0;
+    while i < field.len() {
+        let code = field
+            .get(i + 1..i + 4)
+            .filter(|_| field[i] == b'\\')
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u8::from_str_radix(d, 8).ok());
+        match code {
+            Some(b) => {
+                out.push(b);
+                i += 4;
+            }
+            None => {
+                out.push(field[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(OsString::from_vec(out)))
+}
+
+/// Whether `path` is a mount point in `pid`'s mountinfo (`--has-mount`).
+/// Compared component-wise, so a trailing slash does not matter.
+pub fn has_mount_point(pid: u32, reader: &impl ProcReader, path: &Path) -> io::Result<bool> {
+    for line in BufReader::new(reader.open(pid, "mountinfo")?).split(b'\n') {
+        if mount_point(&line?).is_some_and(|p| p == path) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const MOUNTINFO: &str = "\
+22 1 8:2 / / rw,relatime shared:1 - ext4 /dev/sda2 rw
+23 22 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:12 - proc proc rw
+24 22 0:22 / /sys rw,nosuid,nodev,noexec,relatime shared:7 - sysfs sysfs rw
+36 22 8:3 /data /srv/my\\040data rw,noatime master:1 - xfs /dev/sdb1 rw
+";
+
+    #[test]
+    fn test_count_mountinfo() {
+        let reader = MockProcReader::new()
+            .file(7, "mountinfo", MOUNTINFO)
+            .file(8, "mountinfo", "")
+            .error(9, "mountinfo", io::ErrorKind::PermissionDenied);
+        assert_eq!(count_mountinfo(7, &reader).unwrap(), 4);
+        assert_eq!(count_mountinfo(8, &reader).unwrap(), 0);
+        assert!(count_mountinfo(9, &reader).is_err());
+    }
+
+    #[test]
+    fn test_mount_point() {
+        let lines: Vec<Option<PathBuf>> = MOUNTINFO
+            .lines()
+            .map(|l| mount_point(l.as_bytes()))
+            .collect();
+        assert_eq!(
+            lines,
+            ["/", "/proc", "/sys", "/srv/my data"].map(|p| Some(PathBuf::from(p)))
+        );
+        assert_eq!(
+            mount_point(b"1 0 0:1 / /a\\134b\\ rw - tmpfs x rw"),
+            Some(PathBuf::from("/a\\b\\"))
+        );
+        assert_eq!(mount_point(b"22 1 8:2 /"), None);
+    }
+
+    #[test]
+    fn test_has_mount_point() {
+        let reader = MockProcReader::new().file(7, "mountinfo", MOUNTINFO);
+        let has = |p: &str| has_mount_point(7, &reader, Path::new(p)).unwrap();
+        assert!(has("/proc"));
+        assert!(has("/proc/"));
+        assert!(has("/srv/my data"));
+        assert!(!has("/srv"));
+        assert!(!has("/sys/fs/cgroup"));
+        assert!(has_mount_point(8, &reader, Path::new("/")).is_err());
+    }
+}
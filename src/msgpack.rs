@@ -0,0 +1,240 @@
+//! MessagePack encoding of scan results (`msgpack` feature).
+//!
+//! Each process is a map keyed like the JSON output
+//! (`pid`, `target`, `name`, `exe`, `cmdline`); the result is an array of
+//! them. Only the few MessagePack types this needs are implemented.
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::scan::ProcessInfo;
+
+fn write_uint<W: Write>(w: &mut W, v: u64) -> io::Result<()> {
+    match v {
+        0..=0x7f => w.write_all(&[v as u8]),
+        0x80..=0xff => w.write_all(&[0xcc, v as u8]),
+        0x100..=0xffff => {
+            w.write_all(&[0xcd])?;
+            w.write_all(&(v as u16).to_be_bytes())
+        }
+        0x1_0000..=0xffff_ffff => {
+            w.write_all(&[0xce])?;
+            w.write_all(&(v as u32).to_be_bytes())
+        }
+        _ => {
+            w.write_all(&[0xcf])?;
+            w.write_all(&v.to_be_bytes())
+        }
+    }
+}
+
+// Shared by str (fixstr/str8/16/32), array and map length headers.
+fn write_len<W: Write>(
+    w: &mut W,
+    len: usize,
+    fix: u8,
+    fix_max: usize,
+    wide: [u8; 3],
+) -> io::Result<()> {
+    let too_long = || io::Error::new(io::ErrorKind::InvalidInput, "msgpack length over u32");
+    if len <= fix_max {
+        w.write_all(&[fix | len as u8])
+    } else if wide[0] != 0 && len <= 0xff {
+        w.write_all(&[wide[0], len as u8])
+    } else if len <= 0xffff {
+        w.write_all(&[wide[1]])?;
+        w.write_all(&(len as u16).to_be_bytes())
+    } else {
+        let len = u32::try_from(len).map_err(|_| too_long())?;
+        w.write_all(&[wide[2]])?;
+        w.write_all(&len.to_be_bytes())
+    }
+}
+
+fn write_str<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    write_len(w, s.len(), 0xa0, 31, [0xd9, 0xda, 0xdb])?;
+    w.write_all(s.as_bytes())
+}
+
+fn write_array_len<W: Write>(w: &mut W, len: usize) -> io::Result<()> {
+    write_len(w, len, 0x90, 15, [0, 0xdc, 0xdd])
+}
+
+fn write_map_len<W: Write>(w: &mut W, len: usize) -> io::Result<()> {
+    write_len(w, len, 0x80, 15, [0, 0xde, 0xdf])
+}
+
+/// Writes `processes` as one MessagePack array. Paths and arguments are
+/// lossily decoded to UTF-8, as in the JSON output.
+pub fn write_msgpack<W: Write>(processes: &[ProcessInfo], mut w: W) -> io::Result<()> {
+    write_array_len(&mut w, processes.len())?;
+    for p in processes {
+        write_map_len(&mut w, 5)?;
+        write_str(&mut w, "pid")?;
+        write_uint(&mut w, p.pid.into())?;
+        write_str(&mut w, "target")?;
+        write_uint(&mut w, p.target as u64)?;
+        write_str(&mut w, "name")?;
+        write_str(&mut w, &p.name)?;
+        write_str(&mut w, "exe")?;
+        match &p.exe {
+            Some(exe) => write_str(&mut w, &exe.to_string_lossy())?,
+            None => w.write_all(&[0xc0])?,
+        }
+        write_str(&mut w, "cmdline")?;
+        write_array_len(&mut w, p.cmdline.len())?;
+        for arg in &p.cmdline {
+            write_str(&mut w, arg)?;
+        }
+    }
+    w.flush()
+}
+
+/// Reads back what [`write_msgpack`] writes; fails on anything else.
+pub fn read_msgpack(buf: &[u8]) -> io::Result<Vec<ProcessInfo>> {
+    let mut d = Decoder { buf, pos: 0 };
+    let n = d.array_len()?;
+    let mut out = Vec::with_capacity(n);
+    for _ in 0..n {
+        let mut info = ProcessInfo {
+            pid: 0,
+            target: 0,
+            name: String::new(),
+            exe: None,
+            cmdline: Vec::new(),
+        };
+        for _ in 0..d.map_len()? {
+            match d.str()?.as_str() {
+                "pid" => info.pid = u32::try_from(d.uint()?).map_err(|_| bad())?,
+                "target" => info.target = d.uint()? as usize,
+                "name" => info.name = d.str()?,
+                "exe" => {
+                    info.exe = if d.peek()? == 0xc0 {
+                        d.pos += 1;
+                        None
+                    } else {
+                        Some(PathBuf::from(d.str()?))
+                    }
+                }
+                "cmdline" => {
+                    for _ in 0..d.array_len()? {
+                        info.cmdline.push(d.str()?);
+                    }
+                }
+                _ => return Err(bad()),
+            }
+        }
+        out.push(info);
+    }
+    Ok(out)
+}
+
+fn bad() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "unexpected msgpack data")
+}
+
+struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Decoder<'_> {
+    fn peek(&self) -> io::Result<u8> {
+        self.buf.get(self.pos).copied().ok_or_else(bad)
+    }
+
+    fn take(&mut self, n: usize) -> io::Result<&[u8]> {
+        let bytes = self.buf.get(self.pos..self.pos + n).ok_or_else(bad)?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn be(&mut self, n: usize) -> io::Result<u64> {
+        Ok(self
+            .take(n)?
+            .iter()
+            .fold(0, |acc, b| acc << 8 | u64::from(*b)))
+    }
+
+    fn uint(&mut self) -> io::Result<u64> {
+        let tag = self.be(1)? as u8;
+        match tag {
+            0..=0x7f => Ok(tag.into()),
+            0xcc => self.be(1),
+            0xcd => self.be(2),
+            0xce => self.be(4),
+            0xcf => self.be(8),
+            _ => Err(bad()),
+        }
+    }
+
+    fn len(&mut self, fix: u8, fix_mask: u8, wide: [u8; 3]) -> io::Result<usize> {
+        let tag = self.be(1)? as u8;
+        let len = if tag & !fix_mask == fix {
+            u64::from(tag & fix_mask)
+        } else if wide[0] != 0 && tag == wide[0] {
+            self.be(1)?
+        } else if tag == wide[1] {
+            self.be(2)?
+        } else if tag == wide[2] {
+            self.be(4)?
+        } else {
+            return Err(bad());
+        };
+        Ok(len as usize)
+    }
+
+    fn str(&mut self) -> io::Result<String> {
+        let len = self.len(0xa0, 0x1f, [0xd9, 0xda, 0xdb])?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| bad())
+    }
+
+    fn array_len(&mut self) -> io::Result<usize> {
+        self.len(0x90, 0x0f, [0, 0xdc, 0xdd])
+    }
+
+    fn map_len(&mut self) -> io::Result<usize> {
+        self.len(0x80, 0x0f, [0, 0xde, 0xdf])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let long = "a".repeat(300);
+        let processes = vec![
+            ProcessInfo {
+                pid: 1,
+                target: 0,
+                name: "systemd".to_string(),
+                exe: Some(PathBuf::from("/usr/lib/systemd/systemd")),
+                cmdline: vec!["/sbin/init".to_string(), "splash".to_string()],
+            },
+            ProcessInfo {
+                pid: 4_194_304,
+                target: 300,
+                name: String::new(),
+                exe: None,
+                cmdline: (0..20).map(|i| format!("{long}{i}")).collect(),
+            },
+        ];
+        let mut buf = Vec::new();
+        write_msgpack(&processes, &mut buf).unwrap();
+        assert_eq!(read_msgpack(&buf).unwrap(), processes);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut buf = Vec::new();
+        write_msgpack(&[], &mut buf).unwrap();
+        assert_eq!(buf, [0x90]);
+        let mut buf = Vec::new();
+        write_uint(&mut buf, 200).unwrap();
+        write_str(&mut buf, "pid").unwrap();
+        assert_eq!(buf, [0xcc, 200, 0xa3, b'p', b'i', b'd']);
+        assert!(read_msgpack(&[0x91, 0xc0]).is_err());
+    }
+}
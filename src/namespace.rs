@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use crate::error::ParseError;
+use crate::reader::ProcReader;
+use crate::status::parse_ns_pids;
+
+/// Namespace types reported by `--print-ns`, in output order.
+pub const NS_TYPES: [&str; 6] = ["pid", "mnt", "net", "uts", "ipc", "user"];
+
+/// The entry of [`NS_TYPES`] equal to `name`, e.g. for `--ns-type`.
+pub fn ns_type(name: &str) -> Option<&'static str> {
+    NS_TYPES.iter().copied().find(|t| *t == name)
+}
+
+/// Inode of a namespace link target such as `pid:[4026531836]`.
+pub fn parse_ns_link(target: &Path) -> Option<u64> {
+    let s = target.to_str()?;
+    let (_, rest) = s.split_once(":[")?;
+    rest.strip_suffix(']')?.parse().ok()
+}
+
+/// Inode identifying the PID namespace of `pid`, from `/proc/<pid>/ns/pid`.
+///
+/// Reading another user's namespace links needs ptrace access, so `EACCES`
+/// is expected without root.
+pub fn pid_namespace(pid: u32, reader: &impl ProcReader) -> io::Result<u64> {
+    ns_inode(pid, "pid", reader)
+}
+
+/// Inode of `/proc/<pid>/ns/<kind>` for any namespace type.
+pub fn ns_inode(pid: u32, kind: &str, reader: &impl ProcReader) -> io::Result<u64> {
+    let target = reader.read_link(pid, &format!("ns/{}", kind))?;
+    parse_ns_link(&target)
+        .ok_or_else(|| ParseError::InvalidValue(target.display().to_string()).into())
+}
+
+/// Inodes of every namespace in [`NS_TYPES`]; fails on the first link that
+/// can't be read, since without ptrace access none of them can.
+pub fn read_ns_inodes(
+    pid: u32,
+    reader: &impl ProcReader,
+) -> Result<HashMap<&'static str, u64>, io::Error> {
+    NS_TYPES
+        .iter()
+        .map(|kind| Ok((*kind, ns_inode(pid, kind, reader)?)))
+        .collect()
+}
+
+/// Whether both processes live in the same PID namespace.
+pub fn same_pid_namespace(pid1: u32, pid2: u32, reader: &impl ProcReader) -> io::Result<bool> {
+    Ok(pid_namespace(pid1, reader)? == pid_namespace(pid2, reader)?)
+}
+
+/// Whether `pid` is the init (PID 1) of its innermost PID namespace, e.g.
+/// a container's init seen from the host. False when status can't be read
+/// or lacks `NSpid` (kernels before 4.1).
+pub fn is_ns_init(pid: u32, reader: &impl ProcReader) -> bool {
+    reader
+        .read(pid, "status")
+        .is_ok_and(|buf| parse_ns_pids(&buf).last() == Some(&1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const HOST: &str = "pid:[4026531836]";
+    const CONTAINER: &str = "pid:[4026532201]";
+
+    #[test]
+    fn test_parse_ns_link() {
+        assert_eq!(parse_ns_link(Path::new(HOST)), Some(4026531836));
+        assert_eq!(
+            parse_ns_link(Path::new("net:[4026531840]")),
+            Some(4026531840)
+        );
+        assert_eq!(parse_ns_link(Path::new("pid:[]")), None);
+        assert_eq!(parse_ns_link(Path::new("/usr/bin/bash")), None);
+    }
+
+    #[test]
+    fn test_same_pid_namespace() {
+        let reader = MockProcReader::new()
+            .link(1, "ns/pid", HOST)
+            .link(500, "ns/pid", HOST)
+            .link(900, "ns/pid", CONTAINER)
+            .link(901, "ns/pid", "garbage")
+            .error(902, "ns/pid", io::ErrorKind::PermissionDenied);
+        assert_eq!(pid_namespace(900, &reader).unwrap(), 4026532201);
+        assert!(same_pid_namespace(1, 500, &reader).unwrap());
+        assert!(!same_pid_namespace(1, 900, &reader).unwrap());
+        assert_eq!(
+            same_pid_namespace(1, 901, &reader).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            same_pid_namespace(902, 1, &reader).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_is_ns_init() {
+        let reader = MockProcReader::new()
+            .file(1234, "status", "Name:\tinit\nNSpid:\t1234\t1\n")
+            .file(5678, "status", "Name:\tsh\nNSpid:\t5678\t42\n")
+            .file(1, "status", "Name:\tsystemd\nNSpid:\t1\n")
+            .file(9, "status", "Name:\told\nPid:\t9\n");
+        assert!(is_ns_init(1234, &reader));
+        assert!(!is_ns_init(5678, &reader));
+        assert!(is_ns_init(1, &reader));
+        assert!(!is_ns_init(9, &reader));
+        assert!(!is_ns_init(10, &reader));
+    }
+
+    #[test]
+    fn test_read_ns_inodes() {
+        let reader = NS_TYPES
+            .iter()
+            .enumerate()
+            .fold(MockProcReader::new(), |r, (i, kind)| {
+                r.link(
+                    7,
+                    &format!("ns/{kind}"),
+                    format!("{kind}:[{}]", 4026531835 + i),
+                )
+            })
+            .link(8, "ns/pid", HOST)
+            .error(8, "ns/net", io::ErrorKind::PermissionDenied);
+        let inodes = read_ns_inodes(7, &reader).unwrap();
+        assert_eq!(inodes.len(), 6);
+        assert_eq!(inodes["pid"], 4026531835);
+        assert_eq!(inodes["user"], 4026531840);
+        assert_eq!(ns_inode(7, "net", &reader).unwrap(), 4026531837);
+        assert!(read_ns_inodes(8, &reader).is_err());
+        assert_eq!(ns_type("net"), Some("net"));
+        assert_eq!(ns_type("cgroupv3"), None);
+    }
+}
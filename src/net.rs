@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::io;
+
+use crate::fd::iter_fds;
+use crate::reader::{FsProcReader, ProcReader};
+
+/// Protocol family of a socket inode, as listed under `/proc/net`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SocketKind {
+    Tcp,
+    Udp,
+    Unix,
+}
+
+impl SocketKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SocketKind::Tcp => "tcp",
+            SocketKind::Udp => "udp",
+            SocketKind::Unix => "unix",
+        }
+    }
+}
+
+// TCP state code of a listening socket in `/proc/net/tcp{,6}`.
+const TCP_LISTEN: u8 = 0x0A;
+
+/// One row of a `/proc/net` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketEntry {
+    pub kind: SocketKind,
+    /// Local port; `None` for Unix sockets.
+    pub local_port: Option<u16>,
+    /// Kernel state code (`0A` is TCP LISTEN); 0 for Unix sockets.
+    pub state: u8,
+}
+
+impl SocketEntry {
+    /// Accepting connections or datagrams: TCP in LISTEN, or any UDP
+    /// socket bound to a port.
+    pub fn is_listening(&self) -> bool {
+        match self.kind {
+            SocketKind::Tcp => self.state == TCP_LISTEN,
+            SocketKind::Udp => self.local_port.is_some_and(|port| port != 0),
+            SocketKind::Unix => false,
+        }
+    }
+}
+
+// The tables read, with the whitespace-separated column holding the inode.
+const NET_TABLES: [(&str, SocketKind, usize); 5] = [
+    ("net/tcp", SocketKind::Tcp, 9),
+    ("net/tcp6", SocketKind::Tcp, 9),
+    ("net/udp", SocketKind::Udp, 9),
+    ("net/udp6", SocketKind::Udp, 9),
+    ("net/unix", SocketKind::Unix, 6),
+];
+
+/// Socket inodes of the `/proc/net` tables, mapped to their row.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SocketInodeDb {
+    entries: HashMap<u64, SocketEntry>,
+}
+
+impl SocketInodeDb {
+    /// Adds the inodes of one `/proc/net` table; the header line and
+    /// malformed rows are skipped. For TCP and UDP the local port and state
+    /// come from the `local_address` (`hexaddr:hexport`) and `st` columns.
+    pub fn add_table(&mut self, buf: &[u8], kind: SocketKind, inode_col: usize) {
+        for line in String::from_utf8_lossy(buf).lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some(inode) = fields
+                .get(inode_col)
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|inode| *inode != 0)
+            else {
+                continue;
+            };
+            let (local_port, state) = match kind {
+                SocketKind::Unix => (None, 0),
+                SocketKind::Tcp | SocketKind::Udp => {
+                    let port = fields
+                        .get(1)
+                        .and_then(|addr| addr.rsplit_once(':'))
+                        .and_then(|(_, port)| u16::from_str_radix(port, 16).ok());
+                    let state = fields.get(3).and_then(|st| u8::from_str_radix(st, 16).ok());
+                    (port, state.unwrap_or(0))
+                }
+            };
+            self.entries.insert(
+                inode,
+                SocketEntry {
+                    kind,
+                    local_port,
+                    state,
+                },
+            );
+        }
+    }
+
+    pub fn get(&self, inode: u64) -> Option<&SocketEntry> {
+        self.entries.get(&inode)
+    }
+
+    pub fn kind(&self, inode: u64) -> Option<SocketKind> {
+        self.get(inode).map(|e| e.kind)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Reads every `/proc/net` table once. The tables describe fpid's own
+/// network namespace, so sockets of processes in other namespaces are not
+/// found.
+pub fn build_socket_inode_db() -> SocketInodeDb {
+    build_socket_inode_db_with(&FsProcReader::default())
+}
+
+/// [`build_socket_inode_db`] under `reader`'s root; missing tables (no
+/// IPv6, say) are skipped.
+pub fn build_socket_inode_db_with(reader: &impl ProcReader) -> SocketInodeDb {
+    let mut db = SocketInodeDb::default();
+    for (file, kind, inode_col) in NET_TABLES {
+        if let Ok(buf) = reader.read_root(file) {
+            db.add_table(&buf, kind, inode_col);
+        }
+    }
+    db
+}
+
+/// Inode of an fd target such as `socket:[4242]`.
+pub fn socket_inode(target: &[u8]) -> Option<u64> {
+    let inode = target.strip_prefix(b"socket:[")?.strip_suffix(b"]")?;
+    std::str::from_utf8(inode).ok()?.parse().ok()
+}
+
+/// Sockets of one process by protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SocketCounts {
+    pub tcp: usize,
+    pub udp: usize,
+    pub unix: usize,
+}
+
+/// Counts the socket fds of `pid` found in `db`; sockets of other families
+/// (netlink, packet, ...) are not counted.
+pub fn socket_counts(
+    pid: u32,
+    reader: &impl ProcReader,
+    db: &SocketInodeDb,
+) -> Result<SocketCounts, io::Error> {
+    let mut counts = SocketCounts::default();
+    for target in iter_fds(pid, reader)?.flatten() {
+        let kind = socket_inode(target.as_os_str().as_encoded_bytes()).and_then(|i| db.kind(i));
+        match kind {
+            Some(SocketKind::Tcp) => counts.tcp += 1,
+            Some(SocketKind::Udp) => counts.udp += 1,
+            Some(SocketKind::Unix) => counts.unix += 1,
+            None => {}
+        }
+    }
+    Ok(counts)
+}
+
+/// Ports `pid` listens on (see [`SocketEntry::is_listening`]), TCP before
+/// UDP, each sorted and without duplicates (a dual-stack server has one
+/// socket in tcp and one in tcp6).
+pub fn find_listen_ports(
+    pid: u32,
+    reader: &impl ProcReader,
+    db: &SocketInodeDb,
+) -> Result<Vec<(SocketKind, u16)>, io::Error> {
+    let mut ports: Vec<(SocketKind, u16)> = iter_fds(pid, reader)?
+        .flatten()
+        .filter_map(|target| db.get(socket_inode(target.as_os_str().as_encoded_bytes())?))
+        .filter(|entry| entry.is_listening())
+        .filter_map(|entry| Some((entry.kind, entry.local_port?)))
+        .collect();
+    ports.sort_unstable_by_key(|(kind, port)| (*kind != SocketKind::Tcp, *port));
+    ports.dedup();
+    Ok(ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 20481 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 20490 1 0000000000000000 20 4 30 10 -1
+";
+    const UDP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  120: 00000000000000000000000000000000:14E9 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000   104        0 18112 2 0000000000000000 0
+";
+    const UNIX: &str = "Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 17345 /run/systemd/notify
+0000000000000000: 00000003 00000000 00000000 0001 03 20500
+";
+
+    fn db_reader() -> MockProcReader {
+        MockProcReader::new()
+            .root_file("net/tcp", TCP)
+            .root_file("net/udp6", UDP6)
+            .root_file("net/unix", UNIX)
+    }
+
+    #[test]
+    fn test_build_socket_inode_db() {
+        let db = build_socket_inode_db_with(&db_reader());
+        assert_eq!(db.len(), 5);
+        assert_eq!(db.kind(20481), Some(SocketKind::Tcp));
+        assert_eq!(db.kind(20490), Some(SocketKind::Tcp));
+        assert_eq!(db.kind(18112), Some(SocketKind::Udp));
+        assert_eq!(db.kind(17345), Some(SocketKind::Unix));
+        assert_eq!(db.kind(20500), Some(SocketKind::Unix));
+        assert_eq!(db.kind(1), None);
+        assert!(build_socket_inode_db_with(&MockProcReader::new()).is_empty());
+        assert_eq!(
+            db.get(20481),
+            Some(&SocketEntry {
+                kind: SocketKind::Tcp,
+                local_port: Some(22),
+                state: 0x0A
+            })
+        );
+        assert_eq!(db.get(20490).unwrap().local_port, Some(8080));
+        assert_eq!(db.get(18112).unwrap().local_port, Some(5353));
+        assert_eq!(db.get(17345).unwrap().local_port, None);
+    }
+
+    #[test]
+    fn test_socket_entry_is_listening() {
+        let db = build_socket_inode_db_with(&db_reader());
+        // LISTEN, ESTABLISHED, bound UDP, Unix.
+        let listening: Vec<bool> = [20481, 20490, 18112, 17345]
+            .iter()
+            .map(|inode| db.get(*inode).unwrap().is_listening())
+            .collect();
+        assert_eq!(listening, [true, false, true, false]);
+    }
+
+    #[test]
+    fn test_find_listen_ports() {
+        const TCP6: &str = "  sl  local_address remote_address st tx_queue rx_queue tr tm->when retrnsmt uid timeout inode
+   0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 20482 1
+   1: 00000000000000000000000000000000:01BB 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 20483 1
+";
+        let reader = db_reader()
+            .root_file("net/tcp6", TCP6)
+            .link(7, "fd/3", "socket:[20483]")
+            .link(7, "fd/4", "socket:[18112]")
+            .link(7, "fd/5", "socket:[20481]")
+            .link(7, "fd/6", "socket:[20482]")
+            .link(7, "fd/7", "socket:[20490]")
+            .link(7, "fd/8", "socket:[17345]")
+            .link(8, "fd/0", "/dev/null");
+        let db = build_socket_inode_db_with(&reader);
+        assert_eq!(
+            find_listen_ports(7, &reader, &db).unwrap(),
+            vec![
+                (SocketKind::Tcp, 22),
+                (SocketKind::Tcp, 443),
+                (SocketKind::Udp, 5353)
+            ]
+        );
+        assert!(find_listen_ports(8, &reader, &db).unwrap().is_empty());
+        assert!(find_listen_ports(9, &reader, &db).is_err());
+    }
+
+    #[test]
+    fn test_socket_inode() {
+        assert_eq!(socket_inode(b"socket:[20481]"), Some(20481));
+        for bad in [
+            &b"pipe:[20481]"[..],
+            b"socket:[]",
+            b"socket:[12",
+            b"/tmp/socket:[1]",
+        ] {
+            assert_eq!(
+                socket_inode(bad),
+                None,
+                "{:?}",
+                String::from_utf8_lossy(bad)
+            );
+        }
+    }
+
+    #[test]
+    fn test_socket_counts() {
+        let reader = db_reader()
+            .link(7, "fd/0", "/dev/null")
+            .link(7, "fd/3", "socket:[20481]")
+            .link(7, "fd/4", "socket:[20490]")
+            .link(7, "fd/5", "socket:[17345]")
+            .link(7, "fd/6", "socket:[18112]")
+            // Netlink and other unlisted families.
+            .link(7, "fd/7", "socket:[99999]")
+            .error(7, "fd/8", io::ErrorKind::PermissionDenied);
+        let db = build_socket_inode_db_with(&reader);
+        assert_eq!(
+            socket_counts(7, &reader, &db).unwrap(),
+            SocketCounts {
+                tcp: 2,
+                udp: 1,
+                unix: 1
+            }
+        );
+        assert!(socket_counts(8, &reader, &db).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+use crate::sys::set_priority;
+
+/// Nice values `setpriority` accepts; lower is higher priority.
+pub const NICE_RANGE: RangeInclusive<i32> = -20..=19;
+
+/// Renices each matched process (`--renice`), or with `dry_run` only
+/// reports what it would do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReniceAction {
+    pub nice: i32,
+    pub dry_run: bool,
+}
+
+impl ReniceAction {
+    /// Acts on `pid`; `name` is only used in the dry-run message. Lowering
+    /// the nice value needs root or CAP_SYS_NICE.
+    pub fn apply(&self, pid: u32, name: &str, out: &mut dyn Write) -> io::Result<()> {
+        if self.dry_run {
+            return writeln!(
+                out,
+                "[DRY-RUN] would renice pid {} ({}) to {}",
+                pid, name, self.nice
+            );
+        }
+        set_priority(pid, self.nice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stat::ProcStat;
+    use std::process::Command;
+
+    #[test]
+    fn test_renice_action() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let nice = |pid| ProcStat::read(pid).unwrap().nice;
+        // Raising the nice value needs no privileges.
+        let before = nice(pid);
+        let target = (before as i32 + 1).min(*NICE_RANGE.end());
+        let action = ReniceAction {
+            nice: target,
+            dry_run: true,
+        };
+        let mut out = Vec::new();
+        action.apply(pid, "sleep", &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("[DRY-RUN] would renice pid {pid} (sleep) to {target}\n")
+        );
+        assert_eq!(nice(pid), before);
+
+        let real = ReniceAction {
+            dry_run: false,
+            ..action
+        };
+        real.apply(pid, "sleep", &mut Vec::new()).unwrap();
+        assert_eq!(nice(pid), target as i64);
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader};
+
+use crate::reader::ProcReader;
+
+/// The policy tag of one `numa_maps` line (`addr policy flags...`), e.g.
+/// `default`, `bind:0` or `interleave:0-1`.
+pub fn parse_numa_policy(line: &[u8]) -> Option<String> {
+    let mut fields = line
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|f| !f.is_empty());
+    let addr = fields.next()?;
+    if !addr.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    fields
+        .next()
+        .map(|p| String::from_utf8_lossy(p).into_owned())
+}
+
+/// Policy of the first mapping in `/proc/<pid>/numa_maps`; `Ok(None)` when
+/// the process has no mappings (kernel threads).
+///
+/// Newer kernels require ptrace access, so `EACCES` is expected for other
+/// users' processes.
+pub fn read_numa_policy(pid: u32, reader: &impl ProcReader) -> Result<Option<String>, io::Error> {
+    let mut first = Vec::new();
+    BufReader::new(reader.open(pid, "numa_maps")?).read_until(b'\n', &mut first)?;
+    Ok(parse_numa_policy(&first))
+}
+
+/// Pages per NUMA node summed over every `numa_maps` line, from the
+/// `N<node>=<pages>` fields. Pages are counted as the kernel lists them, so a
+/// huge page counts once.
+pub fn parse_numa_maps_stats(buf: &[u8]) -> HashMap<u32, u64> {
+    let mut pages = HashMap::new();
+    for field in buf
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|f| f.first() == Some(&b'N'))
+    {
+        let Some((node, count)) = std::str::from_utf8(&field[1..])
+            .ok()
+            .and_then(|f| f.split_once('='))
+        else {
+            continue;
+        };
+        if let (Ok(node), Ok(count)) = (node.parse::<u32>(), count.parse::<u64>()) {
+            *pages.entry(node).or_insert(0) += count;
+        }
+    }
+    pages
+}
+
+/// Share of the pages on `node`, in percent; `None` without any pages.
+pub fn node_local_pct(pages: &HashMap<u32, u64>, node: u32) -> Option<f64> {
+    let total: u64 = pages.values().sum();
+    (total > 0).then(|| pages.get(&node).copied().unwrap_or(0) as f64 / total as f64 * 100.0)
+}
+
+/// [`node_local_pct`] of `/proc/<pid>/numa_maps`.
+pub fn read_numa_local_pct(
+    pid: u32,
+    reader: &impl ProcReader,
+    node: u32,
+) -> Result<Option<f64>, io::Error> {
+    Ok(node_local_pct(
+        &parse_numa_maps_stats(&reader.read(pid, "numa_maps")?),
+        node,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_parse_numa_policy() {
+        assert_eq!(
+            parse_numa_policy(
+                b"55d4c8e00000 default file=/usr/bin/cat mapped=2 N0=2 kernelpagesize_kB=4\n"
+            ),
+            Some("default".to_string())
+        );
+        assert_eq!(
+            parse_numa_policy(b"7f0c1a000000 interleave:0-1 anon=512 dirty=512 N0=256 N1=256"),
+            Some("interleave:0-1".to_string())
+        );
+        assert_eq!(
+            parse_numa_policy(b"7ffd bind:0 stack anon=3"),
+            Some("bind:0".to_string())
+        );
+        assert_eq!(parse_numa_policy(b""), None);
+        assert_eq!(parse_numa_policy(b"7ffd\n"), None);
+        assert_eq!(parse_numa_policy(b"not-an-addr default"), None);
+    }
+
+    #[test]
+    fn test_read_numa_policy() {
+        let reader = MockProcReader::new()
+            .file(
+                1,
+                "numa_maps",
+                "1000 prefer:1 anon=1\n2000 default anon=1\n",
+            )
+            .file(2, "numa_maps", "")
+            .error(3, "numa_maps", io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            read_numa_policy(1, &reader).unwrap(),
+            Some("prefer:1".to_string())
+        );
+        assert_eq!(read_numa_policy(2, &reader).unwrap(), None);
+        assert_eq!(
+            read_numa_policy(3, &reader).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_parse_numa_maps_stats() {
+        let stats = parse_numa_maps_stats(
+            b"55d4c8e00000 default file=/usr/bin/cat mapped=2 N0=2 kernelpagesize_kB=4\n\
+              7f0c1a000000 interleave:0-1 anon=512 dirty=512 N0=256 N1=256 kernelpagesize_kB=4\n\
+              7ffd00000000 default stack anon=3 dirty=3 N1=3 kernelpagesize_kB=4\n\
+              7ffe00000000 default NX=1 N2=oops\n",
+        );
+        assert_eq!(stats, HashMap::from([(0, 258), (1, 259)]));
+        assert!(parse_numa_maps_stats(b"").is_empty());
+    }
+
+    #[test]
+    fn test_node_local_pct() {
+        let pages = HashMap::from([(0, 300), (1, 100)]);
+        assert_eq!(node_local_pct(&pages, 0), Some(75.0));
+        assert_eq!(node_local_pct(&pages, 1), Some(25.0));
+        assert_eq!(node_local_pct(&pages, 2), Some(0.0));
+        assert_eq!(node_local_pct(&HashMap::new(), 0), None);
+    }
+
+    #[test]
+    fn test_read_numa_local_pct() {
+        let reader = MockProcReader::new()
+            .file(1, "numa_maps", "1000 default anon=4 N0=1 N1=3\n")
+            .file(2, "numa_maps", "")
+            .error(3, "numa_maps", io::ErrorKind::PermissionDenied);
+        assert_eq!(read_numa_local_pct(1, &reader, 0).unwrap(), Some(25.0));
+        assert_eq!(read_numa_local_pct(1, &reader, 1).unwrap(), Some(75.0));
+        assert_eq!(read_numa_local_pct(2, &reader, 0).unwrap(), None);
+        assert!(read_numa_local_pct(3, &reader, 0).is_err());
+    }
+}
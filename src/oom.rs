@@ -0,0 +1,164 @@
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::reader::{DEFAULT_PROC_ROOT, FsProcReader, ProcReader};
+
+fn parse_int(buf: &[u8]) -> Result<i32, io::Error> {
+    std::str::from_utf8(buf)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed oom value"))
+}
+
+/// `(oom_score, oom_score_adj)`: the kernel's current badness score (0-1000)
+/// and the user-set adjustment (-1000..=1000). Both files are world-readable.
+pub fn read_oom_score(pid: u32) -> Result<(i32, i32), io::Error> {
+    read_oom_score_with(pid, &FsProcReader::default())
+}
+
+pub fn read_oom_score_with(pid: u32, reader: &impl ProcReader) -> Result<(i32, i32), io::Error> {
+    let score = parse_int(&reader.read(pid, "oom_score")?)?;
+    let adj = parse_int(&reader.read(pid, "oom_score_adj")?)?;
+    Ok((score, adj))
+}
+
+/// Values the kernel accepts in `oom_score_adj`.
+pub const OOM_ADJ_RANGE: RangeInclusive<i32> = -1000..=1000;
+
+/// Writes `adj` to `/proc/<pid>/oom_score_adj`. Lowering it below the
+/// current value needs CAP_SYS_RESOURCE; a process that exited gives ENOENT.
+pub fn write_oom_adj(pid: u32, adj: i32) -> Result<(), io::Error> {
+    write_oom_adj_in(Path::new(DEFAULT_PROC_ROOT), pid, adj)
+}
+
+/// [`write_oom_adj`] under another proc root (`--proc-root`), so the write
+/// goes to the same tree the process was matched in.
+pub fn write_oom_adj_in(root: &Path, pid: u32, adj: i32) -> Result<(), io::Error> {
+    // O_TRUNC is a no-op on procfs but keeps a plain-file tree exact.
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(root.join(pid.to_string()).join("oom_score_adj"))?;
+    file.write_all(adj.to_string().as_bytes())
+}
+
+/// Sets `oom_score_adj` on each matched process (`--set-oom-adj`), or with
+/// `dry_run` only reports what it would write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OomAdjAction {
+    pub adj: i32,
+    pub dry_run: bool,
+}
+
+impl OomAdjAction {
+    /// Acts on `pid`; `name` is only used in the dry-run message.
+    pub fn apply(&self, pid: u32, name: &str, out: &mut dyn Write) -> io::Result<()> {
+        self.apply_in(Path::new(DEFAULT_PROC_ROOT), pid, name, out)
+    }
+
+    /// [`OomAdjAction::apply`] for a process under `root`.
+    pub fn apply_in(
+        &self,
+        root: &Path,
+        pid: u32,
+        name: &str,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        if self.dry_run {
+            return writeln!(
+                out,
+                "[DRY-RUN] would set oom_score_adj of pid {} ({}) to {}",
+                pid, name, self.adj
+            );
+        }
+        write_oom_adj_in(root, pid, self.adj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_read_oom_score() {
+        let reader = MockProcReader::new()
+            .file(1, "oom_score", "0\n")
+            .file(1, "oom_score_adj", "-1000\n")
+            .file(2, "oom_score", "667\n")
+            .file(2, "oom_score_adj", "300\n")
+            .file(3, "oom_score", "12\n")
+            .file(3, "oom_score_adj", "lots\n");
+        assert_eq!(read_oom_score_with(1, &reader).unwrap(), (0, -1000));
+        assert_eq!(read_oom_score_with(2, &reader).unwrap(), (667, 300));
+        assert_eq!(
+            read_oom_score_with(3, &reader).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(read_oom_score_with(4, &reader).is_err());
+    }
+
+    #[test]
+    fn test_write_oom_adj_in() {
+        let root = std::env::temp_dir().join(format!("fpid-oom-root-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("42")).unwrap();
+        std::fs::write(root.join("42/oom_score_adj"), "0\n").unwrap();
+        write_oom_adj_in(&root, 42, -500).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("42/oom_score_adj")).unwrap(),
+            "-500"
+        );
+        let action = OomAdjAction {
+            adj: 300,
+            dry_run: false,
+        };
+        action.apply_in(&root, 42, "x", &mut Vec::new()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("42/oom_score_adj")).unwrap(),
+            "300"
+        );
+        // Not created when the process is missing from the tree.
+        assert_eq!(
+            write_oom_adj_in(&root, 43, 0).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_oom_adj_action() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let (_, before) = read_oom_score(pid).unwrap();
+        // Raising the adjustment needs no privileges.
+        let raised = (before + 1).min(*OOM_ADJ_RANGE.end());
+        let action = OomAdjAction {
+            adj: raised,
+            dry_run: true,
+        };
+        let mut out = Vec::new();
+        action.apply(pid, "sleep", &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("[DRY-RUN] would set oom_score_adj of pid {pid} (sleep) to {raised}\n")
+        );
+        assert_eq!(read_oom_score(pid).unwrap().1, before);
+
+        let real = OomAdjAction {
+            dry_run: false,
+            ..action
+        };
+        real.apply(pid, "sleep", &mut Vec::new()).unwrap();
+        assert_eq!(read_oom_score(pid).unwrap().1, raised);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(
+            write_oom_adj(pid, 0).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
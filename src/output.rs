@@ -0,0 +1,686 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::column::MISSING;
+use crate::json;
+use crate::scan::ProcessInfo;
+use crate::stat::ProcStat;
+use crate::sys::{clk_tck, terminal_width};
+
+/// Output format selected with `--format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Text records; the PID is highlighted when writing to a terminal
+    /// and `NO_COLOR` is unset (the default, `--format=auto`).
+    #[default]
+    Auto,
+    /// One record per line, never colored.
+    Text,
+    Json,
+    /// One JSON object per line, written as matches are found
+    /// (`--format=ndjson`).
+    Ndjson,
+    /// `ps -C` style `PID TTY TIME CMD` table (`--format=procps`).
+    Procps,
+    /// Tab-separated values with a header row (`--format=tsv`).
+    Tsv,
+    /// MessagePack array of processes (`--format=msgpack`, `msgpack` feature).
+    Msgpack,
+    /// Aligned columns under a header and separator line, fitted to the
+    /// terminal width (`--format=table`).
+    Table,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "auto" => Ok(Format::Auto),
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "procps" => Ok(Format::Procps),
+            "tsv" => Ok(Format::Tsv),
+            "table" => Ok(Format::Table),
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Ok(Format::Msgpack),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Renders `--list-all` results sorted by name: bare names, one per line, or
+/// a JSON array of `{"name":..,"count":..}` objects.
+pub fn render_name_counts(counts: &HashMap<String, usize>, format: Format) -> String {
+    let mut names: Vec<(&String, &usize)> = counts.iter().collect();
+    names.sort();
+    let mut out = String::new();
+    match format {
+        Format::Auto
+        | Format::Text
+        | Format::Procps
+        | Format::Tsv
+        | Format::Msgpack
+        | Format::Table => {
+            for (name, _) in names {
+                out.push_str(name);
+                out.push('\n');
+            }
+        }
+        Format::Json => {
+            out.push('[');
+            for (i, (name, count)) in names.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                push_name_count(&mut out, name, *count);
+            }
+            out.push_str("]\n");
+        }
+        Format::Ndjson => {
+            for (name, count) in names {
+                push_name_count(&mut out, name, *count);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn push_name_count(out: &mut String, name: &str, count: usize) {
+    out.push('{');
+    json::push_key(out, "name", true);
+    json::push_str(out, name);
+    json::push_key(out, "count", false);
+    out.push_str(&count.to_string());
+    out.push('}');
+}
+
+/// `info` as a single-line JSON object (no trailing newline), one
+/// `--format=ndjson` record: `{"pid":..,"target":..,"name":..,"exe":..,
+/// "cmdline":[..]}`, with `exe` null when unreadable.
+pub fn process_json(info: &ProcessInfo) -> String {
+    let mut out = String::from("{");
+    json::push_key(&mut out, "pid", true);
+    out.push_str(&info.pid.to_string());
+    json::push_key(&mut out, "target", false);
+    out.push_str(&info.target.to_string());
+    json::push_key(&mut out, "name", false);
+    json::push_str(&mut out, &info.name);
+    json::push_key(&mut out, "exe", false);
+    match &info.exe {
+        Some(exe) => json::push_str(&mut out, &exe.to_string_lossy()),
+        None => out.push_str("null"),
+    }
+    json::push_key(&mut out, "cmdline", false);
+    out.push('[');
+    for (i, arg) in info.cmdline.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        json::push_str(&mut out, arg);
+    }
+    out.push_str("]}");
+    out
+}
+
+/// One TSV line (with trailing newline). Values are not quoted; tabs,
+/// newlines and carriage returns inside them are written as `\t`, `\n` and
+/// `\r`, and a backslash as `\\`, so every escape reads back unambiguously.
+pub fn tsv_line(values: &[&str]) -> String {
+    let mut out = tsv_fields(values);
+    out.push('\n');
+    out
+}
+
+// The fields of a TSV line, escaped and tab-joined, without a terminator.
+fn tsv_fields(values: &[&str]) -> String {
+    let mut out = String::new();
+    for (i, v) in values.iter().enumerate() {
+        if i > 0 {
+            out.push('\t');
+        }
+        for c in v.chars() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                '\t' => out.push_str("\\t"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                c => out.push(c),
+            }
+        }
+    }
+    out
+}
+
+/// Writes `--format=tsv` output: an optional header then one row per match,
+/// escaped as by [`tsv_line`].
+pub struct TsvWriter<W: Write> {
+    w: W,
+    end: &'static str,
+}
+
+impl<W: Write> TsvWriter<W> {
+    pub fn new(w: W) -> Self {
+        Self { w, end: "\n" }
+    }
+
+    /// Ends rows with `end` instead of a newline (`\0` for `--pipe-null`).
+    /// The header row always ends with a newline.
+    pub fn with_end(mut self, end: &'static str) -> Self {
+        self.end = end;
+        self
+    }
+
+    pub fn write_header(&mut self, cols: &[&str]) -> io::Result<()> {
+        self.w.write_all(tsv_line(cols).as_bytes())
+    }
+
+    pub fn write_row(&mut self, values: &[&str]) -> io::Result<()> {
+        self.w.write_all(tsv_fields(values).as_bytes())?;
+        self.w.write_all(self.end.as_bytes())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+}
+
+/// Whether tabular formats (`tsv`, `procps`) print their header row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderMode {
+    /// Header row, then the matches.
+    #[default]
+    Emit,
+    /// Matches only (`--no-header`).
+    Suppress,
+    /// Header row only, without scanning (`--header-only`).
+    Only,
+}
+
+/// The newline-terminated header row `format` starts with; `None` for
+/// formats without one or under [`HeaderMode::Suppress`]. `columns` are
+/// the TSV column names.
+pub fn table_header(format: Format, mode: HeaderMode, columns: &[&str]) -> Option<String> {
+    if mode == HeaderMode::Suppress {
+        return None;
+    }
+    match format {
+        Format::Procps => Some(format!("{PROCPS_HEADER}\n")),
+        Format::Tsv => Some(tsv_line(columns)),
+        _ => None,
+    }
+}
+
+/// Horizontal alignment of a [`TableFormatter`] column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+}
+
+// Between two table columns.
+const TABLE_GAP: &str = "  ";
+
+/// Width assumed for `--format=table` when it is not a terminal.
+pub const DEFAULT_TABLE_WIDTH: usize = 80;
+
+/// Lays out `--format=table`: each column is `(header, alignment, width)`,
+/// the width growing to the widest cell as rows are pushed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableFormatter {
+    pub cols: Vec<(String, Align, usize)>,
+    pub rows: Vec<Vec<String>>,
+    /// Print the header and separator lines (not under `--no-header`).
+    pub header: bool,
+}
+
+impl TableFormatter {
+    /// Left-aligned columns with these headers.
+    pub fn new(headers: &[&str]) -> Self {
+        TableFormatter {
+            cols: headers
+                .iter()
+                .map(|h| (h.to_string(), Align::Left, h.chars().count()))
+                .collect(),
+            rows: Vec::new(),
+            header: true,
+        }
+    }
+
+    /// Adds a row with one cell per column; missing cells are blank.
+    pub fn push_row(&mut self, mut row: Vec<String>) {
+        row.resize(self.cols.len(), String::new());
+        for ((_, _, width), cell) in self.cols.iter_mut().zip(&row) {
+            *width = (*width).max(cell.chars().count());
+        }
+        self.rows.push(row);
+    }
+
+    /// Right-aligns the columns whose cells are all numbers, optionally
+    /// with a unit (`5120kB`, `1.5%`), or `-`.
+    pub fn align_numeric(&mut self) {
+        for (i, (_, align, _)) in self.cols.iter_mut().enumerate() {
+            let cells = || self.rows.iter().map(|row| row[i].as_str());
+            if cells().any(is_numeric) && cells().all(|c| c == MISSING || is_numeric(c)) {
+                *align = Align::Right;
+            }
+        }
+    }
+
+    /// The table as lines no wider than `width` where possible: left-aligned
+    /// columns are cut back, last first, down to their header width, and
+    /// cut cells end in `…`. Numbers are never cut.
+    pub fn render(&self, width: usize) -> String {
+        let mut widths: Vec<usize> = self.cols.iter().map(|(_, _, w)| *w).collect();
+        let total = widths.iter().sum::<usize>() + TABLE_GAP.len() * widths.len().saturating_sub(1);
+        let mut excess = total.saturating_sub(width);
+        for ((header, align, _), w) in self.cols.iter().zip(widths.iter_mut()).rev() {
+            if excess == 0 {
+                break;
+            }
+            if *align == Align::Left {
+                let cut = excess.min(w.saturating_sub(header.chars().count().max(1)));
+                *w -= cut;
+                excess -= cut;
+            }
+        }
+        let mut out = String::new();
+        if self.header {
+            let headers: Vec<&str> = self.cols.iter().map(|(h, _, _)| h.as_str()).collect();
+            self.push_line(&mut out, &headers, &widths);
+            let rules: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+            out.push_str(&rules.join(TABLE_GAP));
+            out.push('\n');
+        }
+        for row in &self.rows {
+            let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            self.push_line(&mut out, &cells, &widths);
+        }
+        out
+    }
+
+    fn push_line(&self, out: &mut String, cells: &[&str], widths: &[usize]) {
+        let mut line = String::new();
+        for (i, ((cell, (_, align, _)), w)) in cells.iter().zip(&self.cols).zip(widths).enumerate()
+        {
+            if i > 0 {
+                line.push_str(TABLE_GAP);
+            }
+            let cell = fit_cell(cell, *w);
+            match align {
+                Align::Left => line.push_str(&format!("{cell:<w$}")),
+                Align::Right => line.push_str(&format!("{cell:>w$}")),
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+}
+
+// A number with an optional unit suffix: `42`, `0.50`, `5120kB`, `12%`.
+fn is_numeric(cell: &str) -> bool {
+    let digits = cell.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%');
+    digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+fn fit_cell(cell: &str, width: usize) -> std::borrow::Cow<'_, str> {
+    if cell.chars().count() <= width {
+        return cell.into();
+    }
+    let mut cut: String = cell.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut.into()
+}
+
+/// Width `--format=table` fits to: `COLUMNS` if set to a number, else the
+/// terminal's width when writing to one, else [`DEFAULT_TABLE_WIDTH`].
+pub fn table_width(to_tty: bool) -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|w| *w > 0)
+        .or_else(|| if to_tty { terminal_width(1) } else { None })
+        .unwrap_or(DEFAULT_TABLE_WIDTH)
+}
+
+/// Whether `--format=auto` output should be colored: only on a terminal
+/// and only when `NO_COLOR` is unset or empty.
+pub fn use_color(format: Format, to_tty: bool) -> bool {
+    format == Format::Auto && to_tty && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Record separator: NUL when output is piped and `FPID_NULL_OUTPUT=1`
+/// (or `--pipe-null`), for `xargs -0`; newline otherwise.
+pub fn record_end(to_tty: bool, pipe_null: bool) -> &'static str {
+    let env_null = std::env::var_os("FPID_NULL_OUTPUT").is_some_and(|v| v == "1");
+    if !to_tty && (pipe_null || env_null) {
+        "\0"
+    } else {
+        "\n"
+    }
+}
+
+/// Bold green PID for terminal output.
+pub fn paint_pid(pid: &str) -> String {
+    format!("\x1b[1;32m{pid}\x1b[0m")
+}
+
+/// Opens the `--output` file, truncating it unless `append` is set. `mode`
+/// (e.g. `0o644`) only applies when the file is created, minus the umask.
+pub fn open_output(path: &Path, append: bool, mode: u32) -> io::Result<File> {
+    let mut opts = OpenOptions::new();
+    opts.create(true).mode(mode);
+    if append {
+        opts.append(true);
+    } else {
+        opts.write(true).truncate(true);
+    }
+    opts.open(path)
+}
+
+/// Header printed before `--format=procps` rows, as `ps` does.
+pub const PROCPS_HEADER: &str = "  PID TTY          TIME CMD";
+
+// Longest CMD printed before the command line is cut off.
+const PROCPS_CMD_WIDTH: usize = 80;
+
+/// One `ps`-compatible row: right-aligned PID, controlling terminal, CPU
+/// time (`utime + stime` over `CLK_TCK`) as `HH:MM:SS`, and the command line.
+pub fn format_procps_line(info: &ProcessInfo, stat: &ProcStat) -> String {
+    let secs = (stat.utime + stat.stime) / clk_tck().max(1);
+    let time = format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    let mut cmd = if info.cmdline.is_empty() {
+        format!("[{}]", stat.comm)
+    } else {
+        info.cmdline.join(" ")
+    };
+    if let Some((end, _)) = cmd.char_indices().nth(PROCPS_CMD_WIDTH) {
+        cmd.truncate(end);
+    }
+    format!(
+        "{:>5} {:<8} {} {}",
+        info.pid,
+        tty_name(stat.tty_nr),
+        time,
+        cmd
+    )
+}
+
+// tty_nr packs the device number as minor bits 31-20 and 7-0, major 15-8.
+fn tty_name(tty_nr: i32) -> String {
+    let dev = tty_nr as u32;
+    let major = (dev >> 8) & 0xfff;
+    let minor = (dev & 0xff) | ((dev >> 12) & 0xfff00);
+    match major {
+        0 => "?".to_string(),
+        4 if minor < 64 => format!("tty{minor}"),
+        4 => format!("ttyS{}", minor - 64),
+        136..=143 => format!("pts/{}", (major - 136) * 256 + minor),
+        _ => format!("{major}:{minor}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts() -> HashMap<String, usize> {
+        HashMap::from([
+            ("nginx".to_string(), 4),
+            ("bash".to_string(), 3),
+            ("sshd".to_string(), 1),
+        ])
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(
+            render_name_counts(&counts(), Format::Text),
+            "bash\nnginx\nsshd\n"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        assert_eq!(
+            render_name_counts(&counts(), Format::Json),
+            "[{\"name\":\"bash\",\"count\":3},{\"name\":\"nginx\",\"count\":4},{\"name\":\"sshd\",\"count\":1}]\n"
+        );
+        assert_eq!(render_name_counts(&HashMap::new(), Format::Json), "[]\n");
+    }
+
+    #[test]
+    fn test_render_ndjson() {
+        assert_eq!(
+            render_name_counts(&counts(), Format::Ndjson),
+            "{\"name\":\"bash\",\"count\":3}\n{\"name\":\"nginx\",\"count\":4}\n{\"name\":\"sshd\",\"count\":1}\n"
+        );
+        assert_eq!(render_name_counts(&HashMap::new(), Format::Ndjson), "");
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("json".parse(), Ok(Format::Json));
+        assert_eq!("ndjson".parse(), Ok(Format::Ndjson));
+        assert_eq!("text".parse(), Ok(Format::Text));
+        assert_eq!("table".parse(), Ok(Format::Table));
+        assert!("yaml".parse::<Format>().is_err());
+    }
+
+    fn info(cmdline: &[&str]) -> ProcessInfo {
+        ProcessInfo {
+            pid: 812,
+            target: 0,
+            name: "sshd".to_string(),
+            exe: None,
+            cmdline: cmdline.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_format_procps_line() {
+        let tps = clk_tck();
+        let stat = ProcStat {
+            pid: 812,
+            comm: "sshd".to_string(),
+            tty_nr: 34817, // major 136, minor 1
+            utime: 3600 * tps,
+            stime: 123 * tps,
+            ..ProcStat::default()
+        };
+        assert_eq!(
+            format_procps_line(&info(&["/usr/sbin/sshd", "-D"]), &stat),
+            "  812 pts/1    01:02:03 /usr/sbin/sshd -D"
+        );
+        let kthread = ProcStat {
+            comm: "kworker/0:1".to_string(),
+            ..ProcStat::default()
+        };
+        assert_eq!(
+            format_procps_line(&info(&[]), &kthread),
+            "  812 ?        00:00:00 [kworker/0:1]"
+        );
+        let long = "x".repeat(200);
+        let line = format_procps_line(&info(&[&long]), &kthread);
+        assert!(line.ends_with(&"x".repeat(PROCPS_CMD_WIDTH)));
+        assert!(!line.ends_with(&"x".repeat(PROCPS_CMD_WIDTH + 1)));
+    }
+
+    #[test]
+    fn test_tty_name() {
+        assert_eq!(tty_name(0), "?");
+        assert_eq!(tty_name(4 << 8 | 2), "tty2");
+        assert_eq!(tty_name(4 << 8 | 65), "ttyS1");
+        assert_eq!(tty_name(137 << 8 | 3), "pts/259");
+    }
+
+    #[test]
+    fn test_table_header() {
+        let cols = ["pid", "rss"];
+        for mode in [HeaderMode::Emit, HeaderMode::Only] {
+            assert_eq!(
+                table_header(Format::Tsv, mode, &cols).as_deref(),
+                Some("pid\trss\n")
+            );
+            assert_eq!(
+                table_header(Format::Procps, mode, &cols).as_deref(),
+                Some("  PID TTY          TIME CMD\n")
+            );
+            assert_eq!(table_header(Format::Text, mode, &cols), None);
+        }
+        assert_eq!(table_header(Format::Tsv, HeaderMode::Suppress, &cols), None);
+        assert_eq!(
+            table_header(Format::Procps, HeaderMode::Suppress, &cols),
+            None
+        );
+        assert_eq!(HeaderMode::default(), HeaderMode::Emit);
+    }
+
+    #[test]
+    fn test_tsv_writer() {
+        let mut w = TsvWriter::new(Vec::new());
+        w.write_header(&["pid", "pss", "cpus"]).unwrap();
+        w.write_row(&["42", "2048kB", "0-3"]).unwrap();
+        w.write_row(&["43", "a\tb", "x\ny"]).unwrap();
+        assert_eq!(
+            String::from_utf8(w.into_inner()).unwrap(),
+            "pid\tpss\tcpus\n42\t2048kB\t0-3\n43\ta\\tb\tx\\ny\n"
+        );
+
+        let mut w = TsvWriter::new(Vec::new()).with_end("\0");
+        w.write_header(&["pid", "cwd"]).unwrap();
+        w.write_row(&["7", "C:\\tmp\r"]).unwrap();
+        w.write_row(&[]).unwrap();
+        assert_eq!(
+            String::from_utf8(w.into_inner()).unwrap(),
+            "pid\tcwd\n7\tC:\\\\tmp\\r\0\0"
+        );
+    }
+
+    #[test]
+    fn test_tsv_line() {
+        assert_eq!(tsv_line(&["pid", "pss", "cpus"]), "pid\tpss\tcpus\n");
+        assert_eq!(tsv_line(&["42", "2048kB", "0-3"]), "42\t2048kB\t0-3\n");
+        assert_eq!(tsv_line(&["43", "a\tb", "x\ny"]), "43\ta\\tb\tx\\ny\n");
+        assert_eq!(tsv_line(&["dos\r\n"]), "dos\\r\\n\n");
+        // A literal backslash-t stays distinct from an escaped tab.
+        assert_eq!(tsv_line(&["C:\\tmp", "a\tb"]), "C:\\\\tmp\ta\\tb\n");
+        assert_eq!(tsv_line(&[]), "\n");
+    }
+
+    fn table() -> TableFormatter {
+        let mut t = TableFormatter::new(&["PID", "NAME", "RSS", "CMDLINE"]);
+        for row in [
+            ["1", "systemd", "12800kB", "/sbin/init splash"],
+            ["812", "sshd", "-", "sshd: /usr/sbin/sshd -D [listener]"],
+            ["40213", "bash", "5120kB", "-bash"],
+        ] {
+            t.push_row(row.iter().map(|s| s.to_string()).collect());
+        }
+        t.align_numeric();
+        t
+    }
+
+    #[test]
+    fn test_table_alignment() {
+        let t = table();
+        let aligns: Vec<Align> = t.cols.iter().map(|(_, a, _)| *a).collect();
+        assert_eq!(
+            aligns,
+            vec![Align::Right, Align::Left, Align::Right, Align::Left]
+        );
+        assert_eq!(
+            t.render(80),
+            "  PID  NAME         RSS  CMDLINE\n\
+             -----  -------  -------  ----------------------------------\n\
+             \u{20}   1  systemd  12800kB  /sbin/init splash\n\
+             \u{20} 812  sshd           -  sshd: /usr/sbin/sshd -D [listener]\n\
+             40213  bash      5120kB  -bash\n"
+        );
+        let mut t = table();
+        t.header = false;
+        assert!(t.render(80).starts_with("    1  systemd"));
+    }
+
+    #[test]
+    fn test_table_truncation() {
+        let t = table();
+        let out = t.render(40);
+        assert!(out.lines().all(|l| l.chars().count() <= 40), "{out}");
+        assert!(
+            out.contains("  812  sshd           -  sshd: /usr/sbi…\n"),
+            "{out}"
+        );
+        // Too narrow for every column: strings stop at their header width
+        // and numbers are left whole.
+        let out = t.render(10);
+        assert!(out.contains("40213  bash   5120kB  -bash\n"), "{out}");
+        assert!(out.contains("    1  sys…  12800kB  /sbin/…\n"), "{out}");
+    }
+
+    #[test]
+    fn test_is_numeric() {
+        for cell in ["42", "0.50", "5120kB", "12%"] {
+            assert!(is_numeric(cell), "{cell}");
+        }
+        for cell in ["-", "", "kB", "0-3", "nginx", "1,2"] {
+            assert!(!is_numeric(cell), "{cell}");
+        }
+    }
+
+    #[test]
+    fn test_open_output() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("fpid-output-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        writeln!(open_output(&path, false, 0o600).unwrap(), "1").unwrap();
+        writeln!(open_output(&path, true, 0o600).unwrap(), "2").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n2\n");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        writeln!(open_output(&path, false, 0o600).unwrap(), "3").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "3\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_pipe_mode_helpers() {
+        assert!(!use_color(Format::Auto, false));
+        assert!(!use_color(Format::Text, true));
+        assert_eq!(record_end(true, true), "\n");
+        assert_eq!(record_end(false, true), "\0");
+        assert_eq!(paint_pid("42"), "\x1b[1;32m42\x1b[0m");
+    }
+
+    #[test]
+    fn test_process_json_lines() {
+        let mut nginx = info(&["nginx: master process", "-g", "daemon off;"]);
+        nginx.pid = 10;
+        nginx.name = "nginx".to_string();
+        nginx.exe = Some("/usr/sbin/nginx".into());
+        let mut odd = info(&["a\"b", "line1\nline2"]);
+        odd.target = 1;
+        let stream: String = [&nginx, &odd]
+            .iter()
+            .map(|p| process_json(p) + "\n")
+            .collect();
+
+        let lines: Vec<&str> = stream.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"pid":10,"target":0,"name":"nginx","exe":"/usr/sbin/nginx","cmdline":["nginx: master process","-g","daemon off;"]}"#,
+                r#"{"pid":812,"target":1,"name":"sshd","exe":null,"cmdline":["a\"b","line1\nline2"]}"#,
+            ]
+        );
+    }
+}
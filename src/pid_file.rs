@@ -0,0 +1,102 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use crate::error::FpidError;
+
+/// The PID on the first line of a PID file, surrounding whitespace ignored.
+/// Anything but a positive number there is `FpidError::InvalidPidFile`.
+pub fn read_pid_file(path: &Path) -> Result<u32, FpidError> {
+    let mut line = String::new();
+    BufReader::new(File::open(path)?).read_line(&mut line)?;
+    let line = line.trim();
+    line.parse::<u32>()
+        .ok()
+        .filter(|pid| *pid > 0)
+        .ok_or_else(|| FpidError::InvalidPidFile(line.to_string()))
+}
+
+/// Writes `pid` and a newline to `path`, creating or truncating it. `mode`
+/// only applies when the file is created, minus the umask, as with
+/// `--output-mode`. With `create_dir`, missing parent directories are
+/// created first.
+pub fn write_pid_file(path: &Path, pid: u32, mode: u32, create_dir: bool) -> io::Result<()> {
+    if create_dir && let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(path)?;
+    writeln!(file, "{}", pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_read_pid_file() {
+        let dir = std::env::temp_dir().join(format!("fpid-pid-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        assert_eq!(read_pid_file(&write("ok", "4242\n")).unwrap(), 4242);
+        assert_eq!(
+            read_pid_file(&write("spaces", "  77 \nstale\n")).unwrap(),
+            77
+        );
+        for (name, content) in [
+            ("empty", ""),
+            ("zero", "0\n"),
+            ("text", "nginx\n"),
+            ("neg", "-1"),
+        ] {
+            assert!(
+                matches!(
+                    read_pid_file(&write(name, content)),
+                    Err(FpidError::InvalidPidFile(_))
+                ),
+                "{content:?}"
+            );
+        }
+        match read_pid_file(&dir.join("missing")) {
+            Err(FpidError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+            other => panic!("unexpected {other:?}"),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_pid_file() {
+        let dir = std::env::temp_dir().join(format!("fpid-write-pid-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("run/app.pid");
+        let err = write_pid_file(&path, 4242, 0o644, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        write_pid_file(&path, 4242, 0o600, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "4242\n");
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert_eq!(read_pid_file(&path).unwrap(), 4242);
+
+        // Truncated on rewrite; the mode of an existing file is kept.
+        write_pid_file(&path, 7, 0o644, false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "7\n");
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+use crate::reader::{FsProcReader, ProcReader};
+
+/// The kernel's default `pid_max`, assumed when
+/// `/proc/sys/kernel/pid_max` cannot be read.
+pub const DEFAULT_PID_MAX: u32 = 32768;
+
+/// Highest PID the kernel hands out, plus one.
+pub fn read_pid_max() -> u32 {
+    read_pid_max_with(&FsProcReader::default())
+}
+
+pub fn read_pid_max_with(reader: &impl ProcReader) -> u32 {
+    reader
+        .read_root("sys/kernel/pid_max")
+        .ok()
+        .and_then(|buf| std::str::from_utf8(&buf).ok()?.trim().parse().ok())
+        .filter(|max| *max > 0)
+        .unwrap_or(DEFAULT_PID_MAX)
+}
+
+/// `--min-pid` / `--max-pid` bounds that can never match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidRangeError {
+    MinAbovePidMax { min: u32, pid_max: u32 },
+    MinAboveMax { min: u32, max: u32 },
+}
+
+impl fmt::Display for PidRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PidRangeError::MinAbovePidMax { min, pid_max } => {
+                write!(f, "--min-pid {} exceeds pid_max {}", min, pid_max)
+            }
+            PidRangeError::MinAboveMax { min, max } => {
+                write!(f, "--min-pid {} is greater than --max-pid {}", min, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PidRangeError {}
+
+/// Inclusive PID bounds from `--min-pid` / `--max-pid`, with the upper one
+/// clamped to `pid_max`.
+pub fn pid_range(
+    min: Option<u32>,
+    max: Option<u32>,
+    pid_max: u32,
+) -> Result<(u32, u32), PidRangeError> {
+    let min = min.unwrap_or(0);
+    if min > pid_max {
+        return Err(PidRangeError::MinAbovePidMax { min, pid_max });
+    }
+    if let Some(max) = max
+        && min > max
+    {
+        return Err(PidRangeError::MinAboveMax { min, max });
+    }
+    Ok((min, max.map_or(pid_max, |max| max.min(pid_max))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_read_pid_max() {
+        let reader = MockProcReader::new().root_file("sys/kernel/pid_max", "4194304\n");
+        assert_eq!(read_pid_max_with(&reader), 4194304);
+        for bad in ["", "0\n", "lots\n"] {
+            let reader = MockProcReader::new().root_file("sys/kernel/pid_max", bad);
+            assert_eq!(read_pid_max_with(&reader), DEFAULT_PID_MAX, "{bad:?}");
+        }
+        assert_eq!(read_pid_max_with(&MockProcReader::new()), DEFAULT_PID_MAX);
+    }
+
+    #[test]
+    fn test_pid_range() {
+        assert_eq!(pid_range(None, None, 32768), Ok((0, 32768)));
+        assert_eq!(pid_range(Some(100), Some(200), 32768), Ok((100, 200)));
+        assert_eq!(pid_range(Some(100), Some(99999), 32768), Ok((100, 32768)));
+        assert_eq!(pid_range(Some(300), Some(300), 32768), Ok((300, 300)));
+        assert_eq!(
+            pid_range(Some(40000), None, 32768),
+            Err(PidRangeError::MinAbovePidMax {
+                min: 40000,
+                pid_max: 32768
+            })
+        );
+        assert_eq!(
+            pid_range(Some(200), Some(100), 32768),
+            Err(PidRangeError::MinAboveMax { min: 200, max: 100 })
+        );
+    }
+}
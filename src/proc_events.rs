@@ -0,0 +1,235 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+use std::time::Duration;
+
+use crate::sys::{netlink_socket, wait_readable};
+
+// Kernel ABI from <linux/netlink.h>, <linux/connector.h> and
+// <linux/cn_proc.h>.
+const NETLINK_CONNECTOR: i32 = 11;
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const NLMSG_DONE: u16 = 3;
+const NLMSG_HDRLEN: usize = 16;
+const CN_MSG_LEN: usize = 20;
+
+const PROC_EVENT_FORK: u32 = 0x0000_0001;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+/// Offset of `event_data` in `struct proc_event`, after `what`, `cpu` and
+/// the 64-bit timestamp.
+const EVENT_DATA: usize = 16;
+
+// The multicast group overflowed because we read too slowly; events were
+// dropped.
+#[cfg(any(target_arch = "mips", target_arch = "mips64", target_arch = "sparc64"))]
+const ENOBUFS: i32 = 55;
+#[cfg(not(any(target_arch = "mips", target_arch = "mips64", target_arch = "sparc64")))]
+const ENOBUFS: i32 = 105;
+
+/// A process lifecycle event from the proc connector. Thread events are
+/// dropped; PIDs are thread-group IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcEvent {
+    Fork { parent: u32, child: u32 },
+    Exec { pid: u32 },
+    Exit { pid: u32 },
+}
+
+impl ProcEvent {
+    /// Decodes one `struct proc_event`; `None` for other event types and
+    /// for threads.
+    fn parse(data: &[u8]) -> Option<ProcEvent> {
+        let word = |i: usize| -> Option<u32> {
+            let bytes = data.get(i..i + 4)?;
+            Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+        };
+        let field = |n: usize| word(EVENT_DATA + 4 * n);
+        match word(0)? {
+            PROC_EVENT_FORK => {
+                // parent_pid, parent_tgid, child_pid, child_tgid
+                let child = field(3)?;
+                (field(2)? == child).then_some(ProcEvent::Fork {
+                    parent: field(1)?,
+                    child,
+                })
+            }
+            PROC_EVENT_EXEC => Some(ProcEvent::Exec { pid: field(1)? }),
+            // process_pid, process_tgid
+            PROC_EVENT_EXIT => {
+                let pid = field(1)?;
+                (field(0)? == pid).then_some(ProcEvent::Exit { pid })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Splits a netlink datagram into its messages and decodes the connector
+/// payload of each. Truncated or foreign messages are skipped.
+pub fn parse_proc_events(buf: &[u8]) -> Vec<ProcEvent> {
+    let mut events = Vec::new();
+    let mut rest = buf;
+    while rest.len() >= NLMSG_HDRLEN {
+        let len = u32::from_ne_bytes(rest[0..4].try_into().unwrap()) as usize;
+        if len < NLMSG_HDRLEN || len > rest.len() {
+            break;
+        }
+        let msg = &rest[NLMSG_HDRLEN..len];
+        if let Some(cn) = msg.get(..CN_MSG_LEN) {
+            let idx = u32::from_ne_bytes(cn[0..4].try_into().unwrap());
+            let val = u32::from_ne_bytes(cn[4..8].try_into().unwrap());
+            if (idx, val) == (CN_IDX_PROC, CN_VAL_PROC)
+                && let Some(event) = ProcEvent::parse(&msg[CN_MSG_LEN..])
+            {
+                events.push(event);
+            }
+        }
+        // NLMSG_ALIGN
+        rest = rest.get(len.next_multiple_of(4)..).unwrap_or_default();
+    }
+    events
+}
+
+/// The netlink message that asks the connector for process events: an
+/// `nlmsghdr`, a `cn_msg` and the `PROC_CN_MCAST_LISTEN` op.
+fn listen_message() -> Vec<u8> {
+    let len = NLMSG_HDRLEN + CN_MSG_LEN + 4;
+    let mut msg = Vec::with_capacity(len);
+    msg.extend_from_slice(&(len as u32).to_ne_bytes());
+    msg.extend_from_slice(&NLMSG_DONE.to_ne_bytes());
+    msg.extend_from_slice(&0u16.to_ne_bytes()); // flags
+    msg.extend_from_slice(&0u32.to_ne_bytes()); // seq
+    msg.extend_from_slice(&std::process::id().to_ne_bytes());
+    msg.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+    msg.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+    msg.extend_from_slice(&0u32.to_ne_bytes()); // seq
+    msg.extend_from_slice(&0u32.to_ne_bytes()); // ack
+    msg.extend_from_slice(&4u16.to_ne_bytes()); // payload length
+    msg.extend_from_slice(&0u16.to_ne_bytes()); // flags
+    msg.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+    msg
+}
+
+/// Whether a read error means the kernel dropped events because the
+/// socket buffer was full; the caller should rescan to catch up.
+pub fn is_overrun(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(ENOBUFS)
+}
+
+/// Fork, exec and exit events from the kernel's proc connector
+/// (`--watch-events`). Subscribing needs root or `CAP_NET_ADMIN`.
+#[derive(Debug)]
+pub struct ProcEventListener {
+    sock: File,
+    buf: Vec<u8>,
+}
+
+impl ProcEventListener {
+    /// Joins the proc connector group and turns event reporting on.
+    /// Fails with `PermissionDenied` without the privileges.
+    pub fn subscribe() -> io::Result<Self> {
+        let mut sock = netlink_socket(NETLINK_CONNECTOR, CN_IDX_PROC)?;
+        sock.write_all(&listen_message())?;
+        Ok(Self {
+            sock,
+            buf: vec![0; 8192],
+        })
+    }
+
+    /// Events from the next datagram, or none if nothing arrived within
+    /// `timeout` or a signal came in. See [`is_overrun`] for the one error
+    /// worth recovering from.
+    pub fn next_events(&mut self, timeout: Duration) -> io::Result<Vec<ProcEvent>> {
+        if !wait_readable(self.sock.as_raw_fd(), timeout)? {
+            return Ok(Vec::new());
+        }
+        let n = self.sock.read(&mut self.buf)?;
+        Ok(parse_proc_events(&self.buf[..n]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A netlink datagram carrying one proc_event with `data` as its
+    /// event_data words, laid out as the kernel sends it.
+    fn datagram(what: u32, data: &[u32]) -> Vec<u8> {
+        let mut event = Vec::new();
+        event.extend_from_slice(&what.to_ne_bytes());
+        event.extend_from_slice(&3u32.to_ne_bytes()); // cpu
+        event.extend_from_slice(&123_456_789u64.to_ne_bytes());
+        for word in data {
+            event.extend_from_slice(&word.to_ne_bytes());
+        }
+        let mut msg = listen_message();
+        msg.truncate(NLMSG_HDRLEN + CN_MSG_LEN - 4);
+        msg.extend_from_slice(&(event.len() as u16).to_ne_bytes());
+        msg.extend_from_slice(&0u16.to_ne_bytes());
+        msg.extend_from_slice(&event);
+        let len = msg.len() as u32;
+        msg[0..4].copy_from_slice(&len.to_ne_bytes());
+        msg.resize(msg.len().next_multiple_of(4), 0);
+        msg
+    }
+
+    #[test]
+    fn test_parse_proc_events() {
+        assert_eq!(
+            parse_proc_events(&datagram(PROC_EVENT_FORK, &[100, 100, 4321, 4321])),
+            vec![ProcEvent::Fork {
+                parent: 100,
+                child: 4321
+            }]
+        );
+        assert_eq!(
+            parse_proc_events(&datagram(PROC_EVENT_EXEC, &[4321, 4321])),
+            vec![ProcEvent::Exec { pid: 4321 }]
+        );
+        // exit_code, exit_signal, parent_pid, parent_tgid follow.
+        assert_eq!(
+            parse_proc_events(&datagram(PROC_EVENT_EXIT, &[4321, 4321, 0, 17, 100, 100])),
+            vec![ProcEvent::Exit { pid: 4321 }]
+        );
+        // Several messages in one datagram.
+        let mut both = datagram(PROC_EVENT_EXEC, &[7, 7]);
+        both.extend(datagram(PROC_EVENT_EXIT, &[8, 8, 0, 0]));
+        assert_eq!(
+            parse_proc_events(&both),
+            vec![ProcEvent::Exec { pid: 7 }, ProcEvent::Exit { pid: 8 }]
+        );
+    }
+
+    #[test]
+    fn test_parse_proc_events_skipped() {
+        // A new thread and a thread exiting.
+        assert!(parse_proc_events(&datagram(PROC_EVENT_FORK, &[100, 100, 4322, 100])).is_empty());
+        assert!(parse_proc_events(&datagram(PROC_EVENT_EXIT, &[4322, 100, 0, 0])).is_empty());
+        // PROC_EVENT_NONE (the subscribe ack) and PROC_EVENT_UID.
+        assert!(parse_proc_events(&datagram(0, &[0])).is_empty());
+        assert!(parse_proc_events(&datagram(4, &[4321, 4321, 0, 0])).is_empty());
+        let mut cut = datagram(PROC_EVENT_EXEC, &[7, 7]);
+        cut.truncate(30);
+        assert!(parse_proc_events(&cut).is_empty());
+        assert!(parse_proc_events(b"").is_empty());
+    }
+
+    #[test]
+    fn test_listen_message() {
+        let msg = listen_message();
+        assert_eq!(msg.len(), 40);
+        assert_eq!(&msg[0..4], &40u32.to_ne_bytes());
+        assert_eq!(&msg[36..40], &PROC_CN_MCAST_LISTEN.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_is_overrun() {
+        assert!(is_overrun(&io::Error::from_raw_os_error(ENOBUFS)));
+        assert!(!is_overrun(&io::Error::from(
+            io::ErrorKind::PermissionDenied
+        )));
+    }
+}
@@ -0,0 +1,238 @@
+use std::cell::OnceCell;
+use std::time::Duration;
+
+use crate::io_info::IoInfo;
+use crate::maps::read_stack_usage;
+use crate::meminfo::SystemMemInfo;
+use crate::net::{SocketInodeDb, build_socket_inode_db_with};
+use crate::oom::read_oom_score_with;
+use crate::reader::ProcReader;
+use crate::scan::ProcessInfo;
+use crate::schedstat::SchedInfo;
+use crate::smaps::SmapsRollup;
+use crate::stat::{
+    ProcStat, age_from_ticks, cpu_lifetime_pct, parse_btime, parse_proc_stat_fields, parse_uptime,
+    per_second,
+};
+use crate::statm::StatmInfo;
+use crate::status::{StatusInfo, ctx_rate};
+use crate::sys::clk_tck;
+
+/// System-wide values read at most once per scan.
+#[derive(Debug, Default)]
+pub struct SystemCache {
+    clk_tck: OnceCell<u64>,
+    uptime: OnceCell<Option<f64>>,
+    boot_time: OnceCell<Option<u64>>,
+    meminfo: OnceCell<Option<SystemMemInfo>>,
+    sockets: OnceCell<SocketInodeDb>,
+}
+
+impl SystemCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clk_tck(&self) -> u64 {
+        *self.clk_tck.get_or_init(clk_tck)
+    }
+
+    pub fn uptime(&self, reader: &impl ProcReader) -> Option<f64> {
+        *self
+            .uptime
+            .get_or_init(|| parse_uptime(&reader.read_root("uptime").ok()?))
+    }
+
+    /// Boot time in seconds since the epoch, from `/proc/stat`.
+    pub fn boot_time(&self, reader: &impl ProcReader) -> Option<u64> {
+        *self
+            .boot_time
+            .get_or_init(|| parse_btime(&reader.read_root("stat").ok()?))
+    }
+
+    /// `/proc/meminfo`, read once per scan rather than per process.
+    pub fn meminfo(&self, reader: &impl ProcReader) -> Option<&SystemMemInfo> {
+        self.meminfo
+            .get_or_init(|| SystemMemInfo::read_with(reader).ok())
+            .as_ref()
+    }
+
+    /// Socket inodes of `/proc/net/{tcp,tcp6,udp,udp6,unix}`, read once per
+    /// scan and shared by every process.
+    pub fn sockets(&self, reader: &impl ProcReader) -> &SocketInodeDb {
+        self.sockets
+            .get_or_init(|| build_socket_inode_db_with(reader))
+    }
+}
+
+/// A matched process whose `/proc` files are read lazily and cached, so
+/// several output columns or filters share one read of each file.
+pub struct Process<'a, R: ProcReader> {
+    pid: u32,
+    reader: &'a R,
+    sys: &'a SystemCache,
+    stat: OnceCell<Option<ProcStat>>,
+    smaps: OnceCell<Option<SmapsRollup>>,
+    oom: OnceCell<Option<(i32, i32)>>,
+    statm: OnceCell<Option<StatmInfo>>,
+    status: OnceCell<Option<StatusInfo>>,
+    io: OnceCell<Option<IoInfo>>,
+    sched: OnceCell<Option<SchedInfo>>,
+    info: OnceCell<ProcessInfo>,
+    environ: OnceCell<Option<Vec<u8>>>,
+}
+
+impl<'a, R: ProcReader> Process<'a, R> {
+    pub fn new(pid: u32, reader: &'a R, sys: &'a SystemCache) -> Self {
+        Self {
+            pid,
+            reader,
+            sys,
+            stat: OnceCell::new(),
+            smaps: OnceCell::new(),
+            oom: OnceCell::new(),
+            statm: OnceCell::new(),
+            status: OnceCell::new(),
+            io: OnceCell::new(),
+            sched: OnceCell::new(),
+            info: OnceCell::new(),
+            environ: OnceCell::new(),
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn reader(&self) -> &'a R {
+        self.reader
+    }
+
+    pub fn sys(&self) -> &'a SystemCache {
+        self.sys
+    }
+
+    /// Name, exe and argv. `target` is always 0: a `Process` does not know
+    /// which target matched it.
+    pub fn info(&self) -> &ProcessInfo {
+        self.info
+            .get_or_init(|| ProcessInfo::read(self.pid, 0, self.reader))
+    }
+
+    /// Raw `/proc/<pid>/environ`; `None` for other users' processes.
+    pub fn environ(&self) -> Option<&[u8]> {
+        self.environ
+            .get_or_init(|| self.reader.read(self.pid, "environ").ok())
+            .as_deref()
+    }
+
+    /// Parsed `/proc/<pid>/stat`.
+    pub fn stat(&self) -> Option<&ProcStat> {
+        self.stat
+            .get_or_init(|| {
+                let buf = self.reader.read(self.pid, "stat").ok()?;
+                parse_proc_stat_fields(&buf).ok()
+            })
+            .as_ref()
+    }
+
+    /// Memory breakdown from `smaps_rollup` (or `smaps`).
+    pub fn smaps(&self) -> Option<&SmapsRollup> {
+        self.smaps
+            .get_or_init(|| SmapsRollup::read_with(self.pid, self.reader).ok())
+            .as_ref()
+    }
+
+    /// Parsed `/proc/<pid>/status`.
+    pub fn status(&self) -> Option<&StatusInfo> {
+        self.status
+            .get_or_init(|| StatusInfo::read_with(self.pid, self.reader).ok())
+            .as_ref()
+    }
+
+    /// I/O counters; usually `None` for other users' processes.
+    pub fn io(&self) -> Option<&IoInfo> {
+        self.io
+            .get_or_init(|| IoInfo::read_with(self.pid, self.reader).ok())
+            .as_ref()
+    }
+
+    pub fn sched(&self) -> Option<&SchedInfo> {
+        self.sched
+            .get_or_init(|| SchedInfo::read_with(self.pid, self.reader).ok())
+            .as_ref()
+    }
+
+    pub fn statm(&self) -> Option<&StatmInfo> {
+        self.statm
+            .get_or_init(|| StatmInfo::read_with(self.pid, self.reader).ok())
+            .as_ref()
+    }
+
+    /// `(oom_score, oom_score_adj)`.
+    pub fn oom(&self) -> Option<(i32, i32)> {
+        *self
+            .oom
+            .get_or_init(|| read_oom_score_with(self.pid, self.reader).ok())
+    }
+
+    pub fn age(&self) -> Option<Duration> {
+        let starttime = self.stat()?.starttime;
+        let uptime = self.sys.uptime(self.reader)?;
+        Some(age_from_ticks(starttime, uptime, self.sys.clk_tck()))
+    }
+
+    /// `utime + stime` in seconds.
+    pub fn cpu_seconds(&self) -> Option<f64> {
+        Some(self.stat()?.cpu_seconds(self.sys.clk_tck()))
+    }
+
+    /// `utime` in seconds.
+    pub fn user_seconds(&self) -> Option<f64> {
+        Some(self.stat()?.user_seconds(self.sys.clk_tck()))
+    }
+
+    /// `stime` in seconds.
+    pub fn sys_seconds(&self) -> Option<f64> {
+        Some(self.stat()?.sys_seconds(self.sys.clk_tck()))
+    }
+
+    // Age in seconds for lifetime rates; below one clock tick it is 0, so
+    // the rates below come out as 0 instead of dividing by noise.
+    fn lifetime_secs(&self) -> Option<f64> {
+        let age = self.age()?.as_secs_f64();
+        Some(if age < 1.0 / self.sys.clk_tck().max(1) as f64 {
+            0.0
+        } else {
+            age
+        })
+    }
+
+    /// [`cpu_lifetime_pct`]; a process younger than one clock tick counts
+    /// as 0%.
+    pub fn cpu_lifetime_pct(&self) -> Option<f64> {
+        Some(cpu_lifetime_pct(self.cpu_seconds()?, self.lifetime_secs()?))
+    }
+
+    /// `(minflt, majflt)` per second of the process's lifetime.
+    pub fn fault_rates(&self) -> Option<(f64, f64)> {
+        let stat = self.stat()?;
+        let age = self.lifetime_secs()?;
+        Some((per_second(stat.minflt, age), per_second(stat.majflt, age)))
+    }
+
+    /// [`ctx_rate`] from status and stat.
+    pub fn ctx_rate(&self) -> Option<f64> {
+        let voluntary = self.status()?.voluntary_ctxt_switches;
+        Some(ctx_rate(voluntary, self.cpu_seconds()?))
+    }
+
+    /// Main-thread stack size in kB: `VmStk` from status, falling back to
+    /// the size of the `[stack]` mapping.
+    pub fn stack_kb(&self) -> Option<u64> {
+        if let Some(kb) = self.status().and_then(|st| st.vm_stk) {
+            return Some(kb);
+        }
+        read_stack_usage(self.pid, self.reader).ok()?.main_kb
+    }
+}
@@ -0,0 +1,464 @@
+use std::ffi::OsString;
+use std::fs::{File, read_dir, read_link};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Access to per-process files under a proc root.
+///
+/// Parsers take `&impl ProcReader` instead of touching `/proc` directly so they
+/// can run against a fixture or an in-memory mock.
+pub trait ProcReader {
+    /// Opens `<root>/<pid>/<file>` for streaming reads.
+    fn open(&self, pid: u32, file: &str) -> io::Result<Box<dyn Read + '_>>;
+
+    /// Resolves the symlink `<root>/<pid>/<file>` (e.g. `exe`, `cwd`, `fd/3`).
+    fn read_link(&self, pid: u32, file: &str) -> io::Result<PathBuf>;
+
+    /// Lists entry names of the directory `<root>/<pid>/<dir>` (e.g. `fd`).
+    fn list_dir(&self, pid: u32, dir: &str) -> io::Result<Vec<OsString>>;
+
+    /// Numeric entries of the proc root, in directory order.
+    fn pids(&self) -> io::Result<Vec<u32>>;
+
+    /// Reads a system-wide file `<root>/<file>` (e.g. `uptime`, `net/tcp`).
+    fn read_root(&self, file: &str) -> io::Result<Vec<u8>>;
+
+    /// Reads `<root>/<pid>/<file>` in full.
+    fn read(&self, pid: u32, file: &str) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(4096);
+        self.open(pid, file)?.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Default `--max-proc-read-bytes`.
+pub const DEFAULT_MAX_READ_BYTES: usize = 65536;
+
+// Files a process can grow at will (argv, environment), plus status. Maps
+// and smaps are legitimately large and never capped.
+const LIMITED_FILES: &[&str] = &["cmdline", "environ", "status"];
+
+/// Reads at most `max_bytes` of `path`; anything beyond is silently dropped.
+pub fn read_limited(path: &Path, max_bytes: usize) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(max_bytes.min(4096));
+    File::open(path)?
+        .take(max_bytes as u64)
+        .read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Where procfs is normally mounted.
+pub const DEFAULT_PROC_ROOT: &str = "/proc";
+
+/// Environment variable consulted by [`resolve_proc_root`].
+pub const PROC_ROOT_ENV: &str = "FPID_PROC_ROOT";
+
+/// The proc root to scan: `--proc-root` if given, else a non-empty
+/// `FPID_PROC_ROOT`, else `/proc`.
+pub fn resolve_proc_root(cli_arg: Option<&Path>) -> PathBuf {
+    resolve_proc_root_from(cli_arg, std::env::var_os(PROC_ROOT_ENV))
+}
+
+/// [`resolve_proc_root`] with the `FPID_PROC_ROOT` value passed in.
+pub fn resolve_proc_root_from(cli: Option<&Path>, env: Option<OsString>) -> PathBuf {
+    if let Some(root) = cli {
+        return root.to_path_buf();
+    }
+    match env {
+        Some(root) if !root.is_empty() => PathBuf::from(root),
+        _ => PathBuf::from(DEFAULT_PROC_ROOT),
+    }
+}
+
+/// [`ProcReader`] backed by a real procfs mount, `/proc` by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsProcReader {
+    root: PathBuf,
+    read_limit: Option<usize>,
+}
+
+impl FsProcReader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            read_limit: None,
+        }
+    }
+
+    /// Caps reads of `cmdline`, `environ` and `status` at `max` bytes, so a
+    /// process with a huge argv can't make a scan allocate without bound.
+    /// A cut-off cmdline still parses: the buffer end ends the last argument.
+    pub fn with_read_limit(mut self, max: Option<usize>) -> Self {
+        self.read_limit = max;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn limit_for(&self, file: &str) -> Option<usize> {
+        self.read_limit.filter(|_| LIMITED_FILES.contains(&file))
+    }
+
+    fn path(&self, pid: u32, file: &str) -> PathBuf {
+        let mut path = self.root.clone();
+        path.push(pid.to_string());
+        path.push(file);
+        path
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn trace_open(pid: u32, file: &str, err: Option<&io::Error>) {
+    match err {
+        None => crate::log_debug!("scan_pid{{pid={}}}: open {}", pid, file),
+        Some(e) => crate::log_debug!("scan_pid{{pid={}}}: open {} failed: {}", pid, file, e),
+    }
+}
+
+impl Default for FsProcReader {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROC_ROOT)
+    }
+}
+
+impl ProcReader for FsProcReader {
+    fn pids(&self) -> io::Result<Vec<u32>> {
+        let mut pids = Vec::with_capacity(512);
+        for entry in read_dir(&self.root)?.flatten() {
+            let name = entry.file_name();
+            match name.to_str() {
+                Some(s) if is_all_digits(s) => {
+                    if let Ok(pid) = s.parse() {
+                        pids.push(pid);
+                    }
+                }
+                _ => continue,
+            }
+        }
+        Ok(pids)
+    }
+
+    fn open(&self, pid: u32, file: &str) -> io::Result<Box<dyn Read + '_>> {
+        let res = File::open(self.path(pid, file));
+        #[cfg(feature = "tracing")]
+        trace_open(pid, file, res.as_ref().err());
+        match self.limit_for(file) {
+            Some(max) => Ok(Box::new(res?.take(max as u64))),
+            None => Ok(Box::new(res?)),
+        }
+    }
+
+    fn read(&self, pid: u32, file: &str) -> io::Result<Vec<u8>> {
+        let Some(max) = self.limit_for(file) else {
+            let mut buf = Vec::with_capacity(4096);
+            self.open(pid, file)?.read_to_end(&mut buf)?;
+            return Ok(buf);
+        };
+        let res = read_limited(&self.path(pid, file), max);
+        #[cfg(feature = "tracing")]
+        trace_open(pid, file, res.as_ref().err());
+        res
+    }
+
+    fn read_link(&self, pid: u32, file: &str) -> io::Result<PathBuf> {
+        let res = read_link(self.path(pid, file));
+        #[cfg(feature = "tracing")]
+        trace_open(pid, file, res.as_ref().err());
+        res
+    }
+
+    fn read_root(&self, file: &str) -> io::Result<Vec<u8>> {
+        std::fs::read(self.root.join(file))
+    }
+
+    fn list_dir(&self, pid: u32, dir: &str) -> io::Result<Vec<OsString>> {
+        read_dir(self.path(pid, dir))?
+            .map(|entry| entry.map(|e| e.file_name()))
+            .collect()
+    }
+}
+
+/// True for non-empty strings of ASCII digits (PID directory names).
+pub fn is_all_digits(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    !bytes.is_empty() && all_digits(bytes)
+}
+
+// SWAR: check 8 bytes per step. A byte is a digit iff its high nibble is 3
+// and adding 6 does not carry out of the low nibble (low nibble <= 9). With
+// the high nibble pinned to 3 the add cannot carry into the next byte.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn all_digits(bytes: &[u8]) -> bool {
+    const HIGH: u64 = 0xF0F0_F0F0_F0F0_F0F0;
+    const ZEROS: u64 = 0x3030_3030_3030_3030;
+    const SIXES: u64 = 0x0606_0606_0606_0606;
+
+    let chunks = bytes.chunks_exact(8);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(chunk);
+        let x = u64::from_le_bytes(word);
+        if x & HIGH != ZEROS || (x + SIXES) & HIGH != ZEROS {
+            return false;
+        }
+    }
+    all_digits_bytewise(tail)
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn all_digits(bytes: &[u8]) -> bool {
+    all_digits_bytewise(bytes)
+}
+
+fn all_digits_bytewise(bytes: &[u8]) -> bool {
+    bytes.iter().all(|b: &u8| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// In-memory proc tree for unit tests.
+    #[derive(Default)]
+    pub struct MockProcReader {
+        files: HashMap<(u32, String), Vec<u8>>,
+        links: HashMap<(u32, String), PathBuf>,
+        errors: HashMap<(u32, String), io::ErrorKind>,
+        root_files: HashMap<String, Vec<u8>>,
+    }
+
+    impl MockProcReader {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn file(mut self, pid: u32, file: &str, content: impl AsRef<[u8]>) -> Self {
+            self.files
+                .insert((pid, file.to_string()), content.as_ref().to_vec());
+            self
+        }
+
+        pub fn link(mut self, pid: u32, file: &str, target: impl Into<PathBuf>) -> Self {
+            self.links.insert((pid, file.to_string()), target.into());
+            self
+        }
+
+        pub fn root_file(mut self, file: &str, content: impl AsRef<[u8]>) -> Self {
+            self.root_files
+                .insert(file.to_string(), content.as_ref().to_vec());
+            self
+        }
+
+        /// Makes any access to `file` fail with `kind` (e.g. `PermissionDenied`).
+        pub fn error(mut self, pid: u32, file: &str, kind: io::ErrorKind) -> Self {
+            self.errors.insert((pid, file.to_string()), kind);
+            self
+        }
+
+        fn check(&self, pid: u32, file: &str) -> io::Result<()> {
+            match self.errors.get(&(pid, file.to_string())) {
+                Some(kind) => Err(io::Error::from(*kind)),
+                None => Ok(()),
+            }
+        }
+    }
+
+    fn not_found() -> io::Error {
+        io::Error::from(io::ErrorKind::NotFound)
+    }
+
+    impl ProcReader for MockProcReader {
+        fn pids(&self) -> io::Result<Vec<u32>> {
+            let mut pids: Vec<u32> = self
+                .files
+                .keys()
+                .chain(self.links.keys())
+                .chain(self.errors.keys())
+                .map(|(pid, _)| *pid)
+                .collect();
+            pids.sort_unstable();
+            pids.dedup();
+            Ok(pids)
+        }
+
+        fn open(&self, pid: u32, file: &str) -> io::Result<Box<dyn Read + '_>> {
+            self.check(pid, file)?;
+            match self.files.get(&(pid, file.to_string())) {
+                Some(content) => Ok(Box::new(content.as_slice())),
+                None => Err(not_found()),
+            }
+        }
+
+        fn read_link(&self, pid: u32, file: &str) -> io::Result<PathBuf> {
+            self.check(pid, file)?;
+            self.links
+                .get(&(pid, file.to_string()))
+                .cloned()
+                .ok_or_else(not_found)
+        }
+
+        fn read_root(&self, file: &str) -> io::Result<Vec<u8>> {
+            self.root_files.get(file).cloned().ok_or_else(not_found)
+        }
+
+        // Directories are implied by the files, links and errors below them.
+        fn list_dir(&self, pid: u32, dir: &str) -> io::Result<Vec<OsString>> {
+            self.check(pid, dir)?;
+            let prefix = format!("{dir}/");
+            let mut names: Vec<OsString> = self
+                .files
+                .keys()
+                .chain(self.links.keys())
+                .chain(self.errors.keys())
+                .filter(|(p, _)| *p == pid)
+                .filter_map(|(_, f)| f.strip_prefix(&prefix))
+                .map(|rest| OsString::from(rest.split('/').next().unwrap_or(rest)))
+                .collect();
+            if names.is_empty() {
+                return Err(not_found());
+            }
+            names.sort();
+            names.dedup();
+            Ok(names)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockProcReader;
+    use super::*;
+
+    #[test]
+    fn test_read_limit() {
+        use crate::cmdline::Cmdline;
+
+        let dir = std::env::temp_dir().join(format!("fpid-limit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("7")).unwrap();
+        std::fs::write(dir.join("7/cmdline"), b"/usr/bin/long\0aaaaaaaaaaaa\0").unwrap();
+        std::fs::write(dir.join("7/maps"), [b'x'; 64]).unwrap();
+
+        let path = dir.join("7/cmdline");
+        assert_eq!(read_limited(&path, 27).unwrap().len(), 27);
+        assert_eq!(read_limited(&path, 100).unwrap().len(), 27);
+
+        let reader = FsProcReader::new(&dir).with_read_limit(Some(16));
+        let buf = reader.read(7, "cmdline").unwrap();
+        assert_eq!(buf, b"/usr/bin/long\0aa");
+        let mut streamed = Vec::new();
+        reader
+            .open(7, "cmdline")
+            .unwrap()
+            .read_to_end(&mut streamed)
+            .unwrap();
+        assert_eq!(streamed, buf);
+        // No trailing NUL: the end of the buffer closes the last argument.
+        let cmdline = Cmdline::from_bytes(buf);
+        assert_eq!(cmdline.argv0(), Some(&b"/usr/bin/long"[..]));
+        assert_eq!(cmdline.args().last(), Some(&b"aa"[..]));
+        assert_eq!(reader.read(7, "maps").unwrap().len(), 64);
+        assert_eq!(
+            FsProcReader::new(&dir).read(7, "cmdline").unwrap().len(),
+            27
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fs_reader_self() {
+        let reader = FsProcReader::default();
+        let pid = std::process::id();
+        assert_eq!(
+            reader.read_link(pid, "exe").unwrap(),
+            std::env::current_exe().unwrap()
+        );
+        assert!(!reader.read(pid, "cmdline").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fs_reader_pids() {
+        let pids = FsProcReader::default().pids().unwrap();
+        assert!(pids.contains(&std::process::id()));
+    }
+
+    #[test]
+    fn test_is_all_digits() {
+        assert!(is_all_digits("1"));
+        assert!(is_all_digits("4194304"));
+        assert!(!is_all_digits(""));
+        assert!(!is_all_digits("self"));
+        assert!(!is_all_digits("12a"));
+        assert!(is_all_digits("1234567890123456789"));
+        assert!(!is_all_digits("12345678901234:6789"));
+        assert!(!is_all_digits("1234567/"));
+        assert!(!is_all_digits("12345678a"));
+    }
+
+    #[test]
+    fn test_is_all_digits_matches_bytewise() {
+        // Deterministic xorshift so failures reproduce; bytes are drawn from
+        // around the digit range to hit the nibble edge cases ('/', ':', 0x3f).
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..20_000 {
+            let len = (next() % 20) as usize;
+            let bytes: Vec<u8> = (0..len)
+                .map(|_| match next() % 4 {
+                    0 => 0x20 + (next() % 0x30) as u8,
+                    _ => b'0' + (next() % 10) as u8,
+                })
+                .collect();
+            let s = std::str::from_utf8(&bytes).unwrap();
+            let expected = !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+            assert_eq!(is_all_digits(s), expected, "{s:?}");
+        }
+        for b in 0u8..=0x7f {
+            let s = String::from_utf8(vec![b'1', b'2', b'3', b'4', b'5', b'6', b'7', b]).unwrap();
+            assert_eq!(is_all_digits(&s), b.is_ascii_digit(), "{b:#x}");
+        }
+    }
+
+    #[test]
+    fn test_mock_reader() {
+        let reader = MockProcReader::new()
+            .file(1, "comm", "init\n")
+            .link(1, "exe", "/sbin/init");
+        assert_eq!(reader.read(1, "comm").unwrap(), b"init\n");
+        assert_eq!(
+            reader.read_link(1, "exe").unwrap(),
+            PathBuf::from("/sbin/init")
+        );
+        assert_eq!(
+            reader.read(2, "comm").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    // The only test touching FPID_PROC_ROOT, so no other thread reads it
+    // while it changes.
+    #[test]
+    fn test_resolve_proc_root() {
+        let cli = Path::new("/cli/proc");
+        let env = |v: &str| Some(OsString::from(v));
+        assert_eq!(resolve_proc_root_from(None, None), Path::new("/proc"));
+        assert_eq!(resolve_proc_root_from(Some(cli), None), cli);
+        assert_eq!(
+            resolve_proc_root_from(None, env("/env/proc")),
+            Path::new("/env/proc")
+        );
+        assert_eq!(resolve_proc_root_from(Some(cli), env("/env/proc")), cli);
+        // An empty variable counts as unset.
+        assert_eq!(resolve_proc_root_from(None, env("")), Path::new("/proc"));
+    }
+}
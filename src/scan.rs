@@ -0,0 +1,848 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::batch::BatchedProcIterator;
+use crate::cmdline::open_cmdline_reader;
+use crate::error::FpidError;
+use crate::filter::Filter;
+use crate::matcher::{MultiMatcher, basename};
+use crate::process::{Process, SystemCache};
+use crate::reader::{FsProcReader, ProcReader};
+
+/// Sink for `--debug` trace lines.
+pub type DebugLogger = Box<dyn Fn(&str)>;
+
+/// How a process is matched against several targets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Any one target matching is enough.
+    #[default]
+    Any,
+    /// Every target must match (`--match-all`); reported as target 0.
+    All,
+}
+
+/// What to look for during a scan.
+#[derive(Default)]
+pub struct ScanConfig {
+    matcher: MultiMatcher,
+    /// Whether one target or all of them must match.
+    pub match_mode: MatchMode,
+    /// Treat every process as a target match and leave the choice to the
+    /// filters (`--ppid-chain` without a name).
+    pub match_every: bool,
+    /// Look for the targets anywhere in the cmdline instead of comparing
+    /// argv[0] and exe (`--match-cmdline`).
+    #[cfg(feature = "aho-corasick")]
+    pub match_cmdline: bool,
+    // Matches of these are dropped before the filters run (`--exclude-name`).
+    exclude: MultiMatcher,
+    /// Stop after the first match.
+    pub single: bool,
+    /// Conditions every match must also satisfy.
+    pub filters: Vec<Filter>,
+    /// Checked after each PID; once set the scan stops early (e.g. from a
+    /// SIGINT handler, see [`crate::sys::install_sigint_flag`]).
+    pub interrupt: Option<&'static AtomicBool>,
+    /// Receives `[fpid debug pid=N] ...` lines with the raw data read for
+    /// every PID and each filter result (`--debug`).
+    pub debug_logger: Option<DebugLogger>,
+    /// PIDs read between pauses; 0 scans without pausing.
+    pub batch_size: usize,
+    /// Length of each pause between batches, in microseconds.
+    pub batch_sleep_us: u64,
+}
+
+impl fmt::Debug for ScanConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("ScanConfig");
+        #[cfg(feature = "aho-corasick")]
+        d.field("match_cmdline", &self.match_cmdline);
+        d.field("matcher", &self.matcher)
+            .field("exclude", &self.exclude)
+            .field("match_mode", &self.match_mode)
+            .field("match_every", &self.match_every)
+            .field("single", &self.single)
+            .field("filters", &self.filters)
+            .field("interrupt", &self.interrupt)
+            .field("debug_logger", &self.debug_logger.is_some())
+            .field("batch_size", &self.batch_size)
+            .field("batch_sleep_us", &self.batch_sleep_us)
+            .finish()
+    }
+}
+
+impl ScanConfig {
+    pub fn new<S: AsRef<str>>(targets: &[S]) -> Self {
+        Self {
+            matcher: MultiMatcher::new(targets),
+            ..Self::default()
+        }
+    }
+
+    /// Drops processes matching any of `names` from the results; names
+    /// and paths are compared the same way as the targets.
+    pub fn with_exclude<S: AsRef<str>>(mut self, names: &[S]) -> Self {
+        self.exclude = MultiMatcher::new(names);
+        self
+    }
+
+    pub fn matcher(&self) -> &MultiMatcher {
+        &self.matcher
+    }
+}
+
+/// A matched process with the details read while matching it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// Index of the target that matched.
+    pub target: usize,
+    /// Basename of argv[0], lossily decoded; empty for kernel threads.
+    pub name: String,
+    pub exe: Option<PathBuf>,
+    pub cmdline: Vec<String>,
+}
+
+impl ProcessInfo {
+    pub fn read(pid: u32, target: usize, reader: &impl ProcReader) -> Self {
+        let cmdline = open_cmdline_reader(pid, reader).unwrap_or_default();
+        Self {
+            pid,
+            target,
+            name: cmdline
+                .argv0()
+                .map(|a| String::from_utf8_lossy(basename(a)).into_owned())
+                .unwrap_or_default(),
+            exe: reader.read_link(pid, "exe").ok(),
+            cmdline: cmdline
+                .args()
+                .map(|a| String::from_utf8_lossy(a).into_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Returns the index of the first target `pid` matches, if any.
+pub fn match_pid(pid: u32, matcher: &MultiMatcher, reader: &impl ProcReader) -> Option<usize> {
+    try_match_pid(pid, matcher, reader).ok().flatten()
+}
+
+/// Like [`match_pid`], but reports why a process could not be checked:
+/// the error of the last file needed for a decision.
+pub fn try_match_pid(
+    pid: u32,
+    matcher: &MultiMatcher,
+    reader: &impl ProcReader,
+) -> io::Result<Option<usize>> {
+    let mut exe_err = None;
+    if matcher.has_paths() {
+        match reader.read_link(pid, "exe") {
+            Ok(exe) => {
+                if let Some(idx) = matcher.match_exe(&exe) {
+                    return Ok(Some(idx));
+                }
+            }
+            Err(e) => exe_err = Some(e),
+        }
+    }
+    if matcher.has_names() {
+        let cmdline = open_cmdline_reader(pid, reader)?;
+        return Ok(cmdline.argv0().and_then(|a| matcher.match_argv0(a)));
+    }
+    match exe_err {
+        // Kernel threads have no exe link; only a vanished process is an error.
+        Some(e) if e.kind() == io::ErrorKind::NotFound && reader.open(pid, "stat").is_ok() => {
+            Ok(None)
+        }
+        Some(e) => Err(e),
+        None => Ok(None),
+    }
+}
+
+/// Like [`try_match_pid`] under [`MatchMode::All`]: `Some(0)` when `pid`
+/// matches every target of `matcher`, none of which may be empty.
+pub fn try_match_all(
+    pid: u32,
+    matcher: &MultiMatcher,
+    reader: &impl ProcReader,
+) -> io::Result<Option<usize>> {
+    if matcher.is_empty() {
+        return Ok(None);
+    }
+    let exe = if matcher.has_paths() {
+        match reader.read_link(pid, "exe") {
+            Ok(exe) => Some(exe),
+            // A kernel thread, which no path matches.
+            Err(e) if e.kind() == io::ErrorKind::NotFound && reader.open(pid, "stat").is_ok() => {
+                None
+            }
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+    let cmdline = if matcher.has_names() {
+        Some(open_cmdline_reader(pid, reader)?)
+    } else {
+        None
+    };
+    let argv0 = cmdline.as_ref().and_then(|c| c.argv0());
+    Ok(matcher.match_all(argv0, exe.as_deref()).then_some(0))
+}
+
+// The target `pid` matches under the config's match mode.
+fn try_match_config(
+    pid: u32,
+    config: &ScanConfig,
+    reader: &impl ProcReader,
+) -> io::Result<Option<usize>> {
+    if config.match_every {
+        return Ok(Some(0));
+    }
+    #[cfg(feature = "aho-corasick")]
+    if config.match_cmdline {
+        return Ok(config.matcher.match_cmdline(&reader.read(pid, "cmdline")?));
+    }
+    match config.match_mode {
+        MatchMode::Any => try_match_pid(pid, &config.matcher, reader),
+        MatchMode::All => try_match_all(pid, &config.matcher, reader),
+    }
+}
+
+/// Counters for one scan, reported by `--stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    /// PIDs looked at.
+    pub scanned: u32,
+    /// Processes that matched and passed every filter.
+    pub matched: u32,
+    /// Processes skipped because their files were not readable.
+    pub eperm: u32,
+    /// Processes that exited between listing and reading.
+    pub gone: u32,
+    pub duration: Duration,
+}
+
+impl ScanStats {
+    fn count_error(&mut self, e: &io::Error) {
+        // ESRCH: the task is being torn down while we read it.
+        const ESRCH: i32 = 3;
+        match e.kind() {
+            io::ErrorKind::PermissionDenied => self.eperm += 1,
+            io::ErrorKind::NotFound => self.gone += 1,
+            _ if e.raw_os_error() == Some(ESRCH) => self.gone += 1,
+            _ => {}
+        }
+    }
+
+    /// `Scanned: 1234 PIDs, Matched: 5, Skipped (EPERM): 12, ...`.
+    pub fn summary(&self) -> String {
+        format!(
+            "Scanned: {} PIDs, Matched: {}, Skipped (EPERM): {}, Skipped (gone): {}, Duration: {}ms",
+            self.scanned,
+            self.matched,
+            self.eperm,
+            self.gone,
+            self.duration.as_millis()
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"scanned\":{},\"matched\":{},\"eperm\":{},\"gone\":{},\"duration_ms\":{}}}",
+            self.scanned,
+            self.matched,
+            self.eperm,
+            self.gone,
+            self.duration.as_millis()
+        )
+    }
+}
+
+/// Walks the proc root once, calling `on_match(process, target_index)` for
+/// each matching process until it returns `ControlFlow::Break`.
+///
+/// Only listing the root can fail; processes that vanish or cannot be read
+/// mid-scan are skipped and counted in the returned stats. `config.single`
+/// is left to the callback.
+pub fn scan_each<R: ProcReader>(
+    config: &ScanConfig,
+    reader: &R,
+    mut on_match: impl FnMut(&Process<'_, R>, usize) -> ControlFlow<()>,
+) -> io::Result<ScanStats> {
+    let start = Instant::now();
+    let mut stats = ScanStats::default();
+    let sys = SystemCache::new();
+    #[cfg(feature = "tracing")]
+    crate::log_debug!("scan{{filters={}}}: start", config.filters.len());
+    let pids = BatchedProcIterator::new(
+        reader.pids()?.into_iter(),
+        config.batch_size,
+        config.batch_sleep_us,
+        std::thread::sleep,
+    );
+    for pid in pids {
+        if config.interrupt.is_some_and(|f| f.load(Ordering::Relaxed)) {
+            break;
+        }
+        stats.scanned += 1;
+        let matched = try_match_config(pid, config, reader);
+        if let Some(log) = &config.debug_logger {
+            debug_pid(log, pid, reader, &matched);
+        }
+        let idx = match matched {
+            Ok(Some(idx)) => idx,
+            Ok(None) => {
+                #[cfg(feature = "tracing")]
+                crate::log_debug!("scan_pid{{pid={}}}: no match", pid);
+                continue;
+            }
+            Err(e) => {
+                stats.count_error(&e);
+                continue;
+            }
+        };
+        crate::log_debug!("pid {} matched target {}", pid, idx);
+        if !config.exclude.is_empty() && match_pid(pid, &config.exclude, reader).is_some() {
+            if let Some(log) = &config.debug_logger {
+                log(&format!("[fpid debug pid={}] excluded", pid));
+            }
+            continue;
+        }
+        let proc = Process::new(pid, reader, &sys);
+        let passes = |f: &Filter| {
+            let ok = f.matches(&proc);
+            if let Some(log) = &config.debug_logger {
+                log(&format!(
+                    "[fpid debug pid={}] filter {:?}: {}",
+                    pid,
+                    f,
+                    if ok { "pass" } else { "fail" }
+                ));
+            }
+            ok
+        };
+        if !config.filters.iter().all(passes) {
+            #[cfg(feature = "tracing")]
+            crate::log_debug!("scan_pid{{pid={}}}: filtered out", pid);
+            continue;
+        }
+        stats.matched += 1;
+        if on_match(&proc, idx).is_break() {
+            break;
+        }
+    }
+    stats.duration = start.elapsed();
+    Ok(stats)
+}
+
+/// Whether the one process `pid` is a match: a target, not excluded, and
+/// through every filter (`--pid-file`). No `--debug` output or stats.
+pub fn pid_matches<R: ProcReader>(config: &ScanConfig, reader: &R, pid: u32) -> bool {
+    if !matches!(try_match_config(pid, config, reader), Ok(Some(_)))
+        || (!config.exclude.is_empty() && match_pid(pid, &config.exclude, reader).is_some())
+    {
+        return false;
+    }
+    let sys = SystemCache::new();
+    let proc = Process::new(pid, reader, &sys);
+    config.filters.iter().all(|f| f.matches(&proc))
+}
+
+// Raw inputs of the match decision for `--debug`.
+fn debug_pid(
+    log: &dyn Fn(&str),
+    pid: u32,
+    reader: &impl ProcReader,
+    matched: &io::Result<Option<usize>>,
+) {
+    let tag = format!("[fpid debug pid={}]", pid);
+    match reader.read(pid, "cmdline") {
+        Ok(buf) => log(&format!("{} cmdline={}", tag, escape_bytes(&buf))),
+        Err(e) => log(&format!("{} cmdline error: {}", tag, e)),
+    }
+    match reader.read_link(pid, "exe") {
+        Ok(exe) => log(&format!("{} exe={}", tag, exe.display())),
+        Err(e) => log(&format!("{} exe error: {}", tag, e)),
+    }
+    match reader.read(pid, "comm") {
+        Ok(comm) => log(&format!(
+            "{} comm={}",
+            tag,
+            escape_bytes(comm.trim_ascii_end())
+        )),
+        Err(e) => log(&format!("{} comm error: {}", tag, e)),
+    }
+    match matched {
+        Ok(Some(idx)) => log(&format!("{} match: target {}", tag, idx)),
+        Ok(None) => log(&format!("{} match: none", tag)),
+        Err(e) => log(&format!("{} match error: {}", tag, e)),
+    }
+}
+
+// Printable ASCII as-is, everything else (NUL separators included) as \xNN.
+fn escape_bytes(buf: &[u8]) -> String {
+    let mut out = String::with_capacity(buf.len());
+    for &b in buf {
+        if b.is_ascii_graphic() || b == b' ' {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("\\x{:02x}", b));
+        }
+    }
+    out
+}
+
+/// PIDs of all matching processes, in proc root order.
+pub fn scan_pids(config: &ScanConfig, reader: &impl ProcReader) -> Result<Vec<u32>, FpidError> {
+    let mut pids = Vec::new();
+    scan_each(config, reader, |proc, _| {
+        pids.push(proc.pid());
+        stop_if(config.single)
+    })?;
+    Ok(pids)
+}
+
+/// Like [`scan_pids`], with name, exe and cmdline of each match.
+pub fn scan_processes(
+    config: &ScanConfig,
+    reader: &impl ProcReader,
+) -> Result<Vec<ProcessInfo>, FpidError> {
+    let mut out = Vec::new();
+    scan_each(config, reader, |proc, idx| {
+        out.push(ProcessInfo::read(proc.pid(), idx, reader));
+        stop_if(config.single)
+    })?;
+    Ok(out)
+}
+
+/// Builds a [`Scanner`] over a real proc tree.
+///
+/// ```no_run
+/// use fpid_rs::ScannerBuilder;
+///
+/// let found: Vec<_> = ScannerBuilder::new().name("bash").build().run().collect();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScannerBuilder {
+    proc_root: PathBuf,
+    targets: Vec<String>,
+    excludes: Vec<String>,
+    match_mode: MatchMode,
+    single: bool,
+    filters: Vec<Filter>,
+}
+
+impl Default for ScannerBuilder {
+    fn default() -> Self {
+        Self {
+            proc_root: PathBuf::from("/proc"),
+            targets: Vec::new(),
+            excludes: Vec::new(),
+            match_mode: MatchMode::Any,
+            single: false,
+            filters: Vec::new(),
+        }
+    }
+}
+
+impl ScannerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Directory laid out like `/proc`; defaults to `/proc`.
+    pub fn proc_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.proc_root = root.into();
+        self
+    }
+
+    /// Adds a name or path to look for; any target matching is a hit
+    /// unless [`match_mode`](Self::match_mode) is [`MatchMode::All`].
+    pub fn name(mut self, target: impl Into<String>) -> Self {
+        self.targets.push(target.into());
+        self
+    }
+
+    /// Leaves out processes matching this name or path.
+    pub fn exclude(mut self, name: impl Into<String>) -> Self {
+        self.excludes.push(name.into());
+        self
+    }
+
+    /// Whether one target or all of them must match.
+    pub fn match_mode(mut self, mode: MatchMode) -> Self {
+        self.match_mode = mode;
+        self
+    }
+
+    /// Stop at the first match.
+    pub fn single(mut self, single: bool) -> Self {
+        self.single = single;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn build(self) -> Scanner {
+        let mut config = ScanConfig::new(&self.targets).with_exclude(&self.excludes);
+        config.match_mode = self.match_mode;
+        config.single = self.single;
+        config.filters = self.filters;
+        Scanner {
+            config,
+            reader: FsProcReader::new(self.proc_root),
+        }
+    }
+}
+
+/// A configured scan from [`ScannerBuilder`]; each [`run`](Self::run)
+/// takes a fresh pass over the proc root.
+#[derive(Debug)]
+pub struct Scanner {
+    config: ScanConfig,
+    reader: FsProcReader,
+}
+
+impl Scanner {
+    /// Matches in proc root order, like [`scan_processes`].
+    pub fn try_run(&self) -> Result<Vec<ProcessInfo>, FpidError> {
+        scan_processes(&self.config, &self.reader)
+    }
+
+    /// [`try_run`](Self::try_run) for callers that treat an unreadable
+    /// proc root as "nothing found".
+    pub fn run(&self) -> impl Iterator<Item = ProcessInfo> + use<> {
+        self.try_run().unwrap_or_default().into_iter()
+    }
+
+    pub fn config(&self) -> &ScanConfig {
+        &self.config
+    }
+}
+
+/// Every distinct argv[0] basename with its process count, i.e. the names a
+/// plain `fpid <name>` could find. Kernel threads (empty cmdline) are left
+/// out; an unreadable proc root yields an empty map.
+pub fn list_all_names(reader: &impl ProcReader) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for pid in reader.pids().unwrap_or_default() {
+        let Ok(cmdline) = open_cmdline_reader(pid, reader) else {
+            continue;
+        };
+        if let Some(argv0) = cmdline.argv0() {
+            let name = String::from_utf8_lossy(basename(argv0)).into_owned();
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+fn stop_if(cond: bool) -> ControlFlow<()> {
+    if cond {
+        ControlFlow::Break(())
+    } else {
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    fn mock() -> MockProcReader {
+        MockProcReader::new()
+            .file(1, "cmdline", b"/sbin/init\0splash\0")
+            .file(
+                1,
+                "stat",
+                "1 (systemd) S 0 1 1 0 -1 0 0 0 0 0 900 300 0 0 20 0 1 0 1 0 0\n",
+            )
+            .link(1, "exe", "/usr/lib/systemd/systemd")
+            .file(20, "cmdline", b"")
+            .file(300, "cmdline", b"nginx: master process\0")
+            .link(300, "exe", "/usr/sbin/nginx")
+            .file(301, "cmdline", b"/usr/bin/bash\0-l\0")
+            .file(
+                301,
+                "stat",
+                "301 (bash) S 1 301 301 0 -1 0 0 0 0 0 10 5 0 0 20 0 1 0 1 0 0\n",
+            )
+            .link(301, "exe", "/usr/bin/bash")
+            .file(302, "cmdline", b"bash\0")
+            .error(303, "cmdline", io::ErrorKind::PermissionDenied)
+    }
+
+    fn collect(targets: &[&str]) -> Vec<(u32, usize)> {
+        let mut out = Vec::new();
+        scan_each(&ScanConfig::new(targets), &mock(), |proc, idx| {
+            out.push((proc.pid(), idx));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_scan_by_name() {
+        assert_eq!(collect(&["bash"]), vec![(301, 0), (302, 0)]);
+        assert_eq!(collect(&["init"]), vec![(1, 0)]);
+        assert!(collect(&["nginx"]).is_empty());
+    }
+
+    #[test]
+    fn test_scan_multiple_targets() {
+        assert_eq!(
+            collect(&["init", "/usr/sbin/nginx", "bash"]),
+            vec![(1, 0), (300, 1), (301, 2), (302, 2)]
+        );
+    }
+
+    #[test]
+    fn test_scan_break() {
+        let mut out = Vec::new();
+        scan_each(&ScanConfig::new(&["bash"]), &mock(), |proc, _| {
+            out.push(proc.pid());
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(out, vec![301]);
+    }
+
+    #[test]
+    fn test_scan_match_all() {
+        let reader = mock()
+            .file(310, "cmdline", b"/usr/bin/bash\0/opt/backup.sh\0")
+            .link(310, "exe", "/usr/local/bin/bash");
+        // 301 and 310 are both named bash but only 301 runs /usr/bin/bash;
+        // 302 has no exe link and nginx is not named bash.
+        let mut config = ScanConfig::new(&["bash", "/usr/bin/bash"]);
+        assert_eq!(scan_pids(&config, &reader).unwrap(), vec![301, 302, 310]);
+        config.match_mode = MatchMode::All;
+        assert_eq!(scan_pids(&config, &reader).unwrap(), vec![301]);
+        assert!(pid_matches(&config, &reader, 301));
+        assert!(!pid_matches(&config, &reader, 310));
+        // Targets AND together, then the filters on top.
+        config.filters.push(Filter::MinCpuSec(1.0));
+        assert!(scan_pids(&config, &reader).unwrap().is_empty());
+
+        let mut config = ScanConfig::new(&["bash", "init"]);
+        config.match_mode = MatchMode::All;
+        assert!(scan_pids(&config, &reader).unwrap().is_empty());
+        let mut config = ScanConfig::new(&["/usr/sbin/nginx"]);
+        config.match_mode = MatchMode::All;
+        assert_eq!(scan_pids(&config, &reader).unwrap(), vec![300]);
+    }
+
+    #[cfg(feature = "aho-corasick")]
+    #[test]
+    fn test_scan_match_cmdline() {
+        let mut config = ScanConfig::new(&["master", "bash\0-l"]);
+        assert!(scan_pids(&config, &mock()).unwrap().is_empty());
+        config.match_cmdline = true;
+        let mut found = Vec::new();
+        scan_each(&config, &mock(), |proc, idx| {
+            found.push((proc.pid(), idx));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        // 303's cmdline is unreadable, so it is skipped.
+        assert_eq!(found, vec![(300, 0), (301, 1)]);
+        assert!(pid_matches(&config, &mock(), 300));
+    }
+
+    #[test]
+    fn test_scan_match_every() {
+        let mut config = ScanConfig::new::<&str>(&[]);
+        assert!(scan_pids(&config, &mock()).unwrap().is_empty());
+        config.match_every = true;
+        assert_eq!(
+            scan_pids(&config, &mock()).unwrap(),
+            vec![1, 20, 300, 301, 302, 303]
+        );
+        config
+            .filters
+            .push(Filter::Pids(HashSet::from([300, 301, 999])));
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![300, 301]);
+
+        let mut config = ScanConfig::new(&["bash"]);
+        config
+            .filters
+            .push(Filter::Pids(HashSet::from([300, 301, 999])));
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![301]);
+    }
+
+    #[test]
+    fn test_scan_pids() {
+        let mut config = ScanConfig::new(&["bash", "init"]);
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![1, 301, 302]);
+        config.single = true;
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_scan_filters() {
+        let mut config = ScanConfig::new(&["bash", "init"]);
+        config.filters.push(Filter::MinCpuSec(0.1));
+        // 302 has no stat and is dropped.
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![1, 301]);
+        config.filters.push(Filter::MinCpuSec(1.0));
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_scan_exclude() {
+        let config = ScanConfig::new(&["bash", "init"]).with_exclude(&["/usr/bin/bash"]);
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![1, 302]);
+        // A name exclude drops 302 too, which has no exe link.
+        let config = ScanConfig::new(&["bash", "/usr/sbin/nginx"]).with_exclude(&["init", "bash"]);
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![300]);
+        let config = ScanConfig::new(&["bash"]).with_exclude(&["nope", "/usr/sbin/nginx"]);
+        assert_eq!(scan_pids(&config, &mock()).unwrap(), vec![301, 302]);
+    }
+
+    #[test]
+    fn test_pid_matches() {
+        let reader = mock();
+        let config = ScanConfig::new(&["bash"]);
+        assert!(pid_matches(&config, &reader, 301));
+        assert!(pid_matches(&config, &reader, 302));
+        // Another process, no such PID, unreadable.
+        assert!(!pid_matches(&config, &reader, 300));
+        assert!(!pid_matches(&config, &reader, 999));
+        assert!(!pid_matches(&config, &reader, 303));
+        let config = ScanConfig::new(&["bash"]).with_exclude(&["/usr/bin/bash"]);
+        assert!(!pid_matches(&config, &reader, 301));
+        let mut config = ScanConfig::new(&["bash"]);
+        config.filters.push(Filter::MinCpuSec(0.1));
+        assert!(pid_matches(&config, &reader, 301));
+        assert!(!pid_matches(&config, &reader, 302));
+    }
+
+    #[test]
+    fn test_scan_processes() {
+        let procs = scan_processes(&ScanConfig::new(&["nope", "bash"]), &mock()).unwrap();
+        assert_eq!(procs.len(), 2);
+        assert_eq!(
+            procs[0],
+            ProcessInfo {
+                pid: 301,
+                target: 1,
+                name: "bash".to_string(),
+                exe: Some(PathBuf::from("/usr/bin/bash")),
+                cmdline: vec!["/usr/bin/bash".to_string(), "-l".to_string()],
+            }
+        );
+        assert_eq!(procs[1].exe, None);
+    }
+
+    #[test]
+    fn test_list_all_names() {
+        let counts = list_all_names(&mock());
+        assert_eq!(
+            counts,
+            HashMap::from([
+                ("init".to_string(), 1),
+                ("nginx: master process".to_string(), 1),
+                ("bash".to_string(), 2),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_scan_interrupted() {
+        static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+        let mut config = ScanConfig::new(&["bash", "init"]);
+        config.interrupt = Some(&INTERRUPTED);
+        let mut out = Vec::new();
+        scan_each(&config, &mock(), |proc, _| {
+            out.push(proc.pid());
+            // As if SIGINT arrived while handling the first match.
+            INTERRUPTED.store(true, Ordering::Relaxed);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(out, vec![1]);
+    }
+
+    #[test]
+    fn test_scan_stats() {
+        let reader = mock()
+            .error(304, "cmdline", io::ErrorKind::NotFound)
+            .file(305, "cmdline", b"bash\0");
+        let mut config = ScanConfig::new(&["bash"]);
+        config.filters = vec![Filter::MinCpuSec(0.0)];
+        let stats = scan_each(&config, &reader, |_, _| ControlFlow::Continue(())).unwrap();
+        // 301 passes the filter; 302 and 305 match but have no stat.
+        assert_eq!(
+            (stats.scanned, stats.matched, stats.eperm, stats.gone),
+            (8, 1, 1, 1)
+        );
+        let json = stats.to_json();
+        assert!(json.starts_with("{\"scanned\":8,\"matched\":1,\"eperm\":1,\"gone\":1,"));
+        assert!(stats.summary().starts_with(
+            "Scanned: 8 PIDs, Matched: 1, Skipped (EPERM): 1, Skipped (gone): 1, Duration: "
+        ));
+    }
+
+    #[test]
+    fn test_try_match_pid_errors() {
+        let matcher = MultiMatcher::new(&["/usr/bin/bash"]);
+        // exe unreadable and no name targets: the error is reported.
+        let err = try_match_pid(7, &matcher, &mock()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(try_match_pid(301, &matcher, &mock()).unwrap(), Some(0));
+        let kthread = MockProcReader::new().file(2, "stat", "2 (kthreadd) S 0 0 0 0");
+        assert_eq!(try_match_pid(2, &matcher, &kthread).unwrap(), None);
+    }
+
+    #[test]
+    fn test_debug_logger() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&lines);
+        let mut config = ScanConfig::new(&["bash"]);
+        config.filters = vec![Filter::MinCpuSec(0.0)];
+        config.debug_logger = Some(Box::new(move |line: &str| {
+            sink.borrow_mut().push(line.to_string())
+        }));
+        let reader = mock().file(301, "comm", "bash\n");
+        scan_each(&config, &reader, |_, _| ControlFlow::Continue(())).unwrap();
+
+        let lines = lines.borrow();
+        let for_pid = |pid: u32| -> Vec<&str> {
+            let tag = format!("[fpid debug pid={pid}] ");
+            lines
+                .iter()
+                .filter_map(|l| l.strip_prefix(tag.as_str()))
+                .collect()
+        };
+        assert_eq!(
+            for_pid(301),
+            vec![
+                "cmdline=/usr/bin/bash\\x00-l\\x00",
+                "exe=/usr/bin/bash",
+                "comm=bash",
+                "match: target 0",
+                "filter MinCpuSec(0.0): pass",
+            ]
+        );
+        assert!(for_pid(303).contains(&"match error: permission denied"));
+        assert_eq!(for_pid(300).last(), Some(&"match: none"));
+        assert!(for_pid(302).contains(&"filter MinCpuSec(0.0): fail"));
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt;
+use std::io;
+
+use crate::reader::ProcReader;
+use crate::stat::ProcStat;
+
+const POLICY_NAMES: [(u32, &str); 6] = [
+    (0, "SCHED_OTHER"),
+    (1, "SCHED_FIFO"),
+    (2, "SCHED_RR"),
+    (3, "SCHED_BATCH"),
+    (5, "SCHED_IDLE"),
+    (6, "SCHED_DEADLINE"),
+];
+
+/// Scheduling policy and kernel priority (0-99 real-time, 100-139 normal).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchedPolicy {
+    pub policy: u32,
+    pub prio: i32,
+}
+
+impl SchedPolicy {
+    /// `SCHED_*` name of the policy, `None` for codes this build doesn't know.
+    pub fn name(&self) -> Option<&'static str> {
+        POLICY_NAMES
+            .iter()
+            .find(|(code, _)| *code == self.policy)
+            .map(|(_, name)| *name)
+    }
+
+    pub fn is_realtime(&self) -> bool {
+        matches!(self.policy, 1 | 2 | 6)
+    }
+
+    /// Derived from stat's `policy` and `priority` fields, where `priority`
+    /// is the kernel prio minus 100.
+    pub fn from_stat(stat: &ProcStat) -> SchedPolicy {
+        SchedPolicy {
+            policy: stat.policy,
+            prio: stat.priority as i32 + 100,
+        }
+    }
+}
+
+/// `SCHED_FIFO/prio=80` for real-time policies, `SCHED_OTHER/nice=0` otherwise.
+impl fmt::Display for SchedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name)?,
+            None => write!(f, "policy{}", self.policy)?,
+        }
+        if self.is_realtime() {
+            write!(f, "/prio={}", 99 - self.prio)
+        } else {
+            write!(f, "/nice={}", self.prio - 120)
+        }
+    }
+}
+
+/// Policy code for `SCHED_FIFO`, `fifo`, `rr` and so on, ignoring case.
+pub fn parse_policy_name(s: &str) -> Option<u32> {
+    let upper = s.to_ascii_uppercase();
+    let want = upper.strip_prefix("SCHED_").unwrap_or(&upper);
+    POLICY_NAMES
+        .iter()
+        .find(|(_, name)| &name["SCHED_".len()..] == want)
+        .map(|(code, _)| *code)
+}
+
+/// Parses the `policy` and `prio` lines of `/proc/<pid>/sched`.
+pub fn parse_sched_file(buf: &[u8]) -> Result<SchedPolicy, io::Error> {
+    let (mut policy, mut prio) = (None, None);
+    for line in String::from_utf8_lossy(buf).lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "policy" => policy = value.trim().parse().ok(),
+            "prio" => prio = value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+    match (policy, prio) {
+        (Some(policy), Some(prio)) => Ok(SchedPolicy { policy, prio }),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "sched without policy/prio",
+        )),
+    }
+}
+
+/// Reads `/proc/<pid>/sched`, a debug interface absent without
+/// CONFIG_SCHED_DEBUG, falling back to the stat fields.
+pub fn read_sched_policy(pid: u32, reader: &impl ProcReader) -> Result<SchedPolicy, io::Error> {
+    match reader
+        .read(pid, "sched")
+        .and_then(|buf| parse_sched_file(&buf))
+    {
+        Ok(p) => Ok(p),
+        Err(_) => Ok(SchedPolicy::from_stat(&ProcStat::read_with(pid, reader)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const SCHED: &str = "\
+rtkit-daemon (812, #threads: 3)
+-------------------------------------------------------------------
+se.exec_start                                :       1234567.123456
+nr_switches                                  :                  204
+policy                                       :                    1
+prio                                         :                   19
+clock-delta                                  :                   30
+";
+
+    #[test]
+    fn test_parse_sched_file() {
+        let p = parse_sched_file(SCHED.as_bytes()).unwrap();
+        assert_eq!(
+            p,
+            SchedPolicy {
+                policy: 1,
+                prio: 19
+            }
+        );
+        assert_eq!(p.to_string(), "SCHED_FIFO/prio=80");
+        assert!(parse_sched_file(b"policy : 0\n").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let other = SchedPolicy {
+            policy: 0,
+            prio: 125,
+        };
+        assert_eq!(other.to_string(), "SCHED_OTHER/nice=5");
+        assert_eq!(
+            SchedPolicy {
+                policy: 5,
+                prio: 120
+            }
+            .to_string(),
+            "SCHED_IDLE/nice=0"
+        );
+        assert_eq!(
+            SchedPolicy {
+                policy: 9,
+                prio: 120
+            }
+            .to_string(),
+            "policy9/nice=0"
+        );
+    }
+
+    #[test]
+    fn test_parse_policy_name() {
+        assert_eq!(parse_policy_name("SCHED_FIFO"), Some(1));
+        assert_eq!(parse_policy_name("rr"), Some(2));
+        assert_eq!(parse_policy_name("Deadline"), Some(6));
+        assert_eq!(parse_policy_name("fast"), None);
+    }
+
+    #[test]
+    fn test_stat_fallback() {
+        // priority -11 (rt_priority 10) and policy 2 at fields 18 and 41.
+        let mut fields = vec!["0"; 52];
+        fields[0] = "4";
+        fields[1] = "(x)";
+        fields[2] = "S";
+        fields[17] = "-11";
+        fields[40] = "2";
+        let reader =
+            MockProcReader::new()
+                .file(3, "sched", SCHED)
+                .file(4, "stat", fields.join(" "));
+        assert_eq!(read_sched_policy(3, &reader).unwrap().policy, 1);
+        let p = read_sched_policy(4, &reader).unwrap();
+        assert_eq!(
+            p,
+            SchedPolicy {
+                policy: 2,
+                prio: 89
+            }
+        );
+        assert_eq!(p.to_string(), "SCHED_RR/prio=10");
+        assert!(read_sched_policy(5, &reader).is_err());
+    }
+}
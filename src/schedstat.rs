@@ -0,0 +1,82 @@
+use std::io;
+
+use crate::reader::{FsProcReader, ProcReader};
+
+/// Scheduler statistics from `/proc/<pid>/schedstat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedInfo {
+    /// Time spent on the CPU.
+    pub run_time_ns: u64,
+    /// Time spent runnable but waiting in the run queue.
+    pub wait_time_ns: u64,
+    /// Number of timeslices run on this CPU.
+    pub time_slices: u64,
+}
+
+impl SchedInfo {
+    /// Parses the three space-separated counters.
+    pub fn parse(buf: &[u8]) -> Result<SchedInfo, io::Error> {
+        let text = String::from_utf8_lossy(buf);
+        let mut it = text.split_ascii_whitespace().map(|s| s.parse::<u64>().ok());
+        match (it.next(), it.next(), it.next()) {
+            (Some(Some(run)), Some(Some(wait)), Some(Some(slices))) => Ok(SchedInfo {
+                run_time_ns: run,
+                wait_time_ns: wait,
+                time_slices: slices,
+            }),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "bad schedstat")),
+        }
+    }
+
+    pub fn read(pid: u32) -> Result<SchedInfo, io::Error> {
+        Self::read_with(pid, &FsProcReader::default())
+    }
+
+    pub fn read_with(pid: u32, reader: &impl ProcReader) -> Result<SchedInfo, io::Error> {
+        Self::parse(&reader.read(pid, "schedstat")?)
+    }
+}
+
+/// Fraction of runnable time spent waiting for a CPU, 0.0 for a process
+/// that has never been runnable.
+pub fn sched_cpu_pressure(info: &SchedInfo) -> f64 {
+    let total = info.run_time_ns + info.wait_time_ns;
+    if total == 0 {
+        return 0.0;
+    }
+    info.wait_time_ns as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_parse_schedstat() {
+        assert_eq!(
+            SchedInfo::parse(b"3000000 1000000 42\n").unwrap(),
+            SchedInfo {
+                run_time_ns: 3000000,
+                wait_time_ns: 1000000,
+                time_slices: 42,
+            }
+        );
+        assert!(SchedInfo::parse(b"3000000 1000000\n").is_err());
+        assert!(SchedInfo::parse(b"1 x 2\n").is_err());
+    }
+
+    #[test]
+    fn test_sched_cpu_pressure() {
+        let info = SchedInfo::parse(b"3000000 1000000 42\n").unwrap();
+        assert_eq!(sched_cpu_pressure(&info), 0.25);
+        assert_eq!(sched_cpu_pressure(&SchedInfo::default()), 0.0);
+    }
+
+    #[test]
+    fn test_read_with() {
+        let reader = MockProcReader::new().file(4, "schedstat", "10 20 3\n");
+        assert_eq!(SchedInfo::read_with(4, &reader).unwrap().time_slices, 3);
+        assert!(SchedInfo::read_with(5, &reader).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use std::os::unix::ffi::OsStrExt;
+
+use crate::cmdline::Cmdline;
+use crate::error::FpidError;
+use crate::matcher::basename;
+use crate::reader::{FsProcReader, ProcReader};
+use crate::scan::{ScanConfig, scan_pids};
+
+/// Runs fpid against itself through the library API: finds its own PID by
+/// argv[0] basename and by exe path, and checks that `/proc/self/cmdline`
+/// parses back to this process's argv[0].
+pub fn run_self_test() -> Result<(), FpidError> {
+    run_self_test_with(&FsProcReader::default())
+}
+
+pub fn run_self_test_with(reader: &impl ProcReader) -> Result<(), FpidError> {
+    let own = std::process::id();
+    let fail = |msg: String| Err(FpidError::SelfTest(msg));
+
+    let cmdline = Cmdline::from_bytes(reader.read_root("self/cmdline")?);
+    let argv0 = std::env::args_os().next().unwrap_or_default();
+    if cmdline.argv0() != Some(argv0.as_bytes()) {
+        return fail(format!(
+            "self/cmdline argv[0] is {:?}, expected {:?}",
+            cmdline.argv0().map(String::from_utf8_lossy),
+            argv0
+        ));
+    }
+
+    let name = String::from_utf8_lossy(basename(argv0.as_bytes())).into_owned();
+    let exe = reader.read_link(own, "exe")?;
+    let exe = exe.to_string_lossy().into_owned();
+    for target in [name, exe] {
+        let pids = scan_pids(&ScanConfig::new(&[&target]), reader)?;
+        if !pids.contains(&own) {
+            return fail(format!(
+                "searching '{}' found {:?}, not own pid {}",
+                target, pids, own
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_self_test() {
+        run_self_test().unwrap();
+    }
+}
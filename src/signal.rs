@@ -0,0 +1,223 @@
+use std::io::{self, BufRead, Write};
+
+use crate::error::FpidError;
+use crate::sys::send_signal;
+
+/// A signal accepted by `--signal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signal {
+    pub number: i32,
+    /// Canonical `SIG`-prefixed name.
+    pub name: &'static str,
+}
+
+const fn sig(number: i32, name: &'static str) -> Signal {
+    Signal { number, name }
+}
+
+/// Standard signals with their Linux numbers (x86, ARM and most other
+/// architectures).
+pub const SIGNALS: [Signal; 31] = [
+    sig(1, "SIGHUP"),
+    sig(2, "SIGINT"),
+    sig(3, "SIGQUIT"),
+    sig(4, "SIGILL"),
+    sig(5, "SIGTRAP"),
+    sig(6, "SIGABRT"),
+    sig(7, "SIGBUS"),
+    sig(8, "SIGFPE"),
+    sig(9, "SIGKILL"),
+    sig(10, "SIGUSR1"),
+    sig(11, "SIGSEGV"),
+    sig(12, "SIGUSR2"),
+    sig(13, "SIGPIPE"),
+    sig(14, "SIGALRM"),
+    sig(15, "SIGTERM"),
+    sig(16, "SIGSTKFLT"),
+    sig(17, "SIGCHLD"),
+    sig(18, "SIGCONT"),
+    sig(19, "SIGSTOP"),
+    sig(20, "SIGTSTP"),
+    sig(21, "SIGTTIN"),
+    sig(22, "SIGTTOU"),
+    sig(23, "SIGURG"),
+    sig(24, "SIGXCPU"),
+    sig(25, "SIGXFSZ"),
+    sig(26, "SIGVTALRM"),
+    sig(27, "SIGPROF"),
+    sig(28, "SIGWINCH"),
+    sig(29, "SIGIO"),
+    sig(30, "SIGPWR"),
+    sig(31, "SIGSYS"),
+];
+
+/// Resolves `SIGTERM`, `TERM` (any case) or `15`.
+pub fn parse_signal_name(s: &str) -> Result<Signal, FpidError> {
+    let found = match s.parse::<i32>() {
+        Ok(n) => SIGNALS.iter().find(|sig| sig.number == n),
+        Err(_) => {
+            let upper = s.to_ascii_uppercase();
+            let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+            SIGNALS.iter().find(|sig| &sig.name[3..] == name)
+        }
+    };
+    found
+        .copied()
+        .ok_or_else(|| FpidError::InvalidSignal(s.to_string()))
+}
+
+/// Space-separated names without the `SIG` prefix, for help and errors.
+pub fn signal_names() -> String {
+    SIGNALS
+        .iter()
+        .map(|sig| &sig.name[3..])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Sends one signal to each matched process (`--signal`), or with
+/// `dry_run` only reports what it would send.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalAction {
+    pub signal: Signal,
+    pub dry_run: bool,
+}
+
+impl SignalAction {
+    /// Acts on `pid`; `name` is only used in the dry-run message.
+    pub fn apply(&self, pid: u32, name: &str, out: &mut dyn Write) -> io::Result<()> {
+        if self.dry_run {
+            return writeln!(
+                out,
+                "[DRY-RUN] would send {} to pid {} ({})",
+                self.signal.name, pid, name
+            );
+        }
+        send_signal(pid, self.signal.number)
+    }
+}
+
+/// The `--confirm` question for sending `signal` to `targets` (pid, name).
+pub fn confirm_message(signal: Signal, targets: &[(u32, String)]) -> String {
+    let list: Vec<String> = targets
+        .iter()
+        .map(|(pid, name)| format!("{} ({})", pid, name))
+        .collect();
+    format!(
+        "About to send {} to {} process{}: {}. Proceed? [y/N]",
+        signal.name,
+        targets.len(),
+        if targets.len() == 1 { "" } else { "es" },
+        list.join(", ")
+    )
+}
+
+/// Asks `message` on stderr and reads the answer from stdin; only `y` or
+/// `Y` counts as yes.
+pub fn prompt_confirm(message: &str) -> bool {
+    prompt_confirm_from(message, &mut io::stdin().lock(), &mut io::stderr())
+}
+
+/// [`prompt_confirm`] over any input and prompt stream. EOF or a read
+/// error answers no.
+pub fn prompt_confirm_from(message: &str, input: &mut dyn BufRead, out: &mut dyn Write) -> bool {
+    let _ = write!(out, "{} ", message);
+    let _ = out.flush();
+    let mut answer = String::new();
+    match input.read_line(&mut answer) {
+        Ok(_) => matches!(answer.trim(), "y" | "Y"),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_parse_signal_name() {
+        let term = Signal {
+            number: 15,
+            name: "SIGTERM",
+        };
+        assert_eq!(parse_signal_name("SIGTERM").unwrap(), term);
+        assert_eq!(parse_signal_name("15").unwrap(), term);
+        assert_eq!(parse_signal_name("TERM").unwrap(), term);
+        assert_eq!(parse_signal_name("kill").unwrap().number, 9);
+        for bad in ["SIGTERMM", "0", "64", "SIG", ""] {
+            match parse_signal_name(bad) {
+                Err(FpidError::InvalidSignal(name)) => assert_eq!(name, bad),
+                other => panic!("{bad}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_signal_table() {
+        for (i, sig) in SIGNALS.iter().enumerate() {
+            assert_eq!(sig.number, i as i32 + 1);
+            assert!(sig.name.starts_with("SIG"));
+        }
+        assert!(signal_names().starts_with("HUP INT QUIT"));
+    }
+
+    #[test]
+    fn test_dry_run_sends_nothing() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let action = SignalAction {
+            signal: parse_signal_name("KILL").unwrap(),
+            dry_run: true,
+        };
+        let mut out = Vec::new();
+        action.apply(child.id(), "sleep", &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "[DRY-RUN] would send SIGKILL to pid {} (sleep)\n",
+                child.id()
+            )
+        );
+        assert!(child.try_wait().unwrap().is_none());
+
+        let real = SignalAction {
+            dry_run: false,
+            ..action
+        };
+        real.apply(child.id(), "sleep", &mut Vec::new()).unwrap();
+        assert!(child.wait().unwrap().code().is_none());
+    }
+
+    #[test]
+    fn test_confirm_message() {
+        let term = parse_signal_name("TERM").unwrap();
+        let targets = vec![
+            (1234, "nginx".to_string()),
+            (5678, "nginx".to_string()),
+            (9012, "nginx".to_string()),
+        ];
+        assert_eq!(
+            confirm_message(term, &targets),
+            "About to send SIGTERM to 3 processes: 1234 (nginx), 5678 (nginx), 9012 (nginx). Proceed? [y/N]"
+        );
+        assert!(confirm_message(term, &targets[..1]).contains("1 process: 1234"));
+    }
+
+    #[test]
+    fn test_prompt_confirm_from() {
+        for (input, want) in [
+            ("y\n", true),
+            ("Y\n", true),
+            (" y \n", true),
+            ("n\n", false),
+            ("yes\n", false),
+            ("\n", false),
+            ("", false),
+        ] {
+            let mut out = Vec::new();
+            let got = prompt_confirm_from("Proceed? [y/N]", &mut input.as_bytes(), &mut out);
+            assert_eq!(got, want, "{input:?}");
+            assert_eq!(out, b"Proceed? [y/N] ");
+        }
+    }
+}
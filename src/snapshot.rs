@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::error::ParseError;
+use crate::json::{self, Value};
+use crate::reader::ProcReader;
+use crate::scan::ScanConfig;
+use crate::watch::{WatchEvent, diff_matches, scan_matches};
+
+/// PID → name of the processes recorded in a snapshot: the records printed
+/// by `--format=ndjson`, one per line, or a JSON array of them. Only `pid`
+/// and `name` are used; an empty file is an empty snapshot.
+pub fn parse_snapshot(text: &str) -> Result<BTreeMap<u32, String>, ParseError> {
+    let records = if text.trim_start().starts_with('[') {
+        match json::parse(text)? {
+            Value::Array(items) => items,
+            _ => return Err(ParseError::InvalidValue("snapshot".to_string())),
+        }
+    } else {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(json::parse)
+            .collect::<Result<_, _>>()?
+    };
+    let mut procs = BTreeMap::new();
+    for record in &records {
+        let pid = record
+            .get("pid")
+            .ok_or(ParseError::MissingField("pid"))?
+            .as_u64()
+            .and_then(|pid| u32::try_from(pid).ok())
+            .ok_or_else(|| ParseError::InvalidValue("pid".to_string()))?;
+        let name = record
+            .get("name")
+            .ok_or(ParseError::MissingField("name"))?
+            .as_str()
+            .ok_or_else(|| ParseError::InvalidValue("name".to_string()))?;
+        procs.insert(pid, name.to_string());
+    }
+    Ok(procs)
+}
+
+/// Reads and parses the snapshot file at `path`.
+pub fn load_snapshot(path: &Path) -> io::Result<BTreeMap<u32, String>> {
+    Ok(parse_snapshot(&std::fs::read_to_string(path)?)?)
+}
+
+/// Scans once and reports how the matches differ from `snapshot`, as
+/// [`diff_matches`] does between two `--watch` scans (`--compare-snapshot`).
+pub fn compare_snapshot(
+    snapshot: &BTreeMap<u32, String>,
+    config: &ScanConfig,
+    reader: &impl ProcReader,
+    timestamp: &str,
+) -> io::Result<Vec<WatchEvent>> {
+    Ok(diff_matches(
+        snapshot,
+        &scan_matches(config, reader)?,
+        timestamp,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const NDJSON: &str = r#"{"pid":10,"target":0,"name":"sshd","exe":"/usr/sbin/sshd","cmdline":["sshd","-D"]}
+{"pid":11,"target":0,"name":"sshd","exe":null,"cmdline":["sshd: alice"]}
+"#;
+
+    fn snapshot(list: &[(u32, &str)]) -> BTreeMap<u32, String> {
+        list.iter().map(|(pid, n)| (*pid, n.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let want = snapshot(&[(10, "sshd"), (11, "sshd")]);
+        assert_eq!(parse_snapshot(NDJSON).unwrap(), want);
+        let array = format!("[{}]", NDJSON.trim_end().replace('\n', ",\n"));
+        assert_eq!(parse_snapshot(&array).unwrap(), want);
+        assert!(parse_snapshot("").unwrap().is_empty());
+        assert!(parse_snapshot("[]\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_snapshot_errors() {
+        assert_eq!(
+            parse_snapshot(r#"{"name":"sshd"}"#),
+            Err(ParseError::MissingField("pid"))
+        );
+        assert_eq!(
+            parse_snapshot(r#"{"pid":5000000000,"name":"sshd"}"#),
+            Err(ParseError::InvalidValue("pid".to_string()))
+        );
+        assert_eq!(
+            parse_snapshot(r#"{"pid":1,"name":null}"#),
+            Err(ParseError::InvalidValue("name".to_string()))
+        );
+        assert!(parse_snapshot("{\"pid\":1,\n").is_err());
+        assert!(parse_snapshot(r#"{"pids":[1]}"#).is_err());
+    }
+
+    #[test]
+    fn test_compare_snapshot() {
+        let config = ScanConfig::new(&["sshd"]);
+        let base = parse_snapshot(NDJSON).unwrap();
+        let reader = |list: &[(u32, &str)]| {
+            list.iter()
+                .fold(MockProcReader::new(), |r, (pid, cmdline)| {
+                    r.file(*pid, "cmdline", format!("{cmdline}\0"))
+                })
+        };
+
+        let same = reader(&[(10, "sshd"), (11, "sshd"), (12, "bash")]);
+        assert!(
+            compare_snapshot(&base, &config, &same, "T")
+                .unwrap()
+                .is_empty()
+        );
+
+        let changed = reader(&[(10, "sshd"), (13, "sshd")]);
+        let events: Vec<String> = compare_snapshot(&base, &config, &changed, "T")
+            .unwrap()
+            .iter()
+            .map(WatchEvent::to_text)
+            .collect();
+        assert_eq!(events, vec!["-11", "+13"]);
+    }
+}
@@ -0,0 +1,279 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use crate::maps::read_maps_count;
+use crate::process::Process;
+use crate::reader::ProcReader;
+use crate::statm::statm_to_kb;
+
+/// A per-process value matches can be ordered by (`--sort-by`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Pid,
+    Ppid,
+    /// Process group (`pgrp` in stat).
+    Pgid,
+    /// Real UID.
+    Uid,
+    /// The name `--columns name` prints.
+    Name,
+    /// Resident set size in kB.
+    Rss,
+    VmSize,
+    /// Start time in clock ticks after boot.
+    StartTime,
+    /// utime + stime in clock ticks.
+    Cpu,
+    /// Storage bytes read (`--sort-read-bytes`).
+    ReadBytes,
+    /// Storage bytes written (`--sort-write-bytes`).
+    WriteBytes,
+    /// Number of mappings in maps (`--sort-maps-count`, `--sort-maps-count-desc`).
+    MapsCount,
+}
+
+/// Field names accepted by `--sort-by`.
+pub const SORT_FIELD_NAMES: &str =
+    "pid ppid pgid uid name rss vm_size starttime cpu read_bytes write_bytes maps_count";
+
+impl FromStr for SortField {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Ok(match s {
+            "pid" => SortField::Pid,
+            "ppid" => SortField::Ppid,
+            "pgid" => SortField::Pgid,
+            "uid" => SortField::Uid,
+            "name" => SortField::Name,
+            "rss" => SortField::Rss,
+            "vm_size" => SortField::VmSize,
+            "starttime" => SortField::StartTime,
+            // `cputime` as in --columns.
+            "cpu" | "cputime" => SortField::Cpu,
+            "read_bytes" => SortField::ReadBytes,
+            "write_bytes" => SortField::WriteBytes,
+            "maps_count" => SortField::MapsCount,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// One field's value; fields are either all numbers or all text, so only
+/// like values are ever compared.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortValue {
+    Num(u64),
+    Text(String),
+}
+
+impl SortField {
+    /// The value sorted on; `None` (unreadable) sorts after everything else.
+    pub fn value<R: ProcReader>(&self, proc: &Process<'_, R>) -> Option<SortValue> {
+        let num = match self {
+            SortField::Pid => Some(u64::from(proc.pid())),
+            SortField::Ppid => proc.stat().map(|st| u64::from(st.ppid)),
+            SortField::Pgid => proc.stat().and_then(|st| u64::try_from(st.pgrp).ok()),
+            SortField::Uid => proc.status().map(|st| u64::from(st.uid[0])),
+            SortField::Name => {
+                return match proc.info().name.as_str() {
+                    "" => proc.stat().map(|st| SortValue::Text(st.comm.clone())),
+                    name => Some(SortValue::Text(name.to_string())),
+                };
+            }
+            SortField::Rss => proc.statm().map(|m| statm_to_kb(m.resident)),
+            SortField::VmSize => proc.status().and_then(|st| st.vm_size),
+            SortField::StartTime => proc.stat().map(|st| st.starttime),
+            SortField::Cpu => proc.stat().map(|st| st.utime + st.stime),
+            SortField::ReadBytes => proc.io().map(|io| io.read_bytes),
+            SortField::WriteBytes => proc.io().map(|io| io.write_bytes),
+            SortField::MapsCount => read_maps_count(proc.pid(), proc.reader())
+                .ok()
+                .map(|n| n as u64),
+        };
+        num.map(SortValue::Num)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// One `--sort-by` key: `<field>[,asc|desc]`, ascending by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortSpec {
+    pub field: SortField,
+    pub direction: SortDirection,
+}
+
+impl SortSpec {
+    pub fn new(field: SortField, direction: SortDirection) -> Self {
+        SortSpec { field, direction }
+    }
+}
+
+impl FromStr for SortSpec {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let (field, direction) = match s.split_once(',') {
+            Some((field, "asc")) => (field, SortDirection::Asc),
+            Some((field, "desc")) => (field, SortDirection::Desc),
+            Some(_) => return Err(()),
+            None => (s, SortDirection::Asc),
+        };
+        Ok(SortSpec::new(field.parse()?, direction))
+    }
+}
+
+/// The values of `specs` for one process, in key order.
+pub fn sort_values<R: ProcReader>(
+    proc: &Process<'_, R>,
+    specs: &[SortSpec],
+) -> Vec<Option<SortValue>> {
+    specs.iter().map(|spec| spec.field.value(proc)).collect()
+}
+
+/// Sorts `(values, item)` pairs by the first key of `specs`, ties by the
+/// next and so on; the values come from [`sort_values`]. Unreadable values
+/// go last in either direction, and full ties keep scan order.
+pub fn apply_sort<T>(items: &mut [(Vec<Option<SortValue>>, T)], specs: &[SortSpec]) {
+    items.sort_by(|(a, _), (b, _)| {
+        specs
+            .iter()
+            .zip(a.iter().zip(b))
+            .map(|(spec, (a, b))| match (a, b) {
+                (Some(a), Some(b)) if spec.direction == SortDirection::Desc => b.cmp(a),
+                (Some(a), Some(b)) => a.cmp(b),
+                (a, b) => a.is_none().cmp(&b.is_none()),
+            })
+            .find(|ord| *ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::SystemCache;
+    use crate::reader::mock::MockProcReader;
+
+    use SortDirection::{Asc, Desc};
+
+    fn num(n: u64) -> Option<SortValue> {
+        Some(SortValue::Num(n))
+    }
+
+    fn text(s: &str) -> Option<SortValue> {
+        Some(SortValue::Text(s.to_string()))
+    }
+
+    #[test]
+    fn test_sort_value() {
+        let reader = MockProcReader::new().file(
+            1,
+            "io",
+            "rchar: 1\nwchar: 2\nread_bytes: 30\nwrite_bytes: 40\n",
+        );
+        let sys = SystemCache::new();
+        let proc = Process::new(1, &reader, &sys);
+        assert_eq!(SortField::ReadBytes.value(&proc), num(30));
+        assert_eq!(SortField::WriteBytes.value(&proc), num(40));
+        assert_eq!(SortField::Pid.value(&proc), num(1));
+        assert_eq!(
+            SortField::WriteBytes.value(&Process::new(2, &reader, &sys)),
+            None
+        );
+        let reader = MockProcReader::new().file(1, "maps", "7f00-7f01 rw-p 0 00:00 0\n".repeat(4));
+        assert_eq!(
+            SortField::MapsCount.value(&Process::new(1, &reader, &sys)),
+            num(4)
+        );
+    }
+
+    #[test]
+    fn test_sort_value_stat_fields() {
+        let reader = MockProcReader::new()
+            .file(
+                7,
+                "stat",
+                "7 (kworker/0:1) S 2 0 0 0 -1 69238880 0 0 0 0 15 5 0 0 20 0 1 0 4200 0 0 \
+                 18446744073709551615 0 0 0 0 0 0 0 2147483647 0 0 0 0 17 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n",
+            )
+            .file(7, "statm", "100 25 0 0 0 0 0\n")
+            .file(8, "cmdline", "/usr/sbin/nginx\0-g\0")
+            .file(8, "status", "Name:\tnginx\nUid:\t33\t33\t33\t33\n");
+        let sys = SystemCache::new();
+        let proc = Process::new(7, &reader, &sys);
+        assert_eq!(SortField::Ppid.value(&proc), num(2));
+        assert_eq!(SortField::Pgid.value(&proc), num(0));
+        assert_eq!(SortField::StartTime.value(&proc), num(4200));
+        assert_eq!(SortField::Cpu.value(&proc), num(20));
+        assert_eq!(SortField::Rss.value(&proc), num(100));
+        // Kernel threads have no cmdline, so the name is the comm.
+        assert_eq!(SortField::Name.value(&proc), text("kworker/0:1"));
+        let proc = Process::new(8, &reader, &sys);
+        assert_eq!(SortField::Name.value(&proc), text("nginx"));
+        assert_eq!(SortField::Uid.value(&proc), num(33));
+        assert_eq!(SortField::Ppid.value(&proc), None);
+    }
+
+    #[test]
+    fn test_sort_spec_from_str() {
+        assert_eq!("rss".parse(), Ok(SortSpec::new(SortField::Rss, Asc)));
+        assert_eq!("name,asc".parse(), Ok(SortSpec::new(SortField::Name, Asc)));
+        assert_eq!(
+            "cputime,desc".parse(),
+            Ok(SortSpec::new(SortField::Cpu, Desc))
+        );
+        for name in SORT_FIELD_NAMES.split_whitespace() {
+            assert!(name.parse::<SortField>().is_ok(), "{name}");
+        }
+        for bad in ["", "rss,", "rss,down", "bogus", "rss,desc,asc"] {
+            assert_eq!(bad.parse::<SortSpec>(), Err(()), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_apply_sort_single_key() {
+        let desc = [SortSpec::new(SortField::ReadBytes, Desc)];
+        let mut items = vec![
+            (vec![num(5)], "a"),
+            (vec![None], "b"),
+            (vec![num(9)], "c"),
+            (vec![num(5)], "d"),
+        ];
+        apply_sort(&mut items, &desc);
+        let order: Vec<&str> = items.iter().map(|(_, s)| *s).collect();
+        assert_eq!(order, vec!["c", "a", "d", "b"]);
+
+        let asc = [SortSpec::new(SortField::ReadBytes, Asc)];
+        apply_sort(&mut items, &asc);
+        let order: Vec<&str> = items.iter().map(|(_, s)| *s).collect();
+        assert_eq!(order, vec!["a", "d", "c", "b"]);
+    }
+
+    #[test]
+    fn test_apply_sort_multi_key_ties() {
+        let specs = [
+            SortSpec::new(SortField::Name, Asc),
+            SortSpec::new(SortField::Rss, Desc),
+        ];
+        let mut items = vec![
+            (vec![text("nginx"), num(100)], 1),
+            (vec![text("bash"), num(50)], 2),
+            (vec![text("nginx"), num(300)], 3),
+            (vec![text("bash"), None], 4),
+            (vec![text("nginx"), num(100)], 5),
+            (vec![None, num(999)], 6),
+            (vec![text("bash"), num(70)], 7),
+        ];
+        apply_sort(&mut items, &specs);
+        let order: Vec<u32> = items.iter().map(|(_, pid)| *pid).collect();
+        // Equal on both keys, 1 and 5 keep scan order.
+        assert_eq!(order, vec![7, 2, 4, 3, 1, 5, 6]);
+    }
+}
@@ -0,0 +1,394 @@
+use std::io;
+use std::time::Duration;
+
+use crate::reader::{FsProcReader, ProcReader};
+use crate::sys::clk_tck;
+
+/// Splits `/proc/<pid>/stat` into `comm` and the fields after it.
+///
+/// `comm` is wrapped in parentheses and may itself contain spaces or `)`, so
+/// the split happens at the last `)`. The returned fields start at field 3
+/// (`state`), i.e. `fields[n - 3]` is field `n` in proc(5) numbering.
+pub fn split_stat(buf: &[u8]) -> Option<(&[u8], Vec<&[u8]>)> {
+    let open = buf.iter().position(|b| *b == b'(')?;
+    let close = buf.iter().rposition(|b| *b == b')')?;
+    if close < open {
+        return None;
+    }
+    let fields = buf[close + 1..]
+        .split(|b| *b == b' ' || *b == b'\n')
+        .filter(|f| !f.is_empty())
+        .collect();
+    Some((&buf[open + 1..close], fields))
+}
+
+// Fields up to `rss` exist on every kernel fpid cares about; later ones were
+// added over time (up to `exit_code` in 3.5) and read as 0 when absent.
+const REQUIRED_FIELDS: usize = 24;
+
+macro_rules! proc_stat {
+    ($($name:ident: $ty:ty = $n:literal),* $(,)?) => {
+        /// All fields of `/proc/<pid>/stat`, see proc(5) for each one.
+        ///
+        /// Times are in clock ticks, `rss` in pages, `vsize` in bytes.
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        pub struct ProcStat {
+            pub pid: u32,
+            pub comm: String,
+            pub state: char,
+            $(pub $name: $ty,)*
+        }
+
+        /// Parses `/proc/<pid>/stat` in one pass; fails if a field is not a
+        /// number or one of the always-present ones is missing.
+        pub fn parse_proc_stat_fields(buf: &[u8]) -> Result<ProcStat, io::Error> {
+            let (comm, fields) = split_stat(buf).ok_or_else(|| invalid("stat"))?;
+            let pid = buf.split(|b| *b == b' ').next().unwrap_or_default();
+            Ok(ProcStat {
+                pid: num(pid)?,
+                comm: String::from_utf8_lossy(comm).into_owned(),
+                state: fields
+                    .first()
+                    .and_then(|f| f.first())
+                    .map(|b| *b as char)
+                    .ok_or_else(|| invalid("stat"))?,
+                $($name: match fields.get($n - 3) {
+                    Some(f) => num(f)?,
+                    None if $n > REQUIRED_FIELDS => <$ty>::default(),
+                    None => return Err(invalid("stat")),
+                },)*
+            })
+        }
+
+        impl ProcStat {
+            /// `(name, value)` for every field in file order.
+            pub fn fields(&self) -> Vec<(&'static str, String)> {
+                let mut out = vec![
+                    ("pid", self.pid.to_string()),
+                    ("comm", self.comm.clone()),
+                    ("state", self.state.to_string()),
+                ];
+                $(out.push((stringify!($name), self.$name.to_string()));)*
+                out
+            }
+        }
+    };
+}
+
+proc_stat! {
+    ppid: u32 = 4,
+    pgrp: i32 = 5,
+    session: i32 = 6,
+    tty_nr: i32 = 7,
+    tpgid: i32 = 8,
+    flags: u32 = 9,
+    minflt: u64 = 10,
+    cminflt: u64 = 11,
+    majflt: u64 = 12,
+    cmajflt: u64 = 13,
+    utime: u64 = 14,
+    stime: u64 = 15,
+    cutime: i64 = 16,
+    cstime: i64 = 17,
+    priority: i64 = 18,
+    nice: i64 = 19,
+    num_threads: i64 = 20,
+    itrealvalue: i64 = 21,
+    starttime: u64 = 22,
+    vsize: u64 = 23,
+    rss: i64 = 24,
+    rsslim: u64 = 25,
+    startcode: u64 = 26,
+    endcode: u64 = 27,
+    startstack: u64 = 28,
+    kstkesp: u64 = 29,
+    kstkeip: u64 = 30,
+    signal: u64 = 31,
+    blocked: u64 = 32,
+    sigignore: u64 = 33,
+    sigcatch: u64 = 34,
+    wchan: u64 = 35,
+    nswap: u64 = 36,
+    cnswap: u64 = 37,
+    exit_signal: i32 = 38,
+    processor: i32 = 39,
+    rt_priority: u32 = 40,
+    policy: u32 = 41,
+    delayacct_blkio_ticks: u64 = 42,
+    guest_time: u64 = 43,
+    cguest_time: i64 = 44,
+    start_data: u64 = 45,
+    end_data: u64 = 46,
+    start_brk: u64 = 47,
+    arg_start: u64 = 48,
+    arg_end: u64 = 49,
+    env_start: u64 = 50,
+    env_end: u64 = 51,
+    exit_code: i32 = 52,
+}
+
+fn num<T: std::str::FromStr>(field: &[u8]) -> Result<T, io::Error> {
+    std::str::from_utf8(field)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid("stat"))
+}
+
+impl ProcStat {
+    pub fn read(pid: u32) -> Result<ProcStat, io::Error> {
+        Self::read_with(pid, &FsProcReader::default())
+    }
+
+    pub fn read_with(pid: u32, reader: &impl ProcReader) -> Result<ProcStat, io::Error> {
+        parse_proc_stat_fields(&reader.read(pid, "stat")?)
+    }
+
+    /// `utime + stime` in seconds.
+    pub fn cpu_seconds(&self, ticks_per_sec: u64) -> f64 {
+        (self.utime + self.stime) as f64 / ticks_per_sec.max(1) as f64
+    }
+
+    /// `utime` in seconds: time spent in user mode.
+    pub fn user_seconds(&self, ticks_per_sec: u64) -> f64 {
+        self.utime as f64 / ticks_per_sec.max(1) as f64
+    }
+
+    /// `stime` in seconds: time spent in the kernel on the process's behalf.
+    pub fn sys_seconds(&self, ticks_per_sec: u64) -> f64 {
+        self.stime as f64 / ticks_per_sec.max(1) as f64
+    }
+}
+
+/// First value of `/proc/uptime`: seconds since boot.
+pub fn parse_uptime(buf: &[u8]) -> Option<f64> {
+    let s = std::str::from_utf8(buf).ok()?;
+    s.split_whitespace().next()?.parse().ok()
+}
+
+/// `btime` from `/proc/stat`: boot time in seconds since the epoch.
+pub fn parse_btime(buf: &[u8]) -> Option<u64> {
+    let s = std::str::from_utf8(buf).ok()?;
+    s.lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn invalid(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed {what}"))
+}
+
+/// Age from a process's `starttime` (field 22, ticks after boot) given the
+/// current uptime.
+pub fn age_from_ticks(starttime: u64, uptime_secs: f64, ticks_per_sec: u64) -> Duration {
+    let start = starttime as f64 / ticks_per_sec.max(1) as f64;
+    Duration::from_secs_f64((uptime_secs - start).max(0.0))
+}
+
+/// CPU time as a percentage of the process's lifetime: its historical
+/// utilization rather than current load. Can exceed 100 for multi-threaded
+/// processes; 0 when the age is zero.
+pub fn cpu_lifetime_pct(cpu_sec: f64, age_sec: f64) -> f64 {
+    if age_sec <= 0.0 {
+        return 0.0;
+    }
+    cpu_sec / age_sec * 100.0
+}
+
+/// A lifetime counter as a per-second rate; 0 when the age is zero.
+pub fn per_second(count: u64, age_sec: f64) -> f64 {
+    if age_sec <= 0.0 {
+        return 0.0;
+    }
+    count as f64 / age_sec
+}
+
+/// How long ago the process started.
+pub fn process_age(pid: u32, reader: &impl ProcReader) -> Result<Duration, io::Error> {
+    let stat = ProcStat::read_with(pid, reader)?;
+    let uptime = parse_uptime(&reader.read_root("uptime")?).ok_or_else(|| invalid("uptime"))?;
+    Ok(age_from_ticks(stat.starttime, uptime, clk_tck()))
+}
+
+/// Total CPU time (`utime + stime`) in seconds.
+pub fn read_stat_cputime(pid: u32, reader: &impl ProcReader) -> Result<f64, io::Error> {
+    let stat = ProcStat::read_with(pid, reader)?;
+    Ok(stat.cpu_seconds(clk_tck()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    // starttime (field 22) = 150000 ticks.
+    const STAT: &[u8] = b"4242 (my (odd) app) S 1 4242 4242 0 -1 4194560 120 0 0 0 35 12 0 0 20 0 1 0 150000 12345678 300 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 2 0 0 0 0 0\n";
+
+    #[test]
+    fn test_split_stat() {
+        let (comm, fields) = split_stat(STAT).unwrap();
+        assert_eq!(comm, b"my (odd) app");
+        assert_eq!(fields[0], b"S");
+        assert_eq!(fields[22 - 3], b"150000");
+        assert!(split_stat(b"garbage").is_none());
+    }
+
+    #[test]
+    fn test_parse_proc_stat_fields() {
+        let st = parse_proc_stat_fields(STAT).unwrap();
+        assert_eq!(st.pid, 4242);
+        assert_eq!(st.comm, "my (odd) app");
+        assert_eq!(st.state, 'S');
+        assert_eq!(st.ppid, 1);
+        assert_eq!(st.tty_nr, 0);
+        assert_eq!(st.minflt, 120);
+        assert_eq!((st.utime, st.stime), (35, 12));
+        assert_eq!((st.priority, st.nice, st.num_threads), (20, 0, 1));
+        assert_eq!(st.starttime, 150000);
+        assert_eq!((st.vsize, st.rss), (12345678, 300));
+        assert!((st.cpu_seconds(100) - 0.47).abs() < 1e-9);
+        assert!((st.user_seconds(100) - 0.35).abs() < 1e-9);
+        assert!((st.sys_seconds(100) - 0.12).abs() < 1e-9);
+        assert!(parse_proc_stat_fields(b"1 (init) S 0 1").is_err());
+        assert!(
+            parse_proc_stat_fields(b"1 (init) S x 1 1 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 5 0 0")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_read_stat_cputime() {
+        let reader = MockProcReader::new().file(4242, "stat", STAT);
+        assert!((read_stat_cputime(4242, &reader).unwrap() - 0.47).abs() < 1e-9);
+        assert!(read_stat_cputime(1, &reader).is_err());
+    }
+
+    #[test]
+    fn test_age_from_ticks() {
+        assert_eq!(
+            age_from_ticks(150000, 2000.0, 100),
+            Duration::from_secs(500)
+        );
+        assert_eq!(
+            age_from_ticks(150000, 2000.0, 250),
+            Duration::from_secs(1400)
+        );
+        // Clock skew between the two reads must not underflow.
+        assert_eq!(age_from_ticks(150000, 1000.0, 100), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_cpu_lifetime_pct() {
+        assert_eq!(cpu_lifetime_pct(30.0, 120.0), 25.0);
+        assert_eq!(cpu_lifetime_pct(0.0, 120.0), 0.0);
+        // Four busy threads.
+        assert_eq!(cpu_lifetime_pct(400.0, 100.0), 400.0);
+        assert_eq!(cpu_lifetime_pct(0.01, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_per_second() {
+        assert_eq!(per_second(1200, 60.0), 20.0);
+        assert_eq!(per_second(0, 60.0), 0.0);
+        assert_eq!(per_second(5, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_parse_btime() {
+        let stat = b"cpu  1 2 3 4\nintr 5\nctxt 6\nbtime 1791969330\nprocesses 7\n";
+        assert_eq!(parse_btime(stat), Some(1791969330));
+        assert_eq!(parse_btime(b"cpu  1 2 3 4\n"), None);
+        assert_eq!(parse_btime(b"btime x\n"), None);
+    }
+
+    #[test]
+    fn test_process_age() {
+        let reader = MockProcReader::new()
+            .file(4242, "stat", STAT)
+            .root_file("uptime", "1600.50 3000.00\n");
+        assert_eq!(
+            process_age(4242, &reader).unwrap(),
+            Duration::from_millis(100_500)
+        );
+        assert!(process_age(1, &reader).is_err());
+    }
+
+    // A full 52-field line as written by a 6.x kernel.
+    const FULL: &[u8] = b"1234 (kworker/u8:2-events) I 2 0 0 0 -1 69238880 0 0 0 0 5 7 0 0 20 0 1 0 3443 0 0 18446744073709551615 0 0 0 0 0 0 0 2147483647 0 1 0 0 17 3 0 0 0 0 0 0 0 0 0 0 0 0 0\n";
+
+    #[test]
+    fn test_parse_all_fields() {
+        let st = parse_proc_stat_fields(FULL).unwrap();
+        assert_eq!(st.comm, "kworker/u8:2-events");
+        assert_eq!(st.state, 'I');
+        assert_eq!(st.ppid, 2);
+        assert_eq!(st.tpgid, -1);
+        assert_eq!(st.flags, 69238880);
+        assert_eq!(st.rsslim, u64::MAX);
+        assert_eq!(st.sigcatch, 0);
+        assert_eq!(st.sigignore, 2147483647);
+        assert_eq!(st.wchan, 1);
+        assert_eq!(st.exit_signal, 17);
+        assert_eq!(st.processor, 3);
+        assert_eq!(st.exit_code, 0);
+        let fields = st.fields();
+        assert_eq!(fields.len(), 52);
+        assert_eq!(fields[1], ("comm", "kworker/u8:2-events".to_string()));
+        assert_eq!(fields[51], ("exit_code", "0".to_string()));
+    }
+
+    #[test]
+    fn test_parse_short_stat_defaults_late_fields() {
+        // Lines cut after rss (field 24) must still parse.
+        let st = parse_proc_stat_fields(b"9 (a) R 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 77 0 5\n")
+            .unwrap();
+        assert_eq!(
+            (st.starttime, st.rss, st.rsslim, st.exit_code),
+            (77, 5, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_tricky_comm() {
+        let cases: &[(&[u8], &str)] = &[
+            (
+                b"5 (a b c) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 77 0 5",
+                "a b c",
+            ),
+            (
+                b"5 ((sd-pam)) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 77 0 5",
+                "(sd-pam)",
+            ),
+            (
+                b"5 (x) S 1) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 77 0 5",
+                "x) S 1",
+            ),
+            (b"5 () S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 77 0 5", ""),
+            (
+                b"5 (:-) ) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 77 0 5",
+                ":-) ",
+            ),
+            (
+                b"5 (( ) S 1 9 9 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 77 0 5",
+                "( ",
+            ),
+        ];
+        for (line, comm) in cases {
+            let st = parse_proc_stat_fields(line).unwrap();
+            assert_eq!(st.comm, *comm);
+            assert_eq!((st.state, st.ppid, st.starttime, st.rss), ('S', 1, 77, 5));
+        }
+    }
+
+    #[test]
+    fn test_proc_stat_read_with() {
+        let reader = MockProcReader::new().file(1234, "stat", FULL);
+        assert_eq!(ProcStat::read_with(1234, &reader).unwrap().pid, 1234);
+        assert!(ProcStat::read_with(1, &reader).is_err());
+        assert_eq!(
+            ProcStat::read(std::process::id()).unwrap().pid,
+            std::process::id()
+        );
+    }
+}
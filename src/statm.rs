@@ -0,0 +1,131 @@
+use std::io;
+
+use crate::reader::{FsProcReader, ProcReader};
+use crate::sys::page_size;
+
+/// `/proc/<pid>/statm`, all values in pages.
+///
+/// A single short line, so this is the cheapest way to get memory sizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatmInfo {
+    pub size: u64,
+    pub resident: u64,
+    pub shared: u64,
+    pub text: u64,
+    pub lib: u64,
+    pub data: u64,
+    pub dirty: u64,
+}
+
+impl StatmInfo {
+    pub fn parse(buf: &[u8]) -> Result<StatmInfo, io::Error> {
+        let s = std::str::from_utf8(buf).map_err(|_| invalid())?;
+        let mut it = s.split_whitespace().map(|v| v.parse::<u64>());
+        let mut next = || it.next().and_then(Result::ok).ok_or_else(invalid);
+        Ok(StatmInfo {
+            size: next()?,
+            resident: next()?,
+            shared: next()?,
+            text: next()?,
+            lib: next()?,
+            data: next()?,
+            dirty: next()?,
+        })
+    }
+
+    pub fn read(pid: u32) -> Result<StatmInfo, io::Error> {
+        Self::read_with(pid, &FsProcReader::default())
+    }
+
+    pub fn read_with(pid: u32, reader: &impl ProcReader) -> Result<StatmInfo, io::Error> {
+        Self::parse(&reader.read(pid, "statm")?)
+    }
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed statm")
+}
+
+/// Converts a page count to kB using the system page size.
+pub fn statm_to_kb(pages: u64) -> u64 {
+    pages_to_kb(pages, page_size())
+}
+
+fn pages_to_kb(pages: u64, page_size: u64) -> u64 {
+    pages * page_size / 1024
+}
+
+/// Which statm value a [`StatmFilter`] looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatmField {
+    Size,
+    Resident,
+    Shared,
+}
+
+/// Keeps processes whose statm value, in kB, lies within `min_kb..=max_kb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatmFilter {
+    pub field: StatmField,
+    pub min_kb: Option<u64>,
+    pub max_kb: Option<u64>,
+}
+
+impl StatmFilter {
+    pub fn matches(&self, info: &StatmInfo) -> bool {
+        let pages = match self.field {
+            StatmField::Size => info.size,
+            StatmField::Resident => info.resident,
+            StatmField::Shared => info.shared,
+        };
+        let kb = statm_to_kb(pages);
+        self.min_kb.is_none_or(|min| kb >= min) && self.max_kb.is_none_or(|max| kb <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_parse_statm() {
+        assert_eq!(
+            StatmInfo::parse(b"2496 1280 1024 244 0 208 0\n").unwrap(),
+            StatmInfo {
+                size: 2496,
+                resident: 1280,
+                shared: 1024,
+                text: 244,
+                lib: 0,
+                data: 208,
+                dirty: 0,
+            }
+        );
+        assert!(StatmInfo::parse(b"1 2 3\n").is_err());
+        assert!(StatmInfo::parse(b"1 2 3 4 5 x 7\n").is_err());
+    }
+
+    #[test]
+    fn test_pages_to_kb() {
+        assert_eq!(pages_to_kb(1280, 4096), 5120);
+        assert_eq!(pages_to_kb(3, 16384), 48);
+        assert_eq!(statm_to_kb(0), 0);
+    }
+
+    #[test]
+    fn test_statm_filter() {
+        let reader = MockProcReader::new().file(1, "statm", "2496 1280 1024 244 0 208 0\n");
+        let info = StatmInfo::read_with(1, &reader).unwrap();
+        let rss_kb = statm_to_kb(1280);
+        let f = |min_kb, max_kb| StatmFilter {
+            field: StatmField::Resident,
+            min_kb,
+            max_kb,
+        };
+        assert!(f(Some(rss_kb), None).matches(&info));
+        assert!(!f(Some(rss_kb + 1), None).matches(&info));
+        assert!(f(None, Some(rss_kb)).matches(&info));
+        assert!(!f(Some(1), Some(rss_kb - 1)).matches(&info));
+    }
+}
@@ -0,0 +1,531 @@
+use std::io::{self, BufRead, BufReader};
+
+use crate::error::ParseError;
+use crate::reader::{FsProcReader, ProcReader};
+
+// /proc/<pid>/status has ~60 lines on current kernels; never read past this.
+pub const MAX_STATUS_LINES: usize = 64;
+
+/// Looks up `fields` (e.g. `"VmRSS"`) in a status-formatted stream.
+///
+/// Returns one slot per requested field, holding the trimmed value after the
+/// `Name:` prefix. Reading stops as soon as every field has been seen.
+pub fn parse_status_fields<R: BufRead>(
+    reader: R,
+    fields: &[&str],
+) -> io::Result<Vec<Option<String>>> {
+    let mut out: Vec<Option<String>> = vec![None; fields.len()];
+    let mut remaining = fields.len();
+
+    for line in reader.lines().take(MAX_STATUS_LINES) {
+        if remaining == 0 {
+            break;
+        }
+        let line = line?;
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        for (i, field) in fields.iter().enumerate() {
+            if out[i].is_none() && key == *field {
+                out[i] = Some(value.trim().to_string());
+                remaining -= 1;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Reads the requested fields from `/proc/<pid>/status`.
+pub fn read_status_fields(
+    pid: u32,
+    fields: &[&str],
+    reader: &impl ProcReader,
+) -> io::Result<Vec<Option<String>>> {
+    parse_status_fields(BufReader::new(reader.open(pid, "status")?), fields)
+}
+
+/// PID of the process ptrace-attached to `pid` (a debugger or strace), 0
+/// when none. Only the status lines up to `TracerPid` are read.
+pub fn read_tracer_pid(pid: u32, reader: &impl ProcReader) -> io::Result<u32> {
+    let fields = read_status_fields(pid, &["TracerPid"], reader)?;
+    fields[0]
+        .as_deref()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| ParseError::MissingField("TracerPid").into())
+}
+
+/// The `NSpid:` list of a status buffer: the process's PID in each nested
+/// PID namespace, host first and innermost last. Empty on kernels before
+/// 4.1, which lack the line.
+pub fn parse_ns_pids(status_buf: &[u8]) -> Vec<u32> {
+    String::from_utf8_lossy(status_buf)
+        .lines()
+        .take(MAX_STATUS_LINES)
+        .find_map(|line| line.strip_prefix("NSpid:"))
+        .map(ids)
+        .unwrap_or_default()
+}
+
+/// `/proc/<pid>/status`, parsed from a single read.
+///
+/// Memory values are in kB. Fields a kernel does not report (Vm* for kernel
+/// threads, HugetlbPages before 4.4, ...) are `None`; masks are parsed from
+/// hex, the CPU/memory node masks and lists are kept verbatim.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusInfo {
+    pub name: String,
+    pub umask: Option<u32>,
+    /// Full state text, e.g. `S (sleeping)`.
+    pub state: String,
+    pub tgid: u32,
+    pub ngid: u32,
+    pub pid: u32,
+    pub ppid: u32,
+    pub tracer_pid: u32,
+    /// Real, effective, saved set and filesystem UID.
+    pub uid: [u32; 4],
+    /// Real, effective, saved set and filesystem GID.
+    pub gid: [u32; 4],
+    pub fd_size: u64,
+    pub groups: Vec<u32>,
+    /// One entry per nested PID namespace, outermost first.
+    pub ns_tgid: Vec<u32>,
+    pub ns_pid: Vec<u32>,
+    pub ns_pgid: Vec<u32>,
+    pub ns_sid: Vec<u32>,
+    pub vm_peak: Option<u64>,
+    pub vm_size: Option<u64>,
+    pub vm_lck: Option<u64>,
+    pub vm_pin: Option<u64>,
+    pub vm_hwm: Option<u64>,
+    pub vm_rss: Option<u64>,
+    pub rss_anon: Option<u64>,
+    pub rss_file: Option<u64>,
+    pub rss_shmem: Option<u64>,
+    pub vm_data: Option<u64>,
+    pub vm_stk: Option<u64>,
+    pub vm_exe: Option<u64>,
+    pub vm_lib: Option<u64>,
+    pub vm_pte: Option<u64>,
+    pub vm_swap: Option<u64>,
+    pub hugetlb_pages: Option<u64>,
+    /// `VmHuge`, listed by some vendor kernels; absent on mainline.
+    pub vm_huge: Option<u64>,
+    pub core_dumping: Option<bool>,
+    pub thp_enabled: Option<bool>,
+    pub threads: u64,
+    /// `queued/limit`.
+    pub sig_q: String,
+    pub sig_pnd: u64,
+    pub shd_pnd: u64,
+    pub sig_blk: u64,
+    pub sig_ign: u64,
+    pub sig_cgt: u64,
+    pub cap_inh: u64,
+    pub cap_prm: u64,
+    pub cap_eff: u64,
+    pub cap_bnd: u64,
+    pub cap_amb: u64,
+    pub seccomp: Option<u8>,
+    pub cpus_allowed: String,
+    pub cpus_allowed_list: String,
+    pub mems_allowed: String,
+    pub mems_allowed_list: String,
+    pub voluntary_ctxt_switches: u64,
+    pub nonvoluntary_ctxt_switches: u64,
+}
+
+/// The `Vm*` status lines that `--print-vm-*` report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VmField {
+    Size,
+    Peak,
+    Data,
+    Exe,
+    Lib,
+    /// `VmPin`: pages pinned in RAM, e.g. by a driver for DMA.
+    Pin,
+    /// `VmLck`: pages locked with `mlock`/`mlockall`.
+    Lck,
+}
+
+impl VmField {
+    /// The field in kB; `None` for kernel threads.
+    pub fn get(self, st: &StatusInfo) -> Option<u64> {
+        match self {
+            VmField::Size => st.vm_size,
+            VmField::Peak => st.vm_peak,
+            VmField::Data => st.vm_data,
+            VmField::Exe => st.vm_exe,
+            VmField::Lib => st.vm_lib,
+            VmField::Pin => st.vm_pin,
+            VmField::Lck => st.vm_lck,
+        }
+    }
+}
+
+/// Name of a `Seccomp:` status value: `DISABLED`, `STRICT` or `FILTER`
+/// (`UNKNOWN` for modes newer than this list).
+pub fn decode_seccomp_mode(n: u8) -> &'static str {
+    match n {
+        0 => "DISABLED",
+        1 => "STRICT",
+        2 => "FILTER",
+        _ => "UNKNOWN",
+    }
+}
+
+/// `--seccomp-mode` value: a name from [`decode_seccomp_mode`] in any case,
+/// or the number.
+pub fn parse_seccomp_mode(s: &str) -> Option<u8> {
+    (0..=2)
+        .find(|n| decode_seccomp_mode(*n).eq_ignore_ascii_case(s))
+        .or_else(|| s.parse().ok())
+}
+
+/// Voluntary context switches per CPU second, a rough lock-wait rate: a
+/// process that keeps blocking on locks or I/O switches out often while
+/// using little CPU. CPU time below one second counts as one.
+pub fn ctx_rate(voluntary: u64, cpu_sec: f64) -> f64 {
+    voluntary as f64 / cpu_sec.max(1.0)
+}
+
+fn ids(v: &str) -> Vec<u32> {
+    v.split_whitespace()
+        .filter_map(|x| x.parse().ok())
+        .collect()
+}
+
+fn id4(v: &str) -> [u32; 4] {
+    let mut out = [0; 4];
+    for (slot, x) in out.iter_mut().zip(ids(v)) {
+        *slot = x;
+    }
+    out
+}
+
+// "  5120 kB" -> 5120
+fn kb(v: &str) -> Option<u64> {
+    v.split_whitespace().next()?.parse().ok()
+}
+
+fn hex(v: &str) -> u64 {
+    u64::from_str_radix(v, 16).unwrap_or(0)
+}
+
+impl StatusInfo {
+    /// Parses status text; unknown lines are ignored and malformed values
+    /// left at their defaults, so newer or older kernels never fail here.
+    pub fn parse(buf: &[u8]) -> StatusInfo {
+        let mut st = StatusInfo::default();
+        for line in String::from_utf8_lossy(buf).lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let v = value.trim();
+            match key {
+                "Name" => st.name = v.to_string(),
+                "Umask" => st.umask = u32::from_str_radix(v, 8).ok(),
+                "State" => st.state = v.to_string(),
+                "Tgid" => st.tgid = v.parse().unwrap_or(0),
+                "Ngid" => st.ngid = v.parse().unwrap_or(0),
+                "Pid" => st.pid = v.parse().unwrap_or(0),
+                "PPid" => st.ppid = v.parse().unwrap_or(0),
+                "TracerPid" => st.tracer_pid = v.parse().unwrap_or(0),
+                "Uid" => st.uid = id4(v),
+                "Gid" => st.gid = id4(v),
+                "FDSize" => st.fd_size = v.parse().unwrap_or(0),
+                "Groups" => st.groups = ids(v),
+                "NStgid" => st.ns_tgid = ids(v),
+                "NSpid" => st.ns_pid = ids(v),
+                "NSpgid" => st.ns_pgid = ids(v),
+                "NSsid" => st.ns_sid = ids(v),
+                "VmPeak" => st.vm_peak = kb(v),
+                "VmSize" => st.vm_size = kb(v),
+                "VmLck" => st.vm_lck = kb(v),
+                "VmPin" => st.vm_pin = kb(v),
+                "VmHWM" => st.vm_hwm = kb(v),
+                "VmRSS" => st.vm_rss = kb(v),
+                "RssAnon" => st.rss_anon = kb(v),
+                "RssFile" => st.rss_file = kb(v),
+                "RssShmem" => st.rss_shmem = kb(v),
+                "VmData" => st.vm_data = kb(v),
+                "VmStk" => st.vm_stk = kb(v),
+                "VmExe" => st.vm_exe = kb(v),
+                "VmLib" => st.vm_lib = kb(v),
+                "VmPTE" => st.vm_pte = kb(v),
+                "VmSwap" => st.vm_swap = kb(v),
+                "HugetlbPages" => st.hugetlb_pages = kb(v),
+                "VmHuge" => st.vm_huge = kb(v),
+                "CoreDumping" => st.core_dumping = Some(v == "1"),
+                "THP_enabled" => st.thp_enabled = Some(v == "1"),
+                "Threads" => st.threads = v.parse().unwrap_or(0),
+                "SigQ" => st.sig_q = v.to_string(),
+                "SigPnd" => st.sig_pnd = hex(v),
+                "ShdPnd" => st.shd_pnd = hex(v),
+                "SigBlk" => st.sig_blk = hex(v),
+                "SigIgn" => st.sig_ign = hex(v),
+                "SigCgt" => st.sig_cgt = hex(v),
+                "CapInh" => st.cap_inh = hex(v),
+                "CapPrm" => st.cap_prm = hex(v),
+                "CapEff" => st.cap_eff = hex(v),
+                "CapBnd" => st.cap_bnd = hex(v),
+                "CapAmb" => st.cap_amb = hex(v),
+                "Seccomp" => st.seccomp = v.parse().ok(),
+                "Cpus_allowed" => st.cpus_allowed = v.to_string(),
+                "Cpus_allowed_list" => st.cpus_allowed_list = v.to_string(),
+                "Mems_allowed" => st.mems_allowed = v.to_string(),
+                "Mems_allowed_list" => st.mems_allowed_list = v.to_string(),
+                "voluntary_ctxt_switches" => st.voluntary_ctxt_switches = v.parse().unwrap_or(0),
+                "nonvoluntary_ctxt_switches" => {
+                    st.nonvoluntary_ctxt_switches = v.parse().unwrap_or(0)
+                }
+                _ => {}
+            }
+        }
+        st
+    }
+
+    pub fn read(pid: u32) -> io::Result<StatusInfo> {
+        Self::read_with(pid, &FsProcReader::default())
+    }
+
+    pub fn read_with(pid: u32, reader: &impl ProcReader) -> io::Result<StatusInfo> {
+        Ok(Self::parse(&reader.read(pid, "status")?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    const SAMPLE: &str = "Name:\tbash\nUmask:\t0022\nState:\tS (sleeping)\nTgid:\t4242\nPid:\t4242\nPPid:\t1\nVmRSS:\t    5120 kB\n";
+
+    #[test]
+    fn test_parse_status_fields() {
+        let res =
+            parse_status_fields(SAMPLE.as_bytes(), &["PPid", "Name", "VmRSS", "Missing"]).unwrap();
+        assert_eq!(
+            res,
+            vec![
+                Some("1".to_string()),
+                Some("bash".to_string()),
+                Some("5120 kB".to_string()),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_status_stops_early() {
+        // Invalid UTF-8 past the last requested field would error if read.
+        let input: &[u8] = b"Name:\tbash\n\xff\xfe\n";
+        let res = parse_status_fields(input, &["Name"]).unwrap();
+        assert_eq!(res, vec![Some("bash".to_string())]);
+        assert!(parse_status_fields(input, &["Name", "Pid"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_status_line_cap() {
+        let mut input = "Filler:\t0\n".repeat(MAX_STATUS_LINES);
+        input.push_str("Name:\tlate\n");
+        let res = parse_status_fields(input.as_bytes(), &["Name"]).unwrap();
+        assert_eq!(res, vec![None]);
+    }
+
+    const FULL: &str = "\
+Name:\tnginx
+Umask:\t0022
+State:\tS (sleeping)
+Tgid:\t4242
+Ngid:\t0
+Pid:\t4242
+PPid:\t1
+TracerPid:\t0
+Uid:\t33\t33\t33\t33
+Gid:\t33\t33\t33\t34
+FDSize:\t64
+Groups:\t33 44 
+NStgid:\t4242\t1
+NSpid:\t4242\t1
+NSpgid:\t4242\t1
+NSsid:\t4242\t1
+VmPeak:\t   10000 kB
+VmSize:\t    9984 kB
+VmLck:\t       0 kB
+VmPin:\t       0 kB
+VmHWM:\t    5200 kB
+VmRSS:\t    5120 kB
+RssAnon:\t    1024 kB
+RssFile:\t    4096 kB
+RssShmem:\t       0 kB
+VmData:\t     832 kB
+VmStk:\t     132 kB
+VmExe:\t     976 kB
+VmLib:\t    3000 kB
+VmPTE:\t      52 kB
+VmSwap:\t       8 kB
+HugetlbPages:\t       0 kB
+CoreDumping:\t0
+THP_enabled:\t1
+Threads:\t1
+SigQ:\t0/63471
+SigPnd:\t0000000000000000
+ShdPnd:\t0000000000000000
+SigBlk:\t0000000000000000
+SigIgn:\t0000000040001000
+SigCgt:\t0000000198016a07
+CapInh:\t0000000000000000
+CapPrm:\t0000000000000000
+CapEff:\t0000000000000400
+CapBnd:\t000001ffffffffff
+CapAmb:\t0000000000000000
+NoNewPrivs:\t0
+Seccomp:\t2
+Cpus_allowed:\tf
+Cpus_allowed_list:\t0-3
+Mems_allowed:\t00000000,00000001
+Mems_allowed_list:\t0
+voluntary_ctxt_switches:\t150
+nonvoluntary_ctxt_switches:\t7
+";
+
+    #[test]
+    fn test_status_info_parse() {
+        let st = StatusInfo::parse(FULL.as_bytes());
+        assert_eq!(st.name, "nginx");
+        assert_eq!(st.umask, Some(0o022));
+        assert_eq!(st.state, "S (sleeping)");
+        assert_eq!(
+            (st.tgid, st.pid, st.ppid, st.tracer_pid),
+            (4242, 4242, 1, 0)
+        );
+        assert_eq!(st.uid, [33; 4]);
+        assert_eq!(st.gid, [33, 33, 33, 34]);
+        assert_eq!(st.groups, vec![33, 44]);
+        assert_eq!(st.ns_pid, vec![4242, 1]);
+        assert_eq!(st.vm_rss, Some(5120));
+        assert_eq!(st.vm_swap, Some(8));
+        assert_eq!(st.hugetlb_pages, Some(0));
+        assert_eq!(st.vm_huge, None);
+        assert_eq!(st.core_dumping, Some(false));
+        assert_eq!(st.thp_enabled, Some(true));
+        assert_eq!(st.sig_ign, 0x40001000);
+        assert_eq!(st.cap_eff, 0x400);
+        assert_eq!(st.cap_bnd, 0x1ff_ffff_ffff);
+        assert_eq!(st.seccomp, Some(2));
+        assert_eq!(st.cpus_allowed, "f");
+        assert_eq!(st.cpus_allowed_list, "0-3");
+        assert_eq!(st.mems_allowed, "00000000,00000001");
+        assert_eq!(
+            (st.voluntary_ctxt_switches, st.nonvoluntary_ctxt_switches),
+            (150, 7)
+        );
+    }
+
+    #[test]
+    fn test_vm_field() {
+        let st = StatusInfo {
+            vm_size: Some(1),
+            vm_peak: Some(2),
+            vm_data: Some(3),
+            vm_exe: Some(4),
+            vm_lib: Some(5),
+            vm_pin: Some(6),
+            vm_lck: Some(7),
+            ..Default::default()
+        };
+        let fields = [
+            VmField::Size,
+            VmField::Peak,
+            VmField::Data,
+            VmField::Exe,
+            VmField::Lib,
+            VmField::Pin,
+            VmField::Lck,
+        ];
+        let got: Vec<Option<u64>> = fields.iter().map(|f| f.get(&st)).collect();
+        assert_eq!(got, [1, 2, 3, 4, 5, 6, 7].map(Some));
+        assert_eq!(VmField::Size.get(&StatusInfo::default()), None);
+    }
+
+    #[test]
+    fn test_status_info_huge_pages() {
+        let st =
+            StatusInfo::parse(b"Name:\tdb\nVmHuge:\t    4096 kB\nHugetlbPages:\t 2097152 kB\n");
+        assert_eq!((st.vm_huge, st.hugetlb_pages), (Some(4096), Some(2097152)));
+        // Before Linux 4.4 neither line exists.
+        let old = StatusInfo::parse(b"Name:\tdb\nVmRSS:\t    5120 kB\nVmSwap:\t       0 kB\n");
+        assert_eq!((old.vm_huge, old.hugetlb_pages), (None, None));
+    }
+
+    #[test]
+    fn test_seccomp_mode() {
+        let names: Vec<&str> = (0..4).map(decode_seccomp_mode).collect();
+        assert_eq!(names, ["DISABLED", "STRICT", "FILTER", "UNKNOWN"]);
+        assert_eq!(parse_seccomp_mode("filter"), Some(2));
+        assert_eq!(parse_seccomp_mode("STRICT"), Some(1));
+        assert_eq!(parse_seccomp_mode("0"), Some(0));
+        assert_eq!(parse_seccomp_mode("unknown"), None);
+        assert_eq!(parse_seccomp_mode("bpf"), None);
+    }
+
+    #[test]
+    fn test_ctx_rate() {
+        assert_eq!(ctx_rate(5000, 2.5), 2000.0);
+        assert_eq!(ctx_rate(150, 0.0), 150.0);
+        assert_eq!(ctx_rate(150, 0.25), 150.0);
+        assert_eq!(ctx_rate(0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn test_status_info_kernel_thread() {
+        // Kernel threads have no memory lines at all.
+        let st = StatusInfo::parse(
+            b"Name:\tkthreadd\nState:\tS (sleeping)\nPid:\t2\nPPid:\t0\nThreads:\t1\n",
+        );
+        assert_eq!(st.name, "kthreadd");
+        assert_eq!(st.vm_rss, None);
+        assert_eq!(st.vm_size, None);
+        assert_eq!(st.seccomp, None);
+        assert!(st.ns_pid.is_empty());
+    }
+
+    #[test]
+    fn test_status_info_read_with() {
+        let reader = MockProcReader::new().file(4242, "status", FULL);
+        assert_eq!(StatusInfo::read_with(4242, &reader).unwrap().name, "nginx");
+        assert!(StatusInfo::read_with(1, &reader).is_err());
+        assert_eq!(
+            StatusInfo::read(std::process::id()).unwrap().pid,
+            std::process::id()
+        );
+    }
+
+    #[test]
+    fn test_parse_ns_pids() {
+        assert_eq!(parse_ns_pids(FULL.as_bytes()), vec![4242, 1]);
+        // Host, container, and a nested sandbox inside the container.
+        let nested = "Name:\tsh\nNStgid:\t9000\t310\t7\nNSpid:\t9001\t311\t8\n";
+        assert_eq!(parse_ns_pids(nested.as_bytes()), vec![9001, 311, 8]);
+        assert_eq!(parse_ns_pids(b"Name:\tsh\nNSpid:\t77\n"), vec![77]);
+        assert!(parse_ns_pids(b"Name:\tsh\nPid:\t77\n").is_empty());
+    }
+
+    #[test]
+    fn test_read_tracer_pid() {
+        let reader = MockProcReader::new()
+            .file(7, "status", FULL)
+            .file(8, "status", "Name:\tx\nTracerPid:\t31337\n")
+            .file(9, "status", "Name:\tx\n");
+        assert_eq!(read_tracer_pid(7, &reader).unwrap(), 0);
+        assert_eq!(read_tracer_pid(8, &reader).unwrap(), 31337);
+        assert_eq!(
+            read_tracer_pid(9, &reader).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(read_tracer_pid(10, &reader).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+// Thin wrappers over the few libc calls fpid needs. std already links libc,
+// so these are declared directly instead of pulling in a bindings crate.
+
+use std::fs::File;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::raw::{c_int, c_long, c_short, c_ulong};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+unsafe extern "C" {
+    fn sysconf(name: c_int) -> c_long;
+    fn kill(pid: c_int, sig: c_int) -> c_int;
+    fn fork() -> c_int;
+    fn setsid() -> c_int;
+    fn dup2(old: c_int, new: c_int) -> c_int;
+    fn isatty(fd: c_int) -> c_int;
+    fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    fn setpriority(which: c_int, who: u32, prio: c_int) -> c_int;
+    // `handler` is a sighandler_t: a function pointer or SIG_DFL (0).
+    fn signal(sig: c_int, handler: usize) -> usize;
+    fn socket(domain: c_int, kind: c_int, protocol: c_int) -> c_int;
+    fn bind(fd: c_int, addr: *const SockaddrNl, len: u32) -> c_int;
+    fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+}
+
+pub const SIGINT: i32 = 2;
+pub const SIGTERM: i32 = 15;
+const SIG_DFL: usize = 0;
+// Returned by signal() on failure.
+const SIG_ERR: usize = usize::MAX;
+
+/// Set by the handler [`install_sigint_flag`] installs.
+pub static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sigint(_: c_int) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+// Bionic numbers its sysconf names differently from glibc/musl.
+#[cfg(target_os = "android")]
+const SC_CLK_TCK: c_int = 6;
+#[cfg(not(target_os = "android"))]
+const SC_CLK_TCK: c_int = 2;
+#[cfg(target_os = "android")]
+const SC_PAGESIZE: c_int = 39;
+#[cfg(not(target_os = "android"))]
+const SC_PAGESIZE: c_int = 30;
+
+/// Clock ticks per second used by `/proc/<pid>/stat` times (`USER_HZ`).
+pub fn clk_tck() -> u64 {
+    // SAFETY: sysconf has no preconditions; unknown names return -1.
+    let v = unsafe { sysconf(SC_CLK_TCK) };
+    if v > 0 { v as u64 } else { 100 }
+}
+
+/// Size of a memory page in bytes (statm and `rss` are in pages).
+pub fn page_size() -> u64 {
+    // SAFETY: as above.
+    let v = unsafe { sysconf(SC_PAGESIZE) };
+    if v > 0 { v as u64 } else { 4096 }
+}
+
+/// Sends `sig` to `pid`; 0 only checks that the process exists.
+pub fn send_signal(pid: u32, sig: i32) -> io::Result<()> {
+    let pid = c_int::try_from(pid).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    // SAFETY: kill has no memory preconditions; a positive pid targets one process.
+    if unsafe { kill(pid, sig) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+const PRIO_PROCESS: c_int = 0;
+
+/// Sets the nice value of `pid` (`setpriority(PRIO_PROCESS, ...)`).
+pub fn set_priority(pid: u32, nice: i32) -> io::Result<()> {
+    // SAFETY: setpriority only takes integers; pid 0 would mean ourselves,
+    // which callers never pass for a matched process.
+    if unsafe { setpriority(PRIO_PROCESS, pid, nice) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Forks into a new session. Returns the child's PID in the parent and
+/// `None` in the child. Call before spawning any threads.
+///
+/// The child lets go of everything tying it to the caller: stdin, stdout
+/// and stderr point at `/dev/null`, so it neither holds the terminal or a
+/// pipe open nor gets SIGPIPE from one, and it runs in `/` so it doesn't
+/// pin a mounted working directory.
+pub fn fork_detached() -> io::Result<Option<u32>> {
+    // SAFETY: fpid is single-threaded when this runs, so the child may keep
+    // using std after fork.
+    match unsafe { fork() } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => {
+            // SAFETY: no preconditions; fails only if already a group leader.
+            unsafe { setsid() };
+            std::env::set_current_dir("/")?;
+            detach_stdio()?;
+            Ok(None)
+        }
+        child => Ok(Some(child as u32)),
+    }
+}
+
+// Points fds 0, 1 and 2 at /dev/null.
+fn detach_stdio() -> io::Result<()> {
+    let null = File::options().read(true).write(true).open("/dev/null")?;
+    for fd in 0..=2 {
+        // SAFETY: both descriptors are open; dup2 replaces `fd` atomically
+        // and `null` is closed when it drops.
+        if unsafe { dup2(null.as_raw_fd(), fd) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the default SIGINT action (terminate) with setting
+/// [`INTERRUPTED`], so a scan can stop and flush what it has.
+pub fn install_sigint_flag() -> io::Result<()> {
+    let handler: extern "C" fn(c_int) = on_sigint;
+    // SAFETY: the handler only stores to an atomic, which is
+    // async-signal-safe.
+    if unsafe { signal(SIGINT, handler as usize) } == SIG_ERR {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Puts back the default SIGINT action, so Ctrl-C terminates fpid again.
+/// Blocking reads such as the `--confirm` prompt are restarted after a
+/// handled signal, so only the default action can interrupt them.
+pub fn restore_default_sigint() -> io::Result<()> {
+    // SAFETY: SIG_DFL is always a valid disposition.
+    if unsafe { signal(SIGINT, SIG_DFL) } == SIG_ERR {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Whether `fd` refers to a terminal.
+pub fn is_tty(fd: i32) -> bool {
+    // SAFETY: isatty only inspects the descriptor; invalid ones return 0.
+    unsafe { isatty(fd) == 1 }
+}
+
+/// Whether stdout is a terminal, i.e. not piped or redirected.
+pub fn is_stdout_tty() -> bool {
+    is_tty(1)
+}
+
+// The few architectures with BSD-style ioctl numbers encode it differently.
+#[cfg(any(
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "sparc64"
+))]
+const TIOCGWINSZ: c_ulong = 0x4008_7468;
+#[cfg(not(any(
+    target_arch = "powerpc",
+    target_arch = "powerpc64",
+    target_arch = "mips",
+    target_arch = "mips64",
+    target_arch = "sparc64"
+)))]
+const TIOCGWINSZ: c_ulong = 0x5413;
+
+#[repr(C)]
+#[derive(Default)]
+struct Winsize {
+    ws_row: u16,
+    ws_col: u16,
+    ws_xpixel: u16,
+    ws_ypixel: u16,
+}
+
+/// Width in columns of the terminal on `fd` (`TIOCGWINSZ`); `None` when
+/// `fd` is not a terminal or reports a width of 0.
+pub fn terminal_width(fd: i32) -> Option<usize> {
+    let mut ws = Winsize::default();
+    // SAFETY: TIOCGWINSZ writes one struct winsize through the pointer,
+    // which is valid for the duration of the call.
+    if unsafe { ioctl(fd, TIOCGWINSZ, &mut ws as *mut Winsize) } != 0 {
+        return None;
+    }
+    (ws.ws_col > 0).then_some(usize::from(ws.ws_col))
+}
+
+const AF_NETLINK: c_int = 16;
+#[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+const SOCK_DGRAM: c_int = 1;
+#[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+const SOCK_DGRAM: c_int = 2;
+const POLLIN: c_short = 1;
+
+#[repr(C)]
+struct SockaddrNl {
+    nl_family: u16,
+    nl_pad: u16,
+    nl_pid: u32,
+    nl_groups: u32,
+}
+
+#[repr(C)]
+struct PollFd {
+    fd: c_int,
+    events: c_short,
+    revents: c_short,
+}
+
+/// Opens a netlink socket for `protocol` and joins the multicast `groups`
+/// (a bit mask). The kernel picks the port ID. Reads return one datagram
+/// each; writes go to the kernel.
+pub fn netlink_socket(protocol: i32, groups: u32) -> io::Result<File> {
+    // SAFETY: socket only takes integers.
+    let fd = unsafe { socket(AF_NETLINK, SOCK_DGRAM, protocol) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: fd is a fresh descriptor nothing else owns; the File closes it.
+    let sock = unsafe { File::from_raw_fd(fd) };
+    let addr = SockaddrNl {
+        nl_family: AF_NETLINK as u16,
+        nl_pad: 0,
+        nl_pid: 0,
+        nl_groups: groups,
+    };
+    // SAFETY: addr is a valid sockaddr_nl for the duration of the call.
+    if unsafe { bind(fd, &addr, std::mem::size_of::<SockaddrNl>() as u32) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(sock)
+}
+
+/// Waits up to `timeout` for `fd` to have data to read. A signal ends the
+/// wait early and counts as a timeout, so callers can check
+/// [`INTERRUPTED`].
+pub fn wait_readable(fd: i32, timeout: Duration) -> io::Result<bool> {
+    let mut pfd = PollFd {
+        fd,
+        events: POLLIN,
+        revents: 0,
+    };
+    let ms = c_int::try_from(timeout.as_millis()).unwrap_or(c_int::MAX);
+    // SAFETY: pfd is one valid pollfd for the duration of the call.
+    match unsafe { poll(&mut pfd, 1, ms) } {
+        -1 => {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::Interrupted {
+                Ok(false)
+            } else {
+                Err(e)
+            }
+        }
+        n => Ok(n > 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clk_tck() {
+        // USER_HZ is 100 on every mainstream Linux configuration.
+        assert_eq!(clk_tck(), 100);
+    }
+
+    #[test]
+    fn test_page_size() {
+        let size = page_size();
+        assert!(size >= 4096 && size.is_power_of_two());
+    }
+
+    #[test]
+    fn test_send_signal() {
+        send_signal(std::process::id(), 0).unwrap();
+        assert!(send_signal(u32::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn test_is_tty_redirected() {
+        use std::os::fd::AsRawFd;
+
+        // Stand-ins for a redirected stdout: a regular file and /dev/null.
+        let file = std::fs::File::open("/proc/self/stat").unwrap();
+        assert!(!is_tty(file.as_raw_fd()));
+        let null = std::fs::File::create("/dev/null").unwrap();
+        assert!(!is_tty(null.as_raw_fd()));
+        assert!(!is_tty(-1));
+    }
+
+    #[test]
+    fn test_terminal_width_not_a_tty() {
+        use std::os::fd::AsRawFd;
+
+        let null = std::fs::File::create("/dev/null").unwrap();
+        assert_eq!(terminal_width(null.as_raw_fd()), None);
+        assert_eq!(terminal_width(-1), None);
+    }
+
+    #[test]
+    fn test_wait_readable() {
+        use std::os::fd::AsRawFd;
+
+        let file = std::fs::File::open("/proc/self/stat").unwrap();
+        assert!(wait_readable(file.as_raw_fd(), Duration::ZERO).unwrap());
+    }
+}
@@ -0,0 +1,430 @@
+use std::io;
+
+use crate::error::ParseError;
+use crate::reader::ProcReader;
+
+/// x86-64 syscall numbers and names: everything up to `utimes` that current
+/// kernels implement, then the common later additions.
+pub const SYSCALL_NAMES: [(i64, &str); 242] = [
+    (0, "read"),
+    (1, "write"),
+    (2, "open"),
+    (3, "close"),
+    (4, "stat"),
+    (5, "fstat"),
+    (6, "lstat"),
+    (7, "poll"),
+    (8, "lseek"),
+    (9, "mmap"),
+    (10, "mprotect"),
+    (11, "munmap"),
+    (12, "brk"),
+    (13, "rt_sigaction"),
+    (14, "rt_sigprocmask"),
+    (15, "rt_sigreturn"),
+    (16, "ioctl"),
+    (17, "pread64"),
+    (18, "pwrite64"),
+    (19, "readv"),
+    (20, "writev"),
+    (21, "access"),
+    (22, "pipe"),
+    (23, "select"),
+    (24, "sched_yield"),
+    (25, "mremap"),
+    (26, "msync"),
+    (27, "mincore"),
+    (28, "madvise"),
+    (29, "shmget"),
+    (30, "shmat"),
+    (31, "shmctl"),
+    (32, "dup"),
+    (33, "dup2"),
+    (34, "pause"),
+    (35, "nanosleep"),
+    (36, "getitimer"),
+    (37, "alarm"),
+    (38, "setitimer"),
+    (39, "getpid"),
+    (40, "sendfile"),
+    (41, "socket"),
+    (42, "connect"),
+    (43, "accept"),
+    (44, "sendto"),
+    (45, "recvfrom"),
+    (46, "sendmsg"),
+    (47, "recvmsg"),
+    (48, "shutdown"),
+    (49, "bind"),
+    (50, "listen"),
+    (51, "getsockname"),
+    (52, "getpeername"),
+    (53, "socketpair"),
+    (54, "setsockopt"),
+    (55, "getsockopt"),
+    (56, "clone"),
+    (57, "fork"),
+    (58, "vfork"),
+    (59, "execve"),
+    (60, "exit"),
+    (61, "wait4"),
+    (62, "kill"),
+    (63, "uname"),
+    (64, "semget"),
+    (65, "semop"),
+    (66, "semctl"),
+    (67, "shmdt"),
+    (68, "msgget"),
+    (69, "msgsnd"),
+    (70, "msgrcv"),
+    (71, "msgctl"),
+    (72, "fcntl"),
+    (73, "flock"),
+    (74, "fsync"),
+    (75, "fdatasync"),
+    (76, "truncate"),
+    (77, "ftruncate"),
+    (78, "getdents"),
+    (79, "getcwd"),
+    (80, "chdir"),
+    (81, "fchdir"),
+    (82, "rename"),
+    (83, "mkdir"),
+    (84, "rmdir"),
+    (85, "creat"),
+    (86, "link"),
+    (87, "unlink"),
+    (88, "symlink"),
+    (89, "readlink"),
+    (90, "chmod"),
+    (91, "fchmod"),
+    (92, "chown"),
+    (93, "fchown"),
+    (94, "lchown"),
+    (95, "umask"),
+    (96, "gettimeofday"),
+    (97, "getrlimit"),
+    (98, "getrusage"),
+    (99, "sysinfo"),
+    (100, "times"),
+    (101, "ptrace"),
+    (102, "getuid"),
+    (103, "syslog"),
+    (104, "getgid"),
+    (105, "setuid"),
+    (106, "setgid"),
+    (107, "geteuid"),
+    (108, "getegid"),
+    (109, "setpgid"),
+    (110, "getppid"),
+    (111, "getpgrp"),
+    (112, "setsid"),
+    (113, "setreuid"),
+    (114, "setregid"),
+    (115, "getgroups"),
+    (116, "setgroups"),
+    (117, "setresuid"),
+    (118, "getresuid"),
+    (119, "setresgid"),
+    (120, "getresgid"),
+    (121, "getpgid"),
+    (122, "setfsuid"),
+    (123, "setfsgid"),
+    (124, "getsid"),
+    (125, "capget"),
+    (126, "capset"),
+    (127, "rt_sigpending"),
+    (128, "rt_sigtimedwait"),
+    (129, "rt_sigqueueinfo"),
+    (130, "rt_sigsuspend"),
+    (131, "sigaltstack"),
+    (132, "utime"),
+    (133, "mknod"),
+    (134, "uselib"),
+    (135, "personality"),
+    (136, "ustat"),
+    (137, "statfs"),
+    (138, "fstatfs"),
+    (139, "sysfs"),
+    (140, "getpriority"),
+    (141, "setpriority"),
+    (142, "sched_setparam"),
+    (143, "sched_getparam"),
+    (144, "sched_setscheduler"),
+    (145, "sched_getscheduler"),
+    (146, "sched_get_priority_max"),
+    (147, "sched_get_priority_min"),
+    (148, "sched_rr_get_interval"),
+    (149, "mlock"),
+    (150, "munlock"),
+    (151, "mlockall"),
+    (152, "munlockall"),
+    (153, "vhangup"),
+    (154, "modify_ldt"),
+    (155, "pivot_root"),
+    (157, "prctl"),
+    (158, "arch_prctl"),
+    (159, "adjtimex"),
+    (160, "setrlimit"),
+    (161, "chroot"),
+    (162, "sync"),
+    (163, "acct"),
+    (164, "settimeofday"),
+    (165, "mount"),
+    (166, "umount2"),
+    (167, "swapon"),
+    (168, "swapoff"),
+    (169, "reboot"),
+    (170, "sethostname"),
+    (171, "setdomainname"),
+    (172, "iopl"),
+    (173, "ioperm"),
+    (175, "init_module"),
+    (176, "delete_module"),
+    (179, "quotactl"),
+    (186, "gettid"),
+    (187, "readahead"),
+    (188, "setxattr"),
+    (189, "lsetxattr"),
+    (190, "fsetxattr"),
+    (191, "getxattr"),
+    (192, "lgetxattr"),
+    (193, "fgetxattr"),
+    (194, "listxattr"),
+    (195, "llistxattr"),
+    (196, "flistxattr"),
+    (197, "removexattr"),
+    (198, "lremovexattr"),
+    (199, "fremovexattr"),
+    (200, "tkill"),
+    (201, "time"),
+    (202, "futex"),
+    (203, "sched_setaffinity"),
+    (204, "sched_getaffinity"),
+    (205, "set_thread_area"),
+    (206, "io_setup"),
+    (207, "io_destroy"),
+    (208, "io_getevents"),
+    (209, "io_submit"),
+    (210, "io_cancel"),
+    (211, "get_thread_area"),
+    (213, "epoll_create"),
+    (216, "remap_file_pages"),
+    (217, "getdents64"),
+    (218, "set_tid_address"),
+    (219, "restart_syscall"),
+    (220, "semtimedop"),
+    (221, "fadvise64"),
+    (222, "timer_create"),
+    (223, "timer_settime"),
+    (224, "timer_gettime"),
+    (225, "timer_getoverrun"),
+    (226, "timer_delete"),
+    (227, "clock_settime"),
+    (228, "clock_gettime"),
+    (229, "clock_getres"),
+    (230, "clock_nanosleep"),
+    (231, "exit_group"),
+    (232, "epoll_wait"),
+    (233, "epoll_ctl"),
+    (234, "tgkill"),
+    (235, "utimes"),
+    (247, "waitid"),
+    (254, "inotify_add_watch"),
+    (257, "openat"),
+    (262, "newfstatat"),
+    (270, "pselect6"),
+    (271, "ppoll"),
+    (281, "epoll_pwait"),
+    (288, "accept4"),
+    (290, "eventfd2"),
+    (291, "epoll_create1"),
+    (293, "pipe2"),
+    (299, "recvmmsg"),
+    (302, "prlimit64"),
+    (307, "sendmmsg"),
+    (318, "getrandom"),
+    (332, "statx"),
+    (426, "io_uring_enter"),
+    (435, "clone3"),
+    (441, "epoll_pwait2"),
+];
+
+/// Name of x86-64 syscall `nr`, if it is in [`SYSCALL_NAMES`].
+pub fn syscall_name(nr: i64) -> Option<&'static str> {
+    SYSCALL_NAMES
+        .iter()
+        .find(|(n, _)| *n == nr)
+        .map(|(_, name)| *name)
+}
+
+/// Number of the x86-64 syscall called `name`.
+pub fn syscall_number(name: &str) -> Option<i64> {
+    SYSCALL_NAMES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(nr, _)| *nr)
+}
+
+/// What `/proc/<pid>/syscall` says the process is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyscallState {
+    /// On a CPU in user space (`running`).
+    Running,
+    /// Blocked in the kernel outside a syscall, e.g. on a page fault (`-1`).
+    Blocked,
+    /// Inside this syscall.
+    Syscall(i64),
+}
+
+impl SyscallState {
+    /// `running`, `blocked`, the syscall name or, on other architectures
+    /// and for numbers not in the table, the number itself.
+    pub fn label(&self) -> String {
+        match self {
+            SyscallState::Running => "running".to_string(),
+            SyscallState::Blocked => "blocked".to_string(),
+            SyscallState::Syscall(nr) => match native_syscall_name(*nr) {
+                Some(name) => name.to_string(),
+                None => nr.to_string(),
+            },
+        }
+    }
+
+    /// Parses a `--in-syscall` argument: `running`, `blocked`, a syscall
+    /// number or a name from the table.
+    pub fn parse_arg(s: &str) -> Option<SyscallState> {
+        match s {
+            "running" => Some(SyscallState::Running),
+            "blocked" => Some(SyscallState::Blocked),
+            _ => s
+                .parse()
+                .ok()
+                .or_else(|| native_syscall_number(s))
+                .map(SyscallState::Syscall),
+        }
+    }
+}
+
+// The table only holds x86-64 numbers; elsewhere syscalls stay numeric.
+#[cfg(target_arch = "x86_64")]
+fn native_syscall_name(nr: i64) -> Option<&'static str> {
+    syscall_name(nr)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn native_syscall_name(_: i64) -> Option<&'static str> {
+    None
+}
+
+#[cfg(target_arch = "x86_64")]
+fn native_syscall_number(name: &str) -> Option<i64> {
+    syscall_number(name)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn native_syscall_number(_: &str) -> Option<i64> {
+    None
+}
+
+/// Parses `/proc/<pid>/syscall`: `running`, `-1 <sp> <pc>`, or the syscall
+/// number followed by its six arguments, stack pointer and program counter.
+/// Only the first field is used.
+pub fn parse_syscall(buf: &[u8]) -> Result<SyscallState, ParseError> {
+    let text = String::from_utf8_lossy(buf);
+    let first = text
+        .split_whitespace()
+        .next()
+        .ok_or(ParseError::MissingField("syscall"))?;
+    match first {
+        "running" => Ok(SyscallState::Running),
+        "-1" => Ok(SyscallState::Blocked),
+        nr => nr
+            .parse()
+            .map(SyscallState::Syscall)
+            .map_err(|_| ParseError::InvalidValue(nr.to_string())),
+    }
+}
+
+/// Reads `/proc/<pid>/syscall`, which needs ptrace access to the process.
+pub fn read_syscall(pid: u32, reader: &impl ProcReader) -> io::Result<SyscallState> {
+    Ok(parse_syscall(&reader.read(pid, "syscall")?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_parse_syscall() {
+        assert_eq!(parse_syscall(b"running\n"), Ok(SyscallState::Running));
+        assert_eq!(
+            parse_syscall(b"-1 0x7ffd3a1c8e48 0x7f2b9c4e1a2d\n"),
+            Ok(SyscallState::Blocked)
+        );
+        assert_eq!(
+            parse_syscall(
+                b"232 0x4 0x7ffd3a1c8f10 0x400 0xffffffff 0x0 0x0 0x7ffd3a1c8ee8 0x7f2b9c51e0da\n"
+            ),
+            Ok(SyscallState::Syscall(232))
+        );
+        assert_eq!(parse_syscall(b""), Err(ParseError::MissingField("syscall")));
+        assert_eq!(
+            parse_syscall(b"0x17 0 0\n"),
+            Err(ParseError::InvalidValue("0x17".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_syscall_table() {
+        assert_eq!(syscall_name(0), Some("read"));
+        assert_eq!(syscall_name(202), Some("futex"));
+        assert_eq!(syscall_name(441), Some("epoll_pwait2"));
+        assert_eq!(syscall_name(184), None);
+        assert_eq!(syscall_number("epoll_wait"), Some(232));
+        assert_eq!(syscall_number("nope"), None);
+        let mut numbers: Vec<i64> = SYSCALL_NAMES.iter().map(|(nr, _)| *nr).collect();
+        let len = numbers.len();
+        numbers.dedup();
+        assert_eq!(numbers.len(), len);
+        assert!(numbers.is_sorted());
+    }
+
+    #[test]
+    fn test_syscall_state_label() {
+        assert_eq!(SyscallState::Running.label(), "running");
+        assert_eq!(SyscallState::Blocked.label(), "blocked");
+        assert_eq!(SyscallState::Syscall(999).label(), "999");
+        assert_eq!(
+            SyscallState::parse_arg("running"),
+            Some(SyscallState::Running)
+        );
+        assert_eq!(
+            SyscallState::parse_arg("202"),
+            Some(SyscallState::Syscall(202))
+        );
+        #[cfg(target_arch = "x86_64")]
+        {
+            assert_eq!(SyscallState::Syscall(7).label(), "poll");
+            assert_eq!(
+                SyscallState::parse_arg("futex"),
+                Some(SyscallState::Syscall(202))
+            );
+        }
+        assert_eq!(SyscallState::parse_arg("bogus"), None);
+    }
+
+    #[test]
+    fn test_read_syscall() {
+        let reader = MockProcReader::new()
+            .file(
+                1,
+                "syscall",
+                "7 0x55d0 0x1 0xffffffff 0x0 0x0 0x0 0x7ffe 0x7f00\n",
+            )
+            .error(2, "syscall", io::ErrorKind::PermissionDenied);
+        assert_eq!(read_syscall(1, &reader).unwrap(), SyscallState::Syscall(7));
+        assert!(read_syscall(2, &reader).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use std::io;
+
+use crate::reader::{ProcReader, is_all_digits};
+
+/// Longest thread name the kernel keeps (TASK_COMM_LEN without the NUL).
+pub const COMM_MAX: usize = 15;
+
+/// Thread IDs listed under `/proc/<pid>/task`, in directory order.
+pub fn task_ids(pid: u32, reader: &impl ProcReader) -> io::Result<Vec<u32>> {
+    let mut tids: Vec<u32> = reader
+        .list_dir(pid, "task")?
+        .iter()
+        .filter_map(|name| name.to_str())
+        .filter(|name| is_all_digits(name))
+        .filter_map(|name| name.parse().ok())
+        .collect();
+    tids.sort_unstable();
+    Ok(tids)
+}
+
+/// Name of thread `tid` of `pid` (as set with `pthread_setname_np`), read
+/// from `task/<tid>/comm` without the trailing newline.
+pub fn scan_task_comm(pid: u32, tid: u32, reader: &impl ProcReader) -> io::Result<String> {
+    let buf = reader.read(pid, &format!("task/{}/comm", tid))?;
+    let comm = buf.strip_suffix(b"\n").unwrap_or(&buf);
+    Ok(String::from_utf8_lossy(comm).into_owned())
+}
+
+/// Whether `comm` is what the kernel stores for a thread named `name`:
+/// names longer than [`COMM_MAX`] bytes are compared by their prefix.
+pub fn comm_matches(comm: &str, name: &str) -> bool {
+    let name = name.as_bytes();
+    comm.as_bytes() == &name[..name.len().min(COMM_MAX)]
+}
+
+/// `(pid, tid)` of every thread whose comm matches one of `names`
+/// (`--thread-name`). Processes that exit during the scan are skipped.
+pub fn scan_threads<S: AsRef<str>>(
+    names: &[S],
+    reader: &impl ProcReader,
+) -> io::Result<Vec<(u32, u32)>> {
+    let mut found = Vec::new();
+    for pid in reader.pids()? {
+        let Ok(tids) = task_ids(pid, reader) else {
+            continue;
+        };
+        for tid in tids {
+            let Ok(comm) = scan_task_comm(pid, tid, reader) else {
+                continue;
+            };
+            if names.iter().any(|n| comm_matches(&comm, n.as_ref())) {
+                found.push((pid, tid));
+            }
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    fn mock() -> MockProcReader {
+        MockProcReader::new()
+            .file(100, "task/100/comm", "java\n")
+            .file(100, "task/101/comm", "GC Thread#0\n")
+            .file(100, "task/102/comm", "C2 CompilerThre\n")
+            .file(200, "task/200/comm", "nginx\n")
+            // Thread gone between listing and reading.
+            .error(200, "task/201/comm", io::ErrorKind::NotFound)
+            .file(300, "cmdline", "no-task-dir\0")
+    }
+
+    #[test]
+    fn test_scan_task_comm() {
+        let reader = mock();
+        assert_eq!(task_ids(100, &reader).unwrap(), vec![100, 101, 102]);
+        assert_eq!(scan_task_comm(100, 101, &reader).unwrap(), "GC Thread#0");
+        assert!(scan_task_comm(100, 999, &reader).is_err());
+        assert!(task_ids(300, &reader).is_err());
+    }
+
+    #[test]
+    fn test_comm_matches_truncated() {
+        assert!(comm_matches("GC Thread#0", "GC Thread#0"));
+        assert!(!comm_matches("GC Thread#0", "GC Thread"));
+        assert!(comm_matches("C2 CompilerThre", "C2 CompilerThread0"));
+        assert!(!comm_matches("C2 CompilerThre", "C2 CompilerThr"));
+    }
+
+    #[test]
+    fn test_scan_threads() {
+        let reader = mock();
+        assert_eq!(
+            scan_threads(&["GC Thread#0", "C2 CompilerThread0"], &reader).unwrap(),
+            vec![(100, 101), (100, 102)]
+        );
+        assert_eq!(scan_threads(&["nginx"], &reader).unwrap(), vec![(200, 200)]);
+        assert!(scan_threads(&["bash"], &reader).unwrap().is_empty());
+    }
+}
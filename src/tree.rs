@@ -0,0 +1,89 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use crate::reader::ProcReader;
+use crate::stat::ProcStat;
+
+/// Parent PID → child PIDs of every process, from the `ppid` field of
+/// stat. Processes that exit while being read are left out.
+pub fn parent_map(reader: &impl ProcReader) -> io::Result<HashMap<u32, Vec<u32>>> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for pid in reader.pids()? {
+        if let Ok(st) = ProcStat::read_with(pid, reader) {
+            children.entry(st.ppid).or_default().push(pid);
+        }
+    }
+    Ok(children)
+}
+
+/// `root` and every process below it in `parent_map` (`--ppid-chain`).
+/// A cycle, possible when PIDs are reused mid-scan, is visited once.
+pub fn collect_descendants(root: u32, parent_map: &HashMap<u32, Vec<u32>>) -> HashSet<u32> {
+    let mut seen = HashSet::from([root]);
+    let mut stack = vec![root];
+    while let Some(pid) = stack.pop() {
+        for &child in parent_map.get(&pid).into_iter().flatten() {
+            if seen.insert(child) {
+                stack.push(child);
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    fn stat(pid: u32, ppid: u32) -> String {
+        format!("{pid} (x) S {ppid} {pid} {pid} 0 -1 4194560 0 0 0 0 0 0 0 0 20 0 1 0 100 0 0")
+    }
+
+    // systemd(1) ─┬─ sshd(100) ─┬─ sshd(200) ── bash(300) ── worker(400)
+    //             │             └─ sshd(201)
+    //             └─ cron(101) ── worker(401)
+    fn hierarchy() -> MockProcReader {
+        [
+            (1, 0),
+            (100, 1),
+            (101, 1),
+            (200, 100),
+            (201, 100),
+            (300, 200),
+            (400, 300),
+            (401, 101),
+        ]
+        .iter()
+        .fold(MockProcReader::new(), |r, (pid, ppid)| {
+            r.file(*pid, "stat", stat(*pid, *ppid))
+        })
+    }
+
+    fn sorted(set: HashSet<u32>) -> Vec<u32> {
+        let mut pids: Vec<u32> = set.into_iter().collect();
+        pids.sort_unstable();
+        pids
+    }
+
+    #[test]
+    fn test_collect_descendants() {
+        let parents = parent_map(&hierarchy()).unwrap();
+        assert_eq!(parents[&100], vec![200, 201]);
+        assert_eq!(
+            sorted(collect_descendants(100, &parents)),
+            vec![100, 200, 201, 300, 400]
+        );
+        assert_eq!(sorted(collect_descendants(101, &parents)), vec![101, 401]);
+        assert_eq!(sorted(collect_descendants(400, &parents)), vec![400]);
+        assert_eq!(collect_descendants(1, &parents).len(), 8);
+        // Not running: only the root itself, which no scan will find.
+        assert_eq!(sorted(collect_descendants(999, &parents)), vec![999]);
+    }
+
+    #[test]
+    fn test_collect_descendants_cycle() {
+        let parents = HashMap::from([(10, vec![11]), (11, vec![10, 12])]);
+        assert_eq!(sorted(collect_descendants(10, &parents)), vec![10, 11, 12]);
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// Local user database.
+pub const PASSWD_PATH: &str = "/etc/passwd";
+/// Local group database.
+pub const GROUP_PATH: &str = "/etc/group";
+
+/// Names to numeric IDs from an `/etc/passwd` or `/etc/group` style file;
+/// both start with `name:password:id:`. Only local entries are known, not
+/// users from LDAP or other NSS sources.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserDb {
+    ids: HashMap<String, u32>,
+}
+
+impl UserDb {
+    /// Comment, blank and malformed lines are skipped; the first entry of a
+    /// repeated name wins, as with getpwnam.
+    pub fn parse(buf: &[u8]) -> Self {
+        let mut ids = HashMap::new();
+        for line in String::from_utf8_lossy(buf).lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split(':');
+            let (Some(name), Some(_), Some(id)) = (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if let Ok(id) = id.parse::<u32>()
+                && !name.is_empty()
+            {
+                ids.entry(name.to_string()).or_insert(id);
+            }
+        }
+        UserDb { ids }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&std::fs::read(path)?))
+    }
+
+    /// The ID of `name`; a name that is all digits and not in the file is
+    /// taken as the ID itself, like `id -u 1000`.
+    pub fn lookup(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied().or_else(|| name.parse().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWD: &str = "\
+root:x:0:0:root:/root:/bin/bash
+# comment
+daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin
+
+www-data:x:33:33:www-data:/var/www:/usr/sbin/nologin
+broken:x
+alice:x:1000:1000:Alice,,,:/home/alice:/bin/bash
+alice:x:2000:2000::/home/alice2:/bin/sh
++nis::::::
+";
+
+    #[test]
+    fn test_user_db_lookup() {
+        let db = UserDb::parse(PASSWD.as_bytes());
+        assert_eq!(db.lookup("root"), Some(0));
+        assert_eq!(db.lookup("www-data"), Some(33));
+        assert_eq!(db.lookup("alice"), Some(1000));
+        assert_eq!(db.lookup("broken"), None);
+        assert_eq!(db.lookup("+nis"), None);
+        assert_eq!(db.lookup("nobody"), None);
+        assert_eq!(db.lookup("1234"), Some(1234));
+    }
+
+    #[test]
+    fn test_group_db_lookup() {
+        let db = UserDb::parse(b"root:x:0:\nwheel:x:10:alice,bob\ndocker:x:998:alice\n");
+        assert_eq!(db.lookup("wheel"), Some(10));
+        assert_eq!(db.lookup("docker"), Some(998));
+        assert_eq!(db.lookup("staff"), None);
+    }
+
+    #[test]
+    fn test_user_db_load() {
+        assert!(UserDb::load(Path::new("/nonexistent/passwd")).is_err());
+    }
+}
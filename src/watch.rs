@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+use crate::json;
+use crate::proc_events::ProcEvent;
+use crate::reader::ProcReader;
+use crate::scan::{ProcessInfo, ScanConfig, pid_matches, scan_each};
+
+/// Pause between two `--watch` scans.
+pub const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A change between two `--watch` scans. Timestamps are preformatted
+/// (ISO 8601 UTC) so events compare and print without a clock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// Everything matching when the watch started.
+    Initial {
+        pids: Vec<u32>,
+        timestamp: String,
+    },
+    Appeared {
+        pid: u32,
+        name: String,
+        timestamp: String,
+    },
+    Disappeared {
+        pid: u32,
+        timestamp: String,
+    },
+}
+
+impl WatchEvent {
+    /// Text form: the initial PIDs one per line as a normal scan prints
+    /// them, then `+<pid>` / `-<pid>`. No trailing newline.
+    pub fn to_text(&self) -> String {
+        match self {
+            WatchEvent::Initial { pids, .. } => pids
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            WatchEvent::Appeared { pid, .. } => format!("+{}", pid),
+            WatchEvent::Disappeared { pid, .. } => format!("-{}", pid),
+        }
+    }
+
+    /// One-line JSON object, e.g.
+    /// `{"event":"appeared","pid":1234,"name":"nginx","timestamp":"..."}`.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        json::push_key(&mut out, "event", true);
+        match self {
+            WatchEvent::Initial { pids, timestamp } => {
+                json::push_str(&mut out, "initial");
+                json::push_key(&mut out, "pids", false);
+                let list: Vec<String> = pids.iter().map(u32::to_string).collect();
+                out.push('[');
+                out.push_str(&list.join(","));
+                out.push(']');
+                json::push_key(&mut out, "timestamp", false);
+                json::push_str(&mut out, timestamp);
+            }
+            WatchEvent::Appeared {
+                pid,
+                name,
+                timestamp,
+            } => {
+                json::push_str(&mut out, "appeared");
+                json::push_key(&mut out, "pid", false);
+                out.push_str(&pid.to_string());
+                json::push_key(&mut out, "name", false);
+                json::push_str(&mut out, name);
+                json::push_key(&mut out, "timestamp", false);
+                json::push_str(&mut out, timestamp);
+            }
+            WatchEvent::Disappeared { pid, timestamp } => {
+                json::push_str(&mut out, "disappeared");
+                json::push_key(&mut out, "pid", false);
+                out.push_str(&pid.to_string());
+                json::push_key(&mut out, "timestamp", false);
+                json::push_str(&mut out, timestamp);
+            }
+        }
+        out.push('}');
+        out
+    }
+}
+
+/// Remembers the matches of the previous scan (`--watch`).
+#[derive(Debug, Default)]
+pub struct Watcher {
+    known: Option<BTreeMap<u32, String>>,
+}
+
+impl Watcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scans once and reports what changed since the last call: an
+    /// `Initial` event the first time, then disappeared and appeared PIDs
+    /// in PID order. A PID reused by a process with another name counts
+    /// as one leaving and one arriving.
+    pub fn poll(
+        &mut self,
+        config: &ScanConfig,
+        reader: &impl ProcReader,
+        timestamp: &str,
+    ) -> io::Result<Vec<WatchEvent>> {
+        let current = scan_matches(config, reader)?;
+        let Some(known) = self.known.take() else {
+            let pids = current.keys().copied().collect();
+            self.known = Some(current);
+            return Ok(vec![WatchEvent::Initial {
+                pids,
+                timestamp: timestamp.to_string(),
+            }]);
+        };
+        let events = diff_matches(&known, &current, timestamp);
+        self.known = Some(current);
+        Ok(events)
+    }
+
+    /// Updates the matches from proc connector events instead of a rescan
+    /// (`--watch-events`): a forked or exec'd PID is checked again, an
+    /// exited one is dropped. Call [`Watcher::poll`] first for the initial
+    /// matches, and again to catch up after events were lost.
+    pub fn apply_events(
+        &mut self,
+        events: &[ProcEvent],
+        config: &ScanConfig,
+        reader: &impl ProcReader,
+        timestamp: &str,
+    ) -> Vec<WatchEvent> {
+        let known = self.known.get_or_insert_default();
+        let mut out = Vec::new();
+        for event in events {
+            let pid = match *event {
+                ProcEvent::Fork { child, .. } => child,
+                ProcEvent::Exec { pid } | ProcEvent::Exit { pid } => pid,
+            };
+            let before: BTreeMap<u32, String> = known.remove_entry(&pid).into_iter().collect();
+            let mut after = BTreeMap::new();
+            if !matches!(event, ProcEvent::Exit { .. }) && pid_matches(config, reader, pid) {
+                after.insert(pid, ProcessInfo::read(pid, 0, reader).name);
+            }
+            out.extend(diff_matches(&before, &after, timestamp));
+            known.extend(after);
+        }
+        out
+    }
+}
+
+/// PID → name of every process `config` matches.
+pub fn scan_matches(
+    config: &ScanConfig,
+    reader: &impl ProcReader,
+) -> io::Result<BTreeMap<u32, String>> {
+    let mut current = BTreeMap::new();
+    scan_each(config, reader, |proc, idx| {
+        let info = ProcessInfo::read(proc.pid(), idx, proc.reader());
+        current.insert(proc.pid(), info.name);
+        ControlFlow::Continue(())
+    })?;
+    Ok(current)
+}
+
+/// Disappeared then appeared PIDs going from `known` to `current`, in PID
+/// order; a PID whose name changed is both.
+pub fn diff_matches(
+    known: &BTreeMap<u32, String>,
+    current: &BTreeMap<u32, String>,
+    timestamp: &str,
+) -> Vec<WatchEvent> {
+    let mut events = Vec::new();
+    for (pid, name) in known {
+        if current.get(pid) != Some(name) {
+            events.push(WatchEvent::Disappeared {
+                pid: *pid,
+                timestamp: timestamp.to_string(),
+            });
+        }
+    }
+    for (pid, name) in current {
+        if known.get(pid) != Some(name) {
+            events.push(WatchEvent::Appeared {
+                pid: *pid,
+                name: name.clone(),
+                timestamp: timestamp.to_string(),
+            });
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    fn procs(list: &[(u32, &str)]) -> MockProcReader {
+        list.iter()
+            .fold(MockProcReader::new(), |r, (pid, cmdline)| {
+                r.file(*pid, "cmdline", format!("{cmdline}\0"))
+            })
+    }
+
+    #[test]
+    fn test_watch_event_sequence() {
+        let config = ScanConfig::new(&["nginx", "php-fpm"]);
+        let mut watcher = Watcher::new();
+        let t = |s: &str| s.to_string();
+
+        let first = procs(&[(10, "nginx"), (11, "nginx"), (20, "bash")]);
+        assert_eq!(
+            watcher.poll(&config, &first, "T0").unwrap(),
+            vec![WatchEvent::Initial {
+                pids: vec![10, 11],
+                timestamp: t("T0"),
+            }]
+        );
+        assert!(watcher.poll(&config, &first, "T1").unwrap().is_empty());
+
+        // 11 exits, 12 starts, and 20 is reused by a matching process.
+        let second = procs(&[(10, "nginx"), (12, "nginx"), (20, "php-fpm")]);
+        assert_eq!(
+            watcher.poll(&config, &second, "T2").unwrap(),
+            vec![
+                WatchEvent::Disappeared {
+                    pid: 11,
+                    timestamp: t("T2"),
+                },
+                WatchEvent::Appeared {
+                    pid: 12,
+                    name: t("nginx"),
+                    timestamp: t("T2"),
+                },
+                WatchEvent::Appeared {
+                    pid: 20,
+                    name: t("php-fpm"),
+                    timestamp: t("T2"),
+                },
+            ]
+        );
+
+        let events = watcher.poll(&config, &procs(&[]), "T3").unwrap();
+        let gone: Vec<String> = events.iter().map(WatchEvent::to_text).collect();
+        assert_eq!(gone, vec!["-10", "-12", "-20"]);
+    }
+
+    #[test]
+    fn test_watch_apply_events() {
+        let config = ScanConfig::new(&["nginx"]);
+        let mut watcher = Watcher::new();
+        let t = |s: &str| s.to_string();
+        let text = |events: Vec<WatchEvent>| -> Vec<String> {
+            events.iter().map(WatchEvent::to_text).collect()
+        };
+
+        watcher
+            .poll(&config, &procs(&[(10, "nginx")]), "T0")
+            .unwrap();
+        // The master forks a worker, a shell starts and then execs nginx.
+        let now = procs(&[(10, "nginx"), (11, "nginx"), (20, "bash")]);
+        let forks = [
+            ProcEvent::Fork {
+                parent: 10,
+                child: 11,
+            },
+            ProcEvent::Fork {
+                parent: 1,
+                child: 20,
+            },
+        ];
+        assert_eq!(
+            watcher.apply_events(&forks, &config, &now, "T1"),
+            vec![WatchEvent::Appeared {
+                pid: 11,
+                name: t("nginx"),
+                timestamp: t("T1"),
+            }]
+        );
+        let now = procs(&[(10, "nginx"), (11, "nginx"), (20, "nginx")]);
+        let exec = [ProcEvent::Exec { pid: 20 }];
+        assert_eq!(
+            text(watcher.apply_events(&exec, &config, &now, "T2")),
+            vec!["+20"]
+        );
+        // Exec of an already known process into the same program.
+        assert!(watcher.apply_events(&exec, &config, &now, "T3").is_empty());
+
+        // 11 execs something else, 10 exits, and an unknown PID exits.
+        let now = procs(&[(11, "sleep"), (20, "nginx")]);
+        let events = [
+            ProcEvent::Exec { pid: 11 },
+            ProcEvent::Exit { pid: 10 },
+            ProcEvent::Exit { pid: 30 },
+        ];
+        assert_eq!(
+            text(watcher.apply_events(&events, &config, &now, "T4")),
+            vec!["-11", "-10"]
+        );
+        // Catching up with a full scan after lost events.
+        assert_eq!(
+            text(
+                watcher
+                    .poll(&config, &procs(&[(21, "nginx")]), "T5")
+                    .unwrap()
+            ),
+            vec!["-20", "+21"]
+        );
+    }
+
+    #[test]
+    fn test_watch_event_json() {
+        let initial = WatchEvent::Initial {
+            pids: vec![1234, 5678],
+            timestamp: "2026-10-14T09:15:30Z".to_string(),
+        };
+        assert_eq!(
+            initial.to_json(),
+            r#"{"event":"initial","pids":[1234,5678],"timestamp":"2026-10-14T09:15:30Z"}"#
+        );
+        assert_eq!(initial.to_text(), "1234\n5678");
+        let appeared = WatchEvent::Appeared {
+            pid: 1234,
+            name: "nginx".to_string(),
+            timestamp: "2026-10-14T09:15:31Z".to_string(),
+        };
+        assert_eq!(
+            appeared.to_json(),
+            r#"{"event":"appeared","pid":1234,"name":"nginx","timestamp":"2026-10-14T09:15:31Z"}"#
+        );
+        let gone = WatchEvent::Disappeared {
+            pid: 1234,
+            timestamp: "2026-10-14T09:15:32Z".to_string(),
+        };
+        assert_eq!(
+            gone.to_json(),
+            r#"{"event":"disappeared","pid":1234,"timestamp":"2026-10-14T09:15:32Z"}"#
+        );
+        assert_eq!(gone.to_text(), "-1234");
+    }
+}
@@ -0,0 +1,37 @@
+use std::io;
+
+use crate::reader::ProcReader;
+
+/// Kernel function `pid` is blocked in, from `/proc/<pid>/wchan` (e.g.
+/// `futex_wait_queue`, `do_sys_poll`). Running processes report `0`;
+/// kernels without symbol lookup or callers lacking ptrace access also see
+/// `0` instead of an error.
+pub fn read_wchan(pid: u32, reader: &impl ProcReader) -> Result<String, io::Error> {
+    let buf = reader.read(pid, "wchan")?;
+    Ok(String::from_utf8_lossy(&buf).trim().to_string())
+}
+
+/// Whether a process is waiting at all: wchan is neither empty nor `0`.
+pub fn is_waiting(wchan: &str) -> bool {
+    !wchan.is_empty() && wchan != "0"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::mock::MockProcReader;
+
+    #[test]
+    fn test_read_wchan() {
+        // wchan has no trailing newline, but tolerate one.
+        let reader = MockProcReader::new()
+            .file(1, "wchan", "ep_poll")
+            .file(2, "wchan", "0")
+            .file(3, "wchan", "futex_wait_queue\n");
+        assert_eq!(read_wchan(1, &reader).unwrap(), "ep_poll");
+        assert_eq!(read_wchan(3, &reader).unwrap(), "futex_wait_queue");
+        assert!(is_waiting(&read_wchan(1, &reader).unwrap()));
+        assert!(!is_waiting(&read_wchan(2, &reader).unwrap()));
+        assert!(read_wchan(4, &reader).is_err());
+    }
+}
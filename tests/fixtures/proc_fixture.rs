@@ -0,0 +1,139 @@
+//! A small proc-like tree on disk for end-to-end scans through
+//! `FsProcReader`, removed again when the returned guard is dropped.
+
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Owns a temporary directory and deletes it on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(tag: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "fpid-{}-{}-{}",
+            tag,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+struct Pid<'a> {
+    pid: u32,
+    comm: &'a str,
+    cmdline: &'a [u8],
+    exe: Option<&'a str>,
+    // utime and stime in clock ticks; no stat file when None.
+    ticks: Option<(u64, u64)>,
+    cpus: &'a str,
+}
+
+const PIDS: &[Pid<'static>] = &[
+    Pid {
+        pid: 1,
+        comm: "systemd",
+        cmdline: b"/sbin/init\0splash\0",
+        exe: Some("/usr/lib/systemd/systemd"),
+        ticks: Some((900, 300)),
+        cpus: "0",
+    },
+    // Kernel thread: empty cmdline, no exe link.
+    Pid {
+        pid: 2,
+        comm: "kthreadd",
+        cmdline: b"",
+        exe: None,
+        ticks: Some((0, 0)),
+        cpus: "0-3",
+    },
+    Pid {
+        pid: 42,
+        comm: "bash",
+        cmdline: b"/bin/bash\0-l\0",
+        exe: Some("/usr/bin/bash"),
+        ticks: Some((500, 100)),
+        cpus: "0-3",
+    },
+    // Another user's shell: exe unreadable, no stat.
+    Pid {
+        pid: 43,
+        comm: "bash",
+        cmdline: b"bash\0",
+        exe: None,
+        ticks: None,
+        cpus: "0-3",
+    },
+    Pid {
+        pid: 100,
+        comm: "nginx",
+        cmdline: b"nginx: master process /usr/sbin/nginx\0",
+        exe: Some("/usr/sbin/nginx"),
+        ticks: Some((20, 10)),
+        cpus: "2",
+    },
+];
+
+/// Writes the fixture and returns the guard with the tree's root:
+///
+/// | pid | comm     | exe                      |
+/// |-----|----------|--------------------------|
+/// | 1   | systemd  | /usr/lib/systemd/systemd |
+/// | 2   | kthreadd | -                        |
+/// | 42  | bash     | /usr/bin/bash            |
+/// | 43  | bash     | -                        |
+/// | 100 | nginx    | /usr/sbin/nginx          |
+///
+/// plus a `self -> 42` link and a non-PID `sys` directory, which scans
+/// must skip, and `uptime` / `meminfo` at the root.
+pub fn create_proc_fixture() -> (TempDir, PathBuf) {
+    let tmp = TempDir::new("proc-fixture");
+    let root = tmp.path().to_path_buf();
+    for p in PIDS {
+        let dir = root.join(p.pid.to_string());
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("cmdline"), p.cmdline).unwrap();
+        fs::write(dir.join("comm"), format!("{}\n", p.comm)).unwrap();
+        fs::write(
+            dir.join("status"),
+            format!(
+                "Name:\t{}\nState:\tS (sleeping)\nPid:\t{}\nCpus_allowed_list:\t{}\n",
+                p.comm, p.pid, p.cpus
+            ),
+        )
+        .unwrap();
+        if let Some((utime, stime)) = p.ticks {
+            fs::write(
+                dir.join("stat"),
+                format!(
+                    "{} ({}) S 1 {} {} 0 -1 0 0 0 0 0 {} {} 0 0 20 0 1 0 1 0 0\n",
+                    p.pid, p.comm, p.pid, p.pid, utime, stime
+                ),
+            )
+            .unwrap();
+        }
+        if let Some(exe) = p.exe {
+            symlink(exe, dir.join("exe")).unwrap();
+        }
+    }
+    symlink("42", root.join("self")).unwrap();
+    fs::create_dir(root.join("sys")).unwrap();
+    fs::write(root.join("uptime"), "12345.67 40000.00\n").unwrap();
+    fs::write(root.join("meminfo"), "MemTotal:       16384000 kB\n").unwrap();
+    (tmp, root)
+}
@@ -0,0 +1,474 @@
+//! End-to-end scans of the on-disk fixture in `fixtures/proc_fixture.rs`.
+
+#[path = "fixtures/proc_fixture.rs"]
+mod proc_fixture;
+
+use fpid_rs::{Filter, ProcessInfo, ScannerBuilder};
+use proc_fixture::create_proc_fixture;
+
+fn pids(found: &[ProcessInfo]) -> Vec<u32> {
+    let mut pids: Vec<u32> = found.iter().map(|p| p.pid).collect();
+    pids.sort_unstable();
+    pids
+}
+
+#[test]
+fn test_scan_by_name() {
+    let (_tmp, root) = create_proc_fixture();
+    let found: Vec<_> = ScannerBuilder::new()
+        .proc_root(&root)
+        .name("bash")
+        .build()
+        .run()
+        .collect();
+    // `self` points at 42 but is not a PID directory.
+    assert_eq!(pids(&found), vec![42, 43]);
+
+    let login = found.iter().find(|p| p.pid == 42).unwrap();
+    assert_eq!(login.name, "bash");
+    assert_eq!(login.exe.as_deref(), Some("/usr/bin/bash".as_ref()));
+    assert_eq!(login.cmdline, vec!["/bin/bash", "-l"]);
+    assert_eq!(found.iter().find(|p| p.pid == 43).unwrap().exe, None);
+}
+
+#[test]
+fn test_scan_by_path_and_several_targets() {
+    let (_tmp, root) = create_proc_fixture();
+    let scanner = ScannerBuilder::new()
+        .proc_root(&root)
+        .name("/usr/sbin/nginx")
+        .name("systemd")
+        .build();
+    let mut found: Vec<_> = scanner.run().map(|p| (p.pid, p.target)).collect();
+    found.sort_unstable();
+    // Bare names only look at argv[0], which is /sbin/init for pid 1.
+    assert_eq!(found, vec![(100, 0)]);
+
+    let init: Vec<_> = ScannerBuilder::new()
+        .proc_root(&root)
+        .name("init")
+        .build()
+        .run()
+        .collect();
+    assert_eq!(pids(&init), vec![1]);
+}
+
+#[test]
+fn test_scan_single_and_no_match() {
+    let (_tmp, root) = create_proc_fixture();
+    let one: Vec<_> = ScannerBuilder::new()
+        .proc_root(&root)
+        .name("bash")
+        .single(true)
+        .build()
+        .run()
+        .collect();
+    assert_eq!(one.len(), 1);
+
+    let none = ScannerBuilder::new()
+        .proc_root(&root)
+        .name("kthreadd")
+        .build()
+        .try_run()
+        .unwrap();
+    assert!(none.is_empty());
+}
+
+#[test]
+fn test_scan_with_filters() {
+    let (_tmp, root) = create_proc_fixture();
+    let busy: Vec<_> = ScannerBuilder::new()
+        .proc_root(&root)
+        .name("bash")
+        .filter(Filter::MinCpuSec(1.0))
+        .build()
+        .run()
+        .collect();
+    // 43 has no stat, so its CPU time is unknown and it is dropped.
+    assert_eq!(pids(&busy), vec![42]);
+
+    let pinned: Vec<_> = ScannerBuilder::new()
+        .proc_root(&root)
+        .name("nginx")
+        .name("bash")
+        .filter(Filter::CpuAffinity(vec![2]))
+        .build()
+        .run()
+        .collect();
+    assert_eq!(pids(&pinned), vec![42, 43, 100]);
+}
+
+#[test]
+fn test_missing_root() {
+    let (tmp, root) = create_proc_fixture();
+    drop(tmp);
+    let scanner = ScannerBuilder::new().proc_root(&root).name("bash").build();
+    assert!(scanner.try_run().is_err());
+    assert_eq!(scanner.run().count(), 0);
+}
+
+#[test]
+fn test_binary_proc_root_env_and_flag() {
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let run = |cmd: &mut Command| {
+        let out = cmd.output().unwrap();
+        let mut pids: Vec<u32> = String::from_utf8(out.stdout)
+            .unwrap()
+            .lines()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        pids.sort_unstable();
+        pids
+    };
+    let bin = env!("CARGO_BIN_EXE_fpid-rs");
+    let env_only = run(Command::new(bin).env("FPID_PROC_ROOT", &root).arg("bash"));
+    assert_eq!(env_only, vec![42, 43]);
+    // --proc-root wins over the environment.
+    let flag = run(Command::new(bin)
+        .env("FPID_PROC_ROOT", "/nonexistent")
+        .arg("--proc-root")
+        .arg(&root)
+        .arg("nginx"));
+    assert_eq!(flag, vec![100]);
+}
+
+#[test]
+fn test_binary_header_modes() {
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let run = |extra: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+            .arg("--proc-root")
+            .arg(&root)
+            .args(["--format=tsv", "--columns=pid,name"])
+            .args(extra)
+            .output()
+            .unwrap();
+        String::from_utf8(out.stdout).unwrap()
+    };
+    assert_eq!(run(&["nginx"]), "pid\tname\n100\tnginx\n");
+    assert_eq!(run(&["--no-header", "nginx"]), "100\tnginx\n");
+    assert_eq!(
+        run(&["--sort-by", "pid,desc", "bash"]),
+        "pid\tname\n43\tbash\n42\tbash\n"
+    );
+    assert_eq!(run(&["--header-only"]), "pid\tname\n");
+    assert_eq!(
+        run(&["--format=table", "--header-only"]),
+        "PID  NAME\n---  ----\n"
+    );
+}
+
+#[test]
+fn test_binary_ppid_chain() {
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let run = |extra: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+            .arg("--proc-root")
+            .arg(&root)
+            .args(extra)
+            .output()
+            .unwrap();
+        // The fixture's directory order is not numeric.
+        let mut pids: Vec<u32> = String::from_utf8(out.stdout)
+            .unwrap()
+            .lines()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        pids.sort_unstable();
+        pids
+    };
+    // Every fixture process with a stat names 1 as its parent; 43 has
+    // none, so it is not in the tree.
+    assert_eq!(run(&["--ppid-chain", "1"]), vec![1, 2, 42, 100]);
+    assert_eq!(run(&["--ppid-chain", "1", "bash"]), vec![42]);
+    assert_eq!(run(&["--ppid-chain=42"]), vec![42]);
+    assert!(run(&["--ppid-chain=100", "bash"]).is_empty());
+}
+
+#[test]
+fn test_binary_ppid_chain_without_target() {
+    use std::process::Command;
+
+    let (tmp, root) = create_proc_fixture();
+    let log = tmp.path().join("audit.log");
+    let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+        .arg("--proc-root")
+        .arg(&root)
+        .args(["--ppid-chain", "42", "--audit-log"])
+        .arg(&log)
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "42\n");
+    let audit = std::fs::read_to_string(&log).unwrap();
+    assert!(
+        audit.ends_with(" MATCH pid=42 name=bash exe=/usr/bin/bash query=-\n"),
+        "{audit}"
+    );
+
+    let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+        .arg("--proc-root")
+        .arg(&root)
+        .args(["--ppid-chain", "42", "--format=ndjson"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "{\"pid\":42,\"target\":0,\"name\":\"bash\",\"exe\":\"/usr/bin/bash\",\"cmdline\":[\"/bin/bash\",\"-l\"]}\n"
+    );
+}
+
+#[test]
+fn test_binary_set_oom_adj_under_proc_root() {
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    std::fs::write(root.join("100/oom_score_adj"), "0\n").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+        .arg("--proc-root")
+        .arg(&root)
+        .args(["--set-oom-adj", "250", "nginx"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(0), "{:?}", out);
+    assert_eq!(
+        std::fs::read_to_string(root.join("100/oom_score_adj")).unwrap(),
+        "250"
+    );
+}
+
+#[test]
+fn test_binary_daemon_detaches() {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let (tmp, root) = create_proc_fixture();
+    let sock = tmp.path().join("fpid.sock");
+    let pidfile = tmp.path().join("fpid.pid");
+    let mut parent = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+        .arg("--proc-root")
+        .arg(&root)
+        .arg("--daemon")
+        .arg("--socket")
+        .arg(&sock)
+        .arg("--pidfile")
+        .arg(&pidfile)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Both pipes reach EOF only once the daemon has let go of them too.
+    let (mut stdout, mut stderr) = (parent.stdout.take().unwrap(), parent.stderr.take().unwrap());
+    let (done, closed) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut sink = Vec::new();
+        let _ = stdout.read_to_end(&mut sink);
+        let _ = stderr.read_to_end(&mut sink);
+        let _ = done.send(sink);
+    });
+    assert!(parent.wait().unwrap().success());
+    let output = closed.recv_timeout(Duration::from_secs(10));
+
+    let pid = std::fs::read_to_string(&pidfile)
+        .unwrap()
+        .trim()
+        .to_string();
+    let cwd = std::fs::read_link(format!("/proc/{pid}/cwd"));
+    let mut client = BufReader::new(UnixStream::connect(&sock).unwrap());
+    client.get_mut().write_all(b"nginx\n").unwrap();
+    let mut reply = String::new();
+    client.read_line(&mut reply).unwrap();
+    client.read_line(&mut reply).unwrap();
+    let _ = Command::new("kill").arg(&pid).status();
+
+    assert_eq!(output.expect("daemon kept stdout/stderr open"), b"");
+    assert_eq!(cwd.unwrap(), std::path::Path::new("/"));
+    assert_eq!(reply, "100\n.\n");
+}
+
+#[test]
+fn test_binary_group_by_target() {
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let run = |args: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+            .arg("--proc-root")
+            .arg(&root)
+            .args(args)
+            .output()
+            .unwrap();
+        (out.status.code(), String::from_utf8(out.stdout).unwrap())
+    };
+    assert_eq!(
+        run(&[
+            "--group-by-target",
+            "nginx",
+            "--name",
+            "sshd",
+            "--name",
+            "bash"
+        ]),
+        (Some(0), "nginx: 100\nbash: 42 43\n".to_string())
+    );
+    assert_eq!(
+        run(&["--group-by-target", "--ppid-chain", "1"]),
+        (Some(0), "-: 1 2 42 100\n".to_string())
+    );
+    assert_eq!(
+        run(&["--group-by-target", "--format=json", "bash"]).0,
+        Some(1)
+    );
+
+    let cmdline = run(&[
+        "--match-cmdline",
+        "--group-by-target",
+        "master",
+        "--name",
+        "splash",
+    ]);
+    if cfg!(feature = "aho-corasick") {
+        assert_eq!(cmdline, (Some(0), "master: 100\nsplash: 1\n".to_string()));
+    } else {
+        assert_eq!(cmdline, (Some(1), String::new()));
+    }
+}
+
+#[test]
+fn test_binary_compare_snapshot() {
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let run = |extra: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+            .arg("--proc-root")
+            .arg(&root)
+            .args(extra)
+            .output()
+            .unwrap();
+        (out.status.code(), String::from_utf8(out.stdout).unwrap())
+    };
+    let (status, baseline) = run(&["--format=ndjson", "bash"]);
+    assert_eq!(status, Some(0));
+    let same = root.join("same.json");
+    fs::write(&same, &baseline).unwrap();
+    let changed = root.join("changed.json");
+    fs::write(
+        &changed,
+        "{\"pid\":42,\"name\":\"bash\"}\n{\"pid\":7,\"name\":\"bash\"}\n",
+    )
+    .unwrap();
+    let compare = |path: &Path, extra: &[&str]| {
+        let mut argv = vec!["--compare-snapshot", path.to_str().unwrap()];
+        argv.extend_from_slice(extra);
+        argv.push("bash");
+        run(&argv)
+    };
+
+    assert_eq!(compare(&same, &[]), (Some(0), String::new()));
+    assert_eq!(compare(&changed, &[]), (Some(1), "-7\n+43\n".to_string()));
+    assert_eq!(compare(&changed, &["-q"]), (Some(1), String::new()));
+    assert_eq!(
+        compare(&root.join("missing.json"), &[]),
+        (Some(2), String::new())
+    );
+}
+
+#[test]
+fn test_binary_pid_file() {
+    use std::fs;
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let check = |content: Option<&str>, target: &str| {
+        let pid_file = root.join("target.pid");
+        let _ = fs::remove_file(&pid_file);
+        if let Some(content) = content {
+            fs::write(&pid_file, content).unwrap();
+        }
+        let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+            .arg("--proc-root")
+            .arg(&root)
+            .arg("--pid-file")
+            .arg(&pid_file)
+            .arg(target)
+            .output()
+            .unwrap();
+        (out.status.code(), String::from_utf8(out.stdout).unwrap())
+    };
+    assert_eq!(
+        check(Some("100\n"), "nginx"),
+        (Some(0), "100\n".to_string())
+    );
+    assert_eq!(check(Some("100\n"), "bash"), (Some(1), String::new()));
+    assert_eq!(check(Some("31337\n"), "nginx"), (Some(1), String::new()));
+    assert_eq!(check(Some("nginx\n"), "nginx"), (Some(2), String::new()));
+    assert_eq!(check(None, "nginx"), (Some(2), String::new()));
+}
+
+#[test]
+fn test_binary_write_pid_file() {
+    use std::fs;
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let path = root.join("run/nested/bash.pid");
+    let run = |extra: &[&str], target: &str| {
+        let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+            .arg("--proc-root")
+            .arg(&root)
+            .arg("--write-pid-file")
+            .arg(&path)
+            .args(extra)
+            .arg(target)
+            .output()
+            .unwrap();
+        assert!(out.stdout.is_empty());
+        out.status.code()
+    };
+    // The directory is only created on request.
+    assert_eq!(run(&[], "bash"), Some(1));
+    assert_eq!(run(&["--write-pid-file-dir-create"], "bash"), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "42\n");
+    assert_eq!(run(&[], "nginx"), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "100\n");
+    assert_eq!(run(&[], "no-such-process"), Some(1));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "100\n");
+}
+
+#[test]
+fn test_binary_format_table() {
+    use std::process::Command;
+
+    let (_tmp, root) = create_proc_fixture();
+    let run = |columns: &str| {
+        let out = Command::new(env!("CARGO_BIN_EXE_fpid-rs"))
+            .env("COLUMNS", columns)
+            .arg("--proc-root")
+            .arg(&root)
+            .args(["--format=table", "--columns", "pid,name,cmdline", "bash"])
+            .output()
+            .unwrap();
+        assert_eq!(out.status.code(), Some(0));
+        String::from_utf8(out.stdout).unwrap()
+    };
+    let table = run("80");
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "PID  NAME  CMDLINE");
+    assert_eq!(lines[1], "---  ----  ------------");
+    assert!(lines.contains(&" 42  bash  /bin/bash -l"), "{table}");
+    assert_eq!(lines.len(), 4);
+    // Narrower than the rows: the command line is cut to its header width.
+    assert!(run("18").lines().all(|l| l.chars().count() <= 18));
+}
@@ -0,0 +1,143 @@
+//! Property tests for the byte helpers on the scan hot path: random inputs
+//! checked against the obvious reference implementations, so faster
+//! variants (SWAR, case folding) can't drift from them.
+//!
+//! The request asked for the `proptest` crate, but this build has no
+//! access to a crate registry, so it cannot be a dev-dependency. Until it
+//! can be fetched, [`check`] covers the parts used here: inputs come from a
+//! fixed-seed xorshift generator, `PROPTEST_CASES` sets the number of cases
+//! like it does for proptest, and a failing input is shrunk by dropping
+//! bytes before it is reported together with its seed.
+
+use std::fmt::Debug;
+
+use fpid_rs::matcher::bytes_eq_ascii;
+use fpid_rs::reader::is_all_digits;
+
+const CASES: usize = 10_000;
+
+fn cases() -> usize {
+    std::env::var("PROPTEST_CASES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(CASES)
+}
+
+/// Runs `prop` on generated inputs; on a failure, keeps taking the first
+/// `shrink` candidate that still fails and panics with the smallest one.
+fn check<T: Clone + Debug>(
+    seed: u64,
+    generate: impl Fn(&mut Rng) -> T,
+    shrink: impl Fn(&T) -> Vec<T>,
+    prop: impl Fn(&T) -> bool,
+) {
+    let mut rng = Rng(seed);
+    for case in 0..cases() {
+        let input = generate(&mut rng);
+        if prop(&input) {
+            continue;
+        }
+        let mut smallest = input.clone();
+        while let Some(next) = shrink(&smallest).into_iter().find(|c| !prop(c)) {
+            smallest = next;
+        }
+        panic!("seed {seed:#x}, case {case}: {input:?} fails; shrunk to {smallest:?}");
+    }
+}
+
+// Every string with one byte removed.
+fn shrink_str(s: &str) -> Vec<String> {
+    (0..s.len())
+        .map(|i| {
+            let mut t = s.to_string();
+            t.remove(i);
+            t
+        })
+        .collect()
+}
+
+fn shrink_pair((a, b): &(String, String)) -> Vec<(String, String)> {
+    let left = shrink_str(a).into_iter().map(|a| (a, b.clone()));
+    let right = shrink_str(b).into_iter().map(|b| (a.clone(), b));
+    left.chain(right).collect()
+}
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    // Lengths up to 40 cover the empty string, short tails and several
+    // 8-byte blocks.
+    fn ascii(&mut self) -> String {
+        let len = self.below(41);
+        (0..len).map(|_| self.below(0x80) as u8 as char).collect()
+    }
+
+    // Mostly digits, so the all-digit path is exercised as well as early
+    // rejects; the odd byte sits next to '0'..='9' ('/', ':') or is
+    // arbitrary ASCII.
+    fn digitish(&mut self) -> String {
+        let len = self.below(41);
+        (0..len)
+            .map(|_| match self.below(20) {
+                0 => '/',
+                1 => ':',
+                2 => self.below(0x80) as u8 as char,
+                _ => (b'0' + self.below(10) as u8) as char,
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn prop_bytes_eq_ascii_reflexive() {
+    check(
+        0x9E37_79B9_7F4A_7C15,
+        Rng::ascii,
+        |s| shrink_str(s),
+        |s| bytes_eq_ascii(s.as_bytes(), s.as_bytes()),
+    );
+}
+
+#[test]
+fn prop_bytes_eq_ascii_differing() {
+    let pair = |rng: &mut Rng| {
+        let a = rng.ascii();
+        // Half the cases differ from `a` only in one byte or by length,
+        // which random pairs would almost never produce.
+        let b = match rng.below(4) {
+            0 if !a.is_empty() => {
+                let mut bytes = a.clone().into_bytes();
+                let i = rng.below(bytes.len());
+                bytes[i] ^= 1 + rng.below(0x7F) as u8;
+                String::from_utf8(bytes).unwrap()
+            }
+            1 => format!("{a}{}", rng.below(10)),
+            _ => rng.ascii(),
+        };
+        (a, b)
+    };
+    check(0xD1B5_4A32_D192_ED03, pair, shrink_pair, |(a, b)| {
+        bytes_eq_ascii(a.as_bytes(), b.as_bytes()) == (a == b)
+    });
+}
+
+#[test]
+fn prop_is_all_digits_matches_reference() {
+    check(
+        0x2545_F491_4F6C_DD1D,
+        Rng::digitish,
+        |s| shrink_str(s),
+        |s| is_all_digits(s) == (!s.is_empty() && s.chars().all(|c| c.is_ascii_digit())),
+    );
+}